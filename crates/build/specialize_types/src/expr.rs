@@ -289,6 +289,10 @@ fn monomorphize_expr(expr: Expr, subs: &mut Subs) -> Expr {
             variable: monomorphize_var(variable, subs),
             symbol,
         },
+        Expr::TypedHole { name, var } => Expr::TypedHole {
+            name,
+            var: monomorphize_var(var, subs),
+        },
         Expr::RuntimeError(error) => Expr::RuntimeError(error),
         Expr::RunLowLevel { op, args, ret_var } => Expr::RunLowLevel {
             op,
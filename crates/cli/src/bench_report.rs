@@ -0,0 +1,184 @@
+//! The results of the benchmarks that `roc bench` ran, and the baseline files that
+//! `roc bench --save-baseline` writes for later runs to compare with.
+use std::fmt::Write;
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+/// The timings of one benchmark, in nanoseconds per run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchRecord {
+    /// Like `Sort.bench_quicksort`
+    pub name: String,
+    pub iterations: u64,
+    pub mean: f64,
+    pub median: f64,
+    pub stddev: f64,
+}
+
+impl BenchRecord {
+    pub fn summary(&self) -> String {
+        format!(
+            "mean {}  median {}  stddev {}  ({} runs)",
+            format_nanos(self.mean),
+            format_nanos(self.median),
+            format_nanos(self.stddev),
+            self.iterations
+        )
+    }
+
+    /// How this record compares with the one of the same benchmark in a baseline. A difference
+    /// in means that's within the standard deviation of either is put down to noise.
+    pub fn compare(&self, baseline: &BenchRecord) -> String {
+        let difference = self.mean - baseline.mean;
+
+        if difference.abs() <= self.stddev.max(baseline.stddev) || baseline.mean == 0.0 {
+            return "no change from the baseline".to_string();
+        }
+
+        let percent = 100.0 * difference.abs() / baseline.mean;
+
+        match difference > 0.0 {
+            true => format!("{percent:.1}% slower than the baseline"),
+            false => format!("{percent:.1}% faster than the baseline"),
+        }
+    }
+}
+
+/// A duration in nanoseconds, in the unit that makes it easiest to read.
+pub fn format_nanos(nanos: f64) -> String {
+    const UNITS: [(f64, &str); 3] = [(1e9, "s"), (1e6, "ms"), (1e3, "µs")];
+
+    for (scale, unit) in UNITS {
+        if nanos >= scale {
+            return format!("{:.2} {unit}", nanos / scale);
+        }
+    }
+
+    format!("{nanos:.2} ns")
+}
+
+pub fn read_baseline(path: &Path) -> Result<Vec<BenchRecord>, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|error| format!("I couldn't read the baseline {}: {error}", path.display()))?;
+
+    parse_baseline(&contents).ok_or_else(|| {
+        format!(
+            "The baseline {} isn't one that `roc bench --save-baseline` wrote.",
+            path.display()
+        )
+    })
+}
+
+pub fn write_baseline(path: &Path, records: &[BenchRecord]) -> std::io::Result<()> {
+    std::fs::write(path, baseline_json(records))
+}
+
+fn baseline_json(records: &[BenchRecord]) -> String {
+    let benchmarks = records
+        .iter()
+        .map(|record| {
+            json!({
+                "name": record.name,
+                "iterations": record.iterations,
+                "mean_ns": record.mean,
+                "median_ns": record.median,
+                "stddev_ns": record.stddev,
+            })
+        })
+        .collect();
+
+    let mut json = serde_json::to_string_pretty(&json!({ "benchmarks": Value::Array(benchmarks) }))
+        .expect("serializing a JSON value cannot fail");
+    json.push('\n');
+
+    json
+}
+
+fn parse_baseline(contents: &str) -> Option<Vec<BenchRecord>> {
+    let value: Value = serde_json::from_str(contents).ok()?;
+
+    value
+        .get("benchmarks")?
+        .as_array()?
+        .iter()
+        .map(|benchmark| {
+            Some(BenchRecord {
+                name: benchmark.get("name")?.as_str()?.to_string(),
+                iterations: benchmark.get("iterations")?.as_u64()?,
+                mean: benchmark.get("mean_ns")?.as_f64()?,
+                median: benchmark.get("median_ns")?.as_f64()?,
+                stddev: benchmark.get("stddev_ns")?.as_f64()?,
+            })
+        })
+        .collect()
+}
+
+/// The line that `roc bench` prints for a benchmark, compared with the baseline if it has one.
+pub fn report_line(record: &BenchRecord, baseline: Option<&[BenchRecord]>) -> String {
+    let mut line = format!("{}\n    {}", record.name, record.summary());
+
+    if let Some(baseline) = baseline {
+        match baseline.iter().find(|old| old.name == record.name) {
+            Some(old) => {
+                let _ = write!(line, "\n    {}", record.compare(old));
+            }
+            None => line.push_str("\n    not in the baseline"),
+        }
+    }
+
+    line
+}
+
+#[cfg(test)]
+mod test {
+    use super::{baseline_json, format_nanos, parse_baseline, BenchRecord};
+
+    fn record(name: &str, mean: f64, stddev: f64) -> BenchRecord {
+        BenchRecord {
+            name: name.to_string(),
+            iterations: 1000,
+            mean,
+            median: mean,
+            stddev,
+        }
+    }
+
+    #[test]
+    fn baseline_round_trip() {
+        let records = vec![
+            record("A.bench_a", 12.5, 0.5),
+            record("B.bench_b", 3e6, 1e4),
+        ];
+
+        assert_eq!(parse_baseline(&baseline_json(&records)), Some(records));
+        assert_eq!(parse_baseline("{ \"benchmarks\": [{}] }"), None);
+        assert_eq!(parse_baseline("not json"), None);
+    }
+
+    #[test]
+    fn comparison() {
+        let baseline = record("A.bench_a", 100.0, 5.0);
+
+        assert_eq!(
+            record("A.bench_a", 103.0, 2.0).compare(&baseline),
+            "no change from the baseline"
+        );
+        assert_eq!(
+            record("A.bench_a", 150.0, 2.0).compare(&baseline),
+            "50.0% slower than the baseline"
+        );
+        assert_eq!(
+            record("A.bench_a", 80.0, 2.0).compare(&baseline),
+            "20.0% faster than the baseline"
+        );
+    }
+
+    #[test]
+    fn units() {
+        assert_eq!(format_nanos(12.0), "12.00 ns");
+        assert_eq!(format_nanos(1_500.0), "1.50 µs");
+        assert_eq!(format_nanos(2_250_000.0), "2.25 ms");
+        assert_eq!(format_nanos(3e9), "3.00 s");
+    }
+}
@@ -0,0 +1,244 @@
+//! The results of `roc test --coverage`: how many times each `when` branch, `if` arm and def
+//! body ran, written as an lcov file and summed up in the terminal.
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use roc_module::symbol::Interns;
+use roc_mono::coverage::{Counted, CoverageRegions};
+use roc_region::all::LineInfo;
+
+#[derive(Debug, Default)]
+pub struct CoverageReport {
+    files: Vec<FileCoverage>,
+}
+
+#[derive(Debug)]
+struct FileCoverage {
+    path: PathBuf,
+    /// The path as it's shown in the terminal, relative to the directory of the tested file.
+    display_path: String,
+    counters: Vec<Counter>,
+}
+
+#[derive(Debug)]
+struct Counter {
+    /// 1-based, like lcov wants
+    line: u32,
+    kind: CounterKind,
+    count: u64,
+}
+
+#[derive(Debug)]
+enum CounterKind {
+    Body { name: String },
+    Branch { block: u32, branch: u32 },
+}
+
+impl CoverageReport {
+    /// Adds the counts of one module's counters. When the same module was tested more than once,
+    /// like when testing all of the files in a directory, the counts are added together.
+    pub fn add_module(
+        &mut self,
+        dir: &Path,
+        path: &Path,
+        source: &str,
+        regions: &CoverageRegions,
+        counts: &[u64],
+        interns: &Interns,
+    ) {
+        // Modules with nothing to count, like a package's main.roc, would only be noise.
+        if regions.counters().is_empty() {
+            return;
+        }
+
+        if let Some(file) = self.files.iter_mut().find(|file| file.path == path) {
+            if file.counters.len() == counts.len() {
+                for (counter, count) in file.counters.iter_mut().zip(counts) {
+                    counter.count += count;
+                }
+
+                return;
+            }
+        }
+
+        let line_info = LineInfo::new(source);
+        let counters = regions
+            .counters()
+            .iter()
+            .zip(counts)
+            .map(|((region, counted), count)| {
+                let position = line_info.convert_pos(region.start());
+                let line = position.line + 1;
+
+                let kind = match counted {
+                    Counted::Body(symbol) => {
+                        let name = symbol.as_str(interns);
+
+                        // Anonymous functions have made-up names like `12`.
+                        let name = if name.starts_with(|c: char| c.is_ascii_digit()) {
+                            format!("(anonymous function at {}:{})", line, position.column + 1)
+                        } else {
+                            name.to_string()
+                        };
+
+                        CounterKind::Body { name }
+                    }
+                    Counted::Branch { block, branch } => CounterKind::Branch {
+                        block: *block,
+                        branch: *branch,
+                    },
+                };
+
+                Counter {
+                    line,
+                    kind,
+                    count: *count,
+                }
+            })
+            .collect();
+
+        self.files.push(FileCoverage {
+            path: path.to_path_buf(),
+            display_path: path.strip_prefix(dir).unwrap_or(path).display().to_string(),
+            counters,
+        });
+        self.files
+            .sort_by(|a, b| a.display_path.cmp(&b.display_path));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// The report in the lcov tracefile format, which tools like genhtml and codecov read.
+    pub fn lcov(&self) -> String {
+        let mut lcov = String::new();
+
+        for file in self.files.iter() {
+            let path = std::fs::canonicalize(&file.path).unwrap_or_else(|_| file.path.clone());
+
+            // Writing to a String can't fail.
+            let _ = writeln!(lcov, "TN:\nSF:{}", path.display());
+
+            let bodies = file.bodies();
+            for (counter, name) in bodies.iter() {
+                let _ = writeln!(lcov, "FN:{},{name}", counter.line);
+            }
+            for (counter, name) in bodies.iter() {
+                let _ = writeln!(lcov, "FNDA:{},{name}", counter.count);
+            }
+            let _ = writeln!(
+                lcov,
+                "FNF:{}\nFNH:{}",
+                bodies.len(),
+                bodies
+                    .iter()
+                    .filter(|(counter, _)| counter.count > 0)
+                    .count()
+            );
+
+            let branches = file.branches();
+            for (counter, block, branch) in branches.iter() {
+                let _ = writeln!(
+                    lcov,
+                    "BRDA:{},{block},{branch},{}",
+                    counter.line, counter.count
+                );
+            }
+            let _ = writeln!(
+                lcov,
+                "BRF:{}\nBRH:{}",
+                branches.len(),
+                branches
+                    .iter()
+                    .filter(|(counter, _, _)| counter.count > 0)
+                    .count()
+            );
+
+            // A line ran as many times as the most-run thing that starts on it.
+            let mut lines = BTreeMap::new();
+            for counter in file.counters.iter() {
+                let count = lines.entry(counter.line).or_insert(0);
+                *count = counter.count.max(*count);
+            }
+            for (line, count) in lines.iter() {
+                let _ = writeln!(lcov, "DA:{line},{count}");
+            }
+            let _ = writeln!(
+                lcov,
+                "LF:{}\nLH:{}\nend_of_record",
+                lines.len(),
+                lines.values().filter(|count| **count > 0).count()
+            );
+        }
+
+        lcov
+    }
+
+    /// How much of each file ran, and the lines of the branches and defs that never did.
+    pub fn summary(&self) -> String {
+        let mut summary = String::from("\nCoverage:\n");
+
+        for file in self.files.iter() {
+            let bodies = file.bodies();
+            let branches = file.branches();
+
+            // Writing to a String can't fail.
+            let _ = writeln!(
+                summary,
+                "    {}: {} of {} branches and {} of {} defs ran.",
+                file.display_path,
+                branches
+                    .iter()
+                    .filter(|(counter, _, _)| counter.count > 0)
+                    .count(),
+                branches.len(),
+                bodies
+                    .iter()
+                    .filter(|(counter, _)| counter.count > 0)
+                    .count(),
+                bodies.len(),
+            );
+
+            let mut never_ran: Vec<_> = (file.counters.iter())
+                .filter(|counter| counter.count == 0)
+                .map(|counter| counter.line)
+                .collect();
+            never_ran.sort_unstable();
+            never_ran.dedup();
+
+            if !never_ran.is_empty() {
+                let lines: Vec<_> = never_ran.iter().map(|line| line.to_string()).collect();
+
+                let _ = writeln!(
+                    summary,
+                    "        These lines never ran: {}",
+                    lines.join(", ")
+                );
+            }
+        }
+
+        summary
+    }
+}
+
+impl FileCoverage {
+    fn bodies(&self) -> Vec<(&Counter, &str)> {
+        (self.counters.iter())
+            .filter_map(|counter| match &counter.kind {
+                CounterKind::Body { name } => Some((counter, name.as_str())),
+                CounterKind::Branch { .. } => None,
+            })
+            .collect()
+    }
+
+    fn branches(&self) -> Vec<(&Counter, u32, u32)> {
+        (self.counters.iter())
+            .filter_map(|counter| match counter.kind {
+                CounterKind::Branch { block, branch } => Some((counter, block, branch)),
+                CounterKind::Body { .. } => None,
+            })
+            .collect()
+    }
+}
@@ -6,8 +6,8 @@ use bumpalo::Bump;
 use roc_error_macros::{internal_error, user_error};
 use roc_fmt::def::fmt_defs;
 use roc_fmt::header::fmt_header;
-use roc_fmt::Buf;
 use roc_fmt::MigrationFlags;
+use roc_fmt::{fmt_to_max_width, Buf};
 use roc_parse::ast::{FullAst, SpacesBefore};
 use roc_parse::header::parse_module_defs;
 use roc_parse::normalize::Normalize;
@@ -68,6 +68,7 @@ pub fn format_files(
     files: std::vec::Vec<PathBuf>,
     mode: FormatMode,
     flags: MigrationFlags,
+    max_width: Option<u16>,
 ) -> Result<(), String> {
     let arena = Bump::new();
     let mut files_to_reformat = Vec::new(); // to track which files failed `roc format --check`
@@ -75,7 +76,7 @@ pub fn format_files(
     for file in flatten_directories(files) {
         let src = std::fs::read_to_string(&file).unwrap();
 
-        match format_src(&arena, &src, flags) {
+        match format_src(&arena, &src, flags, max_width) {
             Ok(buf) => {
                 match mode {
                     FormatMode::CheckOnly => {
@@ -188,12 +189,40 @@ pub enum FormatProblem {
     },
 }
 
-pub fn format_src(arena: &Bump, src: &str, flags: MigrationFlags) -> Result<String, FormatProblem> {
+pub fn format_src(
+    arena: &Bump,
+    src: &str,
+    flags: MigrationFlags,
+    max_width: Option<u16>,
+) -> Result<String, FormatProblem> {
     let ast = arena.alloc(parse_all(arena, src).unwrap_or_else(|e| {
         user_error!("Unexpected parse failure when parsing this formatting:\n\n{src}\n\nParse error was:\n\n{:#?}\n\n", e)
     }));
-    let mut buf = Buf::new_in(arena, flags);
-    fmt_all(&mut buf, ast);
+    let buf = match max_width {
+        None => {
+            let mut buf = Buf::new_in(arena, flags);
+            fmt_all(&mut buf, ast);
+            buf
+        }
+        Some(max_width) => {
+            let normalized = ast.normalize(arena);
+
+            let src = arena.alloc_str(src);
+
+            // Breaking a layout across lines is given up on if the result doesn't parse to the same tree.
+            fmt_to_max_width(arena, flags, max_width, src, |arena, buf, src| {
+                let ast = arena.alloc(parse_all(arena, src).map_err(|_| ())?);
+
+                if !flags.at_least_one_active() && ast.normalize(arena) != normalized {
+                    return Err(());
+                }
+
+                fmt_all(buf, ast);
+                Ok(())
+            })
+            .unwrap_or_else(|()| internal_error!("Source which just parsed failed to parse again"))
+        }
+    };
 
     let reparsed_ast = match arena.alloc(parse_all(arena, buf.as_str())) {
         Ok(ast) => ast,
@@ -310,7 +339,7 @@ main =
             parens_and_commas: false,
         };
 
-        let result = format_files(vec![file_path.clone()], FormatMode::CheckOnly, flags);
+        let result = format_files(vec![file_path.clone()], FormatMode::CheckOnly, flags, None);
         assert!(result.is_err());
         assert_eq!(
             result.unwrap_err(),
@@ -333,7 +362,7 @@ main =
             parens_and_commas: false,
         };
 
-        let result = format_files(vec![file1, file2], FormatMode::CheckOnly, flags);
+        let result = format_files(vec![file1, file2], FormatMode::CheckOnly, flags, None);
         assert!(result.is_err());
         let error_message = result.unwrap_err();
         assert!(error_message.contains("test1.roc") && error_message.contains("test2.roc"));
@@ -350,7 +379,7 @@ main =
             parens_and_commas: false,
         };
 
-        let result = format_files(vec![file_path], FormatMode::CheckOnly, flags);
+        let result = format_files(vec![file_path], FormatMode::CheckOnly, flags, None);
         assert!(result.is_ok());
 
         cleanup_temp_dir(dir);
@@ -371,6 +400,7 @@ main =
             vec![file_formatted, file1_unformated, file2_unformated],
             FormatMode::CheckOnly,
             flags,
+            None,
         );
         assert!(result.is_err());
         let error_message = result.unwrap_err();
@@ -8,6 +8,8 @@ use clap::{
     builder::PossibleValuesParser, parser::ValueSource, value_parser, Arg, ArgAction, ArgMatches,
     Command,
 };
+#[cfg(not(windows))]
+use regex::Regex;
 use roc_build::link::{LinkType, LinkingStrategy};
 use roc_build::program::{
     handle_error_module, handle_loading_problem, standard_load_config, BuildFileError,
@@ -24,6 +26,7 @@ use roc_module::symbol::ModuleId;
 use roc_mono::ir::OptLevel;
 use roc_packaging::cache::RocCacheDir;
 use roc_packaging::tarball::Compression;
+use roc_reporting::cli::{Diagnostics, ReportFormat};
 #[cfg(not(windows))]
 use roc_reporting::report::ANSI_STYLE_CODES;
 use roc_target::{Architecture, Target};
@@ -40,9 +43,19 @@ use std::time::Instant;
 use strum::IntoEnumIterator;
 #[cfg(not(target_os = "linux"))]
 use tempfile::TempDir;
+use test_report::TestReporter;
 
 mod format;
+// `roc test` and `roc bench` don't run on windows yet
+#[cfg_attr(windows, allow(dead_code))]
+mod bench_report;
+#[cfg_attr(windows, allow(dead_code))]
+mod coverage;
+#[cfg_attr(windows, allow(dead_code))]
+mod test_report;
+mod watch;
 pub use format::{format_files, format_src, FormatMode};
+pub use watch::{watch, watch_in_child_process};
 
 pub const CMD_BUILD: &str = "build";
 pub const CMD_RUN: &str = "run";
@@ -53,6 +66,7 @@ pub const CMD_CHECK: &str = "check";
 pub const CMD_VERSION: &str = "version";
 pub const CMD_FORMAT: &str = "format";
 pub const CMD_TEST: &str = "test";
+pub const CMD_BENCH: &str = "bench";
 pub const CMD_GLUE: &str = "glue";
 pub const CMD_PREPROCESS_HOST: &str = "preprocess-host";
 
@@ -80,6 +94,19 @@ pub const FLAG_WASM_STACK_SIZE_KB: &str = "wasm-stack-size-kb";
 pub const FLAG_OUTPUT: &str = "output";
 pub const FLAG_FUZZ: &str = "fuzz";
 pub const FLAG_MAIN: &str = "main";
+pub const FLAG_FORMAT: &str = "format";
+pub const FLAG_WATCH: &str = "watch";
+pub const FLAG_CLEAR: &str = "clear";
+pub const FLAG_FILTER: &str = "filter";
+pub const FLAG_LIST: &str = "list";
+pub const FLAG_FAIL_FAST: &str = "fail-fast";
+pub const FLAG_SLOWEST: &str = "slowest";
+pub const FLAG_COVERAGE: &str = "coverage";
+pub const FLAG_SEED: &str = "seed";
+pub const FLAG_UPDATE_SNAPSHOTS: &str = "update-snapshots";
+pub const FLAG_REPORTER: &str = "reporter";
+pub const FLAG_BASELINE: &str = "baseline";
+pub const FLAG_SAVE_BASELINE: &str = "save-baseline";
 pub const ROC_FILE: &str = "ROC_FILE";
 pub const GLUE_DIR: &str = "GLUE_DIR";
 pub const GLUE_SPEC: &str = "GLUE_SPEC";
@@ -89,6 +116,7 @@ pub const FLAG_PP_HOST: &str = "host";
 pub const FLAG_PP_PLATFORM: &str = "platform";
 pub const FLAG_PP_DYLIB: &str = "lib";
 pub const FLAG_MIGRATE: &str = "migrate";
+pub const FLAG_MAX_WIDTH: &str = "max-width";
 pub const FLAG_DOCS_ROOT: &str = "root-dir";
 
 pub const VERSION: &str = env!("ROC_VERSION");
@@ -173,6 +201,26 @@ pub fn build_app() -> Command {
         .value_parser(value_parser!(PathBuf))
         .required(false);
 
+    let flag_format = Arg::new(FLAG_FORMAT)
+        .long(FLAG_FORMAT)
+        .help("Choose how to print the problems found in the code\n(`json` and `sarif` print a single machine-readable document to stdout.)")
+        .value_parser(ReportFormat::FLAG_VALUES)
+        .default_value("human")
+        .required(false);
+
+    let flag_watch = Arg::new(FLAG_WATCH)
+        .long(FLAG_WATCH)
+        .help("Keep running, and start over whenever one of the .roc files changes")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
+    let flag_clear = Arg::new(FLAG_CLEAR)
+        .long(FLAG_CLEAR)
+        .help("Clear the terminal before starting over in --watch mode")
+        .action(ArgAction::SetTrue)
+        .requires(FLAG_WATCH)
+        .required(false);
+
     let roc_file_to_run = Arg::new(ROC_FILE)
         .help("The .roc file of an app to run")
         .value_parser(value_parser!(PathBuf))
@@ -217,6 +265,7 @@ pub fn build_app() -> Command {
             .arg(flag_build_host.clone())
             .arg(flag_suppress_build_host_warning.clone())
             .arg(flag_fuzz.clone())
+            .arg(flag_format.clone())
             .arg(flag_wasm_stack_size_kb)
             .arg(
                 Arg::new(FLAG_TARGET)
@@ -277,6 +326,9 @@ pub fn build_app() -> Command {
             .arg(flag_build_host.clone())
             .arg(flag_suppress_build_host_warning.clone())
             .arg(flag_fuzz.clone())
+            .arg(flag_format.clone())
+            .arg(flag_watch.clone())
+            .arg(flag_clear.clone())
             .arg(
                 Arg::new(FLAG_VERBOSE)
                     .long(FLAG_VERBOSE)
@@ -284,6 +336,74 @@ pub fn build_app() -> Command {
                     .action(ArgAction::SetTrue)
                     .required(false)
             )
+            .arg(
+                Arg::new(FLAG_FILTER)
+                    .long(FLAG_FILTER)
+                    .help("Only run the tests whose name (like `Module.roc:12`) matches this substring or regex")
+                    .value_parser(value_parser!(String))
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_LIST)
+                    .long(FLAG_LIST)
+                    .help("Print the names of the tests without running them")
+                    .action(ArgAction::SetTrue)
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_FAIL_FAST)
+                    .long(FLAG_FAIL_FAST)
+                    .help("Stop running tests after the first one fails")
+                    .action(ArgAction::SetTrue)
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_SLOWEST)
+                    .long(FLAG_SLOWEST)
+                    .help("Print the N slowest tests and how long each one took")
+                    .value_name("N")
+                    .value_parser(value_parser!(usize))
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_COVERAGE)
+                    .long(FLAG_COVERAGE)
+                    .help("Count how many times each branch and def runs, print a summary of that, and write the counts to an lcov file\n(The file is lcov.info unless another one is given.)")
+                    .value_name("FILE")
+                    .value_parser(value_parser!(PathBuf))
+                    .num_args(0..=1)
+                    .default_missing_value("lcov.info")
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_SEED)
+                    .long(FLAG_SEED)
+                    .help("Generate the arguments of properties (`expect`s of a function) from this seed, to run them with the same arguments as before")
+                    .value_parser(value_parser!(u64))
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_UPDATE_SNAPSHOTS)
+                    .long(FLAG_UPDATE_SNAPSHOTS)
+                    .help("Store the current values of snapshots (`expect Inspect.snapshot(value)`s) in the `.snap` files next to their modules, instead of comparing them with the stored ones, and drop the stored ones that no snapshot has anymore")
+                    .action(ArgAction::SetTrue)
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_REPORTER)
+                    .long(FLAG_REPORTER)
+                    .help("Also write a report of every test that ran, for tools like CI dashboards\n(It goes to stdout unless there's an `--output` file, and then the usual output goes to stderr.)")
+                    .value_parser(TestReporter::FLAG_VALUES)
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_OUTPUT)
+                    .long(FLAG_OUTPUT)
+                    .help("The file to write the `--reporter` report to")
+                    .value_parser(value_parser!(PathBuf))
+                    .requires(FLAG_REPORTER)
+                    .required(false)
+            )
             .arg(
                 Arg::new(ROC_FILE)
                     .help("The .roc file to test")
@@ -294,6 +414,41 @@ pub fn build_app() -> Command {
             )
             .arg(args_for_app.clone().last(true))
         )
+        .subcommand(Command::new(CMD_BENCH)
+            .about("Time all top-level functions whose names start with `bench_` in a main module and any modules it imports, built with `--optimize`")
+            .arg(flag_main.clone())
+            .arg(flag_max_threads.clone())
+            .arg(
+                Arg::new(FLAG_FILTER)
+                    .long(FLAG_FILTER)
+                    .help("Only run the benchmarks whose name (like `Module.bench_sort`) matches this substring or regex")
+                    .value_parser(value_parser!(String))
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_BASELINE)
+                    .long(FLAG_BASELINE)
+                    .help("Compare the timings with the ones in this file, which `--save-baseline` wrote")
+                    .value_name("FILE")
+                    .value_parser(value_parser!(PathBuf))
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_SAVE_BASELINE)
+                    .long(FLAG_SAVE_BASELINE)
+                    .help("Write the timings to this JSON file, for a later `--baseline` to compare with")
+                    .value_name("FILE")
+                    .value_parser(value_parser!(PathBuf))
+                    .required(false)
+            )
+            .arg(
+                Arg::new(ROC_FILE)
+                    .help("The .roc file to benchmark")
+                    .value_parser(value_parser!(PathBuf))
+                    .required(false)
+                    .default_value(DEFAULT_ROC_FILENAME)
+            )
+        )
         .subcommand(Command::new(CMD_REPL)
             .about("Launch the interactive Read Eval Print Loop (REPL)")
             .arg(
@@ -340,6 +495,8 @@ pub fn build_app() -> Command {
             .arg(flag_build_host.clone())
             .arg(flag_suppress_build_host_warning.clone())
             .arg(flag_fuzz.clone())
+            .arg(flag_watch.clone())
+            .arg(flag_clear.clone())
             .arg(roc_file_to_run.clone())
             .arg(args_for_app.clone().last(true))
         )
@@ -365,6 +522,13 @@ pub fn build_app() -> Command {
                     .action(ArgAction::SetTrue)
                     .required(false),
             )
+            .arg(
+                Arg::new(FLAG_MAX_WIDTH)
+                    .long(FLAG_MAX_WIDTH)
+                    .help("Break lists, records, function calls and operator chains across lines when they make a line longer than this many characters")
+                    .value_parser(value_parser!(u16))
+                    .required(false),
+            )
             .arg(
                 Arg::new(FLAG_STDIN)
                     .long(FLAG_STDIN)
@@ -388,6 +552,9 @@ pub fn build_app() -> Command {
             .arg(flag_main.clone())
             .arg(flag_time.clone())
             .arg(flag_max_threads.clone())
+            .arg(flag_format)
+            .arg(flag_watch)
+            .arg(flag_clear)
             .arg(
                 Arg::new(ROC_FILE)
                     .help("The .roc file to check")
@@ -510,6 +677,29 @@ fn opt_level_from_flags(matches: &ArgMatches) -> OptLevel {
     }
 }
 
+/// Subcommands without a `--format` flag always print human-readable reports.
+pub fn report_format_from_flags(matches: &ArgMatches) -> ReportFormat {
+    matches
+        .try_get_one::<String>(FLAG_FORMAT)
+        .ok()
+        .flatten()
+        .map(|format| ReportFormat::try_from(format.as_str()).unwrap())
+        .unwrap_or_default()
+}
+
+/// Matches test and benchmark names against a `--filter` pattern.
+#[cfg(not(windows))]
+fn name_filter(pattern: Option<&str>) -> Regex {
+    match pattern {
+        // Anything that isn't a valid regex is matched as a plain substring.
+        Some(pattern) => {
+            Regex::new(pattern).unwrap_or_else(|_| Regex::new(&regex::escape(pattern)).unwrap())
+        }
+        // The empty regex matches every name.
+        None => Regex::new("").unwrap(),
+    }
+}
+
 #[cfg(windows)]
 pub fn test(_matches: &ArgMatches, _target: Target) -> io::Result<i32> {
     todo!("running tests does not work on windows right now")
@@ -523,15 +713,85 @@ struct ModuleTestResults {
     tests_duration: Duration,
 }
 
+/// What `roc test --watch` remembers about a tested file, so that it isn't tested again
+/// until one of the modules it loaded changes.
+#[cfg(not(windows))]
+struct PreviousTestRun {
+    sources: watch::WatchedFiles,
+    /// Everything that testing the file wrote, so it can be shown again.
+    output: Vec<u8>,
+    failed_count: usize,
+    passed_count: usize,
+    test_records: Vec<test_report::TestRecord>,
+}
+
 #[cfg(not(windows))]
 pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
+    let mut previous_runs = MutMap::default();
+    let report_format = report_format_from_flags(matches);
+
+    // Both documents would be printed to stdout, where they couldn't be told apart.
+    if report_format.is_machine_readable()
+        && matches.contains_id(FLAG_REPORTER)
+        && !matches.contains_id(FLAG_OUTPUT)
+    {
+        user_error!(
+            "`--{FLAG_REPORTER}` needs an `--{FLAG_OUTPUT}` file when the problems are printed with `--{FLAG_FORMAT} json` or `--{FLAG_FORMAT} sarif`"
+        );
+    }
+
+    let mut run = |source_paths: &mut Vec<PathBuf>| {
+        // every run gets its own document
+        let mut diagnostics = Diagnostics::new(report_format);
+        let exit_code = run_tests(
+            matches,
+            target,
+            &mut diagnostics,
+            source_paths,
+            &mut previous_runs,
+        );
+
+        diagnostics.print();
+
+        exit_code
+    };
+
+    if matches.get_flag(FLAG_WATCH) {
+        let roots: Vec<PathBuf> = matches
+            .get_many::<PathBuf>(ROC_FILE)
+            .unwrap()
+            .cloned()
+            .collect();
+
+        watch(matches.get_flag(FLAG_CLEAR), &roots, run)
+    } else {
+        run(&mut Vec::new())
+    }
+}
+
+/// Runs the tests once, pushing the paths of all the modules that were loaded into `source_paths`.
+///
+/// Files in `previous_runs` whose modules didn't change aren't tested again; their results
+/// from the previous run are reused instead.
+#[cfg(not(windows))]
+fn run_tests(
+    matches: &ArgMatches,
+    target: Target,
+    diagnostics: &mut Diagnostics,
+    source_paths: &mut Vec<PathBuf>,
+    previous_runs: &mut MutMap<PathBuf, PreviousTestRun>,
+) -> io::Result<i32> {
+    use coverage::CoverageReport;
     use roc_build::program::report_problems_monomorphized;
     use roc_load::{ExecutionMode, FunctionKind, LoadConfig, LoadMonomorphizedError};
     use roc_packaging::cache;
+    use std::io::Write;
+    use test_report::{SnapshotRecord, TestNames, TestRecord};
 
     let start_time = Instant::now();
     let arena = Bump::new();
     let opt_level = opt_level_from_flags(matches);
+    let report_format = diagnostics.format();
 
     let threading = match matches.get_one::<usize>(FLAG_MAX_THREADS) {
         None => Threading::AllAvailable,
@@ -572,10 +832,56 @@ pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
         flatten_paths
     };
 
+    let filter = name_filter(matches.get_one::<String>(FLAG_FILTER).map(String::as_str));
+    let list_only = matches.get_flag(FLAG_LIST);
+    let fail_fast = matches.get_flag(FLAG_FAIL_FAST);
+    let reporter = matches
+        .get_one::<String>(FLAG_REPORTER)
+        .map(|reporter| TestReporter::try_from(reporter.as_str()).unwrap());
+    let report_path = matches.get_one::<PathBuf>(FLAG_OUTPUT);
+    let lcov_path = matches.get_one::<PathBuf>(FLAG_COVERAGE);
+    let seed = match matches.get_one::<u64>(FLAG_SEED) {
+        Some(&seed) => seed,
+        None => roc_repl_expect::run::random_seed(),
+    };
+    let update_snapshots = matches.get_flag(FLAG_UPDATE_SNAPSHOTS);
+    let exec_mode = match lcov_path {
+        Some(_) => ExecutionMode::TestWithCoverage,
+        None => ExecutionMode::Test,
+    };
+
+    // In machine-readable formats, stdout is reserved for the problems document,
+    // and the same goes for a test report that isn't written to a file.
+    let mut writer: Box<dyn io::Write> =
+        if report_format.is_machine_readable() || (reporter.is_some() && report_path.is_none()) {
+            Box::new(io::stderr())
+        } else {
+            Box::new(io::stdout())
+        };
+
     let mut all_files_total_failed_count = 0;
     let mut all_files_total_passed_count = 0;
+    let mut all_files_listed_count = 0;
+    let mut test_records = Vec::new();
+    let mut coverage_report = CoverageReport::default();
+
+    // Listing tests and measuring coverage need every file to be loaded again.
+    let reuse_previous_runs = !list_only && lcov_path.is_none();
 
     for path in paths.iter() {
+        if let Some(previous) = previous_runs.get(path.as_path()) {
+            if reuse_previous_runs && !previous.sources.any_changed() {
+                source_paths.extend(previous.sources.paths().cloned());
+                writer.write_all(&previous.output)?;
+
+                all_files_total_failed_count += previous.failed_count;
+                all_files_total_passed_count += previous.passed_count;
+                test_records.extend(previous.test_records.iter().cloned());
+
+                continue;
+            }
+        }
+
         let arena = &arena;
         let function_kind = FunctionKind::from_env();
 
@@ -585,11 +891,11 @@ pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
         let load_config = LoadConfig {
             target,
             function_kind,
-            // TODO: expose this from CLI?
-            render: roc_reporting::report::RenderTarget::ColorTerminal,
+            render: report_format.render_target(),
             palette: roc_reporting::report::DEFAULT_PALETTE,
             threading,
-            exec_mode: ExecutionMode::Test,
+            exec_mode,
+            solved_modules: None,
         };
         let load_result = roc_load::load_and_monomorphize(
             arena,
@@ -602,19 +908,58 @@ pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
         let mut loaded = match load_result {
             Ok(loaded) => loaded,
             Err(LoadMonomorphizedError::LoadingProblem(problem)) => {
-                return handle_loading_problem(problem);
+                return handle_loading_problem(problem, diagnostics);
             }
             Err(LoadMonomorphizedError::ErrorModule(module)) => {
-                return handle_error_module(module, start_time.elapsed(), path.as_os_str(), false);
+                source_paths.extend(module.sources.values().map(|(path, _)| path.clone()));
+
+                return handle_error_module(
+                    module,
+                    start_time.elapsed(),
+                    path.as_os_str(),
+                    false,
+                    diagnostics,
+                );
             }
         };
-        let problems = report_problems_monomorphized(&mut loaded);
+        let problems = report_problems_monomorphized(&mut loaded, diagnostics);
 
         let mut expectations = std::mem::take(&mut loaded.expectations);
 
         let interns = loaded.interns.clone();
         let sources = loaded.sources.clone();
 
+        source_paths.extend(sources.values().map(|(path, _)| path.clone()));
+
+        let loaded_sources =
+            watch::WatchedFiles::new(sources.values().map(|(path, _)| path.clone()).collect());
+        let file_records_start = test_records.len();
+        let mut file_output = Vec::new();
+
+        let test_names = TestNames::new(
+            path.parent().unwrap_or(Path::new("")),
+            loaded.toplevel_expects.keys().copied(),
+            &sources,
+        );
+
+        if list_only {
+            let mut names: Vec<_> = (loaded.toplevel_expects.iter())
+                .flat_map(|(module_id, expects)| {
+                    (expects.pure.values()).map(|region| test_names.name(*module_id, *region))
+                })
+                .filter(|name| filter.is_match(&name.to_string()))
+                .collect();
+            names.sort();
+
+            for name in names.iter() {
+                writeln!(writer, "{name}")?;
+            }
+
+            all_files_listed_count += names.len();
+
+            continue;
+        }
+
         let (dyn_lib, expects_by_module, layout_interner) =
             roc_repl_expect::run::expect_mono_module_to_dylib(
                 arena,
@@ -631,7 +976,7 @@ pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
                 problems.errors, 0,
                 "if there were errors, we would have already exited."
             );
-            if problems.warnings > 0 {
+            if problems.warnings > 0 && !report_format.is_machine_readable() {
                 problems.print_error_warning_count(start_time.elapsed());
                 println!(".\n\nRunning tests…\n\n\x1B[36m{}\x1B[39m", "─".repeat(80));
             }
@@ -641,8 +986,6 @@ pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
         let arena = &bumpalo::Bump::new();
         let interns = arena.alloc(interns);
 
-        let mut writer = std::io::stdout();
-
         let mut total_failed_count = 0;
         let mut total_passed_count = 0;
 
@@ -651,54 +994,188 @@ pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
 
         let compilation_duration = start_time.elapsed();
 
+        let mut failed_fast = false;
+        let mut filtered_out = false;
+        let mut snapshot_files = roc_repl_expect::snapshot::SnapshotFiles::new(update_snapshots);
+
         for (module_id, expects) in expects_by_module.into_iter() {
-            let test_start_time = Instant::now();
+            let mut failed_count = 0;
+            let mut passed_count = 0;
+            let mut tests_duration = Duration::ZERO;
 
-            let (failed_count, passed_count) = roc_repl_expect::run::run_toplevel_expects(
-                &mut writer,
-                roc_reporting::report::RenderTarget::ColorTerminal,
-                arena,
-                interns,
-                &global_layout_interner,
-                &dyn_lib,
-                &mut expectations,
-                expects,
-            )
-            .unwrap();
+            for expect in expects.pure {
+                let name = test_names.name(module_id, expect.region);
 
-            let tests_duration = test_start_time.elapsed();
+                if !filter.is_match(&name.to_string()) {
+                    filtered_out = true;
+                    continue;
+                }
 
-            results_by_module.push(ModuleTestResults {
-                module_id,
-                failed_count,
-                passed_count,
-                tests_duration,
-            });
+                // One at a time, so that each one can be timed and reported on its own.
+                let test_start_time = Instant::now();
+                let mut output = Vec::new();
+
+                let (failed, passed) = roc_repl_expect::run::run_toplevel_expects(
+                    &mut output,
+                    roc_reporting::report::RenderTarget::ColorTerminal,
+                    arena,
+                    interns,
+                    &global_layout_interner,
+                    &dyn_lib,
+                    &mut expectations,
+                    roc_repl_expect::run::ExpectFunctions {
+                        pure: bumpalo::vec![in arena; expect],
+                    },
+                    seed,
+                    &mut snapshot_files,
+                )
+                .unwrap();
+
+                let test_duration = test_start_time.elapsed();
+
+                writer.write_all(&output)?;
+                file_output.extend_from_slice(&output);
+
+                failed_count += failed;
+                passed_count += passed;
+                tests_duration += test_duration;
+                test_records.push(TestRecord {
+                    name,
+                    source: test_names.source(module_id, expect.region),
+                    failure: (failed > 0).then(|| {
+                        roc_reporting::report::strip_colors(&String::from_utf8_lossy(&output))
+                    }),
+                    snapshot: snapshot_files.comparison(expect.symbol).map(|comparison| {
+                        SnapshotRecord {
+                            stored: comparison.stored.clone(),
+                            current: comparison.current.clone(),
+                        }
+                    }),
+                    duration: test_duration,
+                });
+
+                if fail_fast && failed > 0 {
+                    failed_fast = true;
+                    break;
+                }
+            }
+
+            // Leave out the modules whose tests were all filtered out.
+            if failed_count + passed_count > 0 {
+                results_by_module.push(ModuleTestResults {
+                    module_id,
+                    failed_count,
+                    passed_count,
+                    tests_duration,
+                });
+            }
 
             total_failed_count += failed_count;
             total_passed_count += passed_count;
+
+            if failed_fast {
+                break;
+            }
+        }
+
+        // The snapshots that didn't run are only stale when every test had a chance to run.
+        snapshot_files.write(!(filtered_out || failed_fast))?;
+
+        // Only the modules of the tested package have coverage counters.
+        for (module_id, module_expectations) in expectations.iter() {
+            if let Some(regions) = &module_expectations.coverage {
+                let counts =
+                    roc_repl_expect::run::read_coverage_counts(&dyn_lib, *module_id, regions);
+                let (module_path, source) = &sources[module_id];
+
+                coverage_report.add_module(
+                    path.parent().unwrap_or(Path::new("")),
+                    module_path,
+                    source,
+                    regions,
+                    &counts,
+                    interns,
+                );
+            }
         }
 
         let total_duration = start_time.elapsed();
         all_files_total_failed_count += total_failed_count;
         all_files_total_passed_count += total_passed_count;
-        if total_failed_count == 0 && total_passed_count == 0 {
-            // Only report no expectations found once.
-            continue;
-        } else if matches.get_flag(FLAG_VERBOSE) {
-            println!("Compiled in {} ms.", compilation_duration.as_millis());
-            for module_test_results in results_by_module {
-                print_test_results(module_test_results, &sources);
+        // Files without tests don't get a summary, "No expectations were found" is only reported once.
+        if total_failed_count + total_passed_count > 0 {
+            if matches.get_flag(FLAG_VERBOSE) {
+                let summary_start = file_output.len();
+
+                writeln!(
+                    file_output,
+                    "Compiled in {} ms.",
+                    compilation_duration.as_millis()
+                )?;
+                for module_test_results in results_by_module {
+                    print_test_results(&mut file_output, module_test_results, &sources)?;
+                }
+
+                writer.write_all(&file_output[summary_start..])?;
+            } else {
+                let test_summary_str =
+                    test_summary(total_failed_count, total_passed_count, total_duration);
+                writeln!(writer, "{test_summary_str}")?;
+                writeln!(file_output, "{test_summary_str}")?;
             }
-        } else {
-            let test_summary_str =
-                test_summary(total_failed_count, total_passed_count, total_duration);
-            println!("{test_summary_str}");
         }
+
+        if failed_fast {
+            break;
+        }
+
+        if reuse_previous_runs {
+            previous_runs.insert(
+                path.to_path_buf(),
+                PreviousTestRun {
+                    sources: loaded_sources,
+                    output: file_output,
+                    failed_count: total_failed_count,
+                    passed_count: total_passed_count,
+                    test_records: test_records[file_records_start..].to_vec(),
+                },
+            );
+        }
+    }
+
+    if let Some(&count) = matches.get_one::<usize>(FLAG_SLOWEST) {
+        print_slowest_tests(&mut writer, &test_records, count)?;
     }
-    if all_files_total_failed_count == 0 && all_files_total_passed_count == 0 {
+
+    if let (Some(lcov_path), false) = (lcov_path, coverage_report.is_empty()) {
+        std::fs::write(lcov_path, coverage_report.lcov())?;
+
+        write!(writer, "{}", coverage_report.summary())?;
+        writeln!(
+            writer,
+            "\nWrote the counts of every branch and def to {}.",
+            lcov_path.display()
+        )?;
+    }
+
+    if let (Some(reporter), false) = (reporter, list_only) {
+        let report = reporter.report(&test_records);
+
+        match report_path {
+            Some(report_path) => std::fs::write(report_path, report)?,
+            None => print!("{report}"),
+        }
+    }
+
+    if list_only && all_files_listed_count > 0 {
+        Ok(0)
+    } else if all_files_total_failed_count == 0 && all_files_total_passed_count == 0 {
         // TODO print this in a more nicely formatted way!
-        println!("No expectations were found.");
+        if report_format.is_machine_readable() {
+            eprintln!("No expectations were found.");
+        } else {
+            println!("No expectations were found.");
+        }
 
         // If no tests ran, treat that as an error. This is perhaps
         // briefly annoying at the very beginning of a project when
@@ -711,6 +1188,184 @@ pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
     }
 }
 
+#[cfg(windows)]
+pub fn bench(_matches: &ArgMatches, _target: Target) -> io::Result<i32> {
+    user_error!("`roc bench` doesn't work on Windows yet")
+}
+
+#[cfg(not(windows))]
+pub fn bench(matches: &ArgMatches, target: Target) -> io::Result<i32> {
+    use bench_report::{read_baseline, report_line, write_baseline, BenchRecord};
+    use roc_build::program::report_problems_monomorphized;
+    use roc_load::{ExecutionMode, FunctionKind, LoadConfig, LoadMonomorphizedError};
+    use roc_packaging::cache;
+    use roc_repl_expect::bench::BenchConfig;
+    use roc_repl_expect::run::{expect_mono_module_to_dylib, run_bench, ExpectMemory};
+
+    let start_time = Instant::now();
+    let arena = Bump::new();
+    let mut diagnostics = Diagnostics::new(report_format_from_flags(matches));
+
+    let threading = match matches.get_one::<usize>(FLAG_MAX_THREADS) {
+        None => Threading::AllAvailable,
+        Some(0) => user_error!("cannot build with at most 0 threads"),
+        Some(1) => Threading::Single,
+        Some(n) => Threading::AtMost(*n),
+    };
+
+    let path = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
+
+    if !path.exists() {
+        eprintln!(
+            "\nThis file was not found: {}\n\nYou can run `roc help` for more information on how to provide a .roc file.\n",
+            path.display()
+        );
+
+        return Ok(1);
+    }
+
+    let filter = name_filter(matches.get_one::<String>(FLAG_FILTER).map(String::as_str));
+
+    let baseline = match matches.get_one::<PathBuf>(FLAG_BASELINE) {
+        Some(baseline_path) => match read_baseline(baseline_path) {
+            Ok(baseline) => Some(baseline),
+            Err(message) => {
+                eprintln!("{message}");
+
+                return Ok(1);
+            }
+        },
+        None => None,
+    };
+
+    let load_config = LoadConfig {
+        target,
+        function_kind: FunctionKind::from_env(),
+        render: diagnostics.format().render_target(),
+        palette: roc_reporting::report::DEFAULT_PALETTE,
+        threading,
+        exec_mode: ExecutionMode::Bench,
+        solved_modules: None,
+    };
+    let load_result = roc_load::load_and_monomorphize(
+        &arena,
+        path.to_path_buf(),
+        matches.get_one::<PathBuf>(FLAG_MAIN).cloned(),
+        RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
+        load_config,
+    );
+
+    let mut loaded = match load_result {
+        Ok(loaded) => loaded,
+        Err(LoadMonomorphizedError::LoadingProblem(problem)) => {
+            let exit_code = handle_loading_problem(problem, &mut diagnostics);
+            diagnostics.print();
+
+            return exit_code;
+        }
+        Err(LoadMonomorphizedError::ErrorModule(module)) => {
+            let exit_code = handle_error_module(
+                module,
+                start_time.elapsed(),
+                path.as_os_str(),
+                false,
+                &mut diagnostics,
+            );
+            diagnostics.print();
+
+            return exit_code;
+        }
+    };
+    let problems = report_problems_monomorphized(&mut loaded, &mut diagnostics);
+
+    let interns = loaded.interns.clone();
+
+    // the benchmarks' output goes to stdout too, so print the problems first
+    diagnostics.print();
+
+    // Benchmarks are only worth timing with all the optimizations that a release build gets.
+    let (dyn_lib, expects_by_module, _layout_interner) = expect_mono_module_to_dylib(
+        &arena,
+        target,
+        loaded,
+        OptLevel::Optimize,
+        LlvmBackendMode::CliTest,
+    )
+    .unwrap();
+
+    if problems.warnings > 0 {
+        problems.print_error_warning_count(start_time.elapsed());
+        println!(
+            ".\n\nRunning benchmarks…\n\n\x1B[36m{}\x1B[39m",
+            "─".repeat(80)
+        );
+    }
+
+    let mut benches: Vec<_> = expects_by_module
+        .into_values()
+        .flat_map(|expects| expects.pure)
+        .map(|expect| {
+            let name = format!(
+                "{}.{}",
+                interns.module_name(expect.symbol.module_id()),
+                expect.symbol.as_str(&interns)
+            );
+
+            (name, expect)
+        })
+        .filter(|(name, _)| filter.is_match(name))
+        .collect();
+    benches.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let shm_name = format!("/roc_expect_buffer_{}", std::process::id());
+    let mut memory = ExpectMemory::create_or_reuse_mmap(&shm_name);
+    let config = BenchConfig::default();
+
+    let mut records = Vec::new();
+    let mut crashed_count = 0;
+
+    for (name, expect) in benches {
+        match run_bench(&dyn_lib, &mut memory, expect, &config) {
+            Ok(measurement) => {
+                let record = BenchRecord {
+                    name,
+                    iterations: measurement.iterations,
+                    mean: measurement.mean,
+                    median: measurement.median,
+                    stddev: measurement.stddev,
+                };
+
+                println!("{}", report_line(&record, baseline.as_deref()));
+                records.push(record);
+            }
+            Err(message) => {
+                crashed_count += 1;
+
+                println!("{name}\n    crashed: {message}");
+            }
+        }
+    }
+
+    if records.is_empty() && crashed_count == 0 {
+        println!("No benchmarks were found.");
+
+        // Like `roc test` without tests, so that CI notices when benchmarks stop running.
+        return Ok(2);
+    }
+
+    if let Some(baseline_path) = matches.get_one::<PathBuf>(FLAG_SAVE_BASELINE) {
+        write_baseline(baseline_path, &records)?;
+
+        println!(
+            "\nWrote the timings of {} benchmarks to {}.",
+            records.len(),
+            baseline_path.display()
+        );
+    }
+
+    Ok((crashed_count > 0) as i32)
+}
+
 fn find_all_roc_files(path: &PathBuf, flatten_paths: &mut Vec<PathBuf>) {
     if path.is_dir() {
         if let Ok(entries) = std::fs::read_dir(path) {
@@ -738,9 +1393,12 @@ fn find_all_roc_files(path: &PathBuf, flatten_paths: &mut Vec<PathBuf>) {
 
 #[cfg(not(windows))]
 fn print_test_results(
+    writer: &mut impl io::Write,
     module_test_results: ModuleTestResults,
     sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
-) {
+) -> io::Result<()> {
+    use std::io::Write;
+
     let ModuleTestResults {
         module_id,
         failed_count,
@@ -753,7 +1411,34 @@ fn print_test_results(
     let (module_path, _) = sources.get(&module_id).unwrap();
     let module_name = module_path.file_name().unwrap().to_str().unwrap();
 
-    println!("\n{module_name}:\n    {test_summary_str}",);
+    writeln!(writer, "\n{module_name}:\n    {test_summary_str}")
+}
+
+#[cfg(not(windows))]
+fn print_slowest_tests(
+    writer: &mut impl io::Write,
+    test_records: &[test_report::TestRecord],
+    count: usize,
+) -> io::Result<()> {
+    if test_records.is_empty() || count == 0 {
+        return Ok(());
+    }
+
+    let mut test_records: Vec<_> = test_records.iter().collect();
+    test_records.sort_by_key(|record| std::cmp::Reverse(record.duration));
+
+    writeln!(writer, "\nSlowest tests:")?;
+
+    for record in test_records.into_iter().take(count) {
+        writeln!(
+            writer,
+            "    {} ran in {} ms.",
+            record.name,
+            record.duration.as_millis()
+        )?;
+    }
+
+    Ok(())
 }
 
 #[cfg(not(windows))]
@@ -913,6 +1598,8 @@ pub fn build(
     let arena = ManuallyDrop::new(Bump::new());
 
     let opt_level = opt_level_from_flags(matches);
+    let report_format = report_format_from_flags(matches);
+    let mut diagnostics = Diagnostics::new(report_format);
 
     let should_run_expects = matches!(opt_level, OptLevel::Development | OptLevel::Normal) &&
         // TODO: once expect is decoupled from roc launching the executable, remove this part of the conditional.
@@ -937,6 +1624,18 @@ pub fn build(
         CodeGenBackend::Llvm(backend_mode)
     };
 
+    // The dev backend only generates x86_64 and aarch64 code.
+    if matches!(code_gen_backend, CodeGenBackend::Assembly(_))
+        && !matches!(
+            target.architecture(),
+            Architecture::X86_64 | Architecture::Aarch64
+        )
+    {
+        user_error!(
+            "The dev backend can't build for {target} yet. Build without `--dev` to use the LLVM backend instead."
+        );
+    }
+
     let emit_llvm_ir = matches.get_flag(FLAG_EMIT_LLVM_IR);
     if emit_llvm_ir && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
         user_error!("Cannot emit llvm ir while using a dev backend.");
@@ -990,7 +1689,7 @@ pub fn build(
         fuzz,
     };
 
-    let load_config = standard_load_config(target, build_ordering, threading);
+    let load_config = standard_load_config(target, build_ordering, threading, report_format);
 
     let res_binary_path = roc_build::program::build_file(
         &arena,
@@ -1007,6 +1706,7 @@ pub fn build(
         load_config,
         out_path,
         verbose,
+        &mut diagnostics,
     );
 
     match res_binary_path {
@@ -1015,7 +1715,13 @@ pub fn build(
             problems,
             total_time,
             expect_metadata,
+            source_paths,
         }) => {
+            watch::report_source_paths(&source_paths);
+
+            // the program's output goes to stdout too, so print the problems before running it
+            diagnostics.print();
+
             match config {
                 BuildOnly => {
                     // If possible, report the generated executable name relative to the current dir.
@@ -1029,8 +1735,12 @@ pub fn build(
                     // since the process is about to exit anyway.
                     // std::mem::forget(arena);
 
-                    problems.print_error_warning_count(total_time);
-                    println!(" while successfully building:\n\n    {generated_filename}");
+                    if report_format.is_machine_readable() {
+                        eprintln!("Successfully built:\n\n    {generated_filename}");
+                    } else {
+                        problems.print_error_warning_count(total_time);
+                        println!(" while successfully building:\n\n    {generated_filename}");
+                    }
 
                     // Return a nonzero exit code if there were problems
                     Ok(problems.exit_code())
@@ -1119,9 +1829,20 @@ pub fn build(
             }
         }
         Err(BuildFileError::ErrorModule { module, total_time }) => {
-            handle_error_module(module, total_time, path.as_os_str(), true)
+            watch::report_source_paths(module.sources.values().map(|(path, _)| path));
+
+            let exit_code =
+                handle_error_module(module, total_time, path.as_os_str(), true, &mut diagnostics);
+            diagnostics.print();
+
+            exit_code
+        }
+        Err(BuildFileError::LoadingProblem(problem)) => {
+            let exit_code = handle_loading_problem(problem, &mut diagnostics);
+            diagnostics.print();
+
+            exit_code
         }
-        Err(BuildFileError::LoadingProblem(problem)) => handle_loading_problem(problem),
     }
 }
 
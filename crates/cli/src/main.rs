@@ -1,22 +1,24 @@
 //! The `roc` binary that brings together all functionality in the Roc toolset.
 use bumpalo::Bump;
 use roc_build::link::LinkType;
-use roc_build::program::{check_file, CodeGenBackend};
+use roc_build::program::{check_file, CheckedFile, CodeGenBackend};
 use roc_cli::{
-    build_app, default_linking_strategy, format_files, format_src, test, BuildConfig, FormatMode,
-    CMD_BUILD, CMD_CHECK, CMD_DEV, CMD_DOCS, CMD_FORMAT, CMD_GLUE, CMD_PREPROCESS_HOST, CMD_REPL,
-    CMD_RUN, CMD_TEST, CMD_VERSION, DIRECTORY_OR_FILES, FLAG_CHECK, FLAG_DEV, FLAG_DOCS_ROOT,
-    FLAG_LIB, FLAG_MAIN, FLAG_MIGRATE, FLAG_NO_COLOR, FLAG_NO_HEADER, FLAG_NO_LINK, FLAG_OUTPUT,
-    FLAG_PP_DYLIB, FLAG_PP_HOST, FLAG_PP_PLATFORM, FLAG_STDIN, FLAG_STDOUT, FLAG_TARGET, FLAG_TIME,
-    FLAG_VERBOSE, GLUE_DIR, GLUE_SPEC, ROC_FILE, VERSION,
+    bench, build_app, default_linking_strategy, format_files, format_src, report_format_from_flags,
+    test, watch, watch_in_child_process, BuildConfig, FormatMode, CMD_BENCH, CMD_BUILD, CMD_CHECK,
+    CMD_DEV, CMD_DOCS, CMD_FORMAT, CMD_GLUE, CMD_PREPROCESS_HOST, CMD_REPL, CMD_RUN, CMD_TEST,
+    CMD_VERSION, DIRECTORY_OR_FILES, FLAG_CHECK, FLAG_CLEAR, FLAG_DEV, FLAG_DOCS_ROOT, FLAG_LIB,
+    FLAG_MAIN, FLAG_MAX_WIDTH, FLAG_MIGRATE, FLAG_NO_COLOR, FLAG_NO_HEADER, FLAG_NO_LINK,
+    FLAG_OUTPUT, FLAG_PP_DYLIB, FLAG_PP_HOST, FLAG_PP_PLATFORM, FLAG_STDIN, FLAG_STDOUT,
+    FLAG_TARGET, FLAG_TIME, FLAG_VERBOSE, FLAG_WATCH, GLUE_DIR, GLUE_SPEC, ROC_FILE, VERSION,
 };
 use roc_docs::generate_docs_html;
 use roc_error_macros::user_error;
 use roc_fmt::MigrationFlags;
 use roc_gen_dev::AssemblyBackendMode;
 use roc_gen_llvm::llvm::build::LlvmBackendMode;
-use roc_load::{LoadingProblem, Threading};
+use roc_load::{LoadingProblem, SolvedModules, Threading};
 use roc_packaging::cache::{self, RocCacheDir};
+use roc_reporting::cli::Diagnostics;
 use roc_target::Target;
 use std::fs::{self, FileType};
 use std::io::BufRead;
@@ -87,8 +89,13 @@ fn main() -> io::Result<()> {
                 Ok(1)
             }
         }
+        Some((CMD_BENCH, matches)) => bench(matches, Triple::host().into()),
         Some((CMD_DEV, matches)) => {
-            if matches.contains_id(ROC_FILE) {
+            if matches.contains_id(ROC_FILE) && matches.get_flag(FLAG_WATCH) {
+                let roc_file_path = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
+
+                watch_in_child_process(matches.get_flag(FLAG_CLEAR), roc_file_path)
+            } else if matches.contains_id(ROC_FILE) {
                 build(
                     matches,
                     &subcommands,
@@ -212,8 +219,6 @@ fn main() -> io::Result<()> {
             )?)
         }
         Some((CMD_CHECK, matches)) => {
-            let arena = Bump::new();
-
             let emit_timings = matches.get_flag(FLAG_TIME);
             let roc_file_path = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
             let threading = match matches.get_one::<usize>(roc_cli::FLAG_MAX_THREADS) {
@@ -224,94 +229,143 @@ fn main() -> io::Result<()> {
             };
 
             let opt_main_path = matches.get_one::<PathBuf>(FLAG_MAIN);
+            let report_format = report_format_from_flags(matches);
+            // Only the modules that changed since the previous run get solved again.
+            let solved_modules = matches.get_flag(FLAG_WATCH).then(SolvedModules::default);
+
+            let check_files = |source_paths: &mut Vec<PathBuf>,
+                               diagnostics: &mut Diagnostics|
+             -> io::Result<i32> {
+                let arena = Bump::new();
 
-            match roc_file_path.extension().and_then(OsStr::to_str) {
-                Some("md") => {
-                    // Extract the blocks of roc code
-                    let file = fs::File::open(roc_file_path.as_path())?;
-                    let markdown_file_reader = io::BufReader::new(file);
-                    let mut roc_blocks: Vec<String> = Vec::new();
-                    let mut in_roc_block: bool = false;
-                    let mut current_block = String::new();
-
-                    for line in markdown_file_reader.lines() {
-                        let line = line.unwrap();
-                        if line == "```roc" {
-                            in_roc_block = true;
-                        } else if (line == "```") & in_roc_block {
-                            in_roc_block = false;
-                            roc_blocks.push(current_block);
-                            current_block = String::new();
-                        } else if in_roc_block {
-                            current_block.push_str(&line);
-                            current_block.push('\n');
+                match roc_file_path.extension().and_then(OsStr::to_str) {
+                    Some("md") => {
+                        source_paths.push(roc_file_path.to_owned());
+
+                        // Extract the blocks of roc code
+                        let file = fs::File::open(roc_file_path.as_path())?;
+                        let markdown_file_reader = io::BufReader::new(file);
+                        let mut roc_blocks: Vec<String> = Vec::new();
+                        let mut in_roc_block: bool = false;
+                        let mut current_block = String::new();
+
+                        for line in markdown_file_reader.lines() {
+                            let line = line.unwrap();
+                            if line == "```roc" {
+                                in_roc_block = true;
+                            } else if (line == "```") & in_roc_block {
+                                in_roc_block = false;
+                                roc_blocks.push(current_block);
+                                current_block = String::new();
+                            } else if in_roc_block {
+                                current_block.push_str(&line);
+                                current_block.push('\n');
+                            }
                         }
-                    }
 
-                    // now check each block, we exit early if any single block does not check
-                    let mut exit_code = 0;
+                        // now check each block, we exit early if any single block does not check
+                        let mut exit_code = 0;
+
+                        for block in roc_blocks.iter() {
+                            let mut file = Builder::new().suffix(".roc").tempfile()?;
+                            write!(file, "{}", block)?;
+
+                            match check_file(
+                                &arena,
+                                file.path().to_owned(),
+                                opt_main_path.cloned(),
+                                emit_timings,
+                                RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
+                                threading,
+                                solved_modules.clone(),
+                                diagnostics,
+                            ) {
+                                Ok(CheckedFile {
+                                    problems,
+                                    total_time,
+                                    source_paths: _,
+                                }) => {
+                                    if !report_format.is_machine_readable() {
+                                        problems.print_error_warning_count(total_time);
+                                        println!(".\n");
+                                    }
+
+                                    exit_code = problems.exit_code();
+                                }
+
+                                Err(LoadingProblem::FormattedReport(report)) => {
+                                    diagnostics.print_formatted_report(&report);
+
+                                    exit_code = 1;
+                                }
+                                Err(other) => {
+                                    panic!("build_file failed with error:\n{other:?}");
+                                }
+                            }
 
-                    for block in roc_blocks.iter() {
-                        let mut file = Builder::new().suffix(".roc").tempfile()?;
-                        write!(file, "{}", block)?;
+                            if exit_code != 0 {
+                                break;
+                            }
+                        }
 
+                        Ok(exit_code)
+                    }
+                    _ => {
                         match check_file(
                             &arena,
-                            file.path().to_owned(),
+                            roc_file_path.to_owned(),
                             opt_main_path.cloned(),
                             emit_timings,
                             RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
                             threading,
+                            solved_modules.clone(),
+                            diagnostics,
                         ) {
-                            Ok((problems, total_time)) => {
-                                problems.print_error_warning_count(total_time);
-                                println!(".\n");
-
-                                exit_code = problems.exit_code();
+                            Ok(CheckedFile {
+                                problems,
+                                total_time,
+                                source_paths: checked_paths,
+                            }) => {
+                                source_paths.extend(checked_paths);
+
+                                if !report_format.is_machine_readable() {
+                                    problems.print_error_warning_count(total_time);
+                                    println!(".\n");
+                                }
+                                Ok(problems.exit_code())
                             }
 
-                            Err(LoadingProblem::FormattedReport(report, _)) => {
-                                print!("{report}");
+                            Err(LoadingProblem::FormattedReport(report)) => {
+                                diagnostics.print_formatted_report(&report);
 
-                                exit_code = 1;
+                                Ok(1)
                             }
                             Err(other) => {
                                 panic!("build_file failed with error:\n{other:?}");
                             }
                         }
-
-                        if exit_code != 0 {
-                            break;
-                        }
                     }
-
-                    Ok(exit_code)
                 }
-                _ => {
-                    match check_file(
-                        &arena,
-                        roc_file_path.to_owned(),
-                        opt_main_path.cloned(),
-                        emit_timings,
-                        RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
-                        threading,
-                    ) {
-                        Ok((problems, total_time)) => {
-                            problems.print_error_warning_count(total_time);
-                            println!(".\n");
-                            Ok(problems.exit_code())
-                        }
+            };
 
-                        Err(LoadingProblem::FormattedReport(report, _)) => {
-                            print!("{report}");
+            let check = |source_paths: &mut Vec<PathBuf>| {
+                // every run gets its own document
+                let mut diagnostics = Diagnostics::new(report_format);
+                let exit_code = check_files(source_paths, &mut diagnostics);
 
-                            Ok(1)
-                        }
-                        Err(other) => {
-                            panic!("build_file failed with error:\n{other:?}");
-                        }
-                    }
-                }
+                diagnostics.print();
+
+                exit_code
+            };
+
+            if matches.get_flag(FLAG_WATCH) {
+                watch(
+                    matches.get_flag(FLAG_CLEAR),
+                    &[roc_file_path.to_owned()],
+                    check,
+                )
+            } else {
+                check(&mut Vec::new())
             }
         }
         Some((CMD_REPL, matches)) => {
@@ -350,6 +404,7 @@ fn main() -> io::Result<()> {
             let from_stdin = matches.get_flag(FLAG_STDIN);
             let to_stdout = matches.get_flag(FLAG_STDOUT);
             let migrate = matches.get_flag(FLAG_MIGRATE);
+            let max_width = matches.get_one::<u16>(FLAG_MAX_WIDTH).copied();
             let format_mode = if to_stdout {
                 FormatMode::WriteToStdout
             } else {
@@ -414,7 +469,7 @@ fn main() -> io::Result<()> {
                     std::process::exit(1);
                 });
 
-                match format_src(&arena, src, flags) {
+                match format_src(&arena, src, flags, max_width) {
                     Ok(formatted_src) => {
                         match format_mode {
                             FormatMode::CheckOnly => {
@@ -446,7 +501,7 @@ fn main() -> io::Result<()> {
                     }
                 }
             } else {
-                match format_files(roc_files, format_mode, flags) {
+                match format_files(roc_files, format_mode, flags, max_width) {
                     Ok(()) => 0,
                     Err(message) => {
                         eprintln!("{message}");
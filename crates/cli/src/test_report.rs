@@ -0,0 +1,377 @@
+//! The results of the tests that `roc test` ran, and the machine-readable reports of them that
+//! `roc test --reporter` writes for CI dashboards.
+use std::fmt::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use roc_collections::MutMap;
+use roc_module::symbol::ModuleId;
+use roc_region::all::{LineInfo, Region};
+use serde_json::{json, Value};
+
+/// The name of a top-level `expect` in the output of `roc test`, like `Module.roc:12`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TestName {
+    pub module_path: String,
+    pub line: u32,
+}
+
+impl fmt::Display for TestName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.module_path, self.line)
+    }
+}
+
+pub struct TestNames<'a> {
+    modules: MutMap<ModuleId, (String, &'a str, LineInfo)>,
+}
+
+impl<'a> TestNames<'a> {
+    /// Module paths are written relative to `dir`, the directory of the file being tested.
+    pub fn new(
+        dir: &Path,
+        module_ids: impl Iterator<Item = ModuleId>,
+        sources: &'a MutMap<ModuleId, (PathBuf, Box<str>)>,
+    ) -> Self {
+        let modules = module_ids
+            .filter_map(|module_id| {
+                let (path, source) = sources.get(&module_id)?;
+                let path = path.strip_prefix(dir).unwrap_or(path);
+
+                Some((
+                    module_id,
+                    (path.display().to_string(), &**source, LineInfo::new(source)),
+                ))
+            })
+            .collect();
+
+        Self { modules }
+    }
+
+    pub fn name(&self, module_id: ModuleId, region: Region) -> TestName {
+        let (module_path, _, line_info) = &self.modules[&module_id];
+
+        TestName {
+            module_path: module_path.clone(),
+            line: line_info.convert_pos(region.start()).line + 1,
+        }
+    }
+
+    /// The source code of the `expect` at `region`.
+    pub fn source(&self, module_id: ModuleId, region: Region) -> String {
+        let (_, source, _) = &self.modules[&module_id];
+
+        source
+            .get(region.start().offset as usize..region.end().offset as usize)
+            .unwrap_or_default()
+            .to_string()
+    }
+}
+
+/// A top-level `expect` that was run.
+#[derive(Debug, Clone)]
+pub struct TestRecord {
+    pub name: TestName,
+    pub source: String,
+    /// How the test failed, rendered without colors, or None if it passed.
+    pub failure: Option<String>,
+    /// The renderings that a snapshot test compared, or None if it isn't one or crashed first.
+    pub snapshot: Option<SnapshotRecord>,
+    pub duration: Duration,
+}
+
+/// The rendering of an `expect Inspect.snapshot(value)`, and the stored one it was compared with.
+#[derive(Debug, Clone)]
+pub struct SnapshotRecord {
+    /// None if the snapshot wasn't stored yet.
+    pub stored: Option<String>,
+    pub current: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestReporter {
+    /// JUnit XML, with a test suite per module.
+    Junit,
+    /// The Test Anything Protocol, version 13.
+    Tap,
+    /// A JSON array with one record per test.
+    Json,
+}
+
+impl TestReporter {
+    pub const FLAG_VALUES: [&'static str; 3] = ["junit", "tap", "json"];
+
+    pub fn report(self, records: &[TestRecord]) -> String {
+        match self {
+            TestReporter::Junit => junit_report(records),
+            TestReporter::Tap => tap_report(records),
+            TestReporter::Json => json_report(records),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for TestReporter {
+    type Error = ();
+
+    fn try_from(flag_value: &'a str) -> Result<Self, Self::Error> {
+        match flag_value {
+            "junit" => Ok(TestReporter::Junit),
+            "tap" => Ok(TestReporter::Tap),
+            "json" => Ok(TestReporter::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+fn junit_report(records: &[TestRecord]) -> String {
+    // One test suite per module, in the order that they ran
+    let mut suites: Vec<(&str, Vec<&TestRecord>)> = Vec::new();
+
+    for record in records {
+        let module_path = record.name.module_path.as_str();
+
+        match suites.iter_mut().find(|(path, _)| *path == module_path) {
+            Some((_, suite)) => suite.push(record),
+            None => suites.push((module_path, vec![record])),
+        }
+    }
+
+    let failures = |records: &[&TestRecord]| records.iter().filter(|r| r.failure.is_some()).count();
+    let seconds = |records: &[&TestRecord]| {
+        records
+            .iter()
+            .map(|record| record.duration)
+            .sum::<Duration>()
+            .as_secs_f64()
+    };
+
+    let all: Vec<_> = records.iter().collect();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+
+    // Writing to a String can't fail.
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"roc test\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">",
+        all.len(),
+        failures(&all),
+        seconds(&all),
+    );
+
+    for (module_path, suite) in suites {
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">",
+            xml_escape(module_path),
+            suite.len(),
+            failures(&suite),
+            seconds(&suite),
+        );
+
+        for record in suite {
+            let _ = writeln!(
+                xml,
+                "    <testcase name=\"{}\" classname=\"{}\" file=\"{}\" line=\"{}\" time=\"{:.3}\">",
+                xml_escape(&record.name.to_string()),
+                xml_escape(module_path),
+                xml_escape(module_path),
+                record.name.line,
+                record.duration.as_secs_f64(),
+            );
+            let _ = writeln!(
+                xml,
+                "      <properties>\n        <property name=\"source\" value=\"{}\"/>\n      </properties>",
+                xml_escape(&record.source),
+            );
+
+            if let Some(failure) = &record.failure {
+                let _ = writeln!(
+                    xml,
+                    "      <failure message=\"This expectation failed\">{}</failure>",
+                    xml_escape(failure),
+                );
+            }
+
+            xml.push_str("    </testcase>\n");
+        }
+
+        xml.push_str("  </testsuite>\n");
+    }
+
+    xml.push_str("</testsuites>\n");
+
+    xml
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            '\t' | '\r' => escaped.push(c),
+            // Other control characters aren't allowed in XML at all
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn tap_report(records: &[TestRecord]) -> String {
+    let mut tap = format!("TAP version 13\n1..{}\n", records.len());
+
+    for (index, record) in records.iter().enumerate() {
+        let status = match record.failure {
+            Some(_) => "not ok",
+            None => "ok",
+        };
+
+        // Writing to a String can't fail.
+        let _ = writeln!(tap, "{status} {} - {}", index + 1, record.name);
+        let _ = writeln!(
+            tap,
+            "  ---\n  duration_ms: {:.3}",
+            record.duration.as_secs_f64() * 1000.0
+        );
+
+        tap_yaml_block(&mut tap, "source", &record.source);
+
+        if let Some(failure) = &record.failure {
+            tap_yaml_block(&mut tap, "failure", failure);
+        }
+
+        tap.push_str("  ...\n");
+    }
+
+    tap
+}
+
+/// A YAML literal block, indented to fit in a TAP diagnostic.
+fn tap_yaml_block(tap: &mut String, key: &str, text: &str) {
+    let _ = writeln!(tap, "  {key}: |");
+
+    for line in text.trim_end().lines() {
+        let _ = writeln!(tap, "    {line}");
+    }
+}
+
+fn json_report(records: &[TestRecord]) -> String {
+    let records = records
+        .iter()
+        .map(|record| {
+            json!({
+                "module": record.name.module_path,
+                "line": record.name.line,
+                "name": record.name.to_string(),
+                "source": record.source,
+                "passed": record.failure.is_none(),
+                "failure": record.failure,
+                "snapshot": record.snapshot.as_ref().map(|snapshot| json!({
+                    "stored": snapshot.stored,
+                    "current": snapshot.current,
+                })),
+                "duration_ms": record.duration.as_secs_f64() * 1000.0,
+            })
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&Value::Array(records))
+        .expect("serializing a JSON value cannot fail")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records() -> Vec<TestRecord> {
+        let record = |line, source: &str, failure: Option<&str>| TestRecord {
+            name: TestName {
+                module_path: "Main.roc".to_string(),
+                line,
+            },
+            source: source.to_string(),
+            failure: failure.map(str::to_string),
+            snapshot: None,
+            duration: Duration::from_millis(2),
+        };
+
+        vec![
+            record(5, "expect double(2) == 4", None),
+            record(
+                7,
+                "expect\n    result = double(3)\n    result == 7",
+                Some("This expectation failed:\n\nresult = 6 & \"<\"\n"),
+            ),
+        ]
+    }
+
+    #[test]
+    fn tap_report_of_failure() {
+        assert_eq!(
+            TestReporter::Tap.report(&records()),
+            "TAP version 13\n1..2\n\
+             ok 1 - Main.roc:5\n  ---\n  duration_ms: 2.000\n  source: |\n    expect double(2) == 4\n  ...\n\
+             not ok 2 - Main.roc:7\n  ---\n  duration_ms: 2.000\n  source: |\n    expect\n        result = double(3)\n        result == 7\n  \
+             failure: |\n    This expectation failed:\n    \n    result = 6 & \"<\"\n  ...\n"
+        );
+    }
+
+    #[test]
+    fn junit_report_of_failure() {
+        let xml = TestReporter::Junit.report(&records());
+
+        assert!(
+            xml.contains("<testsuite name=\"Main.roc\" tests=\"2\" failures=\"1\" time=\"0.004\">"),
+            "{xml}"
+        );
+        assert!(
+            xml.contains("<failure message=\"This expectation failed\">This expectation failed:&#10;&#10;result = 6 &amp; &quot;&lt;&quot;&#10;</failure>"),
+            "{xml}"
+        );
+        assert_eq!(xml.matches("<failure ").count(), 1, "{xml}");
+    }
+
+    #[test]
+    fn json_report_of_failure() {
+        let json: Value = serde_json::from_str(&TestReporter::Json.report(&records())).unwrap();
+
+        assert_eq!(json[0]["passed"], true);
+        assert_eq!(json[0]["failure"], Value::Null);
+        assert_eq!(json[1]["name"], "Main.roc:7");
+        assert_eq!(json[1]["passed"], false);
+        assert_eq!(
+            json[1]["failure"],
+            "This expectation failed:\n\nresult = 6 & \"<\"\n"
+        );
+        assert_eq!(json[1]["snapshot"], Value::Null);
+    }
+
+    #[test]
+    fn json_report_of_snapshot() {
+        let record = TestRecord {
+            name: TestName {
+                module_path: "Main.roc".to_string(),
+                line: 9,
+            },
+            source: "expect Inspect.snapshot(sort([2, 1]))".to_string(),
+            failure: Some("This snapshot doesn't match the stored one".to_string()),
+            snapshot: Some(SnapshotRecord {
+                stored: Some("[2, 1]".to_string()),
+                current: "[1, 2]".to_string(),
+            }),
+            duration: Duration::from_millis(2),
+        };
+
+        let json: Value = serde_json::from_str(&TestReporter::Json.report(&[record])).unwrap();
+
+        assert_eq!(json[0]["passed"], false);
+        assert_eq!(json[0]["snapshot"]["stored"], "[2, 1]");
+        assert_eq!(json[0]["snapshot"]["current"], "[1, 2]");
+    }
+}
@@ -0,0 +1,273 @@
+//! Re-running `roc check`, `roc test` and `roc dev` whenever their .roc files change.
+//!
+//! Solved modules can be kept in memory between runs (see [roc_load::SolvedModules]), so only
+//! the modules that changed (and the modules that import them) are checked again.
+use std::ffi::OsString;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::{find_all_roc_files, FLAG_CLEAR, FLAG_WATCH};
+
+/// How often the watched files are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Set for the `roc` process spawned by [watch_in_child_process]. It names the file that
+/// the child writes the paths of the modules it loaded to, one per line.
+const SOURCE_PATHS_FILE_VAR: &str = "ROC_WATCH_SOURCE_PATHS_FILE";
+
+/// The last-modified times of the files that a run depended on.
+pub(crate) struct WatchedFiles {
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl WatchedFiles {
+    pub(crate) fn new(mut paths: Vec<PathBuf>) -> Self {
+        paths.sort();
+        paths.dedup();
+
+        let files = paths
+            .into_iter()
+            .map(|path| {
+                let modified = modified_time(&path);
+
+                (path, modified)
+            })
+            .collect();
+
+        Self { files }
+    }
+
+    /// Whether any of the files was modified, created or deleted since it was recorded.
+    pub(crate) fn any_changed(&self) -> bool {
+        self.files
+            .iter()
+            .any(|(path, modified)| modified_time(path) != *modified)
+    }
+
+    pub(crate) fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.files.iter().map(|(path, _)| path)
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// The .roc files to watch when a run couldn't tell us which modules it loaded,
+/// e.g. because the main module failed to parse.
+fn fallback_paths(roots: &[PathBuf]) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+
+    for root in roots {
+        let dir = if root.is_dir() {
+            root.clone()
+        } else {
+            // Watch the root itself too, in case it doesn't exist yet.
+            paths.push(root.clone());
+
+            match root.parent() {
+                Some(parent) if parent != Path::new("") => parent.to_path_buf(),
+                _ => PathBuf::from("."),
+            }
+        };
+
+        find_all_roc_files(&dir, &mut paths);
+    }
+
+    paths
+}
+
+fn clear_terminal() {
+    // Clear the screen and the scrollback, then move the cursor to the top left.
+    print!("\x1B[2J\x1B[3J\x1B[H");
+    let _ = io::stdout().flush();
+}
+
+fn wait_for_changes(watched: &WatchedFiles) {
+    // stdout may be reserved for a machine-readable report
+    eprintln!(
+        "\nWatching {} file{} for changes…",
+        watched.files.len(),
+        if watched.files.len() == 1 { "" } else { "s" }
+    );
+
+    while !watched.any_changed() {
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Calls `run` again every time one of the source files it loaded changes.
+///
+/// `run` pushes the paths of the modules it loaded (e.g. the `sources` of the loaded module)
+/// into the given `Vec`. If it doesn't push anything, all the .roc files in the directories
+/// of `roots` are watched instead.
+///
+/// This only returns if `run` fails with an I/O error; users stop watching with Ctrl+C.
+pub fn watch<F>(clear: bool, roots: &[PathBuf], mut run: F) -> io::Result<i32>
+where
+    F: FnMut(&mut Vec<PathBuf>) -> io::Result<i32>,
+{
+    loop {
+        if clear {
+            clear_terminal();
+        }
+
+        let mut source_paths = Vec::new();
+
+        // The exit code only matters for a single run; we keep watching regardless.
+        run(&mut source_paths)?;
+
+        if source_paths.is_empty() {
+            source_paths = fallback_paths(roots);
+        }
+
+        wait_for_changes(&WatchedFiles::new(source_paths));
+    }
+}
+
+/// Like [watch], but for commands that run a program, like `roc dev`.
+///
+/// The program gets built and run by a child `roc` process (with the same arguments,
+/// minus the watch flags), so that it can be stopped and restarted when any of the modules
+/// that `root` imports change, even if it never exits on its own.
+pub fn watch_in_child_process(clear: bool, root: &Path) -> io::Result<i32> {
+    let roc_exe = std::env::current_exe()?;
+    let args = args_without_watch_flags(std::env::args_os().skip(1));
+    let source_paths_file =
+        std::env::temp_dir().join(format!("roc-watch-{}.txt", std::process::id()));
+
+    loop {
+        if clear {
+            clear_terminal();
+        }
+
+        let mut child = Command::new(&roc_exe)
+            .args(&args)
+            .env(SOURCE_PATHS_FILE_VAR, &source_paths_file)
+            .spawn()?;
+        let mut source_paths = wait_for_source_paths(&mut child, &source_paths_file)?;
+
+        if source_paths.is_empty() {
+            source_paths = fallback_paths(&[root.to_path_buf()]);
+        }
+
+        let watched = WatchedFiles::new(source_paths);
+
+        wait_for_child_or_changes(&mut child, &watched)?;
+    }
+}
+
+fn wait_for_child_or_changes(child: &mut Child, watched: &WatchedFiles) -> io::Result<()> {
+    loop {
+        if watched.any_changed() {
+            // The program is still running with outdated code, so stop it.
+            // This fails if it exited in the meantime, which is fine.
+            let _ = child.kill();
+            child.wait()?;
+
+            return Ok(());
+        }
+
+        if child.try_wait()?.is_some() {
+            wait_for_changes(watched);
+
+            return Ok(());
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Waits until `child` reported the paths of the modules it loaded (see [report_source_paths]),
+/// or exited without doing so, e.g. because the main module failed to parse.
+fn wait_for_source_paths(child: &mut Child, file: &Path) -> io::Result<Vec<PathBuf>> {
+    loop {
+        // Check this before reading the file, so that paths written right before exiting are read.
+        let exited = child.try_wait()?.is_some();
+
+        match std::fs::read_to_string(file) {
+            Ok(contents) => {
+                // The next run writes its own.
+                let _ = std::fs::remove_file(file);
+
+                return Ok(contents.lines().map(PathBuf::from).collect());
+            }
+            Err(_) if exited => return Ok(Vec::new()),
+            Err(_) => thread::sleep(POLL_INTERVAL),
+        }
+    }
+}
+
+/// When this process was spawned by [watch_in_child_process], tells the parent which modules
+/// were loaded, so it knows which files to watch.
+pub(crate) fn report_source_paths<'a>(paths: impl IntoIterator<Item = &'a PathBuf>) {
+    let Some(file) = std::env::var_os(SOURCE_PATHS_FILE_VAR).map(PathBuf::from) else {
+        return;
+    };
+
+    let contents: String = paths
+        .into_iter()
+        .map(|path| format!("{}\n", path.display()))
+        .collect();
+
+    // Write to another file first, so that the parent never reads a partial list.
+    let partial = file.with_extension("partial");
+    let result = std::fs::write(&partial, contents).and_then(|()| std::fs::rename(&partial, &file));
+
+    if let Err(err) = result {
+        eprintln!("Could not tell `roc --{FLAG_WATCH}` which files to watch: {err}");
+    }
+}
+
+/// Removes `--watch` and `--clear`, but leaves alone any arguments meant for the app,
+/// which come after `--`.
+fn args_without_watch_flags(args: impl Iterator<Item = OsString>) -> Vec<OsString> {
+    let watch_flag = format!("--{FLAG_WATCH}");
+    let clear_flag = format!("--{FLAG_CLEAR}");
+    let mut reached_app_args = false;
+
+    args.filter(|arg| {
+        if reached_app_args {
+            return true;
+        }
+
+        if *arg == "--" {
+            reached_app_args = true;
+
+            return true;
+        }
+
+        arg.as_os_str() != watch_flag.as_str() && arg.as_os_str() != clear_flag.as_str()
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strip(args: &[&str]) -> Vec<OsString> {
+        args_without_watch_flags(args.iter().map(OsString::from))
+    }
+
+    #[test]
+    fn strips_watch_flags() {
+        assert_eq!(
+            strip(&["dev", "--watch", "--clear", "main.roc"]),
+            strip(&["dev", "main.roc"])
+        );
+    }
+
+    #[test]
+    fn keeps_app_args() {
+        assert_eq!(
+            strip(&["dev", "--watch", "main.roc", "--", "--watch", "--clear"]),
+            strip(&["dev", "main.roc", "--", "--watch", "--clear"])
+        );
+    }
+}
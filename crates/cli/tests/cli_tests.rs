@@ -252,6 +252,295 @@ mod cli_tests {
         insta::assert_snapshot!(cli_test_out.normalize_stdout_and_stderr());
     }
 
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn transitive_expects_list() {
+        let cli_test = ExecCli::new(
+            CMD_TEST,
+            file_from_root(
+                "crates/cli/tests/test-projects/expects_transitive",
+                "main.roc",
+            ),
+        )
+        .arg("--list");
+
+        cli_test
+            .run()
+            .assert_clean_stdout("Direct.roc:10\nDirect.roc:12\nTransitive.roc:7\n");
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn transitive_expects_filter_and_slowest() {
+        let cli_test = ExecCli::new(
+            CMD_TEST,
+            file_from_root(
+                "crates/cli/tests/test-projects/expects_transitive",
+                "main.roc",
+            ),
+        )
+        .add_args(["--filter", "Direct.roc:10", "--slowest", "1"]);
+
+        let cli_test_out = cli_test.run();
+        cli_test_out.assert_clean_success();
+        cli_test_out.assert_stdout_and_stderr_ends_with(
+            "0 failed and 1 passed in <ignored for test> ms.\n\nSlowest tests:\n    Direct.roc:10 ran in <ignored for test> ms.\n",
+        );
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn expects_coverage() {
+        let lcov_dir = tempfile::tempdir().unwrap();
+        let lcov_path = lcov_dir.path().join("lcov.info");
+
+        let cli_test = ExecCli::new(
+            CMD_TEST,
+            file_from_root(
+                "crates/cli/tests/test-projects/expects_coverage",
+                "main.roc",
+            ),
+        )
+        .arg("--coverage")
+        .arg(&lcov_path);
+
+        let cli_test_out = cli_test.run();
+        cli_test_out.assert_clean_success();
+        cli_test_out.assert_stdout_and_stderr_ends_with(&format!(
+            "0 failed and 2 passed in <ignored for test> ms.\n\nCoverage:\n    Sign.roc: 2 of 3 branches and 1 of 1 defs ran.\n        These lines never ran: 9\n\nWrote the counts of every branch and def to {}.\n",
+            lcov_path.display()
+        ));
+
+        let lcov = std::fs::read_to_string(&lcov_path).unwrap();
+        assert!(lcov.contains("FN:6,describe\nFNDA:2,describe\n"), "{lcov}");
+        assert!(lcov.contains("BRDA:9,0,1,0\n"), "{lcov}");
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn transitive_expects_tap_reporter() {
+        let cli_test = ExecCli::new(
+            CMD_TEST,
+            file_from_root(
+                "crates/cli/tests/test-projects/expects_transitive",
+                "main.roc",
+            ),
+        )
+        .add_args(["--filter", "Direct.roc", "--reporter", "tap"]);
+
+        let cli_test_out = cli_test.run();
+        cli_test_out.assert_clean_success();
+
+        // The report goes to stdout, and the usual output to stderr.
+        assert!(
+            cli_test_out
+                .stdout
+                .starts_with("TAP version 13\n1..2\nok 1 - Direct.roc:10\n"),
+            "Unexpected report:\n{}",
+            cli_test_out.stdout
+        );
+        assert!(cli_test_out.stdout.contains("ok 2 - Direct.roc:12\n"));
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn expects_junit_reporter_output() {
+        let report_dir = tempfile::tempdir().unwrap();
+        let report_path = report_dir.path().join("report.xml");
+
+        let cli_test = ExecCli::new(
+            CMD_TEST,
+            file_from_root(
+                "crates/cli/tests/test-projects/expects_reporter",
+                "Main.roc",
+            ),
+        )
+        .add_args(["--reporter", "junit", "--output"])
+        .arg(&report_path);
+
+        let cli_test_out = cli_test.run();
+        cli_test_out.assert_nonzero_exit();
+
+        // With the report in a file, the usual output stays on stdout.
+        assert!(
+            cli_test_out.stdout.contains("1 failed and 1 passed"),
+            "{}",
+            cli_test_out.stdout
+        );
+
+        let xml = std::fs::read_to_string(&report_path).unwrap();
+
+        assert!(
+            xml.contains("<testsuites name=\"roc test\" tests=\"2\" failures=\"1\""),
+            "{xml}"
+        );
+        assert!(
+            xml.contains("<testsuite name=\"Main.roc\" tests=\"2\" failures=\"1\""),
+            "{xml}"
+        );
+        assert!(
+            xml.contains(
+                "<testcase name=\"Main.roc:5\" classname=\"Main.roc\" file=\"Main.roc\" line=\"5\""
+            ),
+            "{xml}"
+        );
+        assert!(
+            xml.contains("<property name=\"source\" value=\"expect&#10;    result = double(3)&#10;    result == 7\"/>"),
+            "{xml}"
+        );
+        assert_eq!(xml.matches("<failure ").count(), 1, "{xml}");
+        assert!(
+            xml.contains("<failure message=\"This expectation failed\">"),
+            "{xml}"
+        );
+        assert!(xml.contains("result = 6"), "{xml}");
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn expects_json_reporter_output() {
+        let report_dir = tempfile::tempdir().unwrap();
+        let report_path = report_dir.path().join("report.json");
+
+        let cli_test = ExecCli::new(
+            CMD_TEST,
+            file_from_root(
+                "crates/cli/tests/test-projects/expects_reporter",
+                "Main.roc",
+            ),
+        )
+        .add_args(["--reporter", "json", "--output"])
+        .arg(&report_path);
+
+        let cli_test_out = cli_test.run();
+        cli_test_out.assert_nonzero_exit();
+        assert!(
+            cli_test_out.stdout.contains("1 failed and 1 passed"),
+            "{}",
+            cli_test_out.stdout
+        );
+
+        let json = std::fs::read_to_string(&report_path).unwrap();
+        let records: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(records.as_array().map(Vec::len), Some(2), "{json}");
+        assert_eq!(records[0]["name"], "Main.roc:5", "{json}");
+        assert_eq!(records[0]["passed"], true, "{json}");
+        assert_eq!(records[0]["failure"], serde_json::Value::Null, "{json}");
+        assert_eq!(records[1]["module"], "Main.roc", "{json}");
+        assert_eq!(records[1]["line"], 7, "{json}");
+        assert_eq!(records[1]["passed"], false, "{json}");
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn expects_json_reporter_failure() {
+        let cli_test = ExecCli::new(
+            CMD_TEST,
+            file_from_root(
+                "crates/cli/tests/test-projects/expects_reporter",
+                "Main.roc",
+            ),
+        )
+        .add_args(["--reporter", "json"]);
+
+        let cli_test_out = cli_test.run();
+        cli_test_out.assert_nonzero_exit();
+
+        // Without an `--output` file, the report is all that goes to stdout.
+        let stdout = cli_test_out.stdout.trim();
+        let records: serde_json::Value = serde_json::from_str(stdout).unwrap_or_else(|err| {
+            panic!("stdout is not a single JSON document ({err}):\n{stdout}")
+        });
+        let failure = records[1]["failure"].as_str().unwrap_or_default();
+
+        assert!(
+            cli_test_out.stderr.contains("1 failed and 1 passed"),
+            "{}",
+            cli_test_out.stderr
+        );
+        assert_eq!(records[1]["name"], "Main.roc:7", "{stdout}");
+        assert_eq!(
+            records[1]["source"], "expect\n    result = double(3)\n    result == 7",
+            "{stdout}"
+        );
+        // The failure is rendered like it is in the usual output, but without colors.
+        assert!(failure.contains("This expectation failed:"), "{stdout}");
+        assert!(failure.contains("result = 6"), "{stdout}");
+        assert!(!failure.contains('\u{1b}'), "{stdout}");
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn expects_failing_properties() {
+        let run_test = |args: &[&str]| {
+            let cli_test = ExecCli::new(
+                CMD_TEST,
+                file_from_root(
+                    "crates/cli/tests/test-projects/expects_property",
+                    "Main.roc",
+                ),
+            )
+            .add_args(args);
+
+            let cli_test_out = cli_test.run();
+            cli_test_out.assert_nonzero_exit();
+
+            // Without colors, and without the time it took, which changes between runs.
+            let output = cli_test_out.normalize_stdout_and_stderr();
+            assert!(output.contains("2 failed and 0 passed"), "{output}");
+
+            output
+        };
+
+        let failures = run_test(&[]);
+
+        // Every failing case is shrunk to the smallest size that it still fails with.
+        assert!(failures.contains("count = 1\n"), "{failures}");
+        assert_eq!(
+            failures
+                .matches(
+                    "To run this property with the same arguments again, use `roc test --seed "
+                )
+                .count(),
+            2,
+            "{failures}"
+        );
+
+        let seed = failures
+            .split("`roc test --seed ")
+            .nth(1)
+            .and_then(|rest| rest.split('`').next())
+            .unwrap();
+
+        assert_eq!(run_test(&["--seed", seed]), failures);
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn json_reporter_and_json_format_need_an_output_file() {
+        let cli_test = ExecCli::new(
+            CMD_TEST,
+            file_from_root(
+                "crates/cli/tests/test-projects/expects_reporter",
+                "Main.roc",
+            ),
+        )
+        .add_args(["--reporter", "json", "--format", "json"]);
+
+        let cli_test_out = cli_test.run();
+        cli_test_out.assert_nonzero_exit();
+
+        assert!(cli_test_out.stdout.is_empty(), "{}", cli_test_out.stdout);
+        assert!(
+            cli_test_out
+                .stderr
+                .contains("`--reporter` needs an `--output` file"),
+            "{}",
+            cli_test_out.stderr
+        );
+    }
+
     #[test]
     #[cfg_attr(windows, ignore)]
     fn multiple_exposed() {
@@ -278,6 +567,76 @@ mod cli_tests {
         );
     }
 
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn build_unlinked_object_for_linux_arm32_and_riscv64() {
+        let out_dir = tempfile::tempdir().unwrap();
+
+        // The e_machine and e_flags that C compilers for these targets use too
+        let targets: [(&str, u16, u32); 2] = [
+            // EM_ARM, with version 5 of the EABI and the hard-float ABI
+            ("linux-arm32", 40, 0x0500_0400),
+            // EM_RISCV, with compressed instructions and the double-precision float ABI
+            ("linux-riscv64", 243, 0x0000_0005),
+        ];
+
+        for (target, expected_machine, expected_flags) in targets {
+            let object_path = out_dir.path().join(format!("{target}.o"));
+
+            let cli_build = ExecCli::new(
+                CMD_BUILD,
+                file_from_root(
+                    "crates/cli/tests/test-projects/test-platform-simple-zig",
+                    "app.roc",
+                ),
+            )
+            .arg(format!("{TARGET_FLAG}={target}"))
+            .arg(concatcp!("--", roc_cli::FLAG_NO_LINK))
+            .arg(concatcp!("--", roc_cli::FLAG_OUTPUT))
+            .arg(&object_path);
+
+            cli_build.run().assert_clean_success();
+
+            let object = std::fs::read(&object_path).unwrap();
+            assert_eq!(&object[..4], b"\x7fELF", "{target}");
+
+            // e_flags comes after three fields that are as wide as a pointer
+            let is_64_bit = object[4] == 2;
+            let flags_offset = if is_64_bit { 48 } else { 36 };
+
+            let machine = u16::from_le_bytes([object[18], object[19]]);
+            let flags =
+                u32::from_le_bytes(object[flags_offset..flags_offset + 4].try_into().unwrap());
+
+            assert_eq!(machine, expected_machine, "{target}");
+            assert_eq!(flags, expected_flags, "{target}: {flags:#x}");
+        }
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn build_dev_backend_rejects_riscv64() {
+        let cli_build = ExecCli::new(
+            CMD_BUILD,
+            file_from_root(
+                "crates/cli/tests/test-projects/test-platform-simple-zig",
+                "app.roc",
+            ),
+        )
+        .arg(concatcp!("--", roc_cli::FLAG_DEV))
+        .arg(format!("{TARGET_FLAG}=linux-riscv64"))
+        .arg(concatcp!("--", roc_cli::FLAG_NO_LINK));
+
+        let cli_build_out = cli_build.run();
+        cli_build_out.assert_nonzero_exit();
+
+        assert!(
+            (cli_build_out.stderr).contains("The dev backend can't build for linux-riscv64 yet"),
+            "{}",
+            cli_build_out.stderr
+        );
+    }
+
     #[test]
     #[cfg_attr(windows, ignore)]
     fn test_module_imports_pkg_w_flag() {
@@ -1387,6 +1746,96 @@ mod cli_tests {
         insta::assert_snapshot!(cli_check_out.normalize_stdout_and_stderr());
     }
 
+    #[test]
+    fn unused_import_json() {
+        let cli_check = ExecCli::new(
+            CMD_CHECK,
+            file_from_root(
+                "crates/cli/tests/test-projects/known_bad",
+                "UnusedImport.roc",
+            ),
+        )
+        .add_args(["--format", "json"]);
+
+        let cli_check_out = cli_check.run();
+        cli_check_out.assert_nonzero_exit();
+
+        // stdout only contains the JSON document, without the error and warning count
+        let stdout = cli_check_out.stdout.trim();
+        let compact: String = stdout.split_whitespace().collect();
+        assert!(stdout.starts_with('[') && stdout.ends_with(']'), "{stdout}");
+        assert!(compact.contains(r#""severity":"warning""#), "{stdout}");
+        assert!(compact.contains(r#""title":"UNUSEDIMPORT""#), "{stdout}");
+        assert!(compact.contains(r#""line":3"#), "{stdout}");
+        assert!(!stdout.contains("warning found"), "{stdout}");
+    }
+
+    #[test]
+    fn unused_import_sarif() {
+        let cli_check = ExecCli::new(
+            CMD_CHECK,
+            file_from_root(
+                "crates/cli/tests/test-projects/known_bad",
+                "UnusedImport.roc",
+            ),
+        )
+        .add_args(["--format", "sarif"]);
+
+        let cli_check_out = cli_check.run();
+        cli_check_out.assert_nonzero_exit();
+
+        let stdout = cli_check_out.stdout.trim();
+        let sarif: serde_json::Value = serde_json::from_str(stdout).unwrap_or_else(|err| {
+            panic!("stdout is not a single JSON document ({err}):\n{stdout}")
+        });
+        let result = &sarif["runs"][0]["results"][0];
+        let location = &result["locations"][0]["physicalLocation"];
+
+        assert_eq!(sarif["version"], "2.1.0", "{stdout}");
+        assert_eq!(result["ruleId"], "UNUSED IMPORT", "{stdout}");
+        assert_eq!(result["level"], "warning", "{stdout}");
+        assert_eq!(location["region"]["startLine"], 3, "{stdout}");
+        assert!(
+            location["artifactLocation"]["uri"]
+                .as_str()
+                .is_some_and(|uri| uri.ends_with("UnusedImport.roc")),
+            "{stdout}"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn test_json_multiple_files_is_one_document() {
+        let cli_test = ExecCli::new(
+            CMD_TEST,
+            dir_from_root("crates/cli/tests/test-projects/json_warnings"),
+        )
+        .add_args(["--format", "json"]);
+
+        let cli_test_out = cli_test.run();
+
+        // the problems of every tested file end up in the same document
+        let stdout = cli_test_out.stdout.trim();
+        let diagnostics: serde_json::Value = serde_json::from_str(stdout).unwrap_or_else(|err| {
+            panic!("stdout is not a single JSON document ({err}):\n{stdout}")
+        });
+        let mut module_paths: Vec<&str> = diagnostics
+            .as_array()
+            .unwrap_or_else(|| panic!("expected a JSON array:\n{stdout}"))
+            .iter()
+            .map(|diagnostic| {
+                assert_eq!(diagnostic["title"], "UNUSED DEFINITION", "{stdout}");
+
+                diagnostic["module_path"].as_str().unwrap()
+            })
+            .collect();
+        module_paths.sort_unstable();
+
+        assert_eq!(module_paths.len(), 2, "{stdout}");
+        assert!(module_paths[0].ends_with("Double.roc"), "{stdout}");
+        assert!(module_paths[1].ends_with("Triple.roc"), "{stdout}");
+    }
+
     #[test]
     fn format_check_good() {
         ExecCli::new(
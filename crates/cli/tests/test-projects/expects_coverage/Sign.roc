@@ -0,0 +1,15 @@
+module [
+    describe,
+]
+
+describe = \num ->
+    if num < 0 then
+        "negative"
+    else if num == 0 then
+        "zero"
+    else
+        "positive"
+
+expect describe(-1) == "negative"
+
+expect describe(1) == "positive"
@@ -0,0 +1,13 @@
+module [halve]
+
+halve : U64 -> U64
+halve = \n -> n // 2
+
+# Only 0 is its own half, so the smallest count that this fails for is 1.
+expect \count -> halve(count) == count
+
+expect \bytes ->
+    list : List U8
+    list = bytes
+
+    List.len(list) < 3
@@ -0,0 +1,9 @@
+module [double]
+
+double = \n -> n * 2
+
+expect double(2) == 4
+
+expect
+    result = double(3)
+    result == 7
@@ -0,0 +1,7 @@
+module [double]
+
+double = |x|
+    unused = 1
+    x * 2
+
+expect double(2) == 4
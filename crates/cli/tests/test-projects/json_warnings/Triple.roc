@@ -0,0 +1,7 @@
+module [triple]
+
+triple = |x|
+    unused = 1
+    x * 3
+
+expect triple(2) == 6
@@ -271,7 +271,12 @@ where
                     m.add_func(entry_point_name, entry_point_function)?;
                 }
             }
-            EntryPoint::Expects { symbols } => {
+            EntryPoint::Expects {
+                symbols,
+                properties,
+                benches,
+                snapshots,
+            } => {
                 // construct a big pattern match picking one of the expects at random
                 let layout: ProcLayout<'a> = ProcLayout {
                     arguments: &[],
@@ -279,14 +284,44 @@ where
                     niche: Niche::NONE,
                 };
 
+                let runner = roc_mono::property::RUNNER_LAYOUT;
+                let bench_runner = roc_mono::bench::RUNNER_LAYOUT;
+
                 let host_exposed: Vec<_> = symbols
                     .iter()
                     .map(|symbol| {
+                        let result = match snapshots.contains(symbol) {
+                            true => Layout::STR,
+                            false => layout.result,
+                        };
+
                         (
-                            func_name_bytes_help(*symbol, [], Niche::NONE, layout.result),
+                            func_name_bytes_help(*symbol, [], Niche::NONE, result),
                             [].as_slice(),
                         )
                     })
+                    .chain(properties.iter().map(|symbol| {
+                        (
+                            func_name_bytes_help(
+                                *symbol,
+                                runner.arguments.iter().copied(),
+                                runner.niche,
+                                runner.result,
+                            ),
+                            runner.arguments,
+                        )
+                    }))
+                    .chain(benches.iter().map(|symbol| {
+                        (
+                            func_name_bytes_help(
+                                *symbol,
+                                bench_runner.arguments.iter().copied(),
+                                bench_runner.niche,
+                                bench_runner.result,
+                            ),
+                            bench_runner.arguments,
+                        )
+                    }))
                     .collect();
 
                 let mut env = Env::new();
@@ -624,6 +659,7 @@ fn stmt_spec<'a>(
             builder.add_choice(block, &cases)
         }
         Dbg { remainder, .. } => stmt_spec(builder, interner, env, block, layout, remainder),
+        Coverage { remainder, .. } => stmt_spec(builder, interner, env, block, layout, remainder),
         Expect { remainder, .. } => stmt_spec(builder, interner, env, block, layout, remainder),
         Ret(symbol) => Ok(env.symbols[symbol]),
         Refcounting(modify_rc, continuation) => {
@@ -476,7 +476,26 @@ pub fn rebuild_host(
                 shared_lib_path,
                 builtins_host_tempfile.path(),
             ),
-            _ => internal_error!("Unsupported architecture {:?}", target.architecture()),
+            Architecture::Aarch32 => build_zig_host_native(
+                &env_path,
+                &env_home,
+                host_dest.to_str().unwrap(),
+                zig_host_src.to_str().unwrap(),
+                "arm-linux-gnueabihf",
+                opt_level,
+                shared_lib_path,
+                builtins_host_tempfile.path(),
+            ),
+            Architecture::Riscv64 => build_zig_host_native(
+                &env_path,
+                &env_home,
+                host_dest.to_str().unwrap(),
+                zig_host_src.to_str().unwrap(),
+                "riscv64-linux-gnu",
+                opt_level,
+                shared_lib_path,
+                builtins_host_tempfile.path(),
+            ),
         };
 
         run_build_command(zig_cmd, "host.zig", 0);
@@ -807,7 +826,11 @@ fn link_linux(
     input_paths: &[&str],
     link_type: LinkType,
 ) -> io::Result<(Child, PathBuf)> {
-    let architecture = format!("{}-linux-gnu", target.architecture());
+    // The multiarch directory names that Debian and its derivatives install libraries into
+    let architecture = match target.architecture() {
+        Architecture::Aarch32 => "arm-linux-gnueabihf".to_string(),
+        arch => format!("{arch}-linux-gnu"),
+    };
 
     //    Command::new("cp")
     //        .args(&[input_paths[0], "/home/folkertdev/roc/wasm/host.o"])
@@ -924,6 +947,8 @@ fn link_linux(
             }
         }
         Architecture::Aarch64 => build_path_or_panic(["/lib", "ld-linux-aarch64.so.1"]),
+        Architecture::Aarch32 => build_path_or_panic(["/lib", "ld-linux-armhf.so.3"]),
+        Architecture::Riscv64 => build_path_or_panic(["/lib", "ld-linux-riscv64-lp64d.so.1"]),
         _ => internal_error!(
             "TODO gracefully handle unsupported linux architecture: {:?}",
             target.architecture()
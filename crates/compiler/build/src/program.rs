@@ -8,13 +8,13 @@ use roc_gen_llvm::llvm::build::{module_from_builtins, LlvmBackendMode};
 use roc_gen_llvm::llvm::externs::add_default_roc_externs;
 use roc_load::{
     EntryPoint, ExecutionMode, ExpectMetadata, FunctionKind, LoadConfig, LoadMonomorphizedError,
-    LoadedModule, LoadingProblem, MonomorphizedModule, Threading,
+    LoadedModule, LoadingProblem, MonomorphizedModule, SolvedModules, Threading,
 };
 use roc_mono::ir::{OptLevel, SingleEntryPoint};
 use roc_packaging::cache::RocCacheDir;
 use roc_reporting::{
-    cli::{report_problems, Problems},
-    report::{RenderTarget, DEFAULT_PALETTE},
+    cli::{report_problems, Diagnostics, Problems, ReportFormat},
+    report::DEFAULT_PALETTE,
 };
 use roc_target::{Architecture, Target};
 use std::ffi::OsStr;
@@ -37,21 +37,29 @@ pub struct CodeGenTiming {
     pub total: Duration,
 }
 
-pub fn report_problems_monomorphized(loaded: &mut MonomorphizedModule) -> Problems {
+pub fn report_problems_monomorphized(
+    loaded: &mut MonomorphizedModule,
+    diagnostics: &mut Diagnostics,
+) -> Problems {
     report_problems(
         &loaded.sources,
         &loaded.interns,
         &mut loaded.can_problems,
         &mut loaded.type_problems,
+        diagnostics,
     )
 }
 
-pub fn report_problems_typechecked(loaded: &mut LoadedModule) -> Problems {
+pub fn report_problems_typechecked(
+    loaded: &mut LoadedModule,
+    diagnostics: &mut Diagnostics,
+) -> Problems {
     report_problems(
         &loaded.sources,
         &loaded.interns,
         &mut loaded.can_problems,
         &mut loaded.type_problems,
+        diagnostics,
     )
 }
 
@@ -245,7 +253,12 @@ fn gen_from_mono_module_llvm<'a>(
 
             roc_mono::ir::EntryPoint::Program(entry_points.into_bump_slice())
         }
-        EntryPoint::Test => roc_mono::ir::EntryPoint::Expects { symbols: &[] },
+        EntryPoint::Test => roc_mono::ir::EntryPoint::Expects {
+            symbols: &[],
+            properties: &[],
+            benches: &[],
+            snapshots: &[],
+        },
     };
 
     roc_gen_llvm::llvm::build::build_procedures(
@@ -373,7 +386,11 @@ fn gen_from_mono_module_llvm<'a>(
 
         // Emit the .o file
         match target.architecture() {
-            Architecture::X86_64 | Architecture::X86_32 | Architecture::Aarch64 => {
+            Architecture::X86_64
+            | Architecture::X86_32
+            | Architecture::Aarch64
+            | Architecture::Aarch32
+            | Architecture::Riscv64 => {
                 let reloc = RelocMode::PIC;
                 let target_machine =
                     target::target_machine(target, convert_opt_level(opt_level), reloc).unwrap();
@@ -387,10 +404,6 @@ fn gen_from_mono_module_llvm<'a>(
                 // module.print_to_file(app_ll_file);
                 module.write_bitcode_to_memory()
             }
-            _ => internal_error!(
-                "TODO gracefully handle unsupported architecture: {:?}",
-                target.architecture()
-            ),
         }
     };
 
@@ -462,6 +475,9 @@ fn gen_from_mono_module_dev<'a>(
         (_, Architecture::Aarch32) => {
             internal_error!("Dev compiler backend does not support 32 bit ARM architectures")
         }
+        (_, Architecture::Riscv64) => {
+            internal_error!("Dev compiler backend does not support RISC-V architectures")
+        }
         (_, Architecture::X86_32) => {
             internal_error!("Dev compiler backend does not support 32 bit x86 architectures")
         }
@@ -615,6 +631,8 @@ pub struct BuiltFile<'a> {
     pub problems: Problems,
     pub total_time: Duration,
     pub expect_metadata: ExpectMetadata<'a>,
+    /// The paths of all the modules that were loaded, e.g. to watch them for changes.
+    pub source_paths: Vec<PathBuf>,
 }
 
 pub enum BuildOrdering {
@@ -653,10 +671,15 @@ pub fn handle_error_module(
     total_time: std::time::Duration,
     filename: &OsStr,
     print_run_anyway_hint: bool,
+    diagnostics: &mut Diagnostics,
 ) -> std::io::Result<i32> {
     debug_assert!(module.total_problems() > 0);
 
-    let problems = report_problems_typechecked(&mut module);
+    let problems = report_problems_typechecked(&mut module, diagnostics);
+
+    if diagnostics.format().is_machine_readable() {
+        return Ok(problems.exit_code());
+    }
 
     problems.print_error_warning_count(total_time);
 
@@ -675,10 +698,13 @@ pub fn handle_error_module(
     Ok(problems.exit_code())
 }
 
-pub fn handle_loading_problem(problem: LoadingProblem) -> std::io::Result<i32> {
+pub fn handle_loading_problem(
+    problem: LoadingProblem,
+    diagnostics: &mut Diagnostics,
+) -> std::io::Result<i32> {
     match problem {
-        LoadingProblem::FormattedReport(report, _) => {
-            print!("{report}");
+        LoadingProblem::FormattedReport(report) => {
+            diagnostics.print_formatted_report(&report);
             Ok(1)
         }
         _ => {
@@ -694,6 +720,7 @@ pub fn standard_load_config(
     target: Target,
     order: BuildOrdering,
     threading: Threading,
+    report_format: ReportFormat,
 ) -> LoadConfig {
     let exec_mode = match order {
         BuildOrdering::BuildIfChecks => ExecutionMode::ExecutableIfCheck,
@@ -703,10 +730,11 @@ pub fn standard_load_config(
     LoadConfig {
         target,
         function_kind: FunctionKind::from_env(),
-        render: RenderTarget::ColorTerminal,
+        render: report_format.render_target(),
         palette: DEFAULT_PALETTE,
         threading,
         exec_mode,
+        solved_modules: None,
     }
 }
 
@@ -726,6 +754,7 @@ pub fn build_file<'a>(
     load_config: LoadConfig,
     out_path: Option<&Path>,
     verbose: bool,
+    diagnostics: &mut Diagnostics,
 ) -> Result<BuiltFile<'a>, BuildFileError<'a>> {
     let compilation_start = Instant::now();
 
@@ -753,6 +782,7 @@ pub fn build_file<'a>(
         compilation_start,
         out_path,
         verbose,
+        diagnostics,
     )
 }
 
@@ -830,6 +860,7 @@ fn build_loaded_file<'a>(
     compilation_start: Instant,
     out_path: Option<&Path>,
     verbose: bool,
+    diagnostics: &mut Diagnostics,
 ) -> Result<BuiltFile<'a>, BuildFileError<'a>> {
     // get the platform path from the app header
     let platform_main_roc_path = match &loaded.entry_point {
@@ -902,10 +933,16 @@ fn build_loaded_file<'a>(
         }
     }
 
+    let source_paths = loaded
+        .sources
+        .values()
+        .map(|(path, _)| path.clone())
+        .collect();
+
     // This only needs to be mutable for report_problems. This can't be done
     // inside a nested scope without causing a borrow error!
     let mut loaded = loaded;
-    let problems = report_problems_monomorphized(&mut loaded);
+    let problems = report_problems_monomorphized(&mut loaded, diagnostics);
     let loaded = loaded;
 
     let (roc_app_bytes, code_gen_timing, expect_metadata) = gen_from_mono_module(
@@ -1052,6 +1089,7 @@ fn build_loaded_file<'a>(
         problems,
         total_time,
         expect_metadata,
+        source_paths,
     })
 }
 
@@ -1342,6 +1380,13 @@ fn spawn_legacy_host_build_thread(
     })
 }
 
+pub struct CheckedFile {
+    pub problems: Problems,
+    pub total_time: Duration,
+    /// The paths of all the modules that were loaded, e.g. to watch them for changes.
+    pub source_paths: Vec<PathBuf>,
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn check_file<'a>(
     arena: &'a Bump,
@@ -1350,7 +1395,9 @@ pub fn check_file<'a>(
     emit_timings: bool,
     roc_cache_dir: RocCacheDir<'_>,
     threading: Threading,
-) -> Result<(Problems, Duration), LoadingProblem<'a>> {
+    solved_modules: Option<SolvedModules>,
+    diagnostics: &mut Diagnostics,
+) -> Result<CheckedFile, LoadingProblem<'a>> {
     let compilation_start = Instant::now();
 
     // only used for generating errors. We don't do code generation, so hardcoding should be fine
@@ -1362,11 +1409,11 @@ pub fn check_file<'a>(
     let load_config = LoadConfig {
         target,
         function_kind: FunctionKind::from_env(),
-        // TODO: expose this from CLI?
-        render: RenderTarget::ColorTerminal,
+        render: diagnostics.format().render_target(),
         palette: DEFAULT_PALETTE,
         threading,
         exec_mode: ExecutionMode::Check,
+        solved_modules,
     };
     let mut loaded = roc_load::load_and_typecheck(
         arena,
@@ -1418,7 +1465,17 @@ pub fn check_file<'a>(
         println!("Finished checking in {} ms\n", compilation_end.as_millis(),);
     }
 
-    Ok((report_problems_typechecked(&mut loaded), compilation_end))
+    let source_paths = loaded
+        .sources
+        .values()
+        .map(|(path, _)| path.clone())
+        .collect();
+
+    Ok(CheckedFile {
+        problems: report_problems_typechecked(&mut loaded, diagnostics),
+        total_time: compilation_end,
+        source_paths,
+    })
 }
 
 pub fn build_str_test<'a>(
@@ -1445,8 +1502,9 @@ pub fn build_str_test<'a>(
     let roc_cache_dir = roc_packaging::cache::RocCacheDir::Disallowed;
     let build_ordering = BuildOrdering::AlwaysBuild;
     let threading = Threading::AtMost(2);
+    let report_format = ReportFormat::Human;
 
-    let load_config = standard_load_config(target, build_ordering, threading);
+    let load_config = standard_load_config(target, build_ordering, threading, report_format);
 
     let compilation_start = std::time::Instant::now();
 
@@ -1480,6 +1538,7 @@ pub fn build_str_test<'a>(
         compilation_start,
         None,
         false,
+        &mut Diagnostics::new(report_format),
     )
 }
 
@@ -14,7 +14,9 @@ pub fn target_triple_str(target: Target) -> &'static str {
     //
     // https://stackoverflow.com/questions/15036909/clang-how-to-list-supported-target-architectures
     match target {
+        Target::LinuxArm32 => "armv7-unknown-linux-gnueabihf",
         Target::LinuxArm64 => "aarch64-unknown-linux-gnu",
+        Target::LinuxRiscv64 => "riscv64-unknown-linux-gnu",
         Target::LinuxX32 => "i386-unknown-linux-gnu",
         Target::LinuxX64 => "x86_64-unknown-linux-gnu",
         Target::MacArm64 => "aarch64-apple-darwin",
@@ -38,6 +40,9 @@ pub fn init_arch(target: Target) {
         Architecture::Aarch32 if cfg!(feature = "target-arm") => {
             LlvmTarget::initialize_arm(&InitializationConfig::default());
         }
+        Architecture::Riscv64 if cfg!(feature = "target-riscv64") => {
+            LlvmTarget::initialize_riscv(&InitializationConfig::default());
+        }
         Architecture::Wasm32 if cfg!(feature = "target-wasm32") => {
             LlvmTarget::initialize_webassembly(&InitializationConfig::default());
         }
@@ -60,6 +65,7 @@ pub fn arch_str(target: Target) -> &'static str {
         roc_target::Architecture::X86_32 if cfg!(feature = "target-x86") => "x86",
         roc_target::Architecture::Aarch64 if cfg!(feature = "target-aarch64") => "aarch64",
         roc_target::Architecture::Aarch32 if cfg!(feature = "target-arm") => "arm",
+        roc_target::Architecture::Riscv64 if cfg!(feature = "target-riscv64") => "riscv64",
         roc_target::Architecture::Wasm32 if cfg!(feature = "target-wasm32") => "wasm32",
         _ => internal_error!(
             "TODO gracefully handle unsupported target architecture: {:?}",
@@ -68,6 +74,18 @@ pub fn arch_str(target: Target) -> &'static str {
     }
 }
 
+/// The CPU features that the C compilers of a target assume by default, so that the object
+/// files we generate use the same calling convention as the host's.
+pub fn target_features(target: Target) -> &'static str {
+    match target {
+        // Hard-float ARMv7, as on the Raspberry Pi 2 and later
+        Target::LinuxArm32 => "+armv7-a,+vfp3,+neon",
+        // RV64GC, which the lp64d ABI passes floats in the registers of
+        Target::LinuxRiscv64 => "+m,+a,+f,+d,+c",
+        _ => "",
+    }
+}
+
 pub fn target_machine(
     target: Target,
     opt: OptimizationLevel,
@@ -95,7 +113,7 @@ pub fn target_machine(
     LlvmTarget::from_name(arch).unwrap().create_target_machine(
         &TargetTriple::create(target_triple_str(target)),
         "generic",
-        "",
+        target_features(target),
         opt,
         reloc,
         code_model,
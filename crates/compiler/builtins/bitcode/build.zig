@@ -38,6 +38,19 @@ pub fn build(b: *Build) void {
         .os_tag = std.Target.Os.Tag.linux,
         .abi = std.Target.Abi.none,
     });
+    const linux_arm_target = b.resolveTargetQuery(.{
+        .cpu_arch = std.Target.Cpu.Arch.arm,
+        .cpu_model = .{ .explicit = &std.Target.arm.cpu.generic },
+        .cpu_features_add = std.Target.arm.featureSet(&.{ .v7a, .vfp3, .neon }),
+        .os_tag = std.Target.Os.Tag.linux,
+        .abi = std.Target.Abi.gnueabihf,
+    });
+    const linux_riscv64_target = b.resolveTargetQuery(.{
+        .cpu_arch = std.Target.Cpu.Arch.riscv64,
+        .cpu_model = .{ .explicit = &std.Target.riscv.cpu.baseline_rv64 },
+        .os_tag = std.Target.Os.Tag.linux,
+        .abi = std.Target.Abi.none,
+    });
     const windows64_target = b.resolveTargetQuery(.{
         .cpu_arch = std.Target.Cpu.Arch.x86_64,
         .os_tag = std.Target.Os.Tag.windows,
@@ -55,6 +68,8 @@ pub fn build(b: *Build) void {
     generateLlvmIrFile(b, mode, linux32_target, main_path, "ir-x86", "builtins-x86");
     generateLlvmIrFile(b, mode, linux_x64_target, main_path, "ir-x86_64", "builtins-x86_64");
     generateLlvmIrFile(b, mode, linux_aarch64_target, main_path, "ir-aarch64", "builtins-aarch64");
+    generateLlvmIrFile(b, mode, linux_arm_target, main_path, "ir-arm", "builtins-arm");
+    generateLlvmIrFile(b, mode, linux_riscv64_target, main_path, "ir-riscv64", "builtins-riscv64");
     generateLlvmIrFile(b, mode, windows64_target, main_path, "ir-windows-x86_64", "builtins-windows-x86_64");
     generateLlvmIrFile(b, mode, wasm32_target, main_path, "ir-wasm32", "builtins-wasm32");
 
@@ -23,6 +23,12 @@ pub fn expectFailedStartSharedBuffer() callconv(.C) [*]u8 {
     return SHARED_BUFFER.ptr;
 }
 
+/// The length of the buffer that failed expects get written to, so that
+/// values that don't fit can be refused instead of written past its end
+pub fn expectSharedBufferLength() callconv(.C) usize {
+    return SHARED_BUFFER.len;
+}
+
 pub fn expectFailedStartSharedFile() callconv(.C) [*]u8 {
     // IMPORTANT: shared memory object names must begin with / and contain no other slashes!
     var name: [100]u8 = undefined;
@@ -44,6 +50,8 @@ pub fn expectFailedStartSharedFile() callconv(.C) [*]u8 {
 
         const ptr = @as([*]u8, @ptrCast(shared_ptr));
 
+        SHARED_BUFFER = ptr[0..length];
+
         return ptr;
     } else {
         unreachable;
@@ -238,12 +238,16 @@ comptime {
     exportUtilsFn(utils.allocateWithRefcountC, "allocate_with_refcount");
     exportUtilsFn(utils.dictPseudoSeed, "dict_pseudo_seed");
 
+    // lets hosts hash keys the way Dict does, e.g. roc_std's RocDict
+    @export(utils.dictPseudoSeed, .{ .name = "roc_dict_pseudo_seed", .linkage = .weak });
+
     @export(panic_utils.panic, .{ .name = "roc_builtins.utils." ++ "panic", .linkage = .weak });
     @export(dbg_utils.dbg_impl, .{ .name = "roc_builtins.utils." ++ "dbg_impl", .linkage = .weak });
 
     if (builtin.target.cpu.arch != .wasm32) {
         exportUtilsFn(expect.expectFailedStartSharedBuffer, "expect_failed_start_shared_buffer");
         exportUtilsFn(expect.expectFailedStartSharedFile, "expect_failed_start_shared_file");
+        exportUtilsFn(expect.expectSharedBufferLength, "expect_shared_buffer_length");
         exportUtilsFn(expect.notifyParentExpect, "notify_parent_expect");
 
         // sets the buffer used for expect failures
@@ -252,7 +256,7 @@ comptime {
         exportUtilsFn(expect.readSharedBufferEnv, "read_env_shared_buffer");
     }
 
-    if (builtin.target.cpu.arch == .aarch64) {
+    if (builtin.target.cpu.arch == .aarch64 or builtin.target.cpu.arch == .riscv64) {
         @export(__roc_force_setjmp, .{ .name = "__roc_force_setjmp", .linkage = .weak });
         @export(__roc_force_longjmp, .{ .name = "__roc_force_longjmp", .linkage = .weak });
     } else if (builtin.os.tag == .windows) {
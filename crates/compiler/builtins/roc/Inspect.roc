@@ -33,6 +33,7 @@ module [
     apply,
     to_inspector,
     to_str,
+    snapshot,
 ]
 
 import Bool exposing [Bool]
@@ -100,6 +101,12 @@ to_str = |val|
     |> inspect
     |> to_dbg_str
 
+## Renders a value like [to_str] does. A top-level `expect Inspect.snapshot(value)` is a
+## snapshot test: `roc test` compares the rendering with the one stored in the `.snap` file
+## next to the module, and `roc test --update-snapshots` stores it there.
+snapshot : val -> Str where val implements Inspect
+snapshot = |val| to_str(val)
+
 # The current default formatter for inspect.
 # This just returns a simple string for debugging.
 # More powerful formatters will likely be wanted in the future.
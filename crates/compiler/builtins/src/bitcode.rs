@@ -43,8 +43,9 @@ impl FloatWidth {
         match self {
             F32 => 4,
             F64 => match target.architecture() {
-                X86_64 | Aarch64 | Wasm32 => 8,
-                X86_32 | Aarch32 => 4,
+                // The ARM EABI aligns doubles to 8 bytes, unlike 32-bit x86
+                X86_64 | Aarch64 | Aarch32 | Riscv64 | Wasm32 => 8,
+                X86_32 => 4,
             },
         }
     }
@@ -117,6 +118,7 @@ impl IntWidth {
                 Architecture::X86_64
                 | Architecture::Aarch64
                 | Architecture::Aarch32
+                | Architecture::Riscv64
                 | Architecture::Wasm32 => 8,
                 Architecture::X86_32 => 4,
             },
@@ -127,7 +129,10 @@ impl IntWidth {
                 // however, rust does not always think that this is true
                 // Our alignmets here are correct, but they will not match rust/zig/llvm until they update to llvm version 18.
                 match target.architecture() {
-                    Architecture::X86_64 | Architecture::Aarch64 | Architecture::X86_32 => 16,
+                    Architecture::X86_64
+                    | Architecture::Aarch64
+                    | Architecture::Riscv64
+                    | Architecture::X86_32 => 16,
                     Architecture::Aarch32 | Architecture::Wasm32 => 8,
                 }
             }
@@ -434,6 +439,8 @@ pub const UTILS_EXPECT_FAILED_START_SHARED_BUFFER: &str =
     "roc_builtins.utils.expect_failed_start_shared_buffer";
 pub const UTILS_EXPECT_FAILED_START_SHARED_FILE: &str =
     "roc_builtins.utils.expect_failed_start_shared_file";
+pub const UTILS_EXPECT_SHARED_BUFFER_LENGTH: &str =
+    "roc_builtins.utils.expect_shared_buffer_length";
 pub const UTILS_EXPECT_READ_ENV_SHARED_BUFFER: &str = "roc_builtins.utils.read_env_shared_buffer";
 pub const NOTIFY_PARENT_EXPECT: &str = "roc_builtins.utils.notify_parent_expect";
 
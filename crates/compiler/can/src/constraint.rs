@@ -7,7 +7,7 @@ use crate::exhaustive::{ExhaustiveContext, SketchedRows};
 use crate::expected::{Expected, PExpected};
 use crate::expr::TryKind;
 use roc_collections::soa::{index_push_new, slice_extend_new};
-use roc_module::ident::{IdentSuffix, TagName};
+use roc_module::ident::{IdentSuffix, Lowercase, TagName};
 use roc_module::symbol::{ModuleId, Symbol};
 use roc_region::all::{Loc, Region};
 use roc_types::subs::{ExhaustiveMark, IllegalCycleMark, Variable};
@@ -34,6 +34,7 @@ pub struct Constraints {
     pub fx_call_constraints: Vec<FxCallConstraint>,
     pub fx_suffix_constraints: Vec<FxSuffixConstraint>,
     pub try_target_constraints: Vec<TryTargetConstraint>,
+    pub typed_hole_constraints: Vec<TypedHoleConstraint>,
 }
 
 impl std::fmt::Debug for Constraints {
@@ -58,6 +59,7 @@ impl std::fmt::Debug for Constraints {
             .field("cycles", &self.cycles)
             .field("fx_call_constraints", &self.fx_call_constraints)
             .field("fx_suffix_constraints", &self.fx_suffix_constraints)
+            .field("typed_hole_constraints", &self.typed_hole_constraints)
             .finish()
     }
 }
@@ -93,6 +95,7 @@ impl Constraints {
         let fx_call_constraints = Vec::with_capacity(16);
         let fx_suffix_constraints = Vec::new();
         let result_type_constraints = Vec::new();
+        let typed_hole_constraints = Vec::new();
 
         categories.extend([
             Category::Record,
@@ -145,6 +148,7 @@ impl Constraints {
             fx_call_constraints,
             fx_suffix_constraints,
             try_target_constraints: result_type_constraints,
+            typed_hole_constraints,
         }
     }
 
@@ -657,6 +661,19 @@ impl Constraints {
         Constraint::TryTarget(constraint_index)
     }
 
+    pub fn typed_hole(
+        &mut self,
+        var: Variable,
+        name: Option<Lowercase>,
+        region: Region,
+    ) -> Constraint {
+        let constraint = TypedHoleConstraint { var, name, region };
+
+        let constraint_index = index_push_new(&mut self.typed_hole_constraints, constraint);
+
+        Constraint::TypedHole(constraint_index)
+    }
+
     pub fn contains_save_the_environment(&self, constraint: &Constraint) -> bool {
         match constraint {
             Constraint::SaveTheEnvironment => true,
@@ -683,6 +700,7 @@ impl Constraints {
             | Constraint::Pattern(..)
             | Constraint::ExpectEffectful(..)
             | Constraint::TryTarget(_)
+            | Constraint::TypedHole(_)
             | Constraint::FxCall(_)
             | Constraint::FxSuffix(_)
             | Constraint::FlexToPure(_)
@@ -876,6 +894,8 @@ pub enum Constraint {
     ExpectEffectful(Variable, ExpectEffectfulReason, Region),
     /// Expect value to be some kind of Result
     TryTarget(Index<TryTargetConstraint>),
+    /// Report the type that a typed hole needs, and the names in scope that have it
+    TypedHole(Index<TypedHoleConstraint>),
     /// Used for things that always unify, e.g. blanks and runtime errors
     True,
     SaveTheEnvironment,
@@ -951,6 +971,13 @@ pub struct TryTargetConstraint {
     pub kind: TryKind,
 }
 
+#[derive(Debug, Clone)]
+pub struct TypedHoleConstraint {
+    pub var: Variable,
+    pub name: Option<Lowercase>,
+    pub region: Region,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Cycle {
     pub def_names: Slice<(Symbol, Region)>,
@@ -1045,6 +1072,9 @@ impl std::fmt::Debug for Constraint {
             Self::TryTarget(arg0) => {
                 write!(f, "ExpectResultType({arg0:?})")
             }
+            Self::TypedHole(arg0) => {
+                write!(f, "TypedHole({arg0:?})")
+            }
             Self::True => write!(f, "True"),
             Self::SaveTheEnvironment => write!(f, "SaveTheEnvironment"),
             Self::Let(arg0, arg1) => f.debug_tuple("Let").field(arg0).field(arg1).finish(),
@@ -735,6 +735,11 @@ fn deep_copy_expr_help<C: CopyEnv>(env: &mut C, copied: &mut Vec<Variable>, expr
             kind: *kind,
         },
 
+        TypedHole { name, var } => TypedHole {
+            name: name.clone(),
+            var: sub!(*var),
+        },
+
         RuntimeError(err) => RuntimeError(err.clone()),
     }
 }
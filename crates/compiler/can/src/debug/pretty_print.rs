@@ -446,6 +446,10 @@ fn expr<'a>(c: &Ctx, p: EPrec, f: &'a Arena<'a>, e: &'a Expr) -> DocBuilder<'a,
         Expect { .. } => todo!(),
         Try { .. } => todo!(),
         Return { .. } => todo!(),
+        TypedHole { name: None, .. } => f.text("_"),
+        TypedHole {
+            name: Some(name), ..
+        } => f.text(format!("?{}", name.as_str())),
         RuntimeError(_) => todo!(),
     }
 }
@@ -474,6 +474,7 @@ pub fn desugar_expr<'a>(
         | Var { .. }
         | AccessorFunction(_)
         | Underscore { .. }
+        | TypedHole(_)
         | MalformedIdent(_, _)
         | PrecedenceConflict { .. }
         | EmptyRecordBuilder(_)
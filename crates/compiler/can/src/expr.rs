@@ -345,6 +345,13 @@ pub enum Expr {
         return_var: Variable,
     },
 
+    /// A `_` or `?name` placeholder for an expression that hasn't been written yet.
+    /// Type checking reports the type that's expected here; it crashes if reached.
+    TypedHole {
+        name: Option<Lowercase>,
+        var: Variable,
+    },
+
     /// Compiles, but will crash if reached
     RuntimeError(RuntimeError),
 }
@@ -413,6 +420,7 @@ impl Expr {
             }
             Self::Expect { .. } => Category::Expect,
             Self::Crash { .. } => Category::Crash,
+            Self::TypedHole { .. } => Category::TypedHole,
             Self::Return { .. } => Category::Return(EarlyReturnKind::Return),
 
             Self::Dbg { .. } => Category::Expect,
@@ -439,6 +447,7 @@ impl Expr {
             | Self::RecordAccessor(_)
             | Self::ZeroArgumentTag { .. }
             | Self::OpaqueWrapFunction(_)
+            | Self::TypedHole { .. }
             | Self::RuntimeError(..) => false,
             Self::Return { .. } | Self::Try { .. } => true,
             Self::List { loc_elems, .. } => loc_elems
@@ -1150,19 +1159,24 @@ pub fn canonicalize_expr<'a>(
         ast::Expr::Var { module_name, ident } => {
             canonicalize_var_lookup(env, var_store, scope, module_name, ident, region)
         }
+        ast::Expr::Underscore("") => {
+            // A hole; type checking reports what should go here
+            let hole = TypedHole {
+                name: None,
+                var: var_store.fresh(),
+            };
+
+            (hole, Output::default())
+        }
         ast::Expr::Underscore(name) => {
-            // we parse underscores, but they are not valid expression syntax
+            // we parse `_name`, but it's not valid expression syntax
 
             let problem = roc_problem::can::RuntimeError::MalformedIdentifier(
                 (*name).into(),
-                if name.is_empty() {
-                    roc_parse::ident::BadIdent::UnderscoreAlone(region.start())
-                } else {
-                    roc_parse::ident::BadIdent::UnderscoreAtStart {
-                        position: region.start(),
-                        // Check if there's an ignored identifier with this name in scope (for better error messages)
-                        declaration_region: scope.lookup_ignored_local(name),
-                    }
+                roc_parse::ident::BadIdent::UnderscoreAtStart {
+                    position: region.start(),
+                    // Check if there's an ignored identifier with this name in scope (for better error messages)
+                    declaration_region: scope.lookup_ignored_local(name),
                 },
                 region,
             );
@@ -1171,6 +1185,15 @@ pub fn canonicalize_expr<'a>(
 
             (RuntimeError(problem), Output::default())
         }
+        ast::Expr::TypedHole(name) => {
+            // A named hole; type checking reports what should go here
+            let hole = TypedHole {
+                name: Some((*name).into()),
+                var: var_store.fresh(),
+            };
+
+            (hole, Output::default())
+        }
         ast::Expr::Crash => {
             // Naked crashes aren't allowed; we'll admit this with our own message, but yield an
             // error.
@@ -2312,6 +2335,7 @@ pub fn is_valid_interpolation(expr: &ast::Expr<'_>) -> bool {
         | ast::Expr::Dbg
         | ast::Expr::Try
         | ast::Expr::Underscore(_)
+        | ast::Expr::TypedHole(_)
         | ast::Expr::MalformedIdent(_, _)
         | ast::Expr::Tag(_)
         | ast::Expr::OpaqueRef(_) => true,
@@ -3024,10 +3048,21 @@ impl Declarations {
                     // the self of this group will be treaded individually by later iterations
                 }
                 Expectation => {
-                    let loc_expr =
-                        toplevel_expect_to_inline_expect_pure(self.expressions[index].clone());
+                    let loc_expr = &self.expressions[index];
 
-                    collector.visit_expr(&loc_expr.value, loc_expr.region, var);
+                    if let Some(closure) = toplevel_expect_property(loc_expr) {
+                        collector
+                            .expects
+                            .insert(closure.loc_body.region, property_lookups(closure));
+
+                        collector.visit_expr(&loc_expr.value, loc_expr.region, var);
+                    } else if toplevel_expect_snapshot(loc_expr).is_some() {
+                        collector.visit_expr(&loc_expr.value, loc_expr.region, var);
+                    } else {
+                        let loc_expr = toplevel_expect_to_inline_expect_pure(loc_expr.clone());
+
+                        collector.visit_expr(&loc_expr.value, loc_expr.region, var);
+                    }
                 }
             }
         }
@@ -3235,6 +3270,7 @@ pub(crate) fn get_lookup_symbols(expr: &Expr) -> Vec<ExpectLookup> {
             | Expr::SingleQuote(..)
             | Expr::EmptyRecord
             | Expr::RuntimeError(_)
+            | Expr::TypedHole { .. }
             | Expr::ImportParams(_, _, None)
             | Expr::OpaqueWrapFunction(_) => {}
         }
@@ -3243,6 +3279,55 @@ pub(crate) fn get_lookup_symbols(expr: &Expr) -> Vec<ExpectLookup> {
     lookups
 }
 
+/// A toplevel `expect` whose expression is a function is a property:
+///
+/// ```ignore
+/// expect \n -> Num.abs n >= 0
+/// ```
+///
+/// Rather than evaluating it once, `roc test` calls the function with generated arguments.
+pub fn toplevel_expect_property(loc_expr: &Loc<Expr>) -> Option<&ClosureData> {
+    match &loc_expr.value {
+        Expr::Closure(closure) if !closure.arguments.is_empty() => Some(closure),
+        _ => None,
+    }
+}
+
+/// A toplevel `expect` whose expression is a call to `Inspect.snapshot` is a snapshot:
+///
+/// ```ignore
+/// expect Inspect.snapshot(List.sort_asc [3, 1, 2])
+/// ```
+///
+/// Rather than checking a condition, `roc test` renders the value and compares it with the
+/// rendering stored in the module's `.snap` file. Returns the value being snapshotted.
+pub fn toplevel_expect_snapshot(loc_expr: &Loc<Expr>) -> Option<&Loc<Expr>> {
+    match &loc_expr.value {
+        Expr::Call(boxed, args, _) => match (&boxed.1.value, args.as_slice()) {
+            (Expr::Var(Symbol::INSPECT_SNAPSHOT, _), [(_, loc_value)]) => Some(loc_value),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The arguments of a property that get reported when it fails. Arguments that are
+/// destructured rather than named are still generated, but there is no name to report.
+pub fn property_lookups(closure: &ClosureData) -> Vec<ExpectLookup> {
+    closure
+        .arguments
+        .iter()
+        .filter_map(|(var, _, loc_pattern)| match loc_pattern.value {
+            Pattern::Identifier(symbol) => Some(ExpectLookup {
+                symbol,
+                var: *var,
+                ability_info: None,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
 /// Here we transform
 ///
 /// ```ignore
@@ -22,6 +22,7 @@ use roc_parse::header::HeaderType;
 use roc_parse::pattern::PatternType;
 use roc_problem::can::{Problem, RuntimeError};
 use roc_region::all::{Loc, Region};
+use roc_serialize::bytes::next_multiple_of;
 use roc_types::subs::{ExposedTypesStorageSubs, Subs, VarStore, Variable};
 use roc_types::types::{AbilitySet, Alias, Type};
 
@@ -1072,6 +1073,7 @@ fn fix_values_captured_in_closure_expr(
         | ParamsVar { .. }
         | AbilityMember(..)
         | EmptyRecord
+        | TypedHole { .. }
         | RuntimeError(_)
         | ZeroArgumentTag { .. }
         | RecordAccessor { .. } => {}
@@ -1235,6 +1237,19 @@ pub struct TypeState {
 }
 
 impl TypeState {
+    /// Each section is deserialized from its own slice, with alignments relative to the start
+    /// of that slice, so every section has to start as aligned as the whole buffer is
+    /// (see include_bytes_align_as! in roc_load).
+    const SECTION_ALIGNMENT: usize = std::mem::align_of::<u128>();
+
+    fn pad_section(written: usize, writer: &mut impl std::io::Write) -> std::io::Result<usize> {
+        let padded = next_multiple_of(written, Self::SECTION_ALIGNMENT);
+
+        writer.write_all(&[0; Self::SECTION_ALIGNMENT][..padded - written])?;
+
+        Ok(padded)
+    }
+
     pub fn serialize(&self, writer: &mut impl std::io::Write) -> std::io::Result<usize> {
         let Self {
             subs,
@@ -1244,7 +1259,9 @@ impl TypeState {
         } = self;
 
         let written_subs = subs.serialize(exposed_vars_by_symbol, writer)?;
+        let written_subs = Self::pad_section(written_subs, writer)?;
         let written_ab = abilities.serialize(writer)?;
+        let written_ab = Self::pad_section(written_ab, writer)?;
         let written_solved_impls =
             crate::abilities::serialize_solved_implementations(solved_implementations, writer)?;
 
@@ -1253,9 +1270,11 @@ impl TypeState {
 
     pub fn deserialize(bytes: &[u8]) -> (Self, usize) {
         let ((subs, exposed_vars_by_symbol), len_subs) = Subs::deserialize(bytes);
+        let len_subs = next_multiple_of(len_subs, Self::SECTION_ALIGNMENT);
         let bytes = &bytes[len_subs..];
 
         let (abilities, len_abilities) = AbilitiesStore::deserialize(bytes);
+        let len_abilities = next_multiple_of(len_abilities, Self::SECTION_ALIGNMENT);
         let bytes = &bytes[len_abilities..];
 
         let (solved_implementations, len_solved_impls) =
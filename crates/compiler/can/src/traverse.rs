@@ -2,7 +2,10 @@
 
 use roc_module::{ident::Lowercase, symbol::Symbol};
 use roc_region::all::{Loc, Position, Region};
-use roc_types::{subs::Variable, types::MemberImpl};
+use roc_types::{
+    subs::Variable,
+    types::{IndexOrField, MemberImpl},
+};
 
 use crate::{
     abilities::AbilitiesStore,
@@ -408,6 +411,7 @@ pub fn walk_expr<V: Visitor>(visitor: &mut V, expr: &Expr, var: Variable) {
         } => {
             visitor.visit_expr(&return_value.value, return_value.region, *return_var);
         }
+        Expr::TypedHole { .. } => { /* terminal */ }
         Expr::RuntimeError(..) => { /* terminal */ }
     }
 }
@@ -970,3 +974,329 @@ pub fn find_declaration(symbol: Symbol, decls: &'_ Declarations) -> Option<Found
         }
     }
 }
+
+/// A place in the source where a symbol is defined or used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolReference {
+    /// A pattern that introduces the symbol; the region is its name.
+    Definition(Region),
+    /// The region is the name of the symbol, possibly qualified, like `foo` or `Foo.foo`.
+    Name(Region),
+    /// A record field whose label is also the name of the symbol, like `foo` in `{ foo }`.
+    /// The region starts with the label.
+    PunnedField(Region),
+    /// The type annotation of a def of the symbol; the name comes before the region,
+    /// like `foo` in `foo : Str`.
+    Annotation(Region),
+    /// A record update, whose record is the symbol, like `{ foo & bar: 1 }`.
+    RecordUpdate(Region),
+    /// An `as` pattern that introduces the symbol, like `Ok x as foo`.
+    /// The region ends with the name.
+    As(Region),
+}
+
+/// Finds all the definitions and uses of `symbol` in `decls`.
+pub fn find_symbol_references(symbol: Symbol, decls: &Declarations) -> Vec<SymbolReference> {
+    let mut visitor = Finder {
+        symbol,
+        found: Vec::new(),
+    };
+    visitor.visit_decls(decls);
+
+    // Top-level annotations aren't part of the declarations that get visited.
+    for (index, loc_symbol) in decls.symbols.iter().enumerate() {
+        if loc_symbol.value == symbol {
+            if let Some(annotation) = &decls.annotations[index] {
+                visitor
+                    .found
+                    .push(SymbolReference::Annotation(annotation.region));
+            }
+        }
+    }
+
+    return visitor.found;
+
+    struct Finder {
+        symbol: Symbol,
+        found: Vec<SymbolReference>,
+    }
+
+    impl Visitor for Finder {
+        fn visit_def(&mut self, def: &Def) {
+            if let (Pattern::Identifier(symbol), Some(annotation)) =
+                (&def.loc_pattern.value, &def.annotation)
+            {
+                if *symbol == self.symbol {
+                    self.found
+                        .push(SymbolReference::Annotation(annotation.region));
+                }
+            }
+
+            walk_def(self, def);
+        }
+
+        fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+            match expr {
+                Expr::Var(symbol, _)
+                | Expr::ParamsVar { symbol, .. }
+                | Expr::AbilityMember(symbol, _, _)
+                    if *symbol == self.symbol =>
+                {
+                    self.found.push(SymbolReference::Name(region));
+                }
+                Expr::Record { fields, .. } => {
+                    for field in fields.values() {
+                        match &field.loc_expr.value {
+                            // A punned field, like `{ foo }`, starts with its value.
+                            Expr::Var(symbol, _)
+                                if *symbol == self.symbol
+                                    && field.region.start() == field.loc_expr.region.start() =>
+                            {
+                                self.found.push(SymbolReference::PunnedField(field.region));
+                            }
+                            value => self.visit_expr(value, field.loc_expr.region, field.var),
+                        }
+                    }
+
+                    return;
+                }
+                Expr::RecordUpdate { symbol, .. } if *symbol == self.symbol => {
+                    self.found.push(SymbolReference::RecordUpdate(region));
+                }
+                _ => {}
+            }
+
+            walk_expr(self, expr, var);
+        }
+
+        fn visit_pattern(&mut self, pattern: &Pattern, region: Region, _opt_var: Option<Variable>) {
+            match pattern {
+                Pattern::Identifier(symbol)
+                | Pattern::AbilityMemberSpecialization { ident: symbol, .. }
+                    if *symbol == self.symbol =>
+                {
+                    self.found.push(SymbolReference::Definition(region));
+                }
+                Pattern::As(_, symbol) if *symbol == self.symbol => {
+                    self.found.push(SymbolReference::As(region));
+                }
+                _ => {}
+            }
+
+            walk_pattern(self, pattern);
+        }
+
+        fn visit_record_destruct(&mut self, destruct: &RecordDestruct, region: Region) {
+            match &destruct.typ {
+                DestructType::Required | DestructType::Optional(..)
+                    if destruct.symbol == self.symbol =>
+                {
+                    self.found.push(SymbolReference::PunnedField(region));
+                }
+                _ => {}
+            }
+
+            walk_record_destruct(self, destruct);
+        }
+    }
+}
+
+/// Finds the records and record destructures in `decls` where a field labeled `label` is punned,
+/// like `{ foo }` or `\{ foo } -> foo`. The regions start with the label.
+pub fn find_field_puns(label: &str, decls: &Declarations) -> Vec<Region> {
+    let mut visitor = Finder {
+        label,
+        found: Vec::new(),
+    };
+    visitor.visit_decls(decls);
+
+    return visitor.found;
+
+    struct Finder<'a> {
+        label: &'a str,
+        found: Vec<Region>,
+    }
+
+    impl Visitor for Finder<'_> {
+        fn visit_expr(&mut self, expr: &Expr, _region: Region, var: Variable) {
+            if let Expr::Record { fields, .. } = expr {
+                for (label, field) in fields.iter() {
+                    if label.as_str() == self.label
+                        && field.region.start() == field.loc_expr.region.start()
+                        && matches!(field.loc_expr.value, Expr::Var(..))
+                    {
+                        self.found.push(field.region);
+                    }
+                }
+            }
+
+            walk_expr(self, expr, var);
+        }
+
+        fn visit_record_destruct(&mut self, destruct: &RecordDestruct, region: Region) {
+            if destruct.label.as_str() == self.label
+                && matches!(
+                    destruct.typ,
+                    DestructType::Required | DestructType::Optional(..)
+                )
+            {
+                self.found.push(region);
+            }
+
+            walk_record_destruct(self, destruct);
+        }
+    }
+}
+
+/// Finds the tags named `tag` in `decls`, along with the variable of the tag union each one
+/// belongs to. The regions are those of the tag's name.
+pub fn find_tag_uses(tag: &str, decls: &Declarations) -> Vec<(Region, Variable)> {
+    let mut visitor = Finder {
+        tag,
+        found: Vec::new(),
+    };
+    visitor.visit_decls(decls);
+
+    return visitor.found;
+
+    struct Finder<'a> {
+        tag: &'a str,
+        found: Vec<(Region, Variable)>,
+    }
+
+    impl Visitor for Finder<'_> {
+        fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+            match expr {
+                Expr::Tag {
+                    tag_union_var: union_var,
+                    name,
+                    ..
+                }
+                | Expr::ZeroArgumentTag {
+                    variant_var: union_var,
+                    name,
+                    ..
+                } if name.0.as_str() == self.tag => {
+                    self.found
+                        .push((name_region(region.start(), self.tag), *union_var));
+                }
+                _ => {}
+            }
+
+            walk_expr(self, expr, var);
+        }
+
+        fn visit_pattern(&mut self, pattern: &Pattern, region: Region, _opt_var: Option<Variable>) {
+            match pattern {
+                Pattern::AppliedTag {
+                    whole_var,
+                    tag_name,
+                    ..
+                } if tag_name.0.as_str() == self.tag => {
+                    self.found
+                        .push((name_region(region.start(), self.tag), *whole_var));
+                }
+                _ => {}
+            }
+
+            walk_pattern(self, pattern);
+        }
+    }
+}
+
+/// Finds the record fields labeled `label` in `decls`, whether in records, record updates and
+/// updaters like `&foo`, accesses and accessors like `.foo`, or destructures, along with the
+/// variable of the record type each one belongs to. The regions are those of the label.
+pub fn find_field_uses(label: &str, decls: &Declarations) -> Vec<(Region, Variable)> {
+    let mut visitor = Finder {
+        label,
+        found: Vec::new(),
+    };
+    visitor.visit_decls(decls);
+
+    return visitor.found;
+
+    struct Finder<'a> {
+        label: &'a str,
+        found: Vec<(Region, Variable)>,
+    }
+
+    impl Visitor for Finder<'_> {
+        fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+            let label_at = |start: Position| name_region(start, self.label);
+
+            match expr {
+                Expr::Record {
+                    record_var, fields, ..
+                } => {
+                    for (field_label, field) in fields.iter() {
+                        if field_label.as_str() == self.label {
+                            self.found
+                                .push((label_at(field.region.start()), *record_var));
+                        }
+                    }
+                }
+                Expr::RecordUpdate {
+                    record_var,
+                    updates,
+                    ..
+                } => {
+                    for (field_label, field) in updates.iter() {
+                        if field_label.as_str() == self.label {
+                            // An updater like `&foo` is desugared to a record update whose
+                            // every part has the updater's region.
+                            let start = if field.region == field.loc_expr.region {
+                                field.region.start().bump_column(1)
+                            } else {
+                                field.region.start()
+                            };
+
+                            self.found.push((label_at(start), *record_var));
+                        }
+                    }
+                }
+                Expr::RecordAccess {
+                    record_var, field, ..
+                } if field.as_str() == self.label => {
+                    let start = region.end().offset - self.label.len() as u32;
+
+                    self.found
+                        .push((label_at(Position::new(start)), *record_var));
+                }
+                Expr::RecordAccessor(StructAccessorData {
+                    record_var,
+                    field: IndexOrField::Field(field),
+                    ..
+                }) if field.as_str() == self.label => {
+                    self.found
+                        .push((label_at(region.start().bump_column(1)), *record_var));
+                }
+                _ => {}
+            }
+
+            walk_expr(self, expr, var);
+        }
+
+        fn visit_pattern(&mut self, pattern: &Pattern, region: Region, _opt_var: Option<Variable>) {
+            if let Pattern::RecordDestructure {
+                whole_var,
+                destructs,
+                ..
+            } = pattern
+            {
+                for destruct in destructs {
+                    if destruct.value.label.as_str() == self.label {
+                        self.found
+                            .push((name_region(destruct.region.start(), self.label), *whole_var));
+                    }
+                }
+            }
+
+            walk_pattern(self, pattern);
+        }
+    }
+}
+
+fn name_region(start: Position, name: &str) -> Region {
+    Region::new(start, start.bump_column(name.len() as u32))
+}
@@ -17,8 +17,9 @@ use roc_can::expected::Expected::{self, *};
 use roc_can::expected::PExpected;
 use roc_can::expr::Expr::{self, *};
 use roc_can::expr::{
-    AnnotatedMark, ClosureData, DeclarationTag, Declarations, DestructureDef, ExpectLookup, Field,
-    FunctionDef, OpaqueWrapFunctionData, StructAccessorData, WhenBranch,
+    toplevel_expect_property, toplevel_expect_snapshot, AnnotatedMark, ClosureData, DeclarationTag,
+    Declarations, DestructureDef, ExpectLookup, Field, FunctionDef, OpaqueWrapFunctionData,
+    StructAccessorData, WhenBranch,
 };
 use roc_can::pattern::Pattern;
 use roc_can::traverse::symbols_introduced_from_pattern;
@@ -689,6 +690,12 @@ pub fn constrain_expr(
 
             constraints.exists([*ret_var], and)
         }
+        TypedHole { name, var } => {
+            let eq = constraints.equal_types_var(*var, expected, Category::TypedHole, region);
+            let hole = constraints.typed_hole(*var, name.clone(), region);
+
+            constraints.exists_many([*var], [eq, hole])
+        }
         Var(symbol, variable)
         | ParamsVar {
             symbol,
@@ -2826,20 +2833,70 @@ pub fn constrain_decls(
                 let loc_expr = &declarations.expressions[index];
 
                 let bool_type = constraints.push_variable(Variable::BOOL);
-                let expected = constraints.push_expected_type(Expected::ForReason(
-                    Reason::ExpectCondition,
-                    bool_type,
-                    loc_expr.region,
-                ));
 
-                let expect_constraint = constrain_expr(
-                    types,
-                    constraints,
-                    &mut env,
-                    loc_expr.region,
-                    &loc_expr.value,
-                    expected,
-                );
+                let expect_constraint = match toplevel_expect_property(loc_expr) {
+                    Some(closure) => {
+                        // A property is a function whose arguments get generated,
+                        // so it's the function's body that needs to be a Bool.
+                        let function_type = constraints.push_variable(closure.function_type);
+                        let expected = constraints.push_expected_type(NoExpectation(function_type));
+
+                        let closure_constraint = constrain_expr(
+                            types,
+                            constraints,
+                            &mut env,
+                            loc_expr.region,
+                            &loc_expr.value,
+                            expected,
+                        );
+
+                        let body_region = closure.loc_body.region;
+                        let expected_return = constraints.push_expected_type(Expected::ForReason(
+                            Reason::ExpectCondition,
+                            bool_type,
+                            body_region,
+                        ));
+                        let return_constraint = constraints.equal_types_var(
+                            closure.return_type,
+                            expected_return,
+                            Category::Expect,
+                            body_region,
+                        );
+
+                        constraints.and_constraint([closure_constraint, return_constraint])
+                    }
+                    None if toplevel_expect_snapshot(loc_expr).is_some() => {
+                        // A snapshot evaluates to the rendering of its value, which the
+                        // test runner compares with the stored one.
+                        let str_type = constraints.push_variable(Variable::STR);
+                        let expected = constraints.push_expected_type(NoExpectation(str_type));
+
+                        constrain_expr(
+                            types,
+                            constraints,
+                            &mut env,
+                            loc_expr.region,
+                            &loc_expr.value,
+                            expected,
+                        )
+                    }
+                    None => {
+                        let expected = constraints.push_expected_type(Expected::ForReason(
+                            Reason::ExpectCondition,
+                            bool_type,
+                            loc_expr.region,
+                        ));
+
+                        constrain_expr(
+                            types,
+                            constraints,
+                            &mut env,
+                            loc_expr.region,
+                            &loc_expr.value,
+                            expected,
+                        )
+                    }
+                };
 
                 constraint = constraints.let_constraint(
                     [],
@@ -4480,6 +4537,7 @@ fn is_generalizable_expr(mut expr: &Expr) -> bool {
             | Dbg { .. }
             | Try { .. }
             | Return { .. }
+            | TypedHole { .. }
             | RuntimeError(..)
             | ZeroArgumentTag { .. }
             | Tag { .. }
@@ -45,8 +45,10 @@ pub fn fmt_collection<'a, 'buf, T: ExtractSpaces<'a> + Formattable + std::fmt::D
 {
     let start = braces.start();
     let end = braces.end();
+    let is_multiline = is_collection_multiline(&items);
+    let single_line = (!is_multiline && !items.is_empty()).then(|| buf.start_single_line());
 
-    if is_collection_multiline(&items) {
+    if is_multiline || single_line.is_some_and(|single_line| single_line.must_break()) {
         let braces_indent = indent;
         let item_indent = braces_indent + INDENT;
         if newline == Newlines::Yes {
@@ -165,6 +167,10 @@ pub fn fmt_collection<'a, 'buf, T: ExtractSpaces<'a> + Formattable + std::fmt::D
     }
 
     buf.push(end);
+
+    if let Some(single_line) = single_line {
+        buf.end_single_line(single_line);
+    }
 }
 
 fn has_comments(spaces: &[CommentOrNewline<'_>]) -> bool {
@@ -914,6 +914,50 @@ pub fn fmt_defs(buf: &mut Buf, defs: &Defs, indent: u16) {
     defs.format(buf, indent);
 }
 
+/// Format only the def at `index`, e.g. to reformat part of a module without rewriting the rest.
+///
+/// This writes everything from the start of `defs.regions[index]` up to the start of the next
+/// def's region, or to the end of the file after the last def, so that's what it replaces.
+/// Comments which were parsed into the def can be past the end of its region, but never past
+/// the start of the next def, so none of them are written twice or lost.
+pub fn fmt_def(buf: &mut Buf, defs: &Defs, index: usize, indent: u16) {
+    let arena = buf.text.bump();
+
+    let Some(def) = defs.defs().nth(index) else {
+        return;
+    };
+
+    // Whatever is lifted out in front of the def comes before its region, so it's left to the
+    // def before it, just like the spaces before it are.
+    let def = def_lift_spaces(arena, def);
+
+    match def.item {
+        Ok(type_def) => type_def.format(buf, indent),
+        Err(value_def) => value_def.format(buf, indent),
+    }
+
+    let spaces_after = &defs.spaces[defs.space_after[index].indices()];
+    let spaces_after = merge_spaces(arena, def.after, spaces_after);
+
+    fmt_spaces(buf, spaces_after.iter(), indent);
+
+    if let Some(next_def) = defs.defs().nth(index + 1) {
+        let next_def = def_lift_spaces(arena, next_def);
+        let spaces_before = &defs.spaces[defs.space_before[index + 1].indices()];
+        let spaces_before = merge_spaces(arena, spaces_before, next_def.before);
+
+        // The same as between these defs when formatting all of them
+        if spaces_after.is_empty() {
+            fmt_default_newline(buf, spaces_before, indent);
+        } else {
+            fmt_spaces(buf, spaces_before.iter(), indent);
+        }
+    }
+
+    // Either the next def or the end of the file comes after this
+    buf.fmt_end_of_file();
+}
+
 pub fn fmt_annotated_body_comment<'a>(
     buf: &mut Buf,
     indent: u16,
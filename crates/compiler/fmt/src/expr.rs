@@ -46,6 +46,12 @@ impl<'a> Formattable for Expr<'a> {
     }
 }
 
+/// Format just `expr`, e.g. an expression that was selected inside a def, the way it would be
+/// formatted at `indent` there. The comments which were parsed into it are written along with it.
+pub fn fmt_expr(buf: &mut Buf, expr: &Expr, indent: u16) {
+    expr.format(buf, indent);
+}
+
 fn format_expr_only(
     item: &Expr<'_>,
     buf: &mut Buf,
@@ -84,6 +90,11 @@ fn format_expr_only(
             buf.push('_');
             buf.push_str(name);
         }
+        Expr::TypedHole(name) => {
+            buf.indent(indent);
+            buf.push('?');
+            buf.push_str(name);
+        }
         Expr::Crash => {
             buf.indent(indent);
             buf.push_str("crash");
@@ -501,6 +512,7 @@ pub fn expr_is_multiline(me: &Expr<'_>, comments_only: bool) -> bool {
         | Expr::RecordUpdater(_)
         | Expr::Var { .. }
         | Expr::Underscore { .. }
+        | Expr::TypedHole(_)
         | Expr::MalformedIdent(_, _)
         | Expr::Tag(_)
         | Expr::OpaqueRef(_)
@@ -717,6 +729,11 @@ fn fmt_apply(
                 })
                 .unwrap_or_default());
 
+    let single_line = (!needs_indent && !should_reflow_outdentable && !loc_args.is_empty())
+        .then(|| buf.start_single_line());
+    let needs_indent =
+        needs_indent || single_line.is_some_and(|single_line| single_line.must_break());
+
     let arg_indent = if needs_indent {
         indent + INDENT
     } else {
@@ -771,6 +788,10 @@ fn fmt_apply(
         }
     }
 
+    if let Some(single_line) = single_line {
+        buf.end_single_line(single_line);
+    }
+
     if !last_after.is_empty() {
         format_spaces(buf, last_after, Newlines::Yes, arg_indent);
     }
@@ -1289,6 +1310,7 @@ pub fn expr_lift_spaces<'a, 'b: 'a>(
         | Expr::TupleAccess(_, _)
         | Expr::Var { .. }
         | Expr::Underscore(_)
+        | Expr::TypedHole(_)
         | Expr::Crash
         | Expr::Tag(_)
         | Expr::OpaqueRef(_)
@@ -1419,6 +1441,7 @@ pub fn expr_prec(expr: Expr<'_>) -> Prec {
         | Expr::RecordUpdater(_)
         | Expr::Var { .. }
         | Expr::Underscore(_)
+        | Expr::TypedHole(_)
         | Expr::Crash
         | Expr::Tag(_)
         | Expr::OpaqueRef(_)
@@ -1501,6 +1524,12 @@ fn fmt_binops<'a>(
 ) {
     let is_multiline = loc_right_side.value.is_multiline()
         || lefts.iter().any(|(expr, _)| expr.value.is_multiline());
+    let single_line = (!is_multiline).then(|| buf.start_single_line());
+    let must_break = single_line.is_some_and(|single_line| single_line.must_break());
+
+    // The first operand might be on the same line as whatever comes before it, like `x = a`,
+    // so operators on new lines go further in until it's formatted again as multi-line.
+    let op_indent = if must_break { indent + INDENT } else { indent };
 
     for (loc_left_side, loc_binop) in lefts {
         let binop = loc_binop.value;
@@ -1529,9 +1558,9 @@ fn fmt_binops<'a>(
 
         format_spaces(buf, lifted_left_side.after, Newlines::Yes, indent);
 
-        if is_multiline {
+        if is_multiline || must_break {
             buf.ensure_ends_with_newline();
-            buf.indent(indent);
+            buf.indent(op_indent);
         } else {
             buf.spaces(1);
         }
@@ -1556,6 +1585,10 @@ fn fmt_binops<'a>(
             .format_with_options(buf, Parens::InOperator, Newlines::Yes, indent);
     }
 
+    if let Some(single_line) = single_line {
+        buf.end_single_line(single_line);
+    }
+
     format_spaces(buf, lifted_right_side.after, Newlines::Yes, indent);
 }
 
@@ -2125,6 +2158,12 @@ fn fmt_record_like<'a, 'b: 'a, Field, ToSpacesAround>(
 {
     let loc_fields = fields.items;
     let final_comments = fields.final_comments();
+    let is_multiline =
+        loc_fields.iter().any(|loc_field| loc_field.is_multiline()) || !final_comments.is_empty();
+    let single_line = (!is_multiline && !loc_fields.is_empty()).then(|| buf.start_single_line());
+    let is_multiline =
+        is_multiline || single_line.is_some_and(|single_line| single_line.must_break());
+
     buf.indent(indent);
     if loc_fields.is_empty() && final_comments.is_empty() && prefix.is_none() {
         buf.push_str("{}");
@@ -2153,9 +2192,6 @@ fn fmt_record_like<'a, 'b: 'a, Field, ToSpacesAround>(
             }
         }
 
-        let is_multiline = loc_fields.iter().any(|loc_field| loc_field.is_multiline())
-            || !final_comments.is_empty();
-
         if is_multiline {
             let field_indent = indent + INDENT;
 
@@ -2236,6 +2272,10 @@ fn fmt_record_like<'a, 'b: 'a, Field, ToSpacesAround>(
         buf.indent(indent);
         buf.push('}');
     }
+
+    if let Some(single_line) = single_line {
+        buf.end_single_line(single_line);
+    }
 }
 
 fn assigned_field_to_spaces<'a, 'b: 'a, T: Copy>(
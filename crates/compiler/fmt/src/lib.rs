@@ -12,6 +12,7 @@ pub mod pattern;
 pub mod spaces;
 
 use bumpalo::{collections::String, Bump};
+use roc_collections::MutSet;
 
 #[derive(Debug)]
 pub struct Buf<'a> {
@@ -21,6 +22,34 @@ pub struct Buf<'a> {
     beginning_of_line: bool,
     line_indent: u16,
     flags: MigrationFlags,
+    single_lines: SingleLines,
+}
+
+/// The layouts which were put on one line because nothing in them is multi-line, but which
+/// could be broken across lines to fit within a max width.
+#[derive(Debug, Default)]
+struct SingleLines {
+    /// How many have been started so far, which numbers them in the order they're formatted
+    started: usize,
+    /// The ones to break across lines anyway
+    to_break: MutSet<usize>,
+    /// Each one which was written, with the range of `text` it covers, if they're being recorded
+    written: Option<std::vec::Vec<(usize, usize, usize)>>,
+}
+
+/// A layout which is being written on one line, unless it must break to fit within a max width.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SingleLine {
+    index: usize,
+    start: usize,
+    must_break: bool,
+}
+
+impl SingleLine {
+    /// Whether to use the multi-line layout after all
+    pub(crate) fn must_break(&self) -> bool {
+        self.must_break
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -44,6 +73,7 @@ impl<'a> Buf<'a> {
             newlines_to_flush: 0,
             beginning_of_line: true,
             flags,
+            single_lines: SingleLines::default(),
         }
     }
 
@@ -187,4 +217,178 @@ impl<'a> Buf<'a> {
     fn is_empty(&self) -> bool {
         self.spaces_to_flush == 0 && self.text.is_empty()
     }
+
+    /// Called where a layout goes on one line because nothing in it is multi-line.
+    /// Unless `must_break` says otherwise, write it on one line and then call `end_single_line`.
+    pub(crate) fn start_single_line(&mut self) -> SingleLine {
+        let index = self.single_lines.started;
+
+        self.single_lines.started += 1;
+
+        SingleLine {
+            index,
+            // Where the next character will go, once pending whitespace is flushed
+            start: self.text.len() + self.newlines_to_flush + self.spaces_to_flush,
+            must_break: self.single_lines.to_break.contains(&index),
+        }
+    }
+
+    pub(crate) fn end_single_line(&mut self, single_line: SingleLine) {
+        if let (Some(written), false) = (&mut self.single_lines.written, single_line.must_break) {
+            written.push((single_line.index, single_line.start, self.text.len()));
+        }
+    }
+
+    /// The text written so far along with where its single-line layouts were, which outlives
+    /// the arena the text was written in.
+    fn into_recorded(self) -> Recorded {
+        Recorded {
+            text: self.text.as_str().to_string(),
+            written: self.single_lines.written.unwrap_or_default(),
+        }
+    }
+}
+
+/// Formatted text along with the range of each single-line layout which was written in it.
+struct Recorded {
+    text: std::string::String,
+    written: std::vec::Vec<(usize, usize, usize)>,
+}
+
+impl Recorded {
+    /// The single-line layouts to break next: on each line longer than `max_width`, the
+    /// outermost one which runs past it, leaving out those whose text is in `unbreakable`.
+    fn single_lines_past(
+        &self,
+        max_width: usize,
+        unbreakable: &MutSet<std::string::String>,
+    ) -> std::vec::Vec<(usize, &str)> {
+        let written = &self.written;
+        let mut line_start = 0;
+        let mut past = std::vec::Vec::new();
+
+        for line in self.text.split_inclusive('\n') {
+            let line_end = line_start + line.trim_end_matches('\n').len();
+            let column = |offset: usize| self.text[line_start..offset].chars().count();
+
+            if column(line_end) > max_width {
+                let outermost = (written.iter())
+                    .filter(|(_, start, end)| {
+                        (line_start..line_end).contains(start)
+                            && column((*end).min(line_end)) > max_width
+                            && !unbreakable.contains(&self.text[*start..*end])
+                    })
+                    .min_by_key(|(_, start, end)| (*start, std::cmp::Reverse(*end)));
+
+                if let Some((index, start, end)) = outermost {
+                    past.push((*index, &self.text[*start..*end]));
+                }
+            }
+
+            line_start += line.len();
+        }
+
+        past
+    }
+}
+
+/// Formats `src` with `fmt` into a new `Buf` in `arena`, breaking the single-line layouts
+/// numbered in `to_break` and recording where the others were written.
+fn fmt_recording<'b, E>(
+    arena: &'b Bump,
+    flags: MigrationFlags,
+    src: &'b str,
+    to_break: MutSet<usize>,
+    fmt: &impl Fn(&'b Bump, &mut Buf<'b>, &'b str) -> Result<(), E>,
+) -> Result<Buf<'b>, E> {
+    let mut buf = Buf::new_in(arena, flags);
+
+    buf.single_lines.to_break = to_break;
+    buf.single_lines.written = Some(std::vec::Vec::new());
+
+    fmt(arena, &mut buf, src).map(|()| buf)
+}
+
+/// Formats `src` with `fmt`, which parses it in the given arena and formats the result into
+/// the given `Buf`.
+///
+/// Then, for as long as a line is longer than `max_width`, the outermost layout on it which
+/// was put on one line (a collection, an application or a chain of operators) is broken across
+/// lines, and the result is formatted again so everything around it can adapt. This always
+/// picks the same layouts to break, so the result is stable. Lines which can't be broken any
+/// further, like ones with long strings in them, stay as they are.
+///
+/// If `fmt` fails on the result of breaking a layout, that layout is left on one line.
+///
+/// Each round is formatted in a scratch arena which is reset before the next one, so only the
+/// final result is left in `arena`.
+pub fn fmt_to_max_width<'a, E>(
+    arena: &'a Bump,
+    flags: MigrationFlags,
+    max_width: u16,
+    src: &'a str,
+    fmt: impl for<'b> Fn(&'b Bump, &mut Buf<'b>, &'b str) -> Result<(), E>,
+) -> Result<Buf<'a>, E> {
+    let mut scratch = Bump::new();
+    let mut current = fmt_recording(&scratch, flags, src, MutSet::default(), &fmt)?.into_recorded();
+    let mut unbreakable = MutSet::default();
+    let mut seen = MutSet::from_iter([current.text.clone()]);
+
+    loop {
+        let past = current.single_lines_past(max_width as usize, &unbreakable);
+
+        if past.is_empty() {
+            break;
+        }
+
+        scratch.reset();
+
+        // Formatting a broken layout on its own can leave what's around it in a layout that
+        // doesn't parse, or one which joins it back onto one line. Either way, it can't be broken.
+        let src = &*scratch.alloc_str(&current.text);
+        let break_in = |to_break: MutSet<usize>| {
+            let broken = fmt_recording(&scratch, flags, src, to_break, &fmt).ok()?;
+            let broken = broken.into_bump_str();
+
+            fmt_recording(&scratch, flags, broken, MutSet::default(), &fmt)
+                .ok()
+                .map(Buf::into_recorded)
+                .filter(|next| !seen.contains(&next.text))
+        };
+
+        // The long lines are usually independent, so break them all at once. If that fails,
+        // find the ones which can't be broken by trying each on its own, and break the rest.
+        let next = break_in(past.iter().map(|(index, _)| *index).collect()).or_else(|| {
+            let mut breakable = MutSet::default();
+            let mut first = None;
+
+            for (index, text) in past.iter() {
+                match break_in(MutSet::from_iter([*index])) {
+                    Some(next) => {
+                        breakable.insert(*index);
+                        first.get_or_insert(next);
+                    }
+                    None => {
+                        unbreakable.insert(text.to_string());
+                    }
+                }
+            }
+
+            match breakable.len() {
+                0 | 1 => first,
+                _ => break_in(breakable).or(first),
+            }
+        });
+
+        if let Some(next) = next {
+            seen.insert(next.text.clone());
+            current = next;
+        }
+    }
+
+    let mut buf = Buf::new_in(arena, flags);
+
+    fmt(arena, &mut buf, arena.alloc_str(&current.text))?;
+
+    Ok(buf)
 }
@@ -381,10 +381,15 @@ impl<'a> Formattable for Node<'a> {
                 items: lefts,
                 after: right,
             } => {
+                // Only collections go on one line without indenting their items; parens always indent.
+                let single_line = (!indent_items && !lefts.is_empty() && !self.is_multiline())
+                    .then(|| buf.start_single_line());
+                let must_break = single_line.is_some_and(|single_line| single_line.must_break());
+
                 buf.indent(indent);
                 buf.push(braces.start());
 
-                let inner_indent = if *indent_items {
+                let inner_indent = if *indent_items || must_break {
                     indent + INDENT
                 } else {
                     indent
@@ -392,21 +397,30 @@ impl<'a> Formattable for Node<'a> {
 
                 for item in *lefts {
                     fmt_spaces(buf, item.before.iter(), inner_indent);
-                    if item.newline {
+                    if item.newline || must_break {
                         buf.ensure_ends_with_newline();
                     } else if item.space {
                         buf.ensure_ends_with_whitespace();
                     }
                     item.node
                         .format_with_options(buf, parens, newlines, inner_indent);
-                    if item.comma_after {
+                    if item.comma_after || must_break {
                         buf.push(',');
                     }
                 }
-                fmt_sp(buf, *right, inner_indent);
+
+                if must_break {
+                    buf.ensure_ends_with_newline();
+                } else {
+                    fmt_sp(buf, *right, inner_indent);
+                }
 
                 buf.indent(indent);
                 buf.push(braces.end());
+
+                if let Some(single_line) = single_line {
+                    buf.end_single_line(single_line);
+                }
             }
             Node::Sequence {
                 first,
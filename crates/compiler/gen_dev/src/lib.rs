@@ -277,6 +277,7 @@ impl<'a> LastSeenMap<'a> {
 
             Stmt::Dbg { .. } => todo!("dbg not implemented in the dev backend"),
             Stmt::Expect { .. } => todo!("expect is not implemented in the dev backend"),
+            Stmt::Coverage { remainder, .. } => self.scan_ast_help(remainder),
 
             Stmt::Crash(msg, _crash_tag) => {
                 self.set_last_seen(*msg, stmt);
@@ -705,6 +706,8 @@ trait Backend<'a> {
                 self.free_symbols(stmt);
             }
             Stmt::Crash(msg, crash_tag) => self.roc_panic(*msg, *crash_tag),
+            // Only `roc test --coverage` counts the runs of code, and it uses the LLVM backend.
+            Stmt::Coverage { remainder, .. } => self.build_stmt(layout_ids, remainder, ret_layout),
             x => todo!("the statement, {:?}", x),
         }
     }
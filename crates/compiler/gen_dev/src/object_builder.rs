@@ -406,6 +406,9 @@ fn create_relocation(target: Target, symbol: SymbolId, offset: u64) -> write::Re
                 )
             }
         }
+        roc_target::Architecture::Riscv64 => {
+            internal_error!("the dev backend doesn't generate riscv64 code")
+        }
         roc_target::Architecture::Wasm32 => todo!(),
         roc_target::Architecture::X86_32 => todo!(),
         roc_target::Architecture::X86_64 => (
@@ -21,9 +21,10 @@ impl LlvmAlignment<'_> for IntWidth {
                 // 128-bit integers are not consistently represented by LLVM.
                 // - AArch64 uses 16-byte alignment (https://godbolt.org/z/dYrfG5o4b)
                 // - x86-64 uses 8-byte alignment (https://godbolt.org/z/qj5Mann6b)
+                // - 32-bit ARM aligns to 8 bytes, like its 64-bit integers
                 let arch = interner.target().architecture();
                 match arch {
-                    Architecture::X86_64 => 8,
+                    Architecture::X86_64 | Architecture::Aarch32 => 8,
                     _ => 16,
                 }
             }
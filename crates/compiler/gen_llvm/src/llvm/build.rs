@@ -22,7 +22,7 @@ use inkwell::debug_info::{
     AsDIScope, DICompileUnit, DIFlagsConstants, DISubprogram, DebugInfoBuilder,
 };
 use inkwell::memory_buffer::MemoryBuffer;
-use inkwell::module::{Linkage, Module};
+use inkwell::module::{FlagBehavior, Linkage, Module};
 use inkwell::passes::PassManager;
 use inkwell::types::{
     AnyType, BasicMetadataTypeEnum, BasicType, BasicTypeEnum, FloatMathType, FunctionType,
@@ -1029,9 +1029,15 @@ pub fn module_from_builtins<'ctx>(
             Target::LinuxX64 => {
                 include_bytes!("../../../builtins/bitcode/zig-out/builtins-x86_64.bc")
             }
+            Target::LinuxArm32 => {
+                include_bytes!("../../../builtins/bitcode/zig-out/builtins-arm.bc")
+            }
             Target::LinuxArm64 => {
                 include_bytes!("../../../builtins/bitcode/zig-out/builtins-aarch64.bc")
             }
+            Target::LinuxRiscv64 => {
+                include_bytes!("../../../builtins/bitcode/zig-out/builtins-riscv64.bc")
+            }
             Target::WinX64 => {
                 include_bytes!("../../../builtins/bitcode/zig-out/builtins-windows-x86_64.bc")
             }
@@ -1044,6 +1050,16 @@ pub fn module_from_builtins<'ctx>(
     let module = Module::parse_bitcode_from_buffer(&memory_buffer, ctx)
         .unwrap_or_else(|err| panic!("Unable to import builtins bitcode. LLVM error: {err:?}"));
 
+    // Without this flag, LLVM would use the soft-float ABI on RISC-V, but C compilers for
+    // RV64GC Linux pass floats in floating-point registers.
+    if target == Target::LinuxRiscv64 && module.get_flag("target-abi").is_none() {
+        module.add_metadata_flag(
+            "target-abi",
+            FlagBehavior::Error,
+            ctx.metadata_string("lp64d"),
+        );
+    }
+
     // In testing, this adds about 20ms extra to compilation.
     // Long term it would be best if we could do this on the zig side.
     // The core issue is that we have to properly labael certain functions as private and DCE them.
@@ -1070,6 +1086,7 @@ pub fn module_from_builtins<'ctx>(
         "__roc_force_longjmp",
         "__roc_force_setjmp",
         "set_shared_buffer",
+        "roc_dict_pseudo_seed",
     ];
     for func in module.get_functions() {
         let has_definition = func.count_basic_blocks() > 0;
@@ -3487,6 +3504,36 @@ pub(crate) fn build_exp_stmt<'a, 'ctx>(
             )
         }
 
+        Coverage {
+            module,
+            counter,
+            remainder,
+        } => {
+            let i64_type = env.context.i64_type();
+            let counter_ptr = get_coverage_counter_ptr(env, *module, *counter);
+
+            let count = env
+                .builder
+                .new_build_load(i64_type, counter_ptr, "load_coverage_count")
+                .into_int_value();
+            let count = env.builder.new_build_int_add(
+                count,
+                i64_type.const_int(1, false),
+                "coverage_count",
+            );
+            env.builder.new_build_store(counter_ptr, count);
+
+            build_exp_stmt(
+                env,
+                layout_interner,
+                layout_ids,
+                func_spec_solutions,
+                scope,
+                parent,
+                remainder,
+            )
+        }
+
         Expect {
             condition: cond_symbol,
             region,
@@ -4899,6 +4946,10 @@ pub fn get_sjlj_buffer<'ctx>(env: &Env<'_, 'ctx, '_>) -> PointerValue<'ctx> {
         // Due to https://github.com/llvm/llvm-project/issues/72908
         // on windows, we store the register contents into this buffer directly!
         30
+    } else if env.target.architecture() == roc_target::Architecture::Riscv64 {
+        // The libc jmp_buf of RISC-V holds 12 integer and 12 floating-point callee-saved
+        // registers besides pc and sp, and glibc adds a signal mask after those.
+        64
     } else {
         5
     };
@@ -4921,8 +4972,12 @@ pub fn get_sjlj_buffer<'ctx>(env: &Env<'_, 'ctx, '_>) -> PointerValue<'ctx> {
 
 pub fn build_setjmp_call<'ctx>(env: &Env<'_, 'ctx, '_>) -> BasicValueEnum<'ctx> {
     let jmp_buf = get_sjlj_buffer(env);
-    if env.target.architecture() == roc_target::Architecture::Aarch64 {
+    if matches!(
+        env.target.architecture(),
+        roc_target::Architecture::Aarch64 | roc_target::Architecture::Riscv64
+    ) {
         // Due to https://github.com/roc-lang/roc/issues/2965, we use a setjmp we linked in from Zig
+        // (LLVM has no SjLj lowering for RISC-V at all)
         call_bitcode_fn(env, &[jmp_buf.into()], bitcode::UTILS_SETJMP)
     } else if env.target.operating_system() == roc_target::OperatingSystem::Windows {
         // Due to https://github.com/llvm/llvm-project/issues/72908, we use a setjmp defined as asm in Zig
@@ -4998,6 +5053,24 @@ pub fn get_panic_msg_ptr<'ctx>(env: &Env<'_, 'ctx, '_>) -> PointerValue<'ctx> {
     global.as_pointer_value()
 }
 
+/// Pointer to a counter of `roc test --coverage`, which the test runner reads by its name.
+fn get_coverage_counter_ptr<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    module_id: ModuleId,
+    counter: u32,
+) -> PointerValue<'ctx> {
+    let i64_typ = env.context.i64_type();
+
+    let global_name = roc_mono::coverage::counter_name(module_id, counter);
+    let global = env.module.get_global(&global_name).unwrap_or_else(|| {
+        let global = env.module.add_global(i64_typ, None, &global_name);
+        global.set_initializer(&i64_typ.const_zero());
+        global
+    });
+
+    global.as_pointer_value()
+}
+
 /// Pointer to the panic tag.
 /// Only non-zero values must be written into here.
 pub fn get_panic_tag_ptr<'ctx>(env: &Env<'_, 'ctx, '_>) -> PointerValue<'ctx> {
@@ -5466,19 +5539,29 @@ pub fn build_procedures_return_main<'a, 'ctx>(
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn build_procedures_expose_expects<'a>(
     env: &Env<'a, '_, '_>,
     layout_interner: &STLayoutInterner<'a>,
     opt_level: OptLevel,
     expects_by_module: MutMap<ModuleId, Vec<'a, Symbol>>,
+    properties: &[Symbol],
+    benches: &[Symbol],
+    snapshots: &[Symbol],
     procedures: MutMap<(Symbol, ProcLayout<'a>), roc_mono::ir::Proc<'a>>,
 ) -> MutMap<ModuleId, Vec<'a, &'a str>> {
     // converts Vec<Vec<Symbol>> into Vec<Symbol>
-    let flattened_symbols: Vec<Symbol> =
-        Vec::from_iter_in(expects_by_module.values().flatten().copied(), env.arena);
+    let flattened_symbols: Vec<Symbol> = Vec::from_iter_in(
+        (expects_by_module.values().flatten().copied())
+            .filter(|s| !properties.contains(s) && !benches.contains(s)),
+        env.arena,
+    );
 
     let entry_point = EntryPoint::Expects {
         symbols: &flattened_symbols,
+        properties,
+        benches,
+        snapshots,
     };
 
     let mod_solutions = build_procedures_help(
@@ -5499,19 +5582,34 @@ pub fn build_procedures_expose_expects<'a>(
         niche: captures_niche,
     };
 
+    let snapshot = ProcLayout {
+        result: Layout::STR,
+        ..top_level
+    };
+
     let mut expect_names_by_module = MutMap::default();
 
     for (module_id, expects) in expects_by_module {
         let mut expect_names = Vec::with_capacity_in(expects.len(), env.arena);
 
         for symbol in expects.iter().copied() {
-            let args_iter = top_level.arguments.iter().copied();
+            let proc_layout = if properties.contains(&symbol) {
+                roc_mono::property::RUNNER_LAYOUT
+            } else if benches.contains(&symbol) {
+                roc_mono::bench::RUNNER_LAYOUT
+            } else if snapshots.contains(&symbol) {
+                snapshot
+            } else {
+                top_level
+            };
+
+            let args_iter = proc_layout.arguments.iter().copied();
 
             let func_name_bytes = roc_alias_analysis::func_name_bytes_help(
                 symbol,
                 args_iter,
                 captures_niche,
-                top_level.result,
+                proc_layout.result,
             );
 
             let func_name = FuncName(&func_name_bytes);
@@ -5545,8 +5643,8 @@ pub fn build_procedures_expose_expects<'a>(
                 layout_interner,
                 name,
                 roc_main_fn,
-                top_level.arguments,
-                top_level.result,
+                proc_layout.arguments,
+                proc_layout.result,
                 &format!("Expect_{name}"),
             );
         }
@@ -1,7 +1,8 @@
 use crate::debug_info_init;
 use crate::llvm::bitcode::call_str_bitcode_fn;
 use crate::llvm::build::{
-    create_entry_block_alloca, get_tag_id, store_roc_value, tag_pointer_clear_tag_id, Env,
+    create_entry_block_alloca, get_tag_id, store_roc_value, tag_pointer_clear_tag_id,
+    throw_internal_exception, Env,
 };
 use crate::llvm::build_list::{self, incrementing_elem_loop};
 use crate::llvm::convert::{basic_type_from_layout, RocUnion};
@@ -9,7 +10,7 @@ use inkwell::builder::Builder;
 use inkwell::module::Linkage;
 use inkwell::types::{BasicMetadataTypeEnum, BasicType, BasicTypeEnum};
 use inkwell::values::{BasicValueEnum, FunctionValue, IntValue, PointerValue};
-use inkwell::AddressSpace;
+use inkwell::{AddressSpace, IntPredicate};
 use roc_builtins::bitcode;
 use roc_error_macros::{internal_error, todo_lambda_erasure};
 use roc_module::symbol::Symbol;
@@ -139,6 +140,39 @@ fn offset_add<'ctx>(
     builder.new_build_int_add(current, intval, "offset_add")
 }
 
+/// Crash instead of writing past the end of the shared memory, if it ends before `end`.
+fn ensure_fits<'ctx>(env: &Env<'_, 'ctx, '_>, end: IntValue<'ctx>) {
+    let bd = env.builder;
+
+    let length_function = env
+        .module
+        .get_function(bitcode::UTILS_EXPECT_SHARED_BUFFER_LENGTH)
+        .unwrap();
+
+    let length = bd
+        .new_build_call(length_function, &[], "call_shared_buffer_length")
+        .try_as_basic_value()
+        .left()
+        .unwrap()
+        .into_int_value();
+
+    let fits = bd.new_build_int_compare(IntPredicate::ULE, end, length, "fits");
+
+    let parent = bd.get_insert_block().and_then(|b| b.get_parent()).unwrap();
+    let fits_block = env.context.append_basic_block(parent, "fits");
+    let too_big_block = env.context.append_basic_block(parent, "too_big");
+
+    bd.new_build_conditional_branch(fits, fits_block, too_big_block);
+
+    bd.position_at_end(too_big_block);
+    throw_internal_exception(
+        env,
+        "The values of this failed expect are too big to report.",
+    );
+
+    bd.position_at_end(fits_block);
+}
+
 pub(crate) fn notify_parent_expect(env: &Env, shared_memory: &SharedMemoryPointer) {
     let func = env
         .module
@@ -181,15 +215,26 @@ pub(crate) fn clone_to_shared_memory<'a, 'ctx>(
 
     let (count, mut offset) = read_state(env, original_ptr);
 
-    offset = write_header(env, original_ptr, offset, condition, region);
-
-    let after_header = offset;
-
     let space_for_offsets = env.ptr_int().const_int(
         (lookups.len() * env.target.ptr_size() + lookups.len() * std::mem::size_of::<u32>()) as _,
         false,
     );
 
+    // the region and the module id that `write_header` writes
+    let header_size = env
+        .ptr_int()
+        .const_int(3 * std::mem::size_of::<u32>() as u64, false);
+    let header_end = env.builder.new_build_int_add(offset, header_size, "offset");
+    ensure_fits(
+        env,
+        env.builder
+            .new_build_int_add(header_end, space_for_offsets, "offset"),
+    );
+
+    offset = write_header(env, original_ptr, offset, condition, region);
+
+    let after_header = offset;
+
     let mut lookup_starts = bumpalo::collections::Vec::with_capacity_in(lookups.len(), env.arena);
 
     offset = env
@@ -206,6 +251,7 @@ pub(crate) fn clone_to_shared_memory<'a, 'ctx>(
             .const_int(layout_interner.stack_size(layout) as u64, false);
 
         let mut extra_offset = env.builder.new_build_int_add(offset, stack_size, "offset");
+        ensure_fits(env, extra_offset);
 
         let cursors = Cursors {
             offset,
@@ -716,6 +762,7 @@ fn build_clone_tag_help<'a, 'ctx>(
                         "new_offset",
                     ),
                 };
+                ensure_fits(env, cursors.extra_offset);
 
                 let answer =
                     build_clone(env, layout_interner, layout_ids, ptr, cursors, data, layout);
@@ -755,6 +802,7 @@ fn build_clone_tag_help<'a, 'ctx>(
                     "new_offset",
                 ),
             };
+            ensure_fits(env, cursors.extra_offset);
 
             let data = load_tag_data(env, layout_interner, union_layout, tag_value, basic_type);
 
@@ -811,6 +859,7 @@ fn build_clone_tag_help<'a, 'ctx>(
                             "new_offset",
                         ),
                     };
+                    ensure_fits(env, cursors.extra_offset);
 
                     let tag_value = tag_pointer_clear_tag_id(env, tag_value.into_pointer_value());
                     let data =
@@ -884,6 +933,7 @@ fn build_clone_tag_help<'a, 'ctx>(
                         "new_offset",
                     ),
                 };
+                ensure_fits(env, cursors.extra_offset);
 
                 let data = load_tag_data(
                     env,
@@ -974,18 +1024,36 @@ fn build_clone_builtin<'a, 'ctx>(
             cursors.extra_offset
         }
 
-        Builtin::Str => call_str_bitcode_fn(
-            env,
-            &[value],
-            &[
-                ptr.into(),
-                cursors.offset.into(),
-                cursors.extra_offset.into(),
-            ],
-            crate::llvm::bitcode::BitcodeReturns::Basic,
-            bitcode::STR_CLONE_TO,
-        )
-        .into_int_value(),
+        Builtin::Str => {
+            // small strings are written inline, so this is more than they need
+            let bytes = call_str_bitcode_fn(
+                env,
+                &[value],
+                &[],
+                crate::llvm::bitcode::BitcodeReturns::Basic,
+                bitcode::STR_COUNT_UTF8_BYTES,
+            )
+            .into_int_value();
+
+            ensure_fits(
+                env,
+                env.builder
+                    .new_build_int_add(cursors.extra_offset, bytes, "str_end"),
+            );
+
+            call_str_bitcode_fn(
+                env,
+                &[value],
+                &[
+                    ptr.into(),
+                    cursors.offset.into(),
+                    cursors.extra_offset.into(),
+                ],
+                crate::llvm::bitcode::BitcodeReturns::Basic,
+                bitcode::STR_CLONE_TO,
+            )
+            .into_int_value()
+        }
         Builtin::List(elem) => {
             let bd = env.builder;
 
@@ -1009,6 +1077,11 @@ fn build_clone_builtin<'a, 'ctx>(
             let elements_start_offset = cursors.extra_offset;
 
             if layout_interner.safe_to_memcpy(elem) {
+                ensure_fits(
+                    env,
+                    bd.new_build_int_add(elements_start_offset, elements_width, "elements_end"),
+                );
+
                 // NOTE we are not actually sure the dest is properly aligned
                 let dest = pointer_at_offset(bd, env.context.i8_type(), ptr, elements_start_offset);
                 let src = bd.new_build_pointer_cast(
@@ -1037,6 +1110,7 @@ fn build_clone_builtin<'a, 'ctx>(
                     bd.new_build_int_mul(len, element_stack_size, "elements_width"),
                     "rest_start_offset",
                 );
+                ensure_fits(env, rest_start_offset);
                 bd.new_build_store(rest_offset, rest_start_offset);
 
                 let body = |layout_interner: &STLayoutInterner<'a>, index, element| {
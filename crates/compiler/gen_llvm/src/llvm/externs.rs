@@ -315,8 +315,12 @@ pub fn add_sjlj_roc_panic(env: &Env<'_, '_, '_>) {
 
 pub fn build_longjmp_call(env: &Env) {
     let jmp_buf = get_sjlj_buffer(env);
-    if env.target.architecture() == roc_target::Architecture::Aarch64 {
+    if matches!(
+        env.target.architecture(),
+        roc_target::Architecture::Aarch64 | roc_target::Architecture::Riscv64
+    ) {
         // Due to https://github.com/roc-lang/roc/issues/2965, we use a setjmp we linked in from Zig
+        // (LLVM has no SjLj lowering for RISC-V at all)
         let tag = env.context.i32_type().const_int(1, false);
         let _call =
             call_void_bitcode_fn(env, &[jmp_buf.into(), tag.into()], bitcode::UTILS_LONGJMP);
@@ -255,7 +255,7 @@ pub(crate) fn run_low_level<'a, 'ctx>(
                         }
                     }
                 }
-                Aarch64 | X86_64 => {
+                Aarch64 | X86_64 | Riscv64 => {
                     let (type_name, width) = {
                         match layout_interner.get_repr(number_layout) {
                             LayoutRepr::Builtin(Builtin::Int(int_width)) => {
@@ -417,7 +417,7 @@ pub(crate) fn run_low_level<'a, 'ctx>(
                         bitcode::STR_FROM_UTF8,
                     );
                 }
-                Aarch64 | X86_64 | Wasm32 => {
+                Aarch64 | X86_64 | Riscv64 | Wasm32 => {
                     arguments!(_list);
 
                     // we use the symbol here instead
@@ -607,6 +607,8 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
 
             Stmt::Dbg { .. } => todo!("dbg is not implemented in the wasm backend"),
             Stmt::Expect { .. } => todo!("expect is not implemented in the wasm backend"),
+            // Only `roc test --coverage` counts the runs of code, and it uses the LLVM backend.
+            Stmt::Coverage { remainder, .. } => self.stmt(remainder),
 
             Stmt::Crash(sym, tag) => self.stmt_crash(*sym, *tag),
         }
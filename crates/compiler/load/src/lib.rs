@@ -24,6 +24,7 @@ pub use roc_load_internal::file::{
 pub use roc_load_internal::module::{
     CheckedModule, EntryPoint, Expectations, ExposedToHost, LoadedModule, MonomorphizedModule,
 };
+pub use roc_load_internal::SolvedModules;
 pub use roc_solve::FunctionKind;
 
 #[allow(clippy::too_many_arguments)]
@@ -72,6 +73,7 @@ pub fn load_single_threaded<'a>(
         palette,
         exec_mode,
         roc_cache_dir,
+        None,
     )
 }
 
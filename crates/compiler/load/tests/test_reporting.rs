@@ -130,6 +130,7 @@ mod test_reporting {
                 threading: Threading::Single,
                 exec_mode: ExecutionMode::Check,
                 function_kind: FunctionKind::LambdaSet,
+                solved_modules: None,
             };
             let result = roc_load::load_and_typecheck(
                 arena,
@@ -187,7 +188,7 @@ mod test_reporting {
         let mut buf = String::new();
 
         match infer_expr_help_new(subdir, arena, src) {
-            Err(LoadingProblem::FormattedReport(fail, _)) => fail,
+            Err(LoadingProblem::FormattedReport(fail)) => fail.text,
             Ok((module_src, type_problems, can_problems, home, interns)) => {
                 let lines = LineInfo::new(&module_src);
                 let src_lines: Vec<&str> = module_src.split('\n').collect();
@@ -10695,15 +10696,18 @@ All branches in an `if` must have the same type!
         |golden| pretty_assertions::assert_eq!(
             golden,
             indoc!(
-                r"── SYNTAX PROBLEM in /code/proj/Main.roc ───────────────────────────────────────
+                r"── TYPED HOLE in /code/proj/Main.roc ───────────────────────────────────────────
 
-                An underscore is being used as a variable here:
+                This hole still needs to be filled in:
 
                 6│      f 1 _ 1
                             ^
 
-                An underscore can be used to ignore a value when pattern matching, but
-                it cannot be used as a variable.
+                It needs a value of this type:
+
+                    Num *
+
+                Nothing in scope has that type.
                 "
             ),
         )
@@ -10768,6 +10772,73 @@ All branches in an `if` must have the same type!
         )
     );
 
+    test_report!(
+        call_with_named_typed_hole,
+        indoc!(
+            r"
+            f = \x, y, z -> x + y + z
+
+            \a, _b -> f a ?r 1
+            "
+        ),
+        |golden| pretty_assertions::assert_eq!(
+            golden,
+            indoc!(
+                r"── TYPED HOLE in /code/proj/Main.roc ───────────────────────────────────────────
+
+                The ?r hole still needs to be filled in:
+
+                6│      \a, _b -> f a ?r 1
+                                      ^^
+
+                It needs a value of this type:
+
+                    Num *
+
+                These names in scope have that type:
+
+                    `a`
+                "
+            ),
+        )
+    );
+
+    test_report!(
+        typed_hole_suggests_names_in_scope,
+        indoc!(
+            r#"
+            count = 2
+            suffix = Str.repeat "!" count
+
+            greet : Str -> Str
+            greet = \name -> Str.concat (Str.concat name suffix) _
+
+            greet "Hello"
+            "#
+        ),
+        |golden| pretty_assertions::assert_eq!(
+            golden,
+            indoc!(
+                r"── TYPED HOLE in /code/proj/Main.roc ───────────────────────────────────────────
+
+                This hole still needs to be filled in:
+
+                8│      greet = \name -> Str.concat (Str.concat name suffix) _
+                                                                             ^
+
+                It needs a value of this type:
+
+                    Str
+
+                These names in scope have that type:
+
+                    `name`
+                    `suffix`
+                "
+            ),
+        )
+    );
+
     test_report!(
         call_with_declared_identifier_with_more_than_one_underscore,
         indoc!(
@@ -7,6 +7,7 @@ use crate::module::{
     ModuleTiming, MonomorphizedModule, ParsedModule, ToplevelExpects, TypeCheckedModule,
 };
 use crate::module_cache::ModuleCache;
+use crate::type_cache::{self, SolvedModules, TypeCache, TypeCacheEntry};
 use bumpalo::{collections::CollectIn, Bump};
 use crossbeam::channel::{bounded, Sender};
 use crossbeam::deque::{Injector, Worker};
@@ -38,6 +39,8 @@ use roc_module::symbol::{
     IdentIds, IdentIdsByModule, Interns, ModuleId, ModuleIds, PQModuleName, PackageModuleIds,
     PackageQualified, Symbol,
 };
+use roc_mono::bench::Bench;
+use roc_mono::coverage::CoverageRegions;
 use roc_mono::ir::{
     CapturedSymbols, ExternalSpecializations, GlueLayouts, HostExposedLambdaSets, PartialProc,
     Proc, ProcLayout, Procs, ProcsBase, UpdateModeIds, UsageTrackingMap,
@@ -45,6 +48,7 @@ use roc_mono::ir::{
 use roc_mono::layout::{
     GlobalLayoutInterner, LambdaName, Layout, LayoutCache, LayoutProblem, Niche, STLayoutInterner,
 };
+use roc_mono::property::Property;
 use roc_mono::reset_reuse;
 use roc_mono::{drop_specialization, inc_dec};
 use roc_packaging::cache::RocCacheDir;
@@ -59,8 +63,10 @@ use roc_problem::Severity;
 use roc_region::all::{LineInfo, Loc, Region};
 use roc_reporting::error::r#type::suggest;
 #[cfg(not(target_family = "wasm"))]
-use roc_reporting::report::to_https_problem_report_string;
-use roc_reporting::report::{to_file_problem_report_string, Palette, RenderTarget};
+use roc_reporting::report::to_https_problem_rendered_report;
+use roc_reporting::report::{
+    to_file_problem_rendered_report, Palette, RenderTarget, RenderedReport,
+};
 use roc_solve::module::{extract_module_owned_implementations, SolveConfig, Solved, SolvedModule};
 use roc_solve::FunctionKind;
 use roc_solve_problem::TypeError;
@@ -113,6 +119,9 @@ pub struct LoadConfig {
     pub threading: Threading,
     pub exec_mode: ExecutionMode,
     pub function_kind: FunctionKind,
+    /// Modules solved by earlier loads, e.g. the previous run of `roc check --watch`.
+    /// The modules solved by this load are added to it.
+    pub solved_modules: Option<SolvedModules>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -124,6 +133,12 @@ pub enum ExecutionMode {
     /// Test is like [`ExecutionMode::ExecutableIfCheck`], but rather than producing a proper
     /// executable, run tests.
     Test,
+    /// Like [`ExecutionMode::Test`], but also count how many times each branch and def body of
+    /// the modules in the root package runs.
+    TestWithCoverage,
+    /// Built like [`ExecutionMode::Test`], but rather than the toplevel `expect`s, run the
+    /// benchmarks: the toplevel functions whose names start with `bench_`.
+    Bench,
 }
 
 impl ExecutionMode {
@@ -132,12 +147,19 @@ impl ExecutionMode {
 
         match self {
             Executable => Phase::MakeSpecializations,
-            Check | ExecutableIfCheck | Test => Phase::SolveTypes,
+            Check | ExecutableIfCheck | Test | TestWithCoverage | Bench => Phase::SolveTypes,
         }
     }
 
     fn build_if_checks(&self) -> bool {
-        matches!(self, Self::ExecutableIfCheck | Self::Test)
+        matches!(
+            self,
+            Self::ExecutableIfCheck | Self::Test | Self::TestWithCoverage | Self::Bench
+        )
+    }
+
+    fn is_test(&self) -> bool {
+        matches!(self, Self::Test | Self::TestWithCoverage | Self::Bench)
     }
 }
 
@@ -301,6 +323,28 @@ fn start_phase<'a>(
                     }
                 }
 
+                // A cached module is still canonicalized, but its constraints aren't generated,
+                // and its solved types are taken from the cache instead of solving it.
+                if let Some(type_cache) = &state.type_cache {
+                    let is_host_exposed = state.root_id == module_id;
+
+                    if let Some(key) = TypeCache::key(
+                        &parsed,
+                        is_host_exposed,
+                        state.function_kind,
+                        &state.module_cache.type_cache_keys,
+                    ) {
+                        state.module_cache.type_cache_keys.insert(module_id, key);
+
+                        if let Vacant(entry) = state.cached_types.lock().entry(module_id) {
+                            if let Some(type_state) = type_cache.read(key) {
+                                entry.insert(type_state);
+                                state.module_cache.types_from_type_cache.insert(module_id);
+                            }
+                        }
+                    }
+                }
+
                 let skip_constraint_gen = {
                     // Give this its own scope to make sure that the Guard from the lock() is dropped
                     // immediately after contains_key returns
@@ -350,6 +394,19 @@ fn start_phase<'a>(
 
                 let is_host_exposed = state.root_id == module.module_id;
 
+                // Modules whose types came from a cache don't need to be written back to it.
+                let type_cache_entry = match (
+                    &state.type_cache,
+                    state.module_cache.type_cache_keys.get(&module_id),
+                ) {
+                    (Some(type_cache), Some(key))
+                        if !state.cached_types.lock().contains_key(&module_id) =>
+                    {
+                        Some(type_cache.entry(*key))
+                    }
+                    _ => None,
+                };
+
                 BuildTask::solve_module(
                     module,
                     ident_ids,
@@ -365,6 +422,7 @@ fn start_phase<'a>(
                     dep_idents,
                     declarations,
                     state.cached_types.clone(),
+                    type_cache_entry,
                     derived_module,
                     state.exec_mode,
                     is_host_exposed,
@@ -416,8 +474,9 @@ fn start_phase<'a>(
 
                 let derived_module = SharedDerivedModule::clone(&state.derived_module);
 
-                let build_expects =
-                    matches!(state.exec_mode, ExecutionMode::Test) && expectations.is_some();
+                let build_expects = state.exec_mode.is_test() && expectations.is_some();
+                let build_benches =
+                    matches!(state.exec_mode, ExecutionMode::Bench) && build_expects;
 
                 BuildTask::BuildPendingSpecializations {
                     layout_cache,
@@ -434,6 +493,7 @@ fn start_phase<'a>(
                     derived_module,
                     expectations,
                     build_expects,
+                    build_benches,
                 }
             }
             Phase::MakeSpecializations => {
@@ -757,9 +817,12 @@ struct State<'a> {
 
     make_specializations_pass: MakeSpecializationsPass,
 
-    // cached types (used for builtin modules, could include packages in the future too)
+    // cached types (used for builtin modules, and for modules found in type_cache)
     cached_types: CachedTypeState,
 
+    /// Solved types of modules from earlier runs, only used by `roc check`
+    type_cache: Option<TypeCache>,
+
     layout_interner: GlobalLayoutInterner<'a>,
 }
 
@@ -786,10 +849,25 @@ impl<'a> State<'a> {
         palette: Palette,
         number_of_workers: usize,
         exec_mode: ExecutionMode,
+        roc_cache_dir: RocCacheDir<'_>,
+        solved_modules: Option<SolvedModules>,
     ) -> Self {
         let cache_dir = roc_packaging::cache::roc_cache_packages_dir();
         let dependencies = Dependencies::new(exec_mode.goal_phase());
 
+        // The later phases need more than what's in the type cache (e.g. the derived module),
+        // so only checking uses it.
+        let type_cache = match exec_mode {
+            ExecutionMode::Check => {
+                TypeCache::new(roc_cache_dir.as_persistent_path(), solved_modules)
+            }
+            ExecutionMode::Executable
+            | ExecutionMode::ExecutableIfCheck
+            | ExecutionMode::Test
+            | ExecutionMode::TestWithCoverage
+            | ExecutionMode::Bench => None,
+        };
+
         Self {
             root_id,
             root_path,
@@ -819,6 +897,7 @@ impl<'a> State<'a> {
             timings: MutMap::default(),
             layout_caches: std::vec::Vec::with_capacity(number_of_workers),
             cached_types: Arc::new(Mutex::new(cached_types)),
+            type_cache,
             render,
             palette,
             exec_mode,
@@ -916,6 +995,7 @@ enum BuildTask<'a> {
         declarations: Declarations,
         dep_idents: IdentIdsByModule,
         cached_subs: CachedTypeState,
+        type_cache_entry: Option<TypeCacheEntry>,
         derived_module: SharedDerivedModule,
         exec_mode: ExecutionMode,
         is_host_exposed: bool,
@@ -937,6 +1017,7 @@ enum BuildTask<'a> {
         derived_module: SharedDerivedModule,
         expectations: Option<Expectations>,
         build_expects: bool,
+        build_benches: bool,
     },
     MakeSpecializations {
         module_id: ModuleId,
@@ -992,8 +1073,8 @@ pub enum LoadingProblem<'a> {
     ErrJoiningWorkerThreads,
     TriedToImportAppModule,
 
-    /// a formatted report
-    FormattedReport(String, Option<Region>),
+    /// a report that was already rendered, e.g. because the problem's source isn't around anymore
+    FormattedReport(RenderedReport),
 
     ImportCycle(PathBuf, Vec<ModuleId>),
     IncorrectModuleName(FileError<'a, IncorrectModuleName<'a>>),
@@ -1031,7 +1112,8 @@ impl<'a> LoadingProblem<'a> {
             LoadingProblem::UnexpectedHeader(_) => None,
             LoadingProblem::ErrJoiningWorkerThreads => None,
             LoadingProblem::TriedToImportAppModule => None,
-            LoadingProblem::FormattedReport(_, region) => *region,
+            // its region is already in lines and columns
+            LoadingProblem::FormattedReport(_) => None,
             LoadingProblem::ImportCycle(_, _) => None,
             LoadingProblem::IncorrectModuleName(_) => None,
             LoadingProblem::CouldNotFindCacheDir => None,
@@ -1121,6 +1203,7 @@ pub fn load_and_typecheck_str<'a>(
         threading,
         exec_mode: ExecutionMode::Check,
         function_kind,
+        solved_modules: None,
     };
 
     match load(
@@ -1213,12 +1296,11 @@ impl<'a> LoadStart<'a> {
                     })
                     .into_inner()
                     .into_module_ids();
-                let region = problem.get_region();
                 let report = report_loading_problem(problem, module_ids, render, palette);
 
                 // TODO try to gracefully recover and continue
                 // instead of changing the control flow to exit.
-                return Err(LoadingProblem::FormattedReport(report, region));
+                return Err(LoadingProblem::FormattedReport(report));
             }
         };
 
@@ -1541,6 +1623,7 @@ pub fn load<'a>(
             load_config.palette,
             load_config.exec_mode,
             roc_cache_dir,
+            load_config.solved_modules,
         ),
         Threads::Many(threads) => load_multi_threaded(
             arena,
@@ -1554,6 +1637,7 @@ pub fn load<'a>(
             threads,
             load_config.exec_mode,
             roc_cache_dir,
+            load_config.solved_modules,
         ),
     }
 }
@@ -1570,6 +1654,7 @@ pub fn load_single_threaded<'a>(
     palette: Palette,
     exec_mode: ExecutionMode,
     roc_cache_dir: RocCacheDir<'_>,
+    solved_modules: Option<SolvedModules>,
 ) -> Result<LoadResult<'a>, LoadingProblem<'a>> {
     let LoadStart {
         arc_modules,
@@ -1607,6 +1692,8 @@ pub fn load_single_threaded<'a>(
         palette,
         number_of_workers,
         exec_mode,
+        roc_cache_dir,
+        solved_modules,
     );
 
     // We'll add tasks to this, and then worker threads will take tasks from it.
@@ -1716,28 +1803,27 @@ fn state_thread_step<'a>(
                     Ok(ControlFlow::Break(LoadResult::Monomorphized(monomorphized)))
                 }
                 Msg::FailedToReadFile { filename, error } => {
-                    let buf = to_file_problem_report_string(filename, error, true);
-                    Err(LoadingProblem::FormattedReport(buf, None))
+                    let report = to_file_problem_rendered_report(filename, error, true);
+                    Err(LoadingProblem::FormattedReport(report))
                 }
 
                 Msg::FailedToParse(problem) => {
-                    let region = problem.problem.problem.get_region();
                     let module_ids = (*state.arc_modules).lock().clone().into_module_ids();
-                    let buf = to_parse_problem_report(
+                    let report = to_parse_problem_report(
                         problem,
                         module_ids,
                         state.constrained_ident_ids,
                         state.render,
                         state.palette,
                     );
-                    Err(LoadingProblem::FormattedReport(buf, region))
+                    Err(LoadingProblem::FormattedReport(report))
                 }
                 Msg::IncorrectModuleName(FileError {
                     problem: SourceError { problem, bytes },
                     filename,
                 }) => {
                     let module_ids = (*state.arc_modules).lock().clone().into_module_ids();
-                    let (buf, region) = to_incorrect_module_name_report(
+                    let report = to_incorrect_module_name_report(
                         module_ids,
                         state.constrained_ident_ids,
                         problem,
@@ -1745,7 +1831,7 @@ fn state_thread_step<'a>(
                         bytes,
                         state.render,
                     );
-                    Err(LoadingProblem::FormattedReport(buf, Some(region)))
+                    Err(LoadingProblem::FormattedReport(report))
                 }
                 msg => {
                     // This is where most of the main thread's work gets done.
@@ -1762,7 +1848,6 @@ fn state_thread_step<'a>(
                     match res_state {
                         Ok(new_state) => Ok(ControlFlow::Continue(new_state)),
                         Err(LoadingProblem::ParsingFailed(problem)) => {
-                            let region = problem.problem.problem.get_region();
                             let module_ids = Arc::try_unwrap(arc_modules)
                                 .unwrap_or_else(|_| {
                                     panic!(
@@ -1774,27 +1859,27 @@ fn state_thread_step<'a>(
 
                             // if parsing failed, this module did not add anything to IdentIds
                             let root_exposed_ident_ids = IdentIds::exposed_builtins(0);
-                            let buf = to_parse_problem_report(
+                            let report = to_parse_problem_report(
                                 problem,
                                 module_ids,
                                 root_exposed_ident_ids,
                                 render,
                                 palette,
                             );
-                            Err(LoadingProblem::FormattedReport(buf, region))
+                            Err(LoadingProblem::FormattedReport(report))
                         }
                         Err(LoadingProblem::ImportCycle(filename, cycle)) => {
                             let module_ids = arc_modules.lock().clone().into_module_ids();
 
                             let root_exposed_ident_ids = IdentIds::exposed_builtins(0);
-                            let buf = to_import_cycle_report(
+                            let report = to_import_cycle_report(
                                 module_ids,
                                 root_exposed_ident_ids,
                                 cycle,
                                 filename,
                                 render,
                             );
-                            return Err(LoadingProblem::FormattedReport(buf, None));
+                            return Err(LoadingProblem::FormattedReport(report));
                         }
                         Err(LoadingProblem::IncorrectModuleName(FileError {
                             problem: SourceError { problem, bytes },
@@ -1803,7 +1888,7 @@ fn state_thread_step<'a>(
                             let module_ids = arc_modules.lock().clone().into_module_ids();
 
                             let root_exposed_ident_ids = IdentIds::exposed_builtins(0);
-                            let (buf, region) = to_incorrect_module_name_report(
+                            let report = to_incorrect_module_name_report(
                                 module_ids,
                                 root_exposed_ident_ids,
                                 problem,
@@ -1811,7 +1896,7 @@ fn state_thread_step<'a>(
                                 bytes,
                                 render,
                             );
-                            return Err(LoadingProblem::FormattedReport(buf, Some(region)));
+                            return Err(LoadingProblem::FormattedReport(report));
                         }
                         Err(LoadingProblem::UnrecognizedPackageShorthand {
                             filename,
@@ -1824,7 +1909,7 @@ fn state_thread_step<'a>(
                             let module_ids = arc_modules.lock().clone().into_module_ids();
 
                             let root_exposed_ident_ids = IdentIds::exposed_builtins(0);
-                            let buf = to_unrecognized_package_shorthand_report(
+                            let report = to_unrecognized_package_shorthand_report(
                                 module_ids,
                                 root_exposed_ident_ids,
                                 module_id,
@@ -1835,7 +1920,7 @@ fn state_thread_step<'a>(
                                 available,
                                 render,
                             );
-                            return Err(LoadingProblem::FormattedReport(buf, Some(region)));
+                            return Err(LoadingProblem::FormattedReport(report));
                         }
                         Err(e) => Err(e),
                     }
@@ -1856,7 +1941,7 @@ pub fn report_loading_problem(
     module_ids: ModuleIds,
     render: RenderTarget,
     palette: Palette,
-) -> String {
+) -> RenderedReport {
     match problem {
         LoadingProblem::ParsingFailed(problem) => {
             // if parsing failed, this module did not add anything to IdentIds
@@ -1883,11 +1968,10 @@ pub fn report_loading_problem(
                 bytes,
                 render,
             )
-            .0
         }
-        LoadingProblem::FormattedReport(report, _region) => report,
+        LoadingProblem::FormattedReport(report) => report,
         LoadingProblem::FileProblem { filename, error } => {
-            to_file_problem_report_string(filename, error, true)
+            to_file_problem_rendered_report(filename, error, true)
         }
         LoadingProblem::NoPlatformPackage {
             filename,
@@ -1951,6 +2035,7 @@ fn load_multi_threaded<'a>(
     available_threads: usize,
     exec_mode: ExecutionMode,
     roc_cache_dir: RocCacheDir<'_>,
+    solved_modules: Option<SolvedModules>,
 ) -> Result<LoadResult<'a>, LoadingProblem<'a>> {
     let LoadStart {
         arc_modules,
@@ -2003,6 +2088,8 @@ fn load_multi_threaded<'a>(
         palette,
         num_workers,
         exec_mode,
+        roc_cache_dir,
+        solved_modules,
     );
 
     // an arena for every worker, stored in an arena-allocated bumpalo vec to make the lifetimes work
@@ -2127,13 +2214,13 @@ fn load_multi_threaded<'a>(
             // )
             // .print_error_warning_count(Duration::default()); // TODO determine total elapsed time and use it here
 
-            Err(LoadingProblem::FormattedReport(
+            Err(LoadingProblem::FormattedReport(RenderedReport::from_text(
                 concat!(
                     "\n\nThere was an unrecoverable error in the Roc compiler. The `roc check` ",
                     "command can sometimes give a more helpful error report than other commands.\n\n"
                 )
                 .to_string(),
-            None))
+            )))
         })
     }
 }
@@ -2489,12 +2576,17 @@ fn update<'a>(
                 .exposes
                 .insert(module_id, solved_module.exposed_vars_by_symbol.clone());
 
-            let should_include_expects = (!loc_expects.is_empty() || has_dbgs) && {
-                let modules = state.arc_modules.lock();
-                modules
-                    .package_eq(module_id, state.root_id)
-                    .expect("root or this module is not yet known - that's a bug!")
-            };
+            let count_coverage = matches!(state.exec_mode, ExecutionMode::TestWithCoverage);
+            // The runners of benchmarks are built like expects, even if the module has none
+            let build_benches = matches!(state.exec_mode, ExecutionMode::Bench);
+
+            let should_include_expects =
+                (!loc_expects.is_empty() || has_dbgs || count_coverage || build_benches) && {
+                    let modules = state.arc_modules.lock();
+                    modules
+                        .package_eq(module_id, state.root_id)
+                        .expect("root or this module is not yet known - that's a bug!")
+                };
 
             let opt_expectations = if should_include_expects {
                 let (path, _) = state.module_cache.sources.get(&module_id).unwrap();
@@ -2504,6 +2596,7 @@ fn update<'a>(
                     subs: solved_subs.clone().into_inner(),
                     path: path.to_owned(),
                     ident_ids: ident_ids.clone(),
+                    coverage: count_coverage.then(|| CoverageRegions::from_declarations(&decls)),
                 })
             } else {
                 None
@@ -2520,7 +2613,7 @@ fn update<'a>(
 
             let add_to_host_exposed = is_host_exposed &&
                 // During testing, we don't need to expose anything to the host.
-                !matches!(state.exec_mode, ExecutionMode::Test);
+                !state.exec_mode.is_test();
 
             if add_to_host_exposed {
                 state.exposed_to_host.top_level_values.extend(
@@ -3069,12 +3162,12 @@ fn register_package_shorthands<'a>(
                         }
                     }
                     Err(url_err) => {
-                        let buf = to_https_problem_report_string(
+                        let report = to_https_problem_rendered_report(
                             url,
                             Problem::InvalidUrl(url_err),
                             module_path.to_path_buf(),
                         );
-                        return Err(LoadingProblem::FormattedReport(buf, None));
+                        return Err(LoadingProblem::FormattedReport(report));
                     }
                 }
             }
@@ -3178,7 +3271,9 @@ fn finish_specialization<'a>(
     let entry_point = {
         let interns: &mut Interns = &mut interns;
         match state.exec_mode {
-            ExecutionMode::Test => Ok(EntryPoint::Test),
+            ExecutionMode::Test | ExecutionMode::TestWithCoverage | ExecutionMode::Bench => {
+                Ok(EntryPoint::Test)
+            }
             ExecutionMode::Executable | ExecutionMode::ExecutableIfCheck => {
                 use PlatformPath::*;
 
@@ -3191,8 +3286,8 @@ fn finish_specialization<'a>(
                     }
                     Valid(To::NewPackage(p_or_p)) => PathBuf::from(p_or_p.as_str()),
                     other => {
-                        let buf = report_cannot_run(state.root_id, state.root_path, other);
-                        return Err(LoadingProblem::FormattedReport(buf, None));
+                        let report = report_cannot_run(state.root_id, state.root_path, other);
+                        return Err(LoadingProblem::FormattedReport(report));
                     }
                 };
 
@@ -3375,6 +3470,7 @@ fn finish(
         resolved_implementations,
         sources,
         timings: state.timings,
+        types_from_type_cache: state.module_cache.types_from_type_cache,
         docs_by_module: documentation,
         abilities_store,
         exposed_imports: state.module_cache.exposed_imports,
@@ -4154,11 +4250,10 @@ fn load_packages<'a>(
                         }
                     }
                     Err(problem) => {
-                        let buf = to_https_problem_report_string(src, problem, filename);
+                        let report = to_https_problem_rendered_report(src, problem, filename);
 
-                        load_messages.push(Msg::FailedToLoad(LoadingProblem::FormattedReport(
-                            buf, None,
-                        )));
+                        load_messages
+                            .push(Msg::FailedToLoad(LoadingProblem::FormattedReport(report)));
                         return;
                     }
                 }
@@ -4354,6 +4449,7 @@ impl<'a> BuildTask<'a> {
         dep_idents: IdentIdsByModule,
         declarations: Declarations,
         cached_subs: CachedTypeState,
+        type_cache_entry: Option<TypeCacheEntry>,
         derived_module: SharedDerivedModule,
         exec_mode: ExecutionMode,
         is_host_exposed: bool,
@@ -4379,6 +4475,7 @@ impl<'a> BuildTask<'a> {
             dep_idents,
             module_timing,
             cached_subs,
+            type_cache_entry,
             derived_module,
             exec_mode,
             is_host_exposed,
@@ -4819,6 +4916,48 @@ fn run_solve_solve(
     }
 }
 
+fn write_to_type_cache(entry: &TypeCacheEntry, solve_result: SolveResult) -> SolveResult {
+    let SolveResult {
+        solved: Solved(subs),
+        solved_implementations,
+        exposed_vars_by_symbol,
+        problems,
+        abilities_store,
+        imported_modules_with_params,
+
+        #[cfg(debug_assertions)]
+        checkmate,
+    } = solve_result;
+
+    let type_state = TypeState {
+        subs,
+        exposed_vars_by_symbol,
+        abilities: abilities_store,
+        solved_implementations,
+    };
+
+    type_cache::write(entry, &type_state);
+
+    let TypeState {
+        subs,
+        exposed_vars_by_symbol,
+        abilities,
+        solved_implementations,
+    } = type_state;
+
+    SolveResult {
+        solved: Solved(subs),
+        solved_implementations,
+        exposed_vars_by_symbol,
+        problems,
+        abilities_store: abilities,
+        imported_modules_with_params,
+
+        #[cfg(debug_assertions)]
+        checkmate,
+    }
+}
+
 fn run_solve<'a>(
     module: Module,
     ident_ids: IdentIds,
@@ -4833,6 +4972,7 @@ fn run_solve<'a>(
     decls: Declarations,
     dep_idents: IdentIdsByModule,
     cached_types: CachedTypeState,
+    type_cache_entry: Option<TypeCacheEntry>,
     derived_module: SharedDerivedModule,
     exec_mode: ExecutionMode,
     is_host_exposed: bool,
@@ -4854,43 +4994,12 @@ fn run_solve<'a>(
     let has_dbgs = module.has_dbgs;
     let module = module;
 
-    let solve_result = {
-        if module_id.is_builtin() {
-            match cached_types.lock().remove(&module_id) {
-                None => run_solve_solve(
-                    exposed_for_module,
-                    types,
-                    constraints,
-                    constraint,
-                    function_kind,
-                    pending_derives,
-                    var_store,
-                    module,
-                    derived_module,
-                    is_host_exposed,
-                    //
-                    #[cfg(debug_assertions)]
-                    checkmate,
-                ),
-                Some(TypeState {
-                    subs,
-                    exposed_vars_by_symbol,
-                    abilities,
-                    solved_implementations,
-                }) => SolveResult {
-                    solved: Solved(subs),
-                    solved_implementations,
-                    exposed_vars_by_symbol,
-                    problems: vec![],
-                    abilities_store: abilities,
-                    imported_modules_with_params: vec![],
+    // Take the cached types out first, so that the lock isn't held while solving.
+    let opt_cached_types = cached_types.lock().remove(&module_id);
 
-                    #[cfg(debug_assertions)]
-                    checkmate: None,
-                },
-            }
-        } else {
-            run_solve_solve(
+    let solve_result = match opt_cached_types {
+        None => {
+            let solve_result = run_solve_solve(
                 exposed_for_module,
                 types,
                 constraints,
@@ -4904,8 +5013,35 @@ fn run_solve<'a>(
                 //
                 #[cfg(debug_assertions)]
                 checkmate,
-            )
+            );
+
+            match type_cache_entry {
+                // Problems aren't cached, so only modules without any can be.
+                Some(entry)
+                    if solve_result.problems.is_empty()
+                        && solve_result.imported_modules_with_params.is_empty() =>
+                {
+                    write_to_type_cache(&entry, solve_result)
+                }
+                _ => solve_result,
+            }
         }
+        Some(TypeState {
+            subs,
+            exposed_vars_by_symbol,
+            abilities,
+            solved_implementations,
+        }) => SolveResult {
+            solved: Solved(subs),
+            solved_implementations,
+            exposed_vars_by_symbol,
+            problems: vec![],
+            abilities_store: abilities,
+            imported_modules_with_params: vec![],
+
+            #[cfg(debug_assertions)]
+            checkmate: None,
+        },
     };
 
     let SolveResult {
@@ -4933,7 +5069,11 @@ fn run_solve<'a>(
         ExecutionMode::Check => {
             // Params are not lowered in check mode
         }
-        ExecutionMode::Executable | ExecutionMode::ExecutableIfCheck | ExecutionMode::Test => {
+        ExecutionMode::Executable
+        | ExecutionMode::ExecutableIfCheck
+        | ExecutionMode::Test
+        | ExecutionMode::TestWithCoverage
+        | ExecutionMode::Bench => {
             roc_lower_params::type_error::remove_module_param_arguments(
                 &mut problems,
                 home_has_params,
@@ -5185,7 +5325,11 @@ fn canonicalize_and_constrain<'a>(
             // No need to lower params for `roc check` and lang server
             // If we did, we'd have to update the language server to exclude the extra arguments
         }
-        ExecutionMode::Executable | ExecutionMode::ExecutableIfCheck | ExecutionMode::Test => {
+        ExecutionMode::Executable
+        | ExecutionMode::ExecutableIfCheck
+        | ExecutionMode::Test
+        | ExecutionMode::TestWithCoverage
+        | ExecutionMode::Bench => {
             // We need to lower params only if the current module has any or imports at least one with params
             if module_output.module_params.is_some() || !imported_module_params.is_empty() {
                 roc_lower_params::lower::lower(
@@ -5614,10 +5758,15 @@ fn make_specializations<'a>(
     let mut update_mode_ids = UpdateModeIds::new();
 
     // do the thing
+    let (expectation_subs, coverage) = match expectations.as_mut() {
+        Some(expectations) => (Some(&mut expectations.subs), expectations.coverage.as_ref()),
+        None => (None, None),
+    };
     let mut mono_env = roc_mono::ir::Env {
         arena,
         subs: &mut subs,
-        expectation_subs: expectations.as_mut().map(|e| &mut e.subs),
+        expectation_subs,
+        coverage,
         home,
         ident_ids: &mut ident_ids,
         target,
@@ -5656,9 +5805,23 @@ fn make_specializations<'a>(
     );
 
     let external_specializations_requested = procs.externals_we_need.clone();
-    let (procedures, host_exposed_lambda_sets, restored_procs_base) =
+    let (mut procedures, host_exposed_lambda_sets, restored_procs_base) =
         procs.get_specialized_procs_without_rc();
 
+    // Now that the functions of properties are specialized, their runners can call them
+    for property in procs_base.properties.iter() {
+        let runner = roc_mono::property::build_runner(&mut mono_env, &mut layout_cache, property);
+
+        procedures.insert((property.symbol, roc_mono::property::RUNNER_LAYOUT), runner);
+    }
+
+    // The same goes for benchmarks
+    for bench in procs_base.benches.iter() {
+        let runner = roc_mono::bench::build_runner(&mut mono_env, &mut layout_cache, bench);
+
+        procedures.insert((bench.function, roc_mono::bench::RUNNER_LAYOUT), runner);
+    }
+
     // Turn `Bytes.Decode.IdentId(238)` into `Bytes.Decode.238`, we rely on this in mono tests
     mono_env.home.register_debug_idents(mono_env.ident_ids);
 
@@ -5698,6 +5861,7 @@ fn build_pending_specializations<'a>(
     derived_module: SharedDerivedModule,
     mut expectations: Option<Expectations>,
     build_expects: bool,
+    build_benches: bool,
 ) -> Msg<'a> {
     let find_specializations_start = Instant::now();
 
@@ -5710,14 +5874,21 @@ fn build_pending_specializations<'a>(
         host_specializations: roc_mono::ir::HostSpecializations::new(),
         runtime_errors: BumpMap::default(),
         imported_module_thunks,
+        properties: Vec::new(),
+        benches: Vec::new(),
     };
 
     let mut update_mode_ids = UpdateModeIds::new();
     let mut subs = solved_subs.into_inner();
+    let (expectation_subs, coverage) = match expectations.as_mut() {
+        Some(expectations) => (Some(&mut expectations.subs), expectations.coverage.as_ref()),
+        None => (None, None),
+    };
     let mut mono_env = roc_mono::ir::Env {
         arena,
         subs: &mut subs,
-        expectation_subs: expectations.as_mut().map(|e| &mut e.subs),
+        expectation_subs,
+        coverage,
         home,
         ident_ids: &mut ident_ids,
         target,
@@ -5873,6 +6044,40 @@ fn build_pending_specializations<'a>(
                     );
                 }
 
+                let is_bench = build_benches
+                    && mono_env
+                        .ident_ids
+                        .get_name(symbol.ident_id())
+                        .is_some_and(|name| name.starts_with(roc_mono::bench::PREFIX));
+
+                if is_bench {
+                    // A benchmark is specialized like an exposed function, and it gets called
+                    // by a runner that is built once that specialization exists.
+                    let layout_result =
+                        layout_cache.raw_from_var(mono_env.arena, expr_var, mono_env.subs);
+
+                    if layout_result.is_ok() && !is_host_exposed {
+                        procs_base.host_specializations.insert_host_exposed(
+                            mono_env.subs,
+                            LambdaName::no_niche(symbol),
+                            None,
+                            expr_var,
+                        );
+                    }
+
+                    let name_region = declarations.symbols[index].region;
+                    let region = Region::span_across(&name_region, &body.region);
+
+                    procs_base.benches.push(Bench {
+                        function: symbol,
+                        function_var: expr_var,
+                        region,
+                    });
+
+                    toplevel_expects.pure.insert(symbol, region);
+                    toplevel_expects.benches.insert(symbol);
+                }
+
                 let is_recursive = matches!(tag, Recursive(_) | TailRecursive(_));
 
                 register_toplevel_function_into_procs_base(
@@ -5967,14 +6172,80 @@ fn build_pending_specializations<'a>(
             }
             Expectation => {
                 // skip expectations if we're not going to run them
-                if !build_expects {
+                if !build_expects || build_benches {
+                    continue;
+                }
+
+                // extend the region of the expect expression with the region of the preceding
+                // comment, so it is shown in failure/panic messages
+                let name_region = declarations.symbols[index].region;
+                let expr_region = declarations.expressions[index].region;
+                let region = Region::span_across(&name_region, &expr_region);
+
+                if let Some(closure) = roc_can::expr::toplevel_expect_property(&body) {
+                    // A property's function is specialized like an exposed function, and it gets
+                    // called by a runner that is built once that specialization exists.
+                    let layout_result = layout_cache.raw_from_var(
+                        mono_env.arena,
+                        closure.function_type,
+                        mono_env.subs,
+                    );
+
+                    if layout_result.is_ok() {
+                        procs_base.host_specializations.insert_host_exposed(
+                            mono_env.subs,
+                            LambdaName::no_niche(closure.name),
+                            None,
+                            closure.function_type,
+                        );
+                    }
+
+                    register_toplevel_function_into_procs_base(
+                        &mut mono_env,
+                        &mut procs_base,
+                        closure.name,
+                        closure.function_type,
+                        closure.arguments.clone(),
+                        closure.return_type,
+                        (*closure.loc_body).clone(),
+                        false,
+                    );
+
+                    let arguments = closure
+                        .arguments
+                        .iter()
+                        .map(|(var, _, loc_pattern)| match loc_pattern.value {
+                            roc_can::pattern::Pattern::Identifier(name) => (*var, Some(name)),
+                            _ => (*var, None),
+                        })
+                        .collect();
+
+                    procs_base.properties.push(Property {
+                        symbol,
+                        function: closure.name,
+                        function_var: closure.function_type,
+                        arguments,
+                        region: closure.loc_body.region,
+                    });
+
+                    toplevel_expects.pure.insert(symbol, region);
+                    toplevel_expects.properties.insert(symbol);
+
                     continue;
                 }
 
+                // A snapshot's thunk returns the rendering of its value, rather than checking
+                // a condition inline.
+                let snapshot_region = roc_can::expr::toplevel_expect_snapshot(&body)
+                    .map(|loc_value| loc_value.region);
+
                 // mark this symbol as a top-level thunk before any other work on the procs
                 module_thunks.push(symbol);
 
-                let expr_var = Variable::EMPTY_RECORD;
+                let expr_var = match snapshot_region {
+                    Some(_) => Variable::STR,
+                    None => Variable::EMPTY_RECORD,
+                };
 
                 let is_host_exposed = true;
 
@@ -6014,7 +6285,14 @@ fn build_pending_specializations<'a>(
                     );
                 }
 
-                let body = roc_can::expr::toplevel_expect_to_inline_expect_pure(body);
+                let body = match snapshot_region {
+                    Some(value_region) => {
+                        toplevel_expects.snapshots.insert(symbol, value_region);
+
+                        body
+                    }
+                    None => roc_can::expr::toplevel_expect_to_inline_expect_pure(body),
+                };
 
                 let proc = PartialProc {
                     annotation: expr_var,
@@ -6028,12 +6306,6 @@ fn build_pending_specializations<'a>(
                     is_self_recursive: false,
                 };
 
-                // extend the region of the expect expression with the region of the preceding
-                // comment, so it is shown in failure/panic messages
-                let name_region = declarations.symbols[index].region;
-                let expr_region = declarations.expressions[index].region;
-                let region = Region::span_across(&name_region, &expr_region);
-
                 toplevel_expects.pure.insert(symbol, region);
                 procs_base.partial_procs.insert(symbol, proc);
             }
@@ -6127,6 +6399,7 @@ fn load_derived_partial_procs<'a>(
             subs,
             // There are no derived expectations.
             expectation_subs: None,
+            coverage: None,
             home,
             ident_ids,
             target,
@@ -6279,6 +6552,7 @@ fn run_task<'a>(
             declarations,
             dep_idents,
             cached_subs,
+            type_cache_entry,
             derived_module,
             exec_mode,
             is_host_exposed,
@@ -6299,6 +6573,7 @@ fn run_task<'a>(
             declarations,
             dep_idents,
             cached_subs,
+            type_cache_entry,
             derived_module,
             exec_mode,
             is_host_exposed,
@@ -6320,6 +6595,7 @@ fn run_task<'a>(
             derived_module,
             expectations,
             build_expects,
+            build_benches,
         } => Ok(build_pending_specializations(
             arena,
             solved_subs,
@@ -6336,6 +6612,7 @@ fn run_task<'a>(
             derived_module,
             expectations,
             build_expects,
+            build_benches,
         )),
         MakeSpecializations {
             module_id,
@@ -6403,7 +6680,7 @@ fn to_import_cycle_report(
     import_cycle: Vec<ModuleId>,
     filename: PathBuf,
     render: RenderTarget,
-) -> String {
+) -> RenderedReport {
     use roc_reporting::report::{Report, RocDocAllocator, DEFAULT_PALETTE};
     use ven_pretty::DocAllocator;
 
@@ -6450,10 +6727,8 @@ fn to_import_cycle_report(
         severity: Severity::RuntimeError,
     };
 
-    let mut buf = String::new();
     let palette = DEFAULT_PALETTE;
-    report.render(render, &mut buf, &alloc, &palette);
-    buf
+    report.render_with_details(render, &alloc, &palette, None)
 }
 
 fn to_incorrect_module_name_report<'a>(
@@ -6463,7 +6738,7 @@ fn to_incorrect_module_name_report<'a>(
     filename: PathBuf,
     src: &'a [u8],
     render: RenderTarget,
-) -> (String, Region) {
+) -> RenderedReport {
     use roc_reporting::report::{Report, RocDocAllocator, DEFAULT_PALETTE};
     use ven_pretty::DocAllocator;
 
@@ -6501,10 +6776,9 @@ fn to_incorrect_module_name_report<'a>(
         severity,
     };
 
-    let mut buf = String::new();
     let palette = DEFAULT_PALETTE;
-    report.render(render, &mut buf, &alloc, &palette);
-    (buf, found.region)
+    let region = lines.convert_region(found.region);
+    report.render_with_details(render, &alloc, &palette, Some(region))
 }
 
 fn to_no_platform_package_report(
@@ -6515,7 +6789,7 @@ fn to_no_platform_package_report(
     region: Region,
     src: &[u8],
     render: RenderTarget,
-) -> String {
+) -> RenderedReport {
     use roc_reporting::report::{Report, RocDocAllocator, DEFAULT_PALETTE};
     use ven_pretty::DocAllocator;
     let severity = Severity::RuntimeError;
@@ -6549,10 +6823,9 @@ fn to_no_platform_package_report(
         severity,
     };
 
-    let mut buf = String::new();
     let palette = DEFAULT_PALETTE;
-    report.render(render, &mut buf, &alloc, &palette);
-    buf
+    let region = lines.convert_region(region);
+    report.render_with_details(render, &alloc, &palette, Some(region))
 }
 
 fn to_multiple_platform_packages_report(
@@ -6563,7 +6836,7 @@ fn to_multiple_platform_packages_report(
     region: Region,
     src: &[u8],
     render: RenderTarget,
-) -> String {
+) -> RenderedReport {
     use roc_reporting::report::{Report, RocDocAllocator, DEFAULT_PALETTE};
     use ven_pretty::DocAllocator;
     let severity = Severity::RuntimeError;
@@ -6593,10 +6866,9 @@ fn to_multiple_platform_packages_report(
         severity,
     };
 
-    let mut buf = String::new();
     let palette = DEFAULT_PALETTE;
-    report.render(render, &mut buf, &alloc, &palette);
-    buf
+    let region = lines.convert_region(region);
+    report.render_with_details(render, &alloc, &palette, Some(region))
 }
 
 fn to_unrecognized_package_shorthand_report(
@@ -6609,7 +6881,7 @@ fn to_unrecognized_package_shorthand_report(
     shorthand: &str,
     available: AvailableShorthands,
     render: RenderTarget,
-) -> String {
+) -> RenderedReport {
     use roc_reporting::report::{Report, RocDocAllocator, DEFAULT_PALETTE};
     use ven_pretty::DocAllocator;
 
@@ -6700,10 +6972,9 @@ fn to_unrecognized_package_shorthand_report(
         severity,
     };
 
-    let mut buf = String::new();
     let palette = DEFAULT_PALETTE;
-    report.render(render, &mut buf, &alloc, &palette);
-    buf
+    let region = lines.convert_region(region);
+    report.render_with_details(render, &alloc, &palette, Some(region))
 }
 
 fn to_parse_problem_report<'a>(
@@ -6712,7 +6983,7 @@ fn to_parse_problem_report<'a>(
     all_ident_ids: IdentIdsByModule,
     render: RenderTarget,
     palette: Palette,
-) -> String {
+) -> RenderedReport {
     use roc_reporting::report::{parse_problem, RocDocAllocator};
 
     // TODO this is not in fact safe
@@ -6734,6 +7005,11 @@ fn to_parse_problem_report<'a>(
     let starting_line = 0;
 
     let lines = LineInfo::new(src);
+    let region = problem
+        .problem
+        .problem
+        .get_region()
+        .map(|region| lines.convert_region(region));
 
     let report = parse_problem(
         &alloc,
@@ -6743,18 +7019,14 @@ fn to_parse_problem_report<'a>(
         problem,
     );
 
-    let mut buf = String::new();
-
-    report.render(render, &mut buf, &alloc, &palette);
-
-    buf
+    report.render_with_details(render, &alloc, &palette, region)
 }
 
 fn report_cannot_run(
     module_id: ModuleId,
     filename: PathBuf,
     platform_path: &PlatformPath,
-) -> String {
+) -> RenderedReport {
     use roc_reporting::report::{Report, RocDocAllocator, DEFAULT_PALETTE};
     use ven_pretty::DocAllocator;
     use PlatformPath::*;
@@ -6832,8 +7104,5 @@ fn report_cannot_run(
     };
 
     let palette = DEFAULT_PALETTE;
-    let mut buf = String::new();
-    report.render_color_terminal(&mut buf, &alloc, &palette);
-
-    buf
+    report.render_with_details(RenderTarget::ColorTerminal, &alloc, &palette, None)
 }
@@ -8,6 +8,9 @@ pub mod docs;
 pub mod file;
 pub mod module;
 mod module_cache;
+mod type_cache;
+
+pub use type_cache::SolvedModules;
 
 #[cfg(target_family = "wasm")]
 mod wasm_instant;
@@ -6,11 +6,12 @@ use roc_can::{
     expr::{Declarations, PendingDerives},
     module::{Module, ResolvedImplementations},
 };
-use roc_collections::{MutMap, MutSet, VecMap};
+use roc_collections::{MutMap, MutSet, VecMap, VecSet};
 use roc_module::ident::Ident;
 use roc_module::symbol::{
     IdentIds, IdentIdsByModule, Interns, ModuleId, PQModuleName, PackageQualified, Symbol,
 };
+use roc_mono::coverage::CoverageRegions;
 use roc_mono::ir::{GlueLayouts, HostExposedLambdaSets, LambdaSetId, Proc, ProcLayout, ProcsBase};
 use roc_mono::layout::{LayoutCache, STLayoutInterner};
 use roc_parse::ast::{CommentOrNewline, Defs, TypeAnnotation};
@@ -45,6 +46,8 @@ pub struct LoadedModule {
     pub resolved_implementations: ResolvedImplementations,
     pub sources: MutMap<ModuleId, (PathBuf, Box<str>)>,
     pub timings: MutMap<ModuleId, ModuleTiming>,
+    /// The modules that weren't solved again, because their types were in the type cache
+    pub types_from_type_cache: MutSet<ModuleId>,
     pub docs_by_module: VecMap<ModuleId, ModuleDocumentation>,
     pub abilities_store: AbilitiesStore,
     pub typechecked: MutMap<ModuleId, CheckedModule>,
@@ -167,6 +170,13 @@ pub(crate) struct LateSpecializationsModule<'a> {
 #[derive(Debug, Default)]
 pub struct ToplevelExpects {
     pub pure: VecMap<Symbol, Region>,
+    /// The expects in `pure` that are properties, whose runners take a seed and a size
+    pub properties: VecSet<Symbol>,
+    /// The expects in `pure` that are snapshots, whose thunks return the rendering of the value
+    /// in the given region rather than checking a condition
+    pub snapshots: VecMap<Symbol, Region>,
+    /// The entries of `pure` that are benchmarks, whose runners are the functions themselves
+    pub benches: VecSet<Symbol>,
 }
 
 #[derive(Debug)]
@@ -224,6 +234,8 @@ pub struct Expectations {
     pub path: PathBuf,
     pub expectations: VecMap<Region, Vec<ExpectLookup>>,
     pub ident_ids: IdentIds,
+    /// What `roc test --coverage` counts the runs of in this module.
+    pub coverage: Option<CoverageRegions>,
 }
 
 #[derive(Clone, Debug, Default)]
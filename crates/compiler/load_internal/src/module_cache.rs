@@ -3,6 +3,7 @@ use crate::module::{
     CheckedModule, ConstrainedModule, FoundSpecializationsModule, LateSpecializationsModule,
     ModuleHeader, ParsedModule, TypeCheckedModule,
 };
+use crate::type_cache::TypeCacheKey;
 use roc_can::abilities::PendingAbilitiesStore;
 use roc_can::module::ModuleParams;
 use roc_collections::{MutMap, MutSet, VecMap};
@@ -45,6 +46,11 @@ pub(crate) struct ModuleCache<'a> {
     pub(crate) type_problems: MutMap<ModuleId, Vec<TypeError>>,
 
     pub(crate) sources: MutMap<ModuleId, (PathBuf, &'a str)>,
+
+    /// Keys into the on-disk type cache, for the modules that can be cached
+    pub(crate) type_cache_keys: MutMap<ModuleId, TypeCacheKey>,
+    /// The modules whose solved types were read from the type cache
+    pub(crate) types_from_type_cache: MutSet<ModuleId>,
 }
 
 impl<'a> ModuleCache<'a> {
@@ -116,6 +122,8 @@ impl Default for ModuleCache<'_> {
             can_problems: Default::default(),
             type_problems: Default::default(),
             sources: Default::default(),
+            type_cache_keys: Default::default(),
+            types_from_type_cache: Default::default(),
         }
     }
 }
@@ -0,0 +1,449 @@
+//! A persistent on-disk cache of solved modules, so that `roc check` can skip constraint
+//! generation and solving for modules that haven't changed since the previous run.
+//!
+//! Only those phases are skipped: cached modules are still parsed and canonicalized, since
+//! the key is made from the parsed module, and the canonical declarations and their problems
+//! (which aren't cached) are part of the checked module.
+//!
+//! This is the same mechanism that the builtins use (see `roc_load`'s build.rs), except that
+//! the [TypeState]s are written at runtime, under the roc cache directory.
+//!
+//! Processes that load the same modules over and over (like `roc check --watch`) can also
+//! keep the entries in memory by passing the same [SolvedModules] to every load, so that only
+//! the modules that changed since the previous load, and the modules that import them,
+//! get solved again.
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use parking_lot::Mutex;
+use roc_can::module::TypeState;
+use roc_collections::MutMap;
+use roc_module::symbol::ModuleId;
+use roc_solve::FunctionKind;
+
+use crate::module::ParsedModule;
+
+/// Bump this when the contents of a cache entry change in a way that the compiler
+/// fingerprint wouldn't catch.
+const FORMAT_VERSION: &str = "roc-type-cache-1";
+
+const TYPES_DIR_NAME: &str = "types";
+
+/// Every compiler binary that uses a directory of entries leaves a file starting with this
+/// in there, so it's only deleted once none of them use it anymore.
+const OWNER_PREFIX: &str = "owner-";
+
+/// The length of the payload, followed by its hash. This is a multiple of the alignment
+/// that [TypeState::deserialize] needs, which keeps the payload aligned when reading it back.
+const HEADER_LEN: usize = 16 + blake3::OUT_LEN;
+
+/// Uniquely identifies the solved types of a module: if two modules have the same key,
+/// solving them produces the same [TypeState].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TypeCacheKey {
+    module_id: ModuleId,
+    hash: blake3::Hash,
+}
+
+/// Where the solved types of a module are written to, once it's solved.
+#[derive(Debug, Clone)]
+pub(crate) struct TypeCacheEntry {
+    key: TypeCacheKey,
+    path: Option<PathBuf>,
+    memory: Option<SolvedModules>,
+}
+
+/// A serialized [TypeState], aligned like [TypeState::deserialize] needs it to be.
+struct AlignedPayload {
+    words: Vec<u128>,
+    len: usize,
+}
+
+impl AlignedPayload {
+    fn new(payload: &[u8]) -> Self {
+        let mut words = vec![0u128; payload.len().div_ceil(std::mem::size_of::<u128>())];
+
+        // SAFETY: u128 has no padding, and any bytes make a valid u128.
+        let bytes =
+            unsafe { std::slice::from_raw_parts_mut(words.as_mut_ptr() as *mut u8, payload.len()) };
+        bytes.copy_from_slice(payload);
+
+        Self {
+            words,
+            len: payload.len(),
+        }
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        // SAFETY: the words hold at least `len` initialized bytes.
+        unsafe { std::slice::from_raw_parts(self.words.as_ptr() as *const u8, self.len) }
+    }
+
+    /// Returns None if the payload doesn't hold exactly one [TypeState].
+    fn deserialize(&self) -> Option<TypeState> {
+        let (type_state, offset) = TypeState::deserialize(self.as_bytes());
+
+        (offset == self.len).then_some(type_state)
+    }
+}
+
+/// The solved types of modules, kept in memory in addition to the roc cache directory, so
+/// that later loads in this process can reuse them even when the persistent cache is
+/// unavailable. Clones share the same entries.
+///
+/// Only the latest entry of every module is kept, so this doesn't grow as modules change.
+/// Like the persistent cache, this is only used when checking.
+#[derive(Clone, Default)]
+pub struct SolvedModules {
+    entries: Arc<Mutex<MutMap<ModuleId, (blake3::Hash, Arc<AlignedPayload>)>>>,
+}
+
+impl SolvedModules {
+    fn read(&self, key: TypeCacheKey) -> Option<Arc<AlignedPayload>> {
+        let entries = self.entries.lock();
+        let (hash, payload) = entries.get(&key.module_id)?;
+
+        (*hash == key.hash).then(|| payload.clone())
+    }
+
+    /// Replaces the module's previous entry, if any, since its key won't come up again
+    /// unless the module changes back.
+    fn write(&self, key: TypeCacheKey, payload: Arc<AlignedPayload>) {
+        self.entries
+            .lock()
+            .insert(key.module_id, (key.hash, payload));
+    }
+}
+
+impl std::fmt::Debug for SolvedModules {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SolvedModules")
+            .field("modules", &self.entries.lock().len())
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct TypeCache {
+    /// None if only the entries in memory can be used.
+    dir: Option<PathBuf>,
+    memory: Option<SolvedModules>,
+}
+
+impl TypeCache {
+    /// Returns None if the cache can't be used, e.g. because entries aren't kept in memory,
+    /// and we can't tell which compiler binary is running, or the cache directory
+    /// can't be created.
+    pub(crate) fn new(roc_cache_dir: Option<&Path>, memory: Option<SolvedModules>) -> Option<Self> {
+        let dir = roc_cache_dir.and_then(Self::persistent_dir);
+
+        if dir.is_none() && memory.is_none() {
+            return None;
+        }
+
+        Some(Self { dir, memory })
+    }
+
+    /// Other compilers (like other installed versions of roc, or the language server) have
+    /// their own directory next to ours, which they may still be using.
+    fn persistent_dir(roc_cache_dir: &Path) -> Option<PathBuf> {
+        let types_dir = roc_cache_dir.join(TYPES_DIR_NAME);
+        let dir = types_dir.join(compiler_fingerprint()?);
+
+        fs::create_dir_all(&dir).ok()?;
+
+        if let Some(owner) = owner_file_name() {
+            if !dir.join(&owner).exists() {
+                // This binary is a different compiler than the last time it ran, e.g. because
+                // it was upgraded, so the entries it wrote back then are useless now.
+                remove_stale_dirs(&types_dir, &dir, &owner);
+
+                let _ = fs::File::create(dir.join(&owner));
+            }
+        }
+
+        Some(dir)
+    }
+
+    /// The key of a module is made up of its source code, how functions are solved, and the
+    /// keys of all the modules it imports. Returns None if any of those imports doesn't have
+    /// a key.
+    pub(crate) fn key(
+        parsed: &ParsedModule,
+        is_host_exposed: bool,
+        function_kind: FunctionKind,
+        keys: &MutMap<ModuleId, TypeCacheKey>,
+    ) -> Option<TypeCacheKey> {
+        // Symbols from the `requires` of a platform depend on the app, not on our imports.
+        if !parsed.symbols_from_requires.is_empty() {
+            return None;
+        }
+
+        let mut imports: Vec<ModuleId> = parsed.available_modules.keys().copied().collect();
+        imports.sort_by_key(|module_id| module_id.to_zero_indexed());
+
+        let mut hasher = blake3::Hasher::new();
+
+        hasher.update(FORMAT_VERSION.as_bytes());
+        // Module ids are handed out in the order that modules are discovered, which can
+        // differ between runs; the serialized types refer to them, so they're part of the key.
+        hasher.update(&(parsed.module_id.to_zero_indexed() as u64).to_le_bytes());
+        hasher.update(&[is_host_exposed as u8]);
+        // Erased functions (see EXPERIMENTAL_ROC_ERASE) are solved to different types.
+        hasher.update(&[function_kind as u8]);
+        hasher.update(&(parsed.src.len() as u64).to_le_bytes());
+        hasher.update(parsed.src.as_bytes());
+
+        for import in imports {
+            hasher.update(keys.get(&import)?.hash.as_bytes());
+        }
+
+        Some(TypeCacheKey {
+            module_id: parsed.module_id,
+            hash: hasher.finalize(),
+        })
+    }
+
+    pub(crate) fn entry(&self, key: TypeCacheKey) -> TypeCacheEntry {
+        TypeCacheEntry {
+            key,
+            path: self
+                .dir
+                .as_ref()
+                .map(|dir| dir.join(key.hash.to_hex().as_str())),
+            memory: self.memory.clone(),
+        }
+    }
+
+    /// Returns None if there's no entry for this key, or if it's unreadable or corrupted.
+    pub(crate) fn read(&self, key: TypeCacheKey) -> Option<TypeState> {
+        if let Some(payload) = self.memory.as_ref().and_then(|memory| memory.read(key)) {
+            return payload.deserialize();
+        }
+
+        let bytes = fs::read(self.entry(key).path?).ok()?;
+
+        if bytes.len() < HEADER_LEN {
+            return None;
+        }
+
+        let (header, payload) = bytes.split_at(HEADER_LEN);
+        let payload_len = u64::from_le_bytes(header[..8].try_into().unwrap()) as usize;
+
+        if payload.len() != payload_len || blake3::hash(payload).as_bytes() != &header[16..] {
+            return None;
+        }
+
+        // The deserializer reads the bytes in place, so they have to be aligned like
+        // the builtins' cached types are (see include_bytes_align_as! in roc_load).
+        let aligned = Arc::new(AlignedPayload::new(payload));
+        let type_state = aligned.deserialize()?;
+
+        if let Some(memory) = &self.memory {
+            memory.write(key, aligned);
+        }
+
+        Some(type_state)
+    }
+}
+
+/// Writes the entry atomically, so that concurrent `roc` processes never see half of it.
+/// Failing to write to the cache isn't a problem for the user, so errors are ignored.
+pub(crate) fn write(entry: &TypeCacheEntry, type_state: &TypeState) {
+    let mut payload = Vec::new();
+
+    if type_state.serialize(&mut payload).is_err() {
+        return;
+    }
+
+    if let Some(memory) = &entry.memory {
+        memory.write(entry.key, Arc::new(AlignedPayload::new(&payload)));
+    }
+
+    let Some(path) = &entry.path else {
+        return;
+    };
+
+    let Some(dir) = path.parent() else {
+        return;
+    };
+
+    let mut header = [0u8; HEADER_LEN];
+    header[..8].copy_from_slice(&(payload.len() as u64).to_le_bytes());
+    header[16..].copy_from_slice(blake3::hash(&payload).as_bytes());
+
+    let Ok(mut file) = tempfile::NamedTempFile::new_in(dir) else {
+        return;
+    };
+
+    if file.write_all(&header).is_ok() && file.write_all(&payload).is_ok() {
+        let _ = file.persist(path);
+    }
+}
+
+/// Identifies the compiler binary, so that entries written by other versions (which may
+/// solve or serialize types differently) are never used. Hashing the whole binary would
+/// take longer than most checks, so its size and modification time stand in for it; every
+/// build of the compiler changes at least the latter.
+fn compiler_fingerprint() -> Option<String> {
+    let metadata = fs::metadata(std::env::current_exe().ok()?).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+
+    let mut hasher = blake3::Hasher::new();
+
+    hasher.update(&metadata.len().to_le_bytes());
+    hasher.update(&modified.as_nanos().to_le_bytes());
+
+    Some(hasher.finalize().to_hex()[..16].to_string())
+}
+
+/// The name of the file that marks the directories used by the running compiler binary.
+fn owner_file_name() -> Option<String> {
+    let exe_path = std::env::current_exe().ok()?;
+    let hash = blake3::hash(exe_path.to_string_lossy().as_bytes());
+
+    Some(format!("{OWNER_PREFIX}{}", &hash.to_hex()[..16]))
+}
+
+/// Deletes the directories that only the given owner used, except for the current one.
+/// The ones that other compiler binaries use too are left for them.
+fn remove_stale_dirs(types_dir: &Path, current_dir: &Path, owner: &str) {
+    let Ok(entries) = fs::read_dir(types_dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let dir = entry.path();
+
+        if dir == current_dir || fs::remove_file(dir.join(owner)).is_err() {
+            continue;
+        }
+
+        let has_other_owners = fs::read_dir(&dir).map_or(true, |mut files| {
+            files.any(|file| {
+                file.is_ok_and(|file| file.file_name().to_string_lossy().starts_with(OWNER_PREFIX))
+            })
+        });
+
+        if !has_other_owners {
+            let _ = fs::remove_dir_all(&dir);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(module_id: ModuleId, src: &[u8]) -> TypeCacheKey {
+        TypeCacheKey {
+            module_id,
+            hash: blake3::hash(src),
+        }
+    }
+
+    fn empty_type_state() -> TypeState {
+        TypeState {
+            subs: roc_types::subs::Subs::new(),
+            exposed_vars_by_symbol: Vec::new(),
+            abilities: Default::default(),
+            solved_implementations: Default::default(),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let type_cache = TypeCache::new(Some(cache_dir.path()), None).unwrap();
+        let key = key(ModuleId::STR, b"RoundTrip");
+
+        let type_state = empty_type_state();
+
+        write(&type_cache.entry(key), &type_state);
+
+        let read = type_cache.read(key).unwrap();
+
+        assert_eq!(read.subs.len(), type_state.subs.len());
+    }
+
+    #[test]
+    fn rejects_corrupted_entries() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let type_cache = TypeCache::new(Some(cache_dir.path()), None).unwrap();
+        let key = key(ModuleId::STR, b"Corrupted");
+
+        assert!(type_cache.read(key).is_none());
+
+        fs::write(type_cache.entry(key).path.unwrap(), [0u8; HEADER_LEN + 4]).unwrap();
+
+        assert!(type_cache.read(key).is_none());
+    }
+
+    #[test]
+    fn removes_only_stale_dirs_of_the_owner() {
+        let types_dir = tempfile::tempdir().unwrap();
+        let dir = |name: &str, owners: &[&str]| {
+            let dir = types_dir.path().join(name);
+
+            fs::create_dir(&dir).unwrap();
+            fs::write(dir.join("0123456789abcdef"), b"entry").unwrap();
+
+            for owner in owners {
+                fs::write(dir.join(owner), b"").unwrap();
+            }
+
+            dir
+        };
+
+        let current = dir("current", &["owner-roc"]);
+        let stale = dir("stale", &["owner-roc"]);
+        let shared = dir("shared", &["owner-roc", "owner-language-server"]);
+        let other = dir("other", &["owner-language-server"]);
+
+        remove_stale_dirs(types_dir.path(), &current, "owner-roc");
+
+        assert!(current.join("owner-roc").exists());
+        assert!(!stale.exists());
+        assert!(!shared.join("owner-roc").exists());
+        assert!(shared.join("owner-language-server").exists());
+        assert!(other.join("owner-language-server").exists());
+    }
+
+    #[test]
+    fn keeps_entries_in_memory() {
+        assert!(TypeCache::new(None, None).is_none());
+
+        let type_cache = TypeCache::new(None, Some(SolvedModules::default())).unwrap();
+        let key = key(ModuleId::STR, b"InMemory");
+        let type_state = empty_type_state();
+
+        assert!(type_cache.read(key).is_none());
+
+        write(&type_cache.entry(key), &type_state);
+
+        let read = type_cache.read(key).unwrap();
+
+        assert_eq!(read.subs.len(), type_state.subs.len());
+    }
+
+    #[test]
+    fn keeps_only_the_latest_entry_of_a_module_in_memory() {
+        let solved_modules = SolvedModules::default();
+        let type_cache = TypeCache::new(None, Some(solved_modules.clone())).unwrap();
+        let before = key(ModuleId::STR, b"Before");
+        let after = key(ModuleId::STR, b"After");
+        let other = key(ModuleId::LIST, b"Other");
+
+        write(&type_cache.entry(before), &empty_type_state());
+        write(&type_cache.entry(after), &empty_type_state());
+        write(&type_cache.entry(other), &empty_type_state());
+
+        assert!(type_cache.read(before).is_none());
+        assert!(type_cache.read(after).is_some());
+        assert!(type_cache.read(other).is_some());
+        assert_eq!(solved_modules.entries.lock().len(), 2);
+    }
+}
@@ -26,7 +26,7 @@ use roc_module::ident::ModuleName;
 use roc_module::symbol::{Interns, ModuleId};
 use roc_packaging::cache::RocCacheDir;
 use roc_problem::can::Problem;
-use roc_region::all::LineInfo;
+use roc_region::all::{LineColumn, LineInfo};
 use roc_reporting::report::{can_problem, DEFAULT_PALETTE};
 use roc_reporting::report::{strip_colors, RenderTarget};
 use roc_reporting::report::{type_problem, RocDocAllocator};
@@ -39,13 +39,14 @@ use roc_types::pretty_print::DebugPrint;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
-fn load_and_typecheck(
-    arena: &Bump,
+fn load_and_typecheck<'a>(
+    arena: &'a Bump,
     filename: PathBuf,
     exposed_types: ExposedByModule,
     target: Target,
     function_kind: FunctionKind,
-) -> Result<LoadedModule, LoadingProblem> {
+    roc_cache_dir: RocCacheDir<'_>,
+) -> Result<LoadedModule, LoadingProblem<'a>> {
     use LoadResult::*;
 
     let load_start = LoadStart::from_path(
@@ -63,6 +64,7 @@ fn load_and_typecheck(
         palette: DEFAULT_PALETTE,
         threading: Threading::Single,
         exec_mode: ExecutionMode::Check,
+        solved_modules: None,
     };
 
     match roc_load_internal::file::load(
@@ -70,7 +72,7 @@ fn load_and_typecheck(
         load_start,
         exposed_types,
         Default::default(), // these tests will re-compile the builtins
-        RocCacheDir::Disallowed,
+        roc_cache_dir,
         load_config,
     )? {
         Monomorphized(_) => unreachable!(""),
@@ -141,7 +143,7 @@ fn multiple_modules(subdir: &str, files: Vec<(&str, &str)>) -> Result<LoadedModu
 
     match multiple_modules_help(subdir, arena, files) {
         Err(io_error) => panic!("IO trouble: {io_error:?}"),
-        Ok(Err(LoadingProblem::FormattedReport(buf, _))) => Err(buf),
+        Ok(Err(LoadingProblem::FormattedReport(report))) => Err(report.text),
         Ok(Err(loading_problem)) => Err(format!("{loading_problem:?}")),
         Ok(Ok(mut loaded_module)) => {
             let home = loaded_module.module_id;
@@ -224,6 +226,7 @@ fn multiple_modules_help<'a>(
             Default::default(),
             TARGET,
             FunctionKind::LambdaSet,
+            RocCacheDir::Disallowed,
         )
     };
 
@@ -234,6 +237,20 @@ fn load_fixture(
     dir_name: &str,
     module_name: &str,
     subs_by_module: ExposedByModule,
+) -> LoadedModule {
+    load_fixture_help(
+        dir_name,
+        module_name,
+        subs_by_module,
+        RocCacheDir::Disallowed,
+    )
+}
+
+fn load_fixture_help(
+    dir_name: &str,
+    module_name: &str,
+    subs_by_module: ExposedByModule,
+    roc_cache_dir: RocCacheDir<'_>,
 ) -> LoadedModule {
     let src_dir = fixtures_dir().join(dir_name);
     let filename = src_dir.join(format!("{module_name}.roc"));
@@ -244,12 +261,13 @@ fn load_fixture(
         subs_by_module,
         TARGET,
         FunctionKind::LambdaSet,
+        roc_cache_dir,
     );
     let mut loaded_module = match loaded {
         Ok(x) => x,
-        Err(roc_load_internal::file::LoadingProblem::FormattedReport(report, _)) => {
-            println!("{report}");
-            panic!("{}", report);
+        Err(roc_load_internal::file::LoadingProblem::FormattedReport(report)) => {
+            println!("{}", report.text);
+            panic!("{}", report.text);
         }
         Err(e) => panic!("{e:?}"),
     };
@@ -424,6 +442,7 @@ fn module_with_deps() {
         subs_by_module,
         TARGET,
         FunctionKind::LambdaSet,
+        RocCacheDir::Disallowed,
     );
 
     let mut loaded_module = loaded.expect("Test module failed to load");
@@ -690,6 +709,58 @@ fn app_dep_types() {
     );
 }
 
+#[test]
+fn iface_dep_types_from_type_cache() {
+    let cache_dir = TmpDir::new("iface_dep_types_from_type_cache");
+
+    let mut modules_from_cache = Vec::new();
+
+    // The first load fills the cache, the second one reads from it.
+    for _ in 0..2 {
+        let loaded_module = load_fixture_help(
+            "module_with_deps",
+            "Primary",
+            Default::default(),
+            RocCacheDir::Persistent(cache_dir.path()),
+        );
+
+        modules_from_cache.push(loaded_module.types_from_type_cache.len());
+
+        expect_types(
+            loaded_module,
+            hashmap! {
+                "blah2" => "Frac *",
+                "blah3" => "Str",
+                "str" => "Str",
+                "always_three" => "* -> Frac *",
+                "identity" => "a -> a",
+                "z" => "Frac *",
+                "w" => "Dep1.Identity {}",
+                "succeed" => "a -> Dep1.Identity a",
+                "yay" => "Res.Res {} err",
+                "with_default" => "Res.Res a err, a -> a",
+            },
+        );
+    }
+
+    // Every compiler directory also has a file marking the compiler binaries that use it.
+    let cached_modules = std::fs::read_dir(cache_dir.path().join("types"))
+        .unwrap()
+        .flat_map(|compiler_dir| std::fs::read_dir(compiler_dir.unwrap().path()).unwrap())
+        .filter(|entry| {
+            !entry
+                .as_ref()
+                .unwrap()
+                .file_name()
+                .to_string_lossy()
+                .starts_with("owner-")
+        })
+        .count();
+
+    assert!(cached_modules > 0);
+    assert_eq!(modules_from_cache, [0, cached_modules]);
+}
+
 #[test]
 fn imported_dep_regression() {
     let subs_by_module = Default::default();
@@ -771,6 +842,36 @@ fn parse_problem() {
     }
 }
 
+#[test]
+fn parse_problem_report_details() {
+    let modules = vec![(
+        "Main.roc",
+        indoc!(
+            r"
+                module [main]
+
+                main = [
+                "
+        ),
+    )];
+
+    let arena = Bump::new();
+
+    match multiple_modules_help("parse_problem_report_details", &arena, modules) {
+        Ok(Err(LoadingProblem::FormattedReport(report))) => {
+            assert_eq!(report.title, "UNFINISHED LIST");
+            assert!(report.filename.unwrap().ends_with("Main.roc"));
+            assert_eq!(
+                report.region.map(|region| region.start),
+                Some(LineColumn { line: 4, column: 0 })
+            );
+        }
+        Ok(Err(other)) => panic!("expected a formatted report, got {other:?}"),
+        Ok(Ok(_)) => unreachable!("we expect failure here"),
+        Err(io_error) => panic!("IO trouble: {io_error:?}"),
+    }
+}
+
 #[test]
 #[should_panic(expected = "FILE NOT FOUND")]
 fn file_not_found() {
@@ -400,6 +400,7 @@ impl<'a> LowerParams<'a> {
                 }
                 | OpaqueWrapFunction(_)
                 | EmptyRecord
+                | TypedHole { .. }
                 | RuntimeError(_)
                 | Num(_, _, _, _)
                 | Int(_, _, _, _, _)
@@ -0,0 +1,143 @@
+//! Benchmarks: a top-level function whose name starts with `bench_`, like
+//! `bench_sort = \{} -> List.sort_asc numbers`, gets timed by `roc bench`.
+//!
+//! For every benchmark, we build a runner proc that calls the function once. The result is a
+//! lookup of an `expect` that only fails if the runner is given a nonzero argument, which the test
+//! runner never does, but the optimizer can't know that, so it can't throw the call away.
+use crate::ir::{
+    runtime_error, Call, CallType, Env, Expr, Literal, Proc, ProcLayout, SelfRecursive, Stmt,
+};
+use crate::layout::{LambdaName, Layout, LayoutCache, LayoutProblem, Niche};
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::Symbol;
+use roc_region::all::Region;
+use roc_types::subs::Variable;
+
+/// The names of the top-level functions that are benchmarks start with this.
+pub const PREFIX: &str = "bench_";
+
+/// A top-level function that `roc bench` times.
+#[derive(Clone, Debug)]
+pub struct Bench {
+    /// The function, which also becomes the symbol of its runner.
+    pub function: Symbol,
+    pub function_var: Variable,
+    pub region: Region,
+}
+
+/// Every runner takes the argument that decides whether its `expect` fails, and returns `{}`.
+pub const RUNNER_LAYOUT: ProcLayout<'static> = ProcLayout {
+    arguments: &[Layout::U64],
+    result: Layout::UNIT,
+    niche: Niche::NONE,
+};
+
+/// Build the runner of `bench`. If the function can't be called like that, the runner crashes
+/// with a message that says why.
+pub fn build_runner<'a>(
+    env: &mut Env<'a, '_>,
+    layout_cache: &mut LayoutCache<'a>,
+    bench: &Bench,
+) -> Proc<'a> {
+    let keep = env.unique_symbol();
+
+    let body = match runner_body(env, layout_cache, bench, keep) {
+        Ok(body) => body,
+        Err(message) => runtime_error(env, message),
+    };
+
+    Proc {
+        name: LambdaName::no_niche(bench.function),
+        args: env.arena.alloc([(Layout::U64, keep)]),
+        body,
+        closure_data_layout: None,
+        ret_layout: Layout::UNIT,
+        is_self_recursive: SelfRecursive::NotSelfRecursive,
+        is_erased: false,
+    }
+}
+
+fn runner_body<'a>(
+    env: &mut Env<'a, '_>,
+    layout_cache: &mut LayoutCache<'a>,
+    bench: &Bench,
+    keep: Symbol,
+) -> Result<Stmt<'a>, &'a str> {
+    let arena = env.arena;
+
+    let raw_layout = layout_cache
+        .raw_from_var(arena, bench.function_var, env.subs)
+        .map_err(|problem| match problem {
+            LayoutProblem::UnresolvedTypeVar(_) => {
+                "This benchmark returns a value that could be of any type. A type annotation would help."
+            }
+            LayoutProblem::Erroneous => "This benchmark has a type error, so I can't run it.",
+        })?;
+    let function_name = LambdaName::no_niche(bench.function);
+    let function_layout = ProcLayout::from_raw_named(arena, function_name, raw_layout);
+
+    if function_layout.arguments != [Layout::UNIT] {
+        return Err("A benchmark should be a function that takes {}, like `bench_sort = \\{} -> List.sort_asc numbers`.");
+    }
+
+    let unit = env.unique_symbol();
+    let result = env.unique_symbol();
+    let zero = env.unique_symbol();
+    let condition = env.unique_symbol();
+    let ret = env.unique_symbol();
+
+    let specialization_id = env.next_call_specialization_id();
+    let call = Expr::Call(Call {
+        call_type: CallType::ByName {
+            name: function_name,
+            ret_layout: function_layout.result,
+            arg_layouts: function_layout.arguments,
+            specialization_id,
+        },
+        arguments: arena.alloc([unit]),
+    });
+    let is_zero = Expr::Call(Call {
+        call_type: CallType::LowLevel {
+            op: LowLevel::Eq,
+            update_mode: env.next_update_mode_id(),
+        },
+        arguments: arena.alloc([keep, zero]),
+    });
+
+    let expectation_subs = env
+        .expectation_subs
+        .as_deref_mut()
+        .expect("if benchmarks are compiled, the subs of expects should be available");
+    let variables = arena.alloc([expectation_subs.fresh_unnamed_flex_var()]);
+
+    let expect = Stmt::Expect {
+        condition,
+        region: bench.region,
+        lookups: arena.alloc([result]),
+        variables,
+        remainder: arena.alloc(Stmt::Let(
+            ret,
+            Expr::Struct(&[]),
+            Layout::UNIT,
+            arena.alloc(Stmt::Ret(ret)),
+        )),
+    };
+
+    let lets = [
+        (unit, Expr::Struct(&[]), Layout::UNIT),
+        (result, call, function_layout.result),
+        (
+            zero,
+            Expr::Literal(Literal::Int(0i128.to_ne_bytes())),
+            Layout::U64,
+        ),
+        (condition, is_zero, Layout::BOOL),
+    ];
+
+    Ok(lets
+        .into_iter()
+        .rev()
+        .fold(expect, |rest, (symbol, expr, layout)| {
+            Stmt::Let(symbol, expr, layout, arena.alloc(rest))
+        }))
+}
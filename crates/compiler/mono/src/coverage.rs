@@ -0,0 +1,147 @@
+//! Counting how many times each `when` branch, `if` arm and def body runs, for
+//! `roc test --coverage`.
+//!
+//! The counted regions of a module are collected from its canonical declarations, so code that
+//! never gets specialized (because nothing calls it) still shows up with a count of zero. While
+//! lowering to mono IR, a [Stmt::Coverage](crate::ir::Stmt::Coverage) is put at the start of
+//! every counted branch and def body, and the backend turns it into an increment of a counter.
+use roc_can::expr::{ClosureData, Declarations, Expr};
+use roc_can::traverse::{walk_decl, walk_expr, DeclarationInfo, Visitor};
+use roc_collections::MutMap;
+use roc_module::symbol::{ModuleId, Symbol};
+use roc_region::all::Region;
+use roc_types::subs::Variable;
+
+/// What a coverage counter counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Counted {
+    /// The body of a function, or of a top-level value.
+    Body(Symbol),
+    /// A branch of the `block`th `when` or `if` in the module. An `if`'s final `else` is its
+    /// last branch.
+    Branch { block: u32, branch: u32 },
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CoverageRegions {
+    counters: Vec<(Region, Counted)>,
+    branches: MutMap<Region, u32>,
+    bodies: MutMap<Symbol, u32>,
+}
+
+impl CoverageRegions {
+    pub fn from_declarations(declarations: &Declarations) -> Self {
+        let mut collector = Collector {
+            regions: CoverageRegions::default(),
+            blocks: 0,
+        };
+
+        collector.visit_decls(declarations);
+
+        collector.regions
+    }
+
+    /// The regions and what they count, indexed by counter.
+    pub fn counters(&self) -> &[(Region, Counted)] {
+        &self.counters
+    }
+
+    /// The counter of the `when` branch or `if` arm whose body is at `region`.
+    pub fn branch_counter(&self, region: Region) -> Option<u32> {
+        self.branches.get(&region).copied()
+    }
+
+    /// The counter of the body of the function or top-level value `symbol`.
+    pub fn body_counter(&self, symbol: Symbol) -> Option<u32> {
+        self.bodies.get(&symbol).copied()
+    }
+
+    fn add(&mut self, region: Region, counted: Counted) -> u32 {
+        let counter = self.counters.len() as u32;
+        self.counters.push((region, counted));
+
+        counter
+    }
+}
+
+/// The name of the global variable that a backend keeps a coverage counter in.
+pub fn counter_name(module_id: ModuleId, counter: u32) -> String {
+    format!("roc__coverage_{}_{counter}", module_id.to_zero_indexed())
+}
+
+struct Collector {
+    regions: CoverageRegions,
+    blocks: u32,
+}
+
+impl Collector {
+    fn add_body(&mut self, symbol: Symbol, region: Region) {
+        let counter = self.regions.add(region, Counted::Body(symbol));
+        self.regions.bodies.insert(symbol, counter);
+    }
+
+    fn add_block(&mut self, branch_regions: impl Iterator<Item = Region>) {
+        let block = self.blocks;
+        self.blocks += 1;
+
+        for (branch, region) in branch_regions.enumerate() {
+            let counter = self.regions.add(
+                region,
+                Counted::Branch {
+                    block,
+                    branch: branch as u32,
+                },
+            );
+            self.regions.branches.insert(region, counter);
+        }
+    }
+}
+
+impl Visitor for Collector {
+    fn visit_decl(&mut self, decl: DeclarationInfo<'_>) {
+        match &decl {
+            // Record accessors like `.name` become functions with made-up names.
+            DeclarationInfo::Value { loc_expr, .. }
+                if matches!(loc_expr.value, Expr::RecordAccessor(_)) => {}
+            DeclarationInfo::Value {
+                loc_symbol,
+                loc_expr,
+                ..
+            } => self.add_body(loc_symbol.value, loc_expr.region),
+            DeclarationInfo::Function {
+                loc_symbol,
+                loc_body,
+                ..
+            } => self.add_body(loc_symbol.value, loc_body.region),
+            // The tests themselves aren't what we want to know the coverage of.
+            DeclarationInfo::Expectation { .. } => return,
+            DeclarationInfo::Destructure { .. } => {}
+        }
+
+        walk_decl(self, decl);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr, _region: Region, var: Variable) {
+        match expr {
+            Expr::When { branches, .. } => {
+                self.add_block(branches.iter().map(|branch| branch.value.region));
+            }
+            Expr::If {
+                branches,
+                final_else,
+                ..
+            } => {
+                self.add_block(
+                    (branches.iter().map(|(_, loc_then)| loc_then.region))
+                        .chain(std::iter::once(final_else.region)),
+                );
+            }
+            Expr::Closure(ClosureData { name, loc_body, .. }) => {
+                self.add_body(*name, loc_body.region);
+            }
+            _ => {}
+        }
+
+        walk_expr(self, expr, var);
+    }
+}
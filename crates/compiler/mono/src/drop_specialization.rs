@@ -629,6 +629,21 @@ fn specialize_drops_stmt<'a, 'i>(
                 remainder,
             ),
         }),
+        Stmt::Coverage {
+            module,
+            counter,
+            remainder,
+        } => arena.alloc(Stmt::Coverage {
+            module: *module,
+            counter: *counter,
+            remainder: specialize_drops_stmt(
+                arena,
+                layout_interner,
+                ident_ids,
+                environment,
+                remainder,
+            ),
+        }),
         Stmt::Join {
             id,
             parameters,
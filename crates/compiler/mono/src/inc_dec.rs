@@ -182,7 +182,9 @@ impl<'a, 'i> SymbolRcTypesEnv<'a, 'i> {
             Stmt::Refcounting(_, _) => unreachable!(
                 "Refcounting operations should not be present in the AST at this point."
             ),
-            Stmt::Expect { remainder, .. } | Stmt::Dbg { remainder, .. } => {
+            Stmt::Expect { remainder, .. }
+            | Stmt::Dbg { remainder, .. }
+            | Stmt::Coverage { remainder, .. } => {
                 self.insert_symbols_rc_type_stmt(remainder);
             }
             Stmt::Join {
@@ -702,6 +704,19 @@ fn insert_refcount_operations_stmt<'v, 'a>(
                 remainder: newer_remainder,
             })
         }
+        Stmt::Coverage {
+            module,
+            counter,
+            remainder,
+        } => {
+            let new_remainder = insert_refcount_operations_stmt(arena, environment, remainder);
+
+            arena.alloc(Stmt::Coverage {
+                module: *module,
+                counter: *counter,
+                remainder: new_remainder,
+            })
+        }
         Stmt::Join {
             id: joinpoint_id,
             parameters,
@@ -1,5 +1,7 @@
 #![allow(clippy::manual_map)]
 
+use crate::bench::Bench;
+use crate::coverage::CoverageRegions;
 use crate::ir::erased::{build_erased_function, ResolvedErasedLambda};
 use crate::ir::literal::{make_num_literal, IntOrFloatValue};
 use crate::layout::{
@@ -8,6 +10,7 @@ use crate::layout::{
     LayoutRepr, Niche, RawFunctionLayout, TLLayoutInterner, TagIdIntType, UnionLayout,
     WrappedVariant,
 };
+use crate::property::Property;
 use bumpalo::collections::{CollectIn, Vec};
 use bumpalo::Bump;
 use roc_can::abilities::SpecializationId;
@@ -83,7 +86,7 @@ roc_error_macros::assert_sizeof_non_wasm!(ProcLayout, 5 * 8);
 roc_error_macros::assert_sizeof_non_wasm!(Call, 9 * 8);
 roc_error_macros::assert_sizeof_non_wasm!(CallType, 7 * 8);
 
-fn runtime_error<'a>(env: &mut Env<'a, '_>, msg: &'a str) -> Stmt<'a> {
+pub(crate) fn runtime_error<'a>(env: &mut Env<'a, '_>, msg: &'a str) -> Stmt<'a> {
     let sym = env.unique_symbol();
     Stmt::Let(
         sym,
@@ -140,7 +143,15 @@ pub struct SingleEntryPoint<'a> {
 #[derive(Debug, Clone, Copy)]
 pub enum EntryPoint<'a> {
     Program(&'a [SingleEntryPoint<'a>]),
-    Expects { symbols: &'a [Symbol] },
+    Expects {
+        symbols: &'a [Symbol],
+        /// The runners of properties, which take a seed and a size
+        properties: &'a [Symbol],
+        /// The runners of benchmarks, which take the argument that decides whether they fail
+        benches: &'a [Symbol],
+        /// The thunks of snapshots, which return the rendering of their value
+        snapshots: &'a [Symbol],
+    },
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -880,6 +891,10 @@ pub struct ProcsBase<'a> {
     pub host_specializations: HostSpecializations<'a>,
     pub runtime_errors: BumpMap<Symbol, &'a str>,
     pub imported_module_thunks: &'a [Symbol],
+    /// Properties whose runners still need to be built, once their functions are specialized
+    pub properties: std::vec::Vec<Property>,
+    /// Benchmarks whose runners still need to be built, once their functions are specialized
+    pub benches: std::vec::Vec<Bench>,
 }
 
 impl<'a> ProcsBase<'a> {
@@ -1021,6 +1036,9 @@ impl<'a> Procs<'a> {
             host_specializations: HostSpecializations::default(),
             runtime_errors: self.runtime_errors,
             imported_module_thunks: self.imported_module_thunks,
+            // The runners of properties were built after the first round of specializations
+            properties: std::vec::Vec::new(),
+            benches: std::vec::Vec::new(),
         };
 
         (
@@ -1363,6 +1381,9 @@ pub struct Env<'a, 'i> {
     /// [Subs] to write specialized variables of lookups in expects.
     /// [None] if this module doesn't produce any expects.
     pub expectation_subs: Option<&'i mut Subs>,
+    /// The branches and def bodies of this module to count the runs of.
+    /// [None] unless `roc test --coverage` asked for them.
+    pub coverage: Option<&'i CoverageRegions>,
     pub home: ModuleId,
     pub ident_ids: &'i mut IdentIds,
     pub target: Target,
@@ -1401,6 +1422,37 @@ impl<'a, 'i> Env<'a, 'i> {
         id
     }
 
+    /// Counts the runs of the `when` branch or `if` arm whose body is at `region`, if
+    /// `roc test --coverage` asked for that.
+    fn count_branch_runs(&self, region: Region, stmt: Stmt<'a>) -> Stmt<'a> {
+        let counter = self
+            .coverage
+            .and_then(|coverage| coverage.branch_counter(region));
+
+        self.count_runs(counter, stmt)
+    }
+
+    /// Counts the runs of the body of the function or top-level value `symbol`, if
+    /// `roc test --coverage` asked for that.
+    fn count_body_runs(&self, symbol: Symbol, stmt: Stmt<'a>) -> Stmt<'a> {
+        let counter = self
+            .coverage
+            .and_then(|coverage| coverage.body_counter(symbol));
+
+        self.count_runs(counter, stmt)
+    }
+
+    fn count_runs(&self, counter: Option<u32>, stmt: Stmt<'a>) -> Stmt<'a> {
+        match counter {
+            Some(counter) => Stmt::Coverage {
+                module: self.home,
+                counter,
+                remainder: self.arena.alloc(stmt),
+            },
+            None => stmt,
+        }
+    }
+
     pub fn is_imported_symbol(&self, symbol: Symbol) -> bool {
         let sym_module = symbol.module_id();
         sym_module != self.home
@@ -1543,6 +1595,14 @@ pub enum Stmt<'a> {
         /// What happens after the dbg
         remainder: &'a Stmt<'a>,
     },
+    /// Count that the code here ran, for `roc test --coverage`
+    Coverage {
+        /// The module whose [CoverageRegions] has this counter
+        module: ModuleId,
+        counter: u32,
+        /// The code being counted
+        remainder: &'a Stmt<'a>,
+    },
     /// a join point `join f <params> = <continuation> in remainder`
     Join {
         id: JoinPointId,
@@ -2256,6 +2316,13 @@ impl<'a> Stmt<'a> {
                 .append(alloc.hardline())
                 .append(remainder.to_doc(alloc, interner, pretty)),
 
+            Coverage {
+                counter, remainder, ..
+            } => alloc
+                .text(format!("coverage {counter};"))
+                .append(alloc.hardline())
+                .append(remainder.to_doc(alloc, interner, pretty)),
+
             Expect {
                 condition,
                 remainder,
@@ -3554,7 +3621,8 @@ fn specialize_proc_help<'a>(
     let body = partial_proc.body.clone();
     let body_var = partial_proc.body_var;
 
-    let mut specialized_body = from_can(env, body_var, body, procs, layout_cache);
+    let specialized_body = from_can(env, body_var, body, procs, layout_cache);
+    let mut specialized_body = env.count_body_runs(lambda_name.name(), specialized_body);
 
     let specialized_proc = match specialized {
         SpecializedLayout::FunctionPointerBody {
@@ -4130,7 +4198,7 @@ impl<'a> ProcLayout<'a> {
         }
     }
 
-    fn from_raw_named(
+    pub(crate) fn from_raw_named(
         arena: &'a Bump,
         lambda_name: LambdaName<'a>,
         raw: RawFunctionLayout<'a>,
@@ -4671,7 +4739,7 @@ pub fn with_hole<'a>(
                     if is_terminated {
                         let terminator = hole;
 
-                        let mut stmt = with_hole(
+                        let else_stmt = with_hole(
                             env,
                             final_else.value,
                             branch_var,
@@ -4680,6 +4748,7 @@ pub fn with_hole<'a>(
                             assigned,
                             terminator,
                         );
+                        let mut stmt = env.count_branch_runs(final_else.region, else_stmt);
 
                         for (loc_cond, loc_then) in branches.into_iter().rev() {
                             let branching_symbol = env.unique_symbol();
@@ -4693,6 +4762,7 @@ pub fn with_hole<'a>(
                                 assigned,
                                 terminator,
                             );
+                            let then = env.count_branch_runs(loc_then.region, then);
 
                             stmt = cond(env, branching_symbol, cond_layout, then, stmt, ret_layout);
 
@@ -4716,7 +4786,7 @@ pub fn with_hole<'a>(
                             .arena
                             .alloc(Stmt::Jump(id, env.arena.alloc([assigned_in_jump])));
 
-                        let mut stmt = with_hole(
+                        let else_stmt = with_hole(
                             env,
                             final_else.value,
                             branch_var,
@@ -4725,6 +4795,7 @@ pub fn with_hole<'a>(
                             assigned_in_jump,
                             terminator,
                         );
+                        let mut stmt = env.count_branch_runs(final_else.region, else_stmt);
 
                         for (loc_cond, loc_then) in branches.into_iter().rev() {
                             let branching_symbol = possible_reuse_symbol_or_specialize(
@@ -4744,6 +4815,7 @@ pub fn with_hole<'a>(
                                 assigned_in_jump,
                                 terminator,
                             );
+                            let then = env.count_branch_runs(loc_then.region, then);
 
                             stmt = cond(env, branching_symbol, cond_layout, then, stmt, ret_layout);
 
@@ -5974,6 +6046,7 @@ pub fn with_hole<'a>(
             )
         }
         RuntimeError(e) => runtime_error(env, env.arena.alloc(e.runtime_message())),
+        TypedHole { .. } => runtime_error(env, "Hit a typed hole"),
         Crash { msg, ret_var: _ } => {
             let msg_sym = possible_reuse_symbol_or_specialize(
                 env,
@@ -7076,7 +7149,8 @@ pub fn from_can<'a>(
                 "invalid condition type in if expression"
             );
 
-            let mut stmt = from_can(env, branch_var, final_else.value, procs, layout_cache);
+            let else_stmt = from_can(env, branch_var, final_else.value, procs, layout_cache);
+            let mut stmt = env.count_branch_runs(final_else.region, else_stmt);
 
             for (loc_cond, loc_then) in branches.into_iter().rev() {
                 let branching_symbol = possible_reuse_symbol_or_specialize(
@@ -7087,6 +7161,7 @@ pub fn from_can<'a>(
                     cond_var,
                 );
                 let then = from_can(env, branch_var, loc_then.value, procs, layout_cache);
+                let then = env.count_branch_runs(loc_then.region, then);
 
                 stmt = cond(env, branching_symbol, cond_layout, then, stmt, ret_layout);
 
@@ -7231,7 +7306,7 @@ pub fn from_can<'a>(
     }
 }
 
-fn store_specialized_expectation_lookups(
+pub(crate) fn store_specialized_expectation_lookups(
     env: &mut Env,
     lookup_variables: impl IntoIterator<Item = Variable>,
     specialized_variables: &[Variable],
@@ -7255,7 +7330,8 @@ fn to_opt_branches<'a>(
 ) -> std::vec::Vec<(
     Pattern<'a>,
     Option<Loc<roc_can::expr::Expr>>,
-    roc_can::expr::Expr,
+    // At the region of the branch's body, even with the pattern's assignments added to it
+    Loc<roc_can::expr::Expr>,
 )> {
     debug_assert!(!branches.is_empty());
 
@@ -7301,14 +7377,18 @@ fn to_opt_branches<'a>(
                     };
 
                     // TODO remove clone?
-                    opt_branches.push((mono_pattern, when_branch.guard.clone(), loc_expr.value));
+                    opt_branches.push((
+                        mono_pattern,
+                        when_branch.guard.clone(),
+                        Loc::at(when_branch.value.region, loc_expr.value),
+                    ));
                 }
                 Err(runtime_error) => {
                     // TODO remove clone?
                     opt_branches.push((
                         Pattern::Underscore,
                         when_branch.guard.clone(),
-                        roc_can::expr::Expr::RuntimeError(runtime_error),
+                        Loc::at_zero(roc_can::expr::Expr::RuntimeError(runtime_error)),
                     ));
                 }
             }
@@ -7321,7 +7401,9 @@ fn to_opt_branches<'a>(
         opt_branches.push((
             Pattern::Underscore,
             None,
-            roc_can::expr::Expr::RuntimeError(roc_problem::can::RuntimeError::NonExhaustivePattern),
+            Loc::at_zero(roc_can::expr::Expr::RuntimeError(
+                roc_problem::can::RuntimeError::NonExhaustivePattern,
+            )),
         ));
     }
 
@@ -7362,7 +7444,7 @@ fn from_can_when<'a>(
     let arena = env.arena;
     let it = opt_branches
         .into_iter()
-        .filter_map(|(pattern, opt_guard, can_expr)| {
+        .filter_map(|(pattern, opt_guard, loc_can_expr)| {
             // If the pattern has a void layout we can drop it; however, we must still perform the
             // work of building the body, because that may contain specializations we must
             // discover for use elsewhere. See
@@ -7379,6 +7461,7 @@ fn from_can_when<'a>(
                 None
             };
 
+            let can_expr = loc_can_expr.value;
             let branch_stmt = match join_point {
                 None => from_can(env, expr_var, can_expr, procs, layout_cache),
                 Some(id) => {
@@ -7389,6 +7472,7 @@ fn from_can_when<'a>(
                     with_hole(env, can_expr, expr_var, procs, layout_cache, symbol, jump)
                 }
             };
+            let branch_stmt = env.count_branch_runs(loc_can_expr.region, branch_stmt);
 
             use decision_tree::Guard;
             let result = if let Some(loc_expr) = opt_guard {
@@ -7683,6 +7767,19 @@ fn substitute_in_stmt_help<'a>(
             Some(arena.alloc(expect))
         }
 
+        Coverage {
+            module,
+            counter,
+            remainder,
+        } => match substitute_in_stmt_help(arena, remainder, subs) {
+            Some(remainder) => Some(arena.alloc(Coverage {
+                module: *module,
+                counter: *counter,
+                remainder,
+            })),
+            None => None,
+        },
+
         Expect {
             condition,
             region,
@@ -0,0 +1,760 @@
+//! Property-based testing: a top-level `expect` whose expression is a function, like
+//! `expect \n -> Num.abs n >= 0`, gets called with generated arguments by `roc test`.
+//!
+//! For every property, we build a runner proc that takes a seed and a size, generates a value for
+//! every argument of the property from its type, calls the property, and `expect`s the result.
+//! Numbers are at most `size` away from zero, and lists and strings are at most `size` long, so a
+//! test runner can reduce the size of a failing case by running the property with smaller sizes.
+//! That generates new arguments rather than shrinking the failing ones, since a runner only knows
+//! the seed and size that its arguments came from, not the values themselves.
+use crate::ir::{
+    runtime_error, store_specialized_expectation_lookups, BranchInfo, Call, CallType, Env, Expr,
+    JoinPointId, Literal, Param, Proc, ProcLayout, SelfRecursive, Stmt,
+};
+use crate::layout::{
+    self, Builtin, InLayout, LambdaName, Layout, LayoutCache, LayoutProblem, LayoutRepr, Niche,
+    UnionVariant, WrappedVariant,
+};
+use bumpalo::collections::Vec;
+use roc_builtins::bitcode::IntWidth;
+use roc_module::ident::TagName;
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::{ModuleId, Symbol};
+use roc_region::all::Region;
+use roc_std::RocDec;
+use roc_types::subs::{Content, FlatType, GetSubsSlice, Variable};
+
+/// A top-level `expect` whose expression is a function.
+#[derive(Clone, Debug)]
+pub struct Property {
+    /// The symbol of the `expect`, which becomes the symbol of its runner.
+    pub symbol: Symbol,
+    /// The function that gets called with the generated arguments.
+    pub function: Symbol,
+    pub function_var: Variable,
+    /// The type of every argument, and its name if it has one to report when the property fails.
+    pub arguments: std::vec::Vec<(Variable, Option<Symbol>)>,
+    /// The region of the function's body, which is where a failure gets reported.
+    pub region: Region,
+}
+
+/// Every runner takes a seed and a size, and returns `{}`.
+pub const RUNNER_LAYOUT: ProcLayout<'static> = ProcLayout {
+    arguments: &[Layout::U64, Layout::U64],
+    result: Layout::UNIT,
+    niche: Niche::NONE,
+};
+
+/// The characters that generated strings are made of: some ASCII, and some that take more than one
+/// byte in UTF-8.
+const STR_PIECES: &[&str] = &[
+    "a", "b", "z", "A", "Z", "0", "9", " ", "-", "_", "\n", "é", "ß", "ø", "中", "🐦",
+];
+
+const TYPE_ERROR: &str = "This property has a type error, so I can't run it.";
+
+const ANY_TYPE: &str = "This property has an argument that could be of any type, so I don't know what values to generate for it. A type annotation would help.";
+
+/// Build the runner of `property`. If some argument's type can't be generated, the runner crashes
+/// with a message that says why.
+pub fn build_runner<'a>(
+    env: &mut Env<'a, '_>,
+    layout_cache: &mut LayoutCache<'a>,
+    property: &Property,
+) -> Proc<'a> {
+    let seed = env.unique_symbol();
+    let size = env.unique_symbol();
+
+    let body = match runner_body(env, layout_cache, property, seed, size) {
+        Ok(body) => body,
+        Err(message) => {
+            let message = env.arena.alloc(message);
+
+            runtime_error(env, message)
+        }
+    };
+
+    Proc {
+        name: LambdaName::no_niche(property.symbol),
+        args: env.arena.alloc([(Layout::U64, seed), (Layout::U64, size)]),
+        body,
+        closure_data_layout: None,
+        ret_layout: Layout::UNIT,
+        is_self_recursive: SelfRecursive::NotSelfRecursive,
+        is_erased: false,
+    }
+}
+
+fn runner_body<'a>(
+    env: &mut Env<'a, '_>,
+    layout_cache: &mut LayoutCache<'a>,
+    property: &Property,
+    seed: Symbol,
+    size: Symbol,
+) -> Result<Stmt<'a>, String> {
+    let arena = env.arena;
+
+    let raw_layout = layout_cache
+        .raw_from_var(arena, property.function_var, env.subs)
+        .map_err(|_| String::from(TYPE_ERROR))?;
+    let function_name = LambdaName::no_niche(property.function);
+    let function_layout = ProcLayout::from_raw_named(arena, function_name, raw_layout);
+
+    let mut generator = Generator {
+        env,
+        layout_cache,
+        steps: std::vec::Vec::new(),
+        state: seed,
+    };
+
+    let mut arguments = Vec::with_capacity_in(property.arguments.len(), arena);
+    for (var, _) in property.arguments.iter() {
+        arguments.push(generator.value(*var, size)?);
+    }
+    let arguments = arguments.into_bump_slice();
+
+    let specialization_id = generator.env.next_call_specialization_id();
+    let condition = generator.let_expr(
+        Expr::Call(Call {
+            call_type: CallType::ByName {
+                name: function_name,
+                ret_layout: function_layout.result,
+                arg_layouts: function_layout.arguments,
+                specialization_id,
+            },
+            arguments,
+        }),
+        Layout::BOOL,
+    );
+
+    let Generator { env, steps, .. } = generator;
+
+    let mut lookups = Vec::new_in(arena);
+    let mut lookup_variables = Vec::new_in(arena);
+    let mut specialized_variables = Vec::new_in(arena);
+
+    for ((var, name), argument) in property.arguments.iter().zip(arguments.iter()) {
+        if name.is_some() {
+            let expectation_subs = env
+                .expectation_subs
+                .as_deref_mut()
+                .expect("if expects are compiled, their subs should be available");
+
+            lookups.push(*argument);
+            lookup_variables.push(*var);
+            specialized_variables.push(expectation_subs.fresh_unnamed_flex_var());
+        }
+    }
+
+    let specialized_variables = specialized_variables.into_bump_slice();
+    store_specialized_expectation_lookups(env, lookup_variables, specialized_variables);
+
+    let unit = env.unique_symbol();
+    let tail = Stmt::Expect {
+        condition,
+        region: property.region,
+        lookups: lookups.into_bump_slice(),
+        variables: specialized_variables,
+        remainder: arena.alloc(Stmt::Let(
+            unit,
+            Expr::Struct(&[]),
+            Layout::UNIT,
+            arena.alloc(Stmt::Ret(unit)),
+        )),
+    };
+
+    Ok(finish(arena, steps, tail))
+}
+
+/// A statement of the runner that the rest of the runner goes into.
+enum Step<'a> {
+    Let(Symbol, Expr<'a>, InLayout<'a>),
+    /// `join id parameters = <the rest> in remainder`
+    Join(JoinPointId, &'a [Param<'a>], Stmt<'a>),
+}
+
+fn finish<'a>(
+    arena: &'a bumpalo::Bump,
+    steps: std::vec::Vec<Step<'a>>,
+    tail: Stmt<'a>,
+) -> Stmt<'a> {
+    steps.into_iter().rev().fold(tail, |rest, step| match step {
+        Step::Let(symbol, expr, layout) => Stmt::Let(symbol, expr, layout, arena.alloc(rest)),
+        Step::Join(id, parameters, remainder) => Stmt::Join {
+            id,
+            parameters,
+            body: arena.alloc(rest),
+            remainder: arena.alloc(remainder),
+        },
+    })
+}
+
+struct Generator<'a, 'r, 'i> {
+    env: &'r mut Env<'a, 'i>,
+    layout_cache: &'r mut LayoutCache<'a>,
+    steps: std::vec::Vec<Step<'a>>,
+    /// The state of the random number generator, which is threaded through the whole runner.
+    state: Symbol,
+}
+
+impl<'a, 'r, 'i> Generator<'a, 'r, 'i> {
+    /// Generate a value of type `var`.
+    fn value(&mut self, var: Variable, size: Symbol) -> Result<Symbol, String> {
+        let layout = self.layout(var)?;
+
+        match *self.env.subs.get_content_without_compacting(var) {
+            Content::Alias(Symbol::BOOL_BOOL, ..) => Ok(self.bool()),
+            Content::Alias(symbol, ..) if symbol.module_id() == ModuleId::NUM => {
+                self.number(layout, size)
+            }
+            Content::RangedNumber(_) => self.number(layout, size),
+            Content::Alias(_, _, real_var, roc_types::types::AliasKind::Structural) => {
+                self.value(real_var, size)
+            }
+            Content::Alias(symbol, _, _, roc_types::types::AliasKind::Opaque) => Err(format!(
+                "I don't know how to generate values of the opaque type {symbol:?} for this property."
+            )),
+            Content::Structure(FlatType::Apply(Symbol::STR_STR, _)) => Ok(self.string(size)),
+            Content::Structure(FlatType::Apply(Symbol::LIST_LIST, elems)) => {
+                let elem_var = self.env.subs.get_subs_slice(elems)[0];
+
+                self.list(elem_var, layout, size)
+            }
+            Content::Structure(FlatType::Record(..)) => self.record(var, layout, size),
+            Content::Structure(FlatType::Tuple(..)) => self.tuple(var, layout, size),
+            Content::Structure(FlatType::EmptyRecord) => {
+                Ok(self.let_expr(Expr::Struct(&[]), Layout::UNIT))
+            }
+            Content::Structure(FlatType::TagUnion(..) | FlatType::FunctionOrTagUnion(..)) => {
+                self.tag_union(var, layout, size)
+            }
+            Content::Structure(FlatType::RecursiveTagUnion(..)) | Content::RecursionVar { .. } => {
+                Err(String::from(
+                    "I don't know how to generate values of recursive types for this property yet.",
+                ))
+            }
+            Content::Structure(FlatType::EmptyTagUnion) => Err(String::from(
+                "This property takes a value of the empty tag union [], which can't be made.",
+            )),
+            Content::Structure(FlatType::Func(..) | FlatType::EffectfulFunc)
+            | Content::LambdaSet(_)
+            | Content::ErasedLambda => Err(String::from(
+                "I don't know how to generate functions for this property.",
+            )),
+            Content::FlexVar(_)
+            | Content::RigidVar(_)
+            | Content::FlexAbleVar(..)
+            | Content::RigidAbleVar(..) => Err(String::from(ANY_TYPE)),
+            Content::Structure(FlatType::Apply(symbol, _)) => Err(format!(
+                "I don't know how to generate values of the type {symbol:?} for this property."
+            )),
+            Content::Error | Content::Pure | Content::Effectful => {
+                Err(String::from(TYPE_ERROR))
+            }
+        }
+    }
+
+    fn bool(&mut self) -> Symbol {
+        let two = self.int_literal(2, Layout::U64);
+        let bit = self.below(two);
+        let one = self.int_literal(1, Layout::U64);
+
+        self.low_level(LowLevel::Eq, &[bit, one], Layout::BOOL)
+    }
+
+    fn number(&mut self, layout: InLayout<'a>, size: Symbol) -> Result<Symbol, String> {
+        match self.layout_cache.get_repr(layout) {
+            LayoutRepr::Builtin(Builtin::Int(width)) => Ok(self.int(width, size)),
+            LayoutRepr::Builtin(Builtin::Float(_) | Builtin::Decimal) => {
+                // Quarters, so the generated fractions aren't all whole numbers.
+                let four = self.int_literal(4, Layout::U64);
+                let quarters_size =
+                    self.low_level(LowLevel::NumMulWrap, &[size, four], Layout::U64);
+                let quarters = self.int(IntWidth::I64, quarters_size);
+                let frac = self.low_level(LowLevel::NumToFrac, &[quarters], layout);
+
+                let four = match self.layout_cache.get_repr(layout) {
+                    LayoutRepr::Builtin(Builtin::Decimal) => {
+                        let four = RocDec::from_str("4").unwrap();
+                        Expr::Literal(Literal::Decimal(four.to_ne_bytes()))
+                    }
+                    _ => Expr::Literal(Literal::Float(4.0)),
+                };
+                let four = self.let_expr(four, layout);
+
+                Ok(self.low_level(LowLevel::NumDivFrac, &[frac, four], layout))
+            }
+            _ => Err(String::from(
+                "I don't know how to generate this kind of number for this property.",
+            )),
+        }
+    }
+
+    /// An integer between `-size` and `size`, or between `0` and `size` if it's unsigned.
+    fn int(&mut self, width: IntWidth, size: Symbol) -> Symbol {
+        let one = self.int_literal(1, Layout::U64);
+
+        let value = if width.is_signed() {
+            let two = self.int_literal(2, Layout::U64);
+            let doubled = self.low_level(LowLevel::NumMulWrap, &[size, two], Layout::U64);
+            let bound = self.low_level(LowLevel::NumAddWrap, &[doubled, one], Layout::U64);
+            let offset = self.below(bound);
+            let shifted = self.low_level(LowLevel::NumSubWrap, &[offset, size], Layout::U64);
+
+            self.low_level(LowLevel::NumIntCast, &[shifted], Layout::I64)
+        } else {
+            let bound = self.low_level(LowLevel::NumAddWrap, &[size, one], Layout::U64);
+
+            self.below(bound)
+        };
+
+        let value_layout = if width.is_signed() {
+            Layout::I64
+        } else {
+            Layout::U64
+        };
+        let layout = Layout::int_width(width);
+
+        if layout == value_layout {
+            value
+        } else {
+            self.low_level(LowLevel::NumIntCast, &[value], layout)
+        }
+    }
+
+    fn string(&mut self, size: Symbol) -> Symbol {
+        let one = self.int_literal(1, Layout::U64);
+        let bound = self.low_level(LowLevel::NumAddWrap, &[size, one], Layout::U64);
+        let length = self.below(bound);
+        let empty = self.let_expr(Expr::Literal(Literal::Str("")), Layout::STR);
+
+        self.repeat(length, empty, Layout::STR, |generator, string| {
+            let count = generator.int_literal(STR_PIECES.len() as i128, Layout::U64);
+            let pick = generator.below(count);
+            let piece = generator.choose(pick, Layout::STR, STR_PIECES.len(), |generator, i| {
+                Ok(generator.let_expr(Expr::Literal(Literal::Str(STR_PIECES[i])), Layout::STR))
+            })?;
+
+            Ok(generator.low_level(LowLevel::StrConcat, &[string, piece], Layout::STR))
+        })
+        .expect("generating a string can't fail")
+    }
+
+    fn list(
+        &mut self,
+        elem_var: Variable,
+        list_layout: InLayout<'a>,
+        size: Symbol,
+    ) -> Result<Symbol, String> {
+        let elem_is_unbound = matches!(
+            self.env.subs.get_content_without_compacting(elem_var),
+            Content::FlexVar(_) | Content::RigidVar(_)
+        );
+
+        if elem_is_unbound {
+            // A list of anything at all can only be empty.
+            let zero = self.int_literal(0, Layout::U64);
+
+            return Ok(self.low_level(LowLevel::ListWithCapacity, &[zero], list_layout));
+        }
+
+        let one = self.int_literal(1, Layout::U64);
+        let bound = self.low_level(LowLevel::NumAddWrap, &[size, one], Layout::U64);
+        let length = self.below(bound);
+        let empty = self.low_level(LowLevel::ListWithCapacity, &[length], list_layout);
+
+        // Elements get smaller as lists get longer, so nested lists don't blow up.
+        let one = self.int_literal(1, Layout::U8);
+        let elem_size = self.low_level(LowLevel::NumShiftRightZfBy, &[size, one], Layout::U64);
+
+        self.repeat(length, empty, list_layout, |generator, list| {
+            let elem = generator.value(elem_var, elem_size)?;
+
+            Ok(generator.low_level(LowLevel::ListAppendUnsafe, &[list, elem], list_layout))
+        })
+    }
+
+    fn record(
+        &mut self,
+        var: Variable,
+        layout: InLayout<'a>,
+        size: Symbol,
+    ) -> Result<Symbol, String> {
+        let sorted_fields = {
+            let mut layout_env =
+                layout::Env::from_components(self.layout_cache, self.env.subs, self.env.arena);
+            layout::sort_record_fields(&mut layout_env, var)
+                .map_err(|_| String::from(TYPE_ERROR))?
+        };
+
+        let mut fields = std::vec::Vec::with_capacity(sorted_fields.len());
+        for (label, field_var, field_layout) in sorted_fields {
+            if field_layout.is_err() {
+                return Err(format!(
+                    "I don't know how to generate records with the optional field {label} for this property."
+                ));
+            }
+
+            fields.push(self.value(field_var, size)?);
+        }
+
+        Ok(self.struct_like(&fields, layout))
+    }
+
+    fn tuple(
+        &mut self,
+        var: Variable,
+        layout: InLayout<'a>,
+        size: Symbol,
+    ) -> Result<Symbol, String> {
+        let sorted_elems = {
+            let mut layout_env =
+                layout::Env::from_components(self.layout_cache, self.env.subs, self.env.arena);
+            layout::sort_tuple_elems(&mut layout_env, var).map_err(|_| String::from(TYPE_ERROR))?
+        };
+
+        let mut elems = std::vec::Vec::with_capacity(sorted_elems.len());
+        for (_, elem_var, _) in sorted_elems {
+            elems.push(self.value(elem_var, size)?);
+        }
+
+        Ok(self.struct_like(&elems, layout))
+    }
+
+    /// Like records and tuples in mono, a struct with only one field is just that field.
+    fn struct_like(&mut self, fields: &[Symbol], layout: InLayout<'a>) -> Symbol {
+        match fields {
+            [only_field] => *only_field,
+            _ => {
+                let fields = self.env.arena.alloc_slice_copy(fields);
+
+                self.let_expr(Expr::Struct(fields), layout)
+            }
+        }
+    }
+
+    fn tag_union(
+        &mut self,
+        var: Variable,
+        layout: InLayout<'a>,
+        size: Symbol,
+    ) -> Result<Symbol, String> {
+        let variant = {
+            let mut layout_env =
+                layout::Env::from_components(self.layout_cache, self.env.subs, self.env.arena);
+            layout::union_sorted_tags(&mut layout_env, var).map_err(|_| String::from(TYPE_ERROR))?
+        };
+
+        let mut tags = std::vec::Vec::new();
+        roc_types::pretty_print::chase_ext_tag_union(self.env.subs, var, &mut tags);
+
+        let payload_vars = |tag_name: &TagName| {
+            tags.iter()
+                .find(|(name, _)| name == tag_name)
+                .map(|(_, vars)| vars.clone())
+                .unwrap_or_default()
+        };
+
+        match variant {
+            UnionVariant::Never => Err(String::from(
+                "This property takes a value of a tag union without tags, which can't be made.",
+            )),
+            UnionVariant::Unit => Ok(self.let_expr(Expr::Struct(&[]), Layout::UNIT)),
+            UnionVariant::BoolUnion { .. } => Ok(self.bool()),
+            UnionVariant::ByteUnion(tag_names) => {
+                let count = self.int_literal(tag_names.len() as i128, Layout::U64);
+                let tag_id = self.below(count);
+
+                Ok(self.low_level(LowLevel::NumIntCast, &[tag_id], Layout::U8))
+            }
+            UnionVariant::Newtype { tag_name, .. } => {
+                let payload = self.payload(payload_vars(tag_name.expect_tag_ref()), size)?;
+
+                Ok(self.struct_like(&payload, layout))
+            }
+            UnionVariant::NewtypeByVoid { data_tag_name, .. } => {
+                let payload = self.payload(payload_vars(data_tag_name.expect_tag_ref()), size)?;
+
+                Ok(self.struct_like(&payload, layout))
+            }
+            UnionVariant::Wrapped(WrappedVariant::NonRecursive { sorted_tag_layouts }) => {
+                let union_layout = match self.layout_cache.get_repr(layout) {
+                    LayoutRepr::Union(union_layout) => union_layout,
+                    _ => return Err(String::from(TYPE_ERROR)),
+                };
+                let tag_payload_vars: std::vec::Vec<_> = sorted_tag_layouts
+                    .iter()
+                    .map(|(tag_name, _)| payload_vars(tag_name.expect_tag_ref()))
+                    .collect();
+
+                let count = self.int_literal(tag_payload_vars.len() as i128, Layout::U64);
+                let pick = self.below(count);
+
+                self.choose(pick, layout, tag_payload_vars.len(), |generator, tag_id| {
+                    let payload = generator.payload(tag_payload_vars[tag_id].clone(), size)?;
+                    let arguments = generator.env.arena.alloc_slice_copy(&payload);
+
+                    Ok(generator.let_expr(
+                        Expr::Tag {
+                            tag_layout: union_layout,
+                            tag_id: tag_id as _,
+                            arguments,
+                            reuse: None,
+                        },
+                        layout,
+                    ))
+                })
+            }
+            UnionVariant::Wrapped(_) => Err(String::from(
+                "I don't know how to generate values of recursive types for this property yet.",
+            )),
+        }
+    }
+
+    /// The payload of a tag, in the order of its layout: by alignment, largest first.
+    fn payload(
+        &mut self,
+        vars: std::vec::Vec<Variable>,
+        size: Symbol,
+    ) -> Result<std::vec::Vec<Symbol>, String> {
+        let mut sorted = std::vec::Vec::with_capacity(vars.len());
+        for var in vars {
+            let layout = self.layout(var)?;
+            let alignment = self
+                .layout_cache
+                .get_repr(layout)
+                .alignment_bytes(&self.layout_cache.interner);
+
+            sorted.push((alignment, var));
+        }
+        sorted.sort_by_key(|(alignment, _)| std::cmp::Reverse(*alignment));
+
+        sorted
+            .into_iter()
+            .map(|(_, var)| self.value(var, size))
+            .collect()
+    }
+
+    /// Run `body` `count` times, threading an accumulator through, starting with `initial`.
+    fn repeat(
+        &mut self,
+        count: Symbol,
+        initial: Symbol,
+        layout: InLayout<'a>,
+        mut body: impl FnMut(&mut Self, Symbol) -> Result<Symbol, String>,
+    ) -> Result<Symbol, String> {
+        let arena = self.env.arena;
+
+        let zero = self.int_literal(0, Layout::U64);
+
+        let done = JoinPointId(self.env.unique_symbol());
+        let result = self.env.unique_symbol();
+        let result_state = self.env.unique_symbol();
+
+        let repeat = JoinPointId(self.env.unique_symbol());
+        let accumulator = self.env.unique_symbol();
+        let index = self.env.unique_symbol();
+        let state = self.env.unique_symbol();
+
+        let outer_steps = std::mem::take(&mut self.steps);
+        let initial_state = self.state;
+
+        self.state = state;
+        let more = self.low_level(LowLevel::NumLt, &[index, count], Layout::BOOL);
+        let check_steps = std::mem::take(&mut self.steps);
+
+        let next_accumulator = body(self, accumulator)?;
+        let one = self.int_literal(1, Layout::U64);
+        let next_index = self.low_level(LowLevel::NumAddWrap, &[index, one], Layout::U64);
+        let again = Stmt::Jump(
+            repeat,
+            arena.alloc([next_accumulator, next_index, self.state]),
+        );
+        let again = finish(arena, std::mem::take(&mut self.steps), again);
+
+        let stop = Stmt::Jump(done, arena.alloc([accumulator, state]));
+
+        let repeat_body = finish(
+            arena,
+            check_steps,
+            Stmt::Switch {
+                cond_symbol: more,
+                cond_layout: Layout::BOOL,
+                branches: arena.alloc([(1, BranchInfo::None, again)]),
+                default_branch: (BranchInfo::None, arena.alloc(stop)),
+                ret_layout: Layout::UNIT,
+            },
+        );
+
+        let repeat_join = Stmt::Join {
+            id: repeat,
+            parameters: arena.alloc([
+                Param {
+                    symbol: accumulator,
+                    layout,
+                },
+                Param {
+                    symbol: index,
+                    layout: Layout::U64,
+                },
+                Param {
+                    symbol: state,
+                    layout: Layout::U64,
+                },
+            ]),
+            body: arena.alloc(repeat_body),
+            remainder: arena.alloc(Stmt::Jump(
+                repeat,
+                arena.alloc([initial, zero, initial_state]),
+            )),
+        };
+
+        self.steps = outer_steps;
+        self.steps.push(Step::Join(
+            done,
+            arena.alloc([
+                Param {
+                    symbol: result,
+                    layout,
+                },
+                Param {
+                    symbol: result_state,
+                    layout: Layout::U64,
+                },
+            ]),
+            repeat_join,
+        ));
+        self.state = result_state;
+
+        Ok(result)
+    }
+
+    /// Generate one of `count` alternatives, the one numbered `pick`, and continue with its result.
+    fn choose(
+        &mut self,
+        pick: Symbol,
+        layout: InLayout<'a>,
+        count: usize,
+        mut alternative: impl FnMut(&mut Self, usize) -> Result<Symbol, String>,
+    ) -> Result<Symbol, String> {
+        let arena = self.env.arena;
+
+        let done = JoinPointId(self.env.unique_symbol());
+        let result = self.env.unique_symbol();
+        let result_state = self.env.unique_symbol();
+
+        let outer_steps = std::mem::take(&mut self.steps);
+        let initial_state = self.state;
+
+        let mut branches = Vec::with_capacity_in(count, arena);
+        for i in 0..count {
+            self.state = initial_state;
+
+            let value = alternative(self, i)?;
+            let jump = Stmt::Jump(done, arena.alloc([value, self.state]));
+
+            branches.push((
+                i as u64,
+                BranchInfo::None,
+                finish(arena, std::mem::take(&mut self.steps), jump),
+            ));
+        }
+
+        // The last alternative is the default, so the switch is exhaustive.
+        let (_, _, default_branch) = branches.pop().expect("there is nothing to choose from");
+
+        let switch = Stmt::Switch {
+            cond_symbol: pick,
+            cond_layout: Layout::U64,
+            branches: branches.into_bump_slice(),
+            default_branch: (BranchInfo::None, arena.alloc(default_branch)),
+            ret_layout: Layout::UNIT,
+        };
+
+        self.steps = outer_steps;
+        self.steps.push(Step::Join(
+            done,
+            arena.alloc([
+                Param {
+                    symbol: result,
+                    layout,
+                },
+                Param {
+                    symbol: result_state,
+                    layout: Layout::U64,
+                },
+            ]),
+            switch,
+        ));
+        self.state = result_state;
+
+        Ok(result)
+    }
+
+    /// A random number below `bound`, which must not be zero.
+    fn below(&mut self, bound: Symbol) -> Symbol {
+        let random = self.next_u64();
+
+        self.low_level(LowLevel::NumRemUnchecked, &[random, bound], Layout::U64)
+    }
+
+    /// The next number of a [SplitMix64](https://prng.di.unimi.it/splitmix64.c) generator.
+    fn next_u64(&mut self) -> Symbol {
+        let golden_gamma = self.int_literal(0x9e3779b97f4a7c15, Layout::U64);
+        let state = self.low_level(
+            LowLevel::NumAddWrap,
+            &[self.state, golden_gamma],
+            Layout::U64,
+        );
+        self.state = state;
+
+        let z = self.xor_shift_multiply(state, 30, 0xbf58476d1ce4e5b9);
+        let z = self.xor_shift_multiply(z, 27, 0x94d049bb133111eb);
+
+        let shift = self.int_literal(31, Layout::U8);
+        let shifted = self.low_level(LowLevel::NumShiftRightZfBy, &[z, shift], Layout::U64);
+
+        self.low_level(LowLevel::NumBitwiseXor, &[z, shifted], Layout::U64)
+    }
+
+    fn xor_shift_multiply(&mut self, z: Symbol, shift: i128, multiplier: i128) -> Symbol {
+        let shift = self.int_literal(shift, Layout::U8);
+        let shifted = self.low_level(LowLevel::NumShiftRightZfBy, &[z, shift], Layout::U64);
+        let mixed = self.low_level(LowLevel::NumBitwiseXor, &[z, shifted], Layout::U64);
+        let multiplier = self.int_literal(multiplier, Layout::U64);
+
+        self.low_level(LowLevel::NumMulWrap, &[mixed, multiplier], Layout::U64)
+    }
+
+    fn layout(&mut self, var: Variable) -> Result<InLayout<'a>, String> {
+        self.layout_cache
+            .from_var(self.env.arena, var, self.env.subs)
+            .map_err(|problem| match problem {
+                LayoutProblem::UnresolvedTypeVar(_) => String::from(ANY_TYPE),
+                LayoutProblem::Erroneous => String::from(TYPE_ERROR),
+            })
+    }
+
+    fn int_literal(&mut self, value: i128, layout: InLayout<'a>) -> Symbol {
+        self.let_expr(Expr::Literal(Literal::Int(value.to_ne_bytes())), layout)
+    }
+
+    fn low_level(&mut self, op: LowLevel, arguments: &[Symbol], layout: InLayout<'a>) -> Symbol {
+        let call = Call {
+            call_type: CallType::LowLevel {
+                op,
+                update_mode: self.env.next_update_mode_id(),
+            },
+            arguments: self.env.arena.alloc_slice_copy(arguments),
+        };
+
+        self.let_expr(Expr::Call(call), layout)
+    }
+
+    fn let_expr(&mut self, expr: Expr<'a>, layout: InLayout<'a>) -> Symbol {
+        let symbol = self.env.unique_symbol();
+        self.steps.push(Step::Let(symbol, expr, layout));
+
+        symbol
+    }
+}
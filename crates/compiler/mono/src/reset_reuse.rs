@@ -650,6 +650,27 @@ fn insert_reset_reuse_operations_stmt<'a, 'i>(
                 remainder: new_remainder,
             })
         }
+        Stmt::Coverage {
+            module,
+            counter,
+            remainder,
+        } => {
+            let new_remainder = insert_reset_reuse_operations_stmt(
+                arena,
+                layout_interner,
+                home,
+                ident_ids,
+                update_mode_ids,
+                environment,
+                remainder,
+            );
+
+            arena.alloc(Stmt::Coverage {
+                module: *module,
+                counter: *counter,
+                remainder: new_remainder,
+            })
+        }
         Stmt::Join {
             id: joinpoint_id,
             parameters,
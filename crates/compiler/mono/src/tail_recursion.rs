@@ -353,6 +353,26 @@ fn insert_jumps<'a>(
             None => None,
         },
 
+        Coverage {
+            module,
+            counter,
+            remainder,
+        } => match insert_jumps(
+            arena,
+            remainder,
+            goal_id,
+            needle,
+            needle_arguments,
+            needle_result,
+        ) {
+            Some(cont) => Some(arena.alloc(Coverage {
+                module: *module,
+                counter: *counter,
+                remainder: cont,
+            })),
+            None => None,
+        },
+
         Expect {
             condition,
             region,
@@ -527,7 +547,9 @@ fn trmc_candidates_help(
             }
         }
         Stmt::Refcounting(_, next) => trmc_candidates_help(function_name, next, candidates),
-        Stmt::Expect { remainder, .. } | Stmt::Dbg { remainder, .. } => {
+        Stmt::Expect { remainder, .. }
+        | Stmt::Dbg { remainder, .. }
+        | Stmt::Coverage { remainder, .. } => {
             trmc_candidates_help(function_name, remainder, candidates)
         }
         Stmt::Join {
@@ -999,6 +1021,15 @@ impl<'a> TrmcEnv<'a> {
                 variable: *variable,
                 remainder: arena.alloc(self.walk_stmt(env, remainder)),
             },
+            Stmt::Coverage {
+                module,
+                counter,
+                remainder,
+            } => Stmt::Coverage {
+                module: *module,
+                counter: *counter,
+                remainder: arena.alloc(self.walk_stmt(env, remainder)),
+            },
             Stmt::Join {
                 id,
                 parameters,
@@ -1092,7 +1123,7 @@ fn stmt_contains_symbol_nonrec(stmt: &Stmt, needle: Symbol) -> bool {
             condition, lookups, ..
         } => needle == *condition || lookups.contains(&needle),
         Stmt::Dbg { symbol, .. } => needle == *symbol,
-        Stmt::Join { .. } => false,
+        Stmt::Coverage { .. } | Stmt::Join { .. } => false,
         Stmt::Jump(_, arguments) => arguments.contains(&needle),
         Stmt::Crash(symbol, _) => needle == *symbol,
     }
@@ -15,7 +15,7 @@ use roc_module::ident::QualifiedModuleName;
 use roc_region::all::{Loc, Position, Region};
 use soa::{EitherIndex, Slice};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct FullAst<'a> {
     pub header: SpacesBefore<'a, Header<'a>>,
     pub defs: Defs<'a>,
@@ -508,6 +508,9 @@ pub enum Expr<'a> {
 
     Underscore(&'a str),
 
+    /// A `?name` placeholder for an expression that hasn't been written yet
+    TypedHole(&'a str),
+
     // The "crash" keyword
     Crash,
 
@@ -943,6 +946,7 @@ impl<'a, 'b> RecursiveValueDefIter<'a, 'b> {
                 | RecordUpdater(_)
                 | Var { .. }
                 | Underscore(_)
+                | TypedHole(_)
                 | Crash
                 | Dbg
                 | Try
@@ -2432,6 +2436,7 @@ impl<'a> Malformed for Expr<'a> {
             RecordUpdater(_) |
             Var { .. } |
             Underscore(_) |
+            TypedHole(_) |
             Tag(_) |
             OpaqueRef(_) |
             SingleQuote(_) | // This is just a &str - not a bunch of segments
@@ -222,6 +222,7 @@ fn loc_term<'a>() -> impl Parser<'a, Loc<Expr<'a>>, EExpr<'a>> {
                 // In some contexts we want to parse the `_` as an expression, so it can then be turned into a
                 // pattern later
                 loc(underscore_expression()),
+                loc(typed_hole_expression()),
                 loc(record_literal_help()),
                 loc(specialize_err(EExpr::List, list_literal_help())),
                 ident_seq(),
@@ -345,6 +346,21 @@ fn underscore_expression<'a>() -> impl Parser<'a, Expr<'a>, EExpr<'a>> {
     }
 }
 
+/// A `?name` typed hole. The name has to follow the `?` right away, so that `a ? b` and
+/// `a ?? b` are still parsed as operators.
+fn typed_hole_expression<'a>() -> impl Parser<'a, Expr<'a>, EExpr<'a>> {
+    move |arena: &'a Bump, state: State<'a>, min_indent: u32| {
+        let start = state.pos();
+
+        let (_, _, next_state) = byte(b'?', EExpr::Start).parse(arena, state, min_indent)?;
+
+        match lowercase_ident().parse(arena, next_state, min_indent) {
+            Ok((_, name, final_state)) => Ok((MadeProgress, Expr::TypedHole(name), final_state)),
+            Err(_) => Err((NoProgress, EExpr::Start(start))),
+        }
+    }
+}
+
 fn crash_kw<'a>() -> impl Parser<'a, Expr<'a>, EExpr<'a>> {
     (move |arena: &'a Bump, state: State<'a>, min_indent: u32| {
         let (_, _, next_state) = crate::parser::keyword(crate::keyword::CRASH, EExpr::Crash)
@@ -2227,6 +2243,7 @@ fn expr_to_pattern_help<'a>(arena: &'a Bump, expr: &Expr<'a>) -> Result<Pattern<
         | Expr::RecordUpdater(_)
         | Expr::UnaryOp(_, _)
         | Expr::TrySuffix { .. }
+        | Expr::TypedHole(_)
         | Expr::Crash
         | Expr::RecordBuilder { .. } => return Err(()),
 
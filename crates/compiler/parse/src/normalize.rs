@@ -717,6 +717,7 @@ impl<'a> Normalize<'a> for Expr<'a> {
             Expr::Tuple(a) => Expr::Tuple(a.normalize(arena)),
             Expr::Var { module_name, ident } => Expr::Var { module_name, ident },
             Expr::Underscore(a) => Expr::Underscore(a),
+            Expr::TypedHole(a) => Expr::TypedHole(a),
             Expr::Tag(a) => Expr::Tag(a),
             Expr::OpaqueRef(a) => Expr::OpaqueRef(a),
             Expr::Closure(a, b) => Expr::Closure(
@@ -41,6 +41,7 @@ pub enum PtrWidth {
 pub enum Architecture {
     Aarch32,
     Aarch64,
+    Riscv64,
     Wasm32,
     X86_32,
     X86_64,
@@ -51,6 +52,7 @@ impl std::fmt::Display for Architecture {
         let arch_str = match self {
             Architecture::Aarch32 => "aarch32",
             Architecture::Aarch64 => "aarch64",
+            Architecture::Riscv64 => "riscv64",
             Architecture::Wasm32 => "wasm32",
             Architecture::X86_32 => "x86_32",
             Architecture::X86_64 => "x86_64",
@@ -64,7 +66,7 @@ impl Architecture {
         use Architecture::*;
 
         match self {
-            X86_64 | Aarch64 => PtrWidth::Bytes8,
+            X86_64 | Aarch64 | Riscv64 => PtrWidth::Bytes8,
             X86_32 | Aarch32 | Wasm32 => PtrWidth::Bytes4,
         }
     }
@@ -78,7 +80,9 @@ impl Architecture {
 pub enum Target {
     LinuxX32,
     LinuxX64,
+    LinuxArm32,
     LinuxArm64,
+    LinuxRiscv64,
     MacX64,
     MacArm64,
     WinX32,
@@ -99,7 +103,9 @@ impl Target {
         match self {
             LinuxX32 | WinX32 => Architecture::X86_32,
             LinuxX64 | WinX64 | MacX64 => Architecture::X86_64,
+            LinuxArm32 => Architecture::Aarch32,
             LinuxArm64 | WinArm64 | MacArm64 => Architecture::Aarch64,
+            LinuxRiscv64 => Architecture::Riscv64,
             Wasm32 => Architecture::Wasm32,
         }
     }
@@ -107,7 +113,7 @@ impl Target {
     pub const fn operating_system(&self) -> OperatingSystem {
         use Target::*;
         match self {
-            LinuxX32 | LinuxX64 | LinuxArm64 => OperatingSystem::Linux,
+            LinuxX32 | LinuxX64 | LinuxArm32 | LinuxArm64 | LinuxRiscv64 => OperatingSystem::Linux,
             MacX64 | MacArm64 => OperatingSystem::Mac,
             WinX32 | WinX64 | WinArm64 => OperatingSystem::Windows,
             Wasm32 => OperatingSystem::Freestanding,
@@ -145,7 +151,7 @@ impl Target {
     pub const fn object_file_ext(&self) -> &str {
         use Target::*;
         match self {
-            LinuxX32 | LinuxX64 | LinuxArm64 | MacX64 | MacArm64 => "o",
+            LinuxX32 | LinuxX64 | LinuxArm32 | LinuxArm64 | LinuxRiscv64 | MacX64 | MacArm64 => "o",
             WinX32 | WinX64 | WinArm64 => "obj",
             Wasm32 => "wasm",
         }
@@ -155,7 +161,7 @@ impl Target {
     pub const fn static_library_file_ext(&self) -> &str {
         use Target::*;
         match self {
-            LinuxX32 | LinuxX64 | LinuxArm64 | MacX64 | MacArm64 => "a",
+            LinuxX32 | LinuxX64 | LinuxArm32 | LinuxArm64 | LinuxRiscv64 | MacX64 | MacArm64 => "a",
             WinX32 | WinX64 | WinArm64 => "lib",
             Wasm32 => "wasm",
         }
@@ -165,7 +171,7 @@ impl Target {
     pub const fn dynamic_library_file_ext(&self) -> &str {
         use Target::*;
         match self {
-            LinuxX32 | LinuxX64 | LinuxArm64 => "so",
+            LinuxX32 | LinuxX64 | LinuxArm32 | LinuxArm64 | LinuxRiscv64 => "so",
             MacX64 | MacArm64 => "dylib",
             WinX32 | WinX64 | WinArm64 => "dll",
             Wasm32 => "wasm",
@@ -176,7 +182,9 @@ impl Target {
     pub const fn executable_file_ext(&self) -> Option<&str> {
         use Target::*;
         match self {
-            LinuxX32 | LinuxX64 | LinuxArm64 | MacX64 | MacArm64 => None,
+            LinuxX32 | LinuxX64 | LinuxArm32 | LinuxArm64 | LinuxRiscv64 | MacX64 | MacArm64 => {
+                None
+            }
             WinX32 | WinX64 | WinArm64 => Some("exe"),
             Wasm32 => Some("wasm"),
         }
@@ -187,7 +195,8 @@ impl Target {
     pub fn prebuilt_static_object(&self) -> String {
         use Target::*;
         match self {
-            LinuxX32 | LinuxX64 | LinuxArm64 | MacX64 | MacArm64 | Wasm32 => {
+            LinuxX32 | LinuxX64 | LinuxArm32 | LinuxArm64 | LinuxRiscv64 | MacX64 | MacArm64
+            | Wasm32 => {
                 format!("{}.o", self)
             }
             WinX32 | WinX64 | WinArm64 => {
@@ -201,7 +210,8 @@ impl Target {
     pub fn prebuilt_static_library(&self) -> String {
         use Target::*;
         match self {
-            LinuxX32 | LinuxX64 | LinuxArm64 | MacX64 | MacArm64 | Wasm32 => {
+            LinuxX32 | LinuxX64 | LinuxArm32 | LinuxArm64 | LinuxRiscv64 | MacX64 | MacArm64
+            | Wasm32 => {
                 format!("{}.a", self)
             }
             WinX32 | WinX64 | WinArm64 => {
@@ -327,7 +337,9 @@ impl FromStr for Target {
             "system" => Ok(Self::default()),
             "linux-x32" => Ok(LinuxX32),
             "linux-x64" => Ok(LinuxX64),
+            "linux-arm32" => Ok(LinuxArm32),
             "linux-arm64" => Ok(LinuxArm64),
+            "linux-riscv64" => Ok(LinuxRiscv64),
             // TODO: Can we change these to just `mac`.
             // Currently, we need to keep it as `macos` to match platform naming.
             "macos-x64" => Ok(MacX64),
@@ -353,7 +365,9 @@ impl From<&Target> for &'static str {
         match target {
             LinuxX32 => "linux-x32",
             LinuxX64 => "linux-x64",
+            LinuxArm32 => "linux-arm32",
             LinuxArm64 => "linux-arm64",
+            LinuxRiscv64 => "linux-riscv64",
             // TODO: Can we change these to just `mac`.
             // Currently, we need to keep it as `macos` to match platform naming.
             MacX64 => "macos-x64",
@@ -386,11 +400,21 @@ impl From<&Triple> for Target {
                 operating_system: OperatingSystem::Linux,
                 ..
             } => Target::LinuxX64,
+            Triple {
+                architecture: Architecture::Arm(_),
+                operating_system: OperatingSystem::Linux,
+                ..
+            } => Target::LinuxArm32,
             Triple {
                 architecture: Architecture::Aarch64(_),
                 operating_system: OperatingSystem::Linux,
                 ..
             } => Target::LinuxArm64,
+            Triple {
+                architecture: Architecture::Riscv64(_),
+                operating_system: OperatingSystem::Linux,
+                ..
+            } => Target::LinuxRiscv64,
             Triple {
                 architecture: Architecture::X86_32(_),
                 operating_system: OperatingSystem::Windows,
@@ -445,7 +469,9 @@ impl TryFrom<(Architecture, OperatingSystem)> for Target {
         match arch_os {
             (Architecture::X86_32, OperatingSystem::Linux) => Ok(Target::LinuxX32),
             (Architecture::X86_64, OperatingSystem::Linux) => Ok(Target::LinuxX64),
+            (Architecture::Aarch32, OperatingSystem::Linux) => Ok(Target::LinuxArm32),
             (Architecture::Aarch64, OperatingSystem::Linux) => Ok(Target::LinuxArm64),
+            (Architecture::Riscv64, OperatingSystem::Linux) => Ok(Target::LinuxRiscv64),
             (Architecture::X86_32, OperatingSystem::Windows) => Ok(Target::WinX32),
             (Architecture::X86_64, OperatingSystem::Windows) => Ok(Target::WinX64),
             (Architecture::Aarch64, OperatingSystem::Windows) => Ok(Target::WinArm64),
@@ -16,7 +16,7 @@ use roc_can::abilities::{AbilitiesStore, MemberSpecializationInfo};
 use roc_can::constraint::Constraint::{self, *};
 use roc_can::constraint::{
     Cycle, FxCallConstraint, FxSuffixConstraint, FxSuffixKind, LetConstraint, OpportunisticResolve,
-    TryTargetConstraint,
+    TryTargetConstraint, TypedHoleConstraint,
 };
 use roc_can::expected::{Expected, PExpected};
 use roc_can::module::ModuleParams;
@@ -25,7 +25,7 @@ use roc_debug_flags::dbg_do;
 #[cfg(debug_assertions)]
 use roc_debug_flags::ROC_VERIFY_RIGID_LET_GENERALIZED;
 use roc_error_macros::internal_error;
-use roc_module::ident::IdentSuffix;
+use roc_module::ident::{IdentSuffix, Lowercase};
 use roc_module::symbol::{ModuleId, Symbol};
 use roc_problem::can::CycleEntry;
 use roc_region::all::{Loc, Region};
@@ -127,7 +127,7 @@ fn run_help(
 ) -> RunSolveOutput {
     let subs = &mut owned_subs;
     let SolveConfig {
-        home: _,
+        home,
         constraints,
         root_constraint,
         mut types,
@@ -182,6 +182,7 @@ fn run_help(
 
     let state = solve(
         &mut env,
+        home,
         types,
         rank,
         problems,
@@ -231,6 +232,7 @@ enum Work<'a> {
 
 fn solve(
     env: &mut InferenceEnv,
+    home: ModuleId,
     mut can_types: Types,
     rank: Rank,
     problems: &mut Vec<TypeError>,
@@ -252,6 +254,7 @@ fn solve(
     };
 
     let mut stack = vec![initial];
+    let mut typed_holes = Vec::new();
 
     let mut state = State {
         scope,
@@ -954,6 +957,25 @@ fn solve(
                     }
                 }
             }
+            TypedHole(index) => {
+                let TypedHoleConstraint { var, name, region } =
+                    &env.constraints.typed_hole_constraints[index.index()];
+
+                // The hole's type isn't known until everything else is solved,
+                // so only remember what was in scope here.
+                let in_scope = (scope.vars_by_symbol())
+                    .filter(|(symbol, _)| symbol.module_id() == home)
+                    .collect();
+
+                typed_holes.push(PendingTypedHole {
+                    var: *var,
+                    name: name.clone(),
+                    region: *region,
+                    in_scope,
+                });
+
+                state
+            }
             Let(index, pool_slice) => {
                 let let_con = &env.constraints.let_constraints[index.index()];
 
@@ -1633,9 +1655,61 @@ fn solve(
         };
     }
 
+    for hole in typed_holes {
+        problems.push(check_typed_hole(env, hole));
+    }
+
     state
 }
 
+struct PendingTypedHole {
+    var: Variable,
+    name: Option<Lowercase>,
+    region: Region,
+    in_scope: Vec<(Symbol, Variable)>,
+}
+
+/// Reports the type that a typed hole needs, and which of the names that were in scope at
+/// the hole have that type.
+fn check_typed_hole(env: &mut InferenceEnv, hole: PendingTypedHole) -> TypeError {
+    let PendingTypedHole {
+        var,
+        name,
+        region,
+        in_scope,
+    } = hole;
+
+    let fits = in_scope
+        .into_iter()
+        .filter(|(_, candidate)| {
+            let snapshot = env.subs.snapshot();
+            let fits = matches!(
+                unify(
+                    &mut env.uenv(),
+                    *candidate,
+                    var,
+                    UnificationMode::EQ,
+                    Polarity::OF_VALUE,
+                ),
+                Success { .. }
+            );
+            env.subs.rollback_to(snapshot);
+
+            fits
+        })
+        .map(|(symbol, _)| symbol)
+        .collect();
+
+    let typ = env.uenv().var_to_error_type(var, Polarity::OF_VALUE);
+
+    TypeError::TypedHole {
+        region,
+        name,
+        typ,
+        fits,
+    }
+}
+
 fn solve_suffix_fx(
     env: &mut InferenceEnv<'_>,
     problems: &mut Vec<TypeError>,
@@ -50,6 +50,13 @@ pub enum TypeError {
     UnsuffixedEffectfulFunction(Region, FxSuffixKind),
     SuffixedPureFunction(Region, FxSuffixKind),
     InvalidTryTarget(Region, ErrorType, TryKind),
+    TypedHole {
+        region: Region,
+        name: Option<Lowercase>,
+        typ: ErrorType,
+        /// The names in scope at the hole that have its type.
+        fits: Vec<Symbol>,
+    },
 }
 
 impl TypeError {
@@ -80,6 +87,7 @@ impl TypeError {
             TypeError::UnsuffixedEffectfulFunction(_, _) => Warning,
             TypeError::SuffixedPureFunction(_, _) => Warning,
             TypeError::InvalidTryTarget(_, _, _) => RuntimeError,
+            TypeError::TypedHole { .. } => RuntimeError,
         }
     }
 
@@ -101,7 +109,8 @@ impl TypeError {
             | TypeError::ExpectedEffectful(region, _)
             | TypeError::UnsuffixedEffectfulFunction(region, _)
             | TypeError::SuffixedPureFunction(region, _)
-            | TypeError::InvalidTryTarget(region, _, _) => Some(*region),
+            | TypeError::InvalidTryTarget(region, _, _)
+            | TypeError::TypedHole { region, .. } => Some(*region),
             TypeError::UnfulfilledAbility(ab, ..) => ab.region(),
             TypeError::Exhaustive(e) => Some(e.region()),
             TypeError::CircularDef(c) => c.first().map(|ce| ce.symbol_region),
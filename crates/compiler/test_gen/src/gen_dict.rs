@@ -550,3 +550,33 @@ fn walk_sum_keys() {
         i64
     );
 }
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn pseudo_seed_is_exported() {
+    // Hosts hash keys the way Dict does with this seed, so it has to stay visible when the
+    // rest of the builtins are made private.
+    let arena = bumpalo::Bump::new();
+    let context = inkwell::context::Context::create();
+    let config = crate::helpers::llvm::HelperConfig {
+        mode: roc_gen_llvm::llvm::build::LlvmBackendMode::GenTest,
+        emit_debug_info: false,
+        ignore_problems: false,
+        opt_level: crate::helpers::llvm::OPT_LEVEL,
+    };
+
+    let (_main_fn_name, errors, lib) = crate::helpers::llvm::helper(
+        &arena,
+        config,
+        "Dict.empty {} |> Dict.insert 42 32 |> Dict.len",
+        &context,
+        roc_load::FunctionKind::LambdaSet,
+    );
+    assert!(errors.is_empty(), "Encountered errors:\n{errors}");
+
+    let seed = unsafe {
+        lib.get::<unsafe extern "C" fn() -> u64>(b"roc_dict_pseudo_seed")
+            .expect("roc_dict_pseudo_seed is not exported")
+    };
+    assert_eq!(unsafe { seed() }, unsafe { seed() });
+}
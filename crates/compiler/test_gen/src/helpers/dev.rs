@@ -62,6 +62,7 @@ pub fn helper(
         threading: Threading::Single,
         exec_mode: ExecutionMode::Executable,
         function_kind: FunctionKind::LambdaSet,
+        solved_modules: None,
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -76,6 +76,7 @@ fn create_llvm_module<'a>(
         palette: DEFAULT_PALETTE,
         threading: Threading::Single,
         exec_mode: ExecutionMode::Executable,
+        solved_modules: None,
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -91,9 +92,8 @@ fn create_llvm_module<'a>(
         Ok(x) => x,
         Err(LoadMonomorphizedError::LoadingProblem(roc_load::LoadingProblem::FormattedReport(
             report,
-            _,
         ))) => {
-            println!("{report}");
+            println!("{}", report.text);
             panic!();
         }
         Err(e) => panic!("{e:?}"),
@@ -94,6 +94,7 @@ fn compile_roc_to_wasm_bytes<'a, T: Wasm32Result>(
         threading: Threading::Single,
         exec_mode: ExecutionMode::Executable,
         function_kind: FunctionKind::LambdaSet,
+        solved_modules: None,
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -131,6 +131,7 @@ fn compiles_to_ir(test_name: &str, src: &str, mode: &str, allow_type_errors: boo
     let exec_mode = match mode {
         "exec" => ExecutionMode::Executable,
         "test" => ExecutionMode::Test,
+        "bench" => ExecutionMode::Bench,
         _ => panic!("Invalid test_mono exec mode {mode}"),
     };
 
@@ -158,6 +159,7 @@ fn compiles_to_ir(test_name: &str, src: &str, mode: &str, allow_type_errors: boo
         render: roc_reporting::report::RenderTarget::Generic,
         palette: roc_reporting::report::DEFAULT_PALETTE,
         exec_mode,
+        solved_modules: None,
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -173,9 +175,8 @@ fn compiles_to_ir(test_name: &str, src: &str, mode: &str, allow_type_errors: boo
         Ok(x) => x,
         Err(LoadMonomorphizedError::LoadingProblem(roc_load::LoadingProblem::FormattedReport(
             report,
-            _,
         ))) => {
-            println!("{report}");
+            println!("{}", report.text);
             panic!();
         }
         Err(e) => panic!("{e:?}"),
@@ -3234,6 +3235,32 @@ fn dbg_in_expect() {
     )
 }
 
+#[mono_test(mode = "test")]
+fn property_in_expect() {
+    indoc!(
+        r#"
+        interface Test exposes [] imports []
+
+        expect \count, tags, flag ->
+            n : U8
+            n = count
+
+            Num.to_u64 n + 1 > List.len tags || flag == Yes
+        "#
+    )
+}
+
+#[mono_test(mode = "bench")]
+fn bench_runner() {
+    indoc!(
+        r#"
+        interface Test exposes [] imports []
+
+        bench_sum = \{} -> List.sum [1u64, 2, 3]
+        "#
+    )
+}
+
 #[mono_test]
 fn drop_specialize_before_jump() {
     indoc!(
@@ -4,12 +4,15 @@ extern crate indoc;
 #[cfg(test)]
 mod test_fmt {
     use bumpalo::Bump;
-    use roc_fmt::def::fmt_defs;
+    use roc_fmt::annotation::Formattable;
+    use roc_fmt::def::{fmt_def, fmt_defs};
+    use roc_fmt::expr::fmt_expr;
     use roc_fmt::header::fmt_header;
-    use roc_fmt::{Buf, MigrationFlags};
+    use roc_fmt::{fmt_to_max_width, Buf, MigrationFlags};
     use roc_parse::ast::{Defs, Header, SpacesBefore};
     use roc_parse::header::{self, parse_module_defs};
     use roc_parse::state::State;
+    use roc_parse::test_helpers::parse_loc_with;
     use roc_test_utils::assert_multiline_str_eq;
     use roc_test_utils_dir::workspace_root;
     use test_syntax::test_helpers::Input;
@@ -46,6 +49,36 @@ mod test_fmt {
         )
     }
 
+    fn expr_formats_to_max_width(max_width: u16, input: &str, expected: &str) {
+        let arena = Bump::new();
+        let flags = MigrationFlags {
+            snakify: false,
+            parens_and_commas: false,
+        };
+        let format = |src: &str| {
+            let src = arena.alloc_str(src.trim());
+
+            fmt_to_max_width(&arena, flags, max_width, src, |arena, buf, src| {
+                parse_loc_with(arena, src)
+                    .map(|expr| expr.format(buf, 0))
+                    .map_err(|err| format!("{err:?}"))
+            })
+            .unwrap_or_else(|err| panic!("Unexpected parse failure:\n\n{src}\n\n{err}"))
+            .into_bump_str()
+        };
+
+        let output = format(input);
+
+        assert_multiline_str_eq!(expected.trim(), output);
+
+        // Formatting to the same width again must not change anything
+        assert_multiline_str_eq!(output, format(output));
+    }
+
+    fn expr_formats_same_with_max_width(max_width: u16, input: &str) {
+        expr_formats_to_max_width(max_width, input, input);
+    }
+
     fn pattern_formats_same(input: &str) {
         Input::Pattern(input.trim()).check_invariants(
             check_formatting(input.trim()),
@@ -89,6 +122,36 @@ mod test_fmt {
         }
     }
 
+    /// Formatting each def on its own and putting them back together, the way the language
+    /// server reformats part of a module, has to give the same as formatting the whole module.
+    fn defs_format_one_at_a_time(src: &str) {
+        let arena = Bump::new();
+        let src = src.trim();
+        let flags = MigrationFlags {
+            snakify: false,
+            parens_and_commas: false,
+        };
+
+        let (header, state) = header::parse_header(&arena, State::new(src.as_bytes())).unwrap();
+        let defs = parse_module_defs(&arena, state.clone(), Defs::default()).unwrap();
+
+        let mut whole = Buf::new_in(&arena, flags);
+        fmt_module_and_defs(&arena, src, &header, state, &mut whole);
+        whole.fmt_end_of_file();
+
+        // Each def replaces the source from its start up to the start of the next def.
+        let first_def = defs.regions[0].start().offset as usize;
+        let mut one_at_a_time = src[..first_def].to_string();
+
+        for index in 0..defs.len() {
+            let mut buf = Buf::new_in(&arena, flags);
+            fmt_def(&mut buf, &defs, index, 0);
+            one_at_a_time.push_str(buf.as_str());
+        }
+
+        assert_multiline_str_eq!(whole.as_str(), one_at_a_time.as_str());
+    }
+
     // Not intended to be used directly in tests; please use module_formats_to or module_formats_same
     fn expect_format_module_helper(src: &str, expected: &str) {
         let arena = Bump::new();
@@ -1424,6 +1487,17 @@ mod test_fmt {
         ));
     }
 
+    #[test]
+    fn typed_holes() {
+        expr_formats_same(indoc!(
+            r"
+            x = f _ ?rest
+
+            x ?? ?fallback
+            "
+        ));
+    }
+
     #[test]
     fn multi_arg_closure() {
         expr_formats_same(indoc!(
@@ -7071,4 +7145,236 @@ mod test_fmt {
     fn pattern_tag_apply_with_pnc_multi_arg() {
         pattern_formats_same(indoc!("Ok(a, b)"));
     }
+
+    // MAX WIDTH
+
+    #[test]
+    fn max_width_breaks_list() {
+        expr_formats_to_max_width(
+            20,
+            "[first, second, third, fourth]",
+            indoc!(
+                r"
+                [
+                    first,
+                    second,
+                    third,
+                    fourth,
+                ]
+                "
+            ),
+        );
+    }
+
+    #[test]
+    fn max_width_keeps_what_fits() {
+        expr_formats_same_with_max_width(40, "[first, second, third, fourth]");
+    }
+
+    #[test]
+    fn max_width_breaks_apply() {
+        expr_formats_to_max_width(
+            30,
+            "some_function first_argument second_argument",
+            indoc!(
+                r"
+                some_function
+                    first_argument
+                    second_argument
+                "
+            ),
+        );
+    }
+
+    #[test]
+    fn max_width_breaks_pipeline() {
+        expr_formats_to_max_width(
+            30,
+            indoc!(
+                r#"
+                joined = items |> List.map(to_str) |> Str.join_with(", ")
+                joined
+                "#
+            ),
+            indoc!(
+                r#"
+                joined =
+                    items
+                    |> List.map(to_str)
+                    |> Str.join_with(", ")
+                joined
+                "#
+            ),
+        );
+    }
+
+    #[test]
+    fn max_width_breaks_outermost_first() {
+        expr_formats_to_max_width(
+            50,
+            r#"{ name: "Roc", tags: ["fast", "friendly", "functional"] }"#,
+            indoc!(
+                r#"
+                {
+                    name: "Roc",
+                    tags: ["fast", "friendly", "functional"],
+                }
+                "#
+            ),
+        );
+
+        expr_formats_to_max_width(
+            30,
+            r#"{ name: "Roc", tags: ["fast", "friendly", "functional"] }"#,
+            indoc!(
+                r#"
+                {
+                    name: "Roc",
+                    tags: [
+                        "fast",
+                        "friendly",
+                        "functional",
+                    ],
+                }
+                "#
+            ),
+        );
+    }
+
+    #[test]
+    fn max_width_breaks_annotation() {
+        expr_formats_to_max_width(
+            30,
+            indoc!(
+                r"
+                user : { name : Str, age : U32, email : Str }
+                user = get_user({})
+                user
+                "
+            ),
+            indoc!(
+                r"
+                user : {
+                    name : Str,
+                    age : U32,
+                    email : Str,
+                }
+                user = get_user({})
+                user
+                "
+            ),
+        );
+    }
+
+    #[test]
+    fn max_width_leaves_unbreakable_lines() {
+        expr_formats_same_with_max_width(
+            10,
+            r#"greeting = "Hello, World!"
+greeting"#,
+        );
+    }
+
+    #[test]
+    fn max_width_breaks_every_long_line() {
+        expr_formats_to_max_width(
+            24,
+            indoc!(
+                r#"
+                greeting = "Hello, World! Hello again!"
+                names = [first, second, third]
+                ages = [one, two, three, four]
+                greeting
+                "#
+            ),
+            indoc!(
+                r#"
+                greeting = "Hello, World! Hello again!"
+                names = [
+                    first,
+                    second,
+                    third,
+                ]
+                ages = [
+                    one,
+                    two,
+                    three,
+                    four,
+                ]
+                greeting
+                "#
+            ),
+        );
+    }
+
+    #[test]
+    fn def_on_its_own_with_comments_before() {
+        defs_format_one_at_a_time(indoc!(
+            r"
+            module []
+
+            # Leave this alone
+            first   =    1
+
+            ## Docs for second
+            second =   [1,2,   3]
+            "
+        ));
+    }
+
+    #[test]
+    fn def_on_its_own_with_comments_inside() {
+        defs_format_one_at_a_time(indoc!(
+            r#"
+            module []
+
+            main =
+                # The answer
+                x   =   42
+
+                x
+
+            other : Str # Not a number
+            other = "forty-two"
+            "#
+        ));
+    }
+
+    #[test]
+    fn def_on_its_own_with_comments_after() {
+        defs_format_one_at_a_time(indoc!(
+            r"
+            module []
+
+            first = when x is
+                A -> 1
+                # After the last branch
+            second = 2 # Two
+            # At the end
+            "
+        ));
+    }
+
+    #[test]
+    fn expr_on_its_own_keeps_its_comments() {
+        let arena = Bump::new();
+        let flags = MigrationFlags {
+            snakify: false,
+            parens_and_commas: false,
+        };
+        let src = indoc!(
+            r"
+            [
+                1,
+                # Two is next
+                2,
+            ]"
+        );
+
+        let expr = parse_loc_with(&arena, src).unwrap();
+        let mut buf = Buf::new_in(&arena, flags);
+        fmt_expr(&mut buf, &expr.value, 0);
+
+        assert_multiline_str_eq!(src, buf.as_str());
+    }
 }
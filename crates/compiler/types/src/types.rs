@@ -3530,6 +3530,7 @@ pub enum Category {
     AbilityMemberSpecialization(Symbol),
 
     Crash,
+    TypedHole,
 
     Expect,
     Dbg,
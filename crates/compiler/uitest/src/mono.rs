@@ -49,6 +49,7 @@ pub(crate) fn write_compiled_ir<'a>(
         render: roc_reporting::report::RenderTarget::Generic,
         palette: roc_reporting::report::DEFAULT_PALETTE,
         exec_mode,
+        solved_modules: None,
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -64,9 +65,8 @@ pub(crate) fn write_compiled_ir<'a>(
         Ok(x) => x,
         Err(LoadMonomorphizedError::LoadingProblem(roc_load::LoadingProblem::FormattedReport(
             report,
-            _,
         ))) => {
-            println!("{report}");
+            println!("{}", report.text);
             panic!();
         }
         Err(e) => panic!("{e:?}"),
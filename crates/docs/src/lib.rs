@@ -694,6 +694,7 @@ pub fn load_module_for_docs(filename: PathBuf) -> LoadedModule {
         palette: roc_reporting::report::DEFAULT_PALETTE,
         threading: Threading::AllAvailable,
         exec_mode: ExecutionMode::Check,
+        solved_modules: None,
     };
     match roc_load::load_and_typecheck(
         &arena,
@@ -703,8 +704,8 @@ pub fn load_module_for_docs(filename: PathBuf) -> LoadedModule {
         load_config,
     ) {
         Ok(loaded) => loaded,
-        Err(LoadingProblem::FormattedReport(report, _)) => {
-            eprintln!("{report}");
+        Err(LoadingProblem::FormattedReport(report)) => {
+            eprintln!("{}", report.text);
             std::process::exit(1);
         }
         Err(e) => panic!("{e:?}"),
@@ -8,7 +8,10 @@ import "../static/Cargo.toml" as roc_app_cargo_toml : Str
 import "../../roc_std/Cargo.toml" as roc_std_cargo_toml : Str
 import "../../roc_std/src/lib.rs" as roc_std_lib : Str
 import "../../roc_std/src/roc_box.rs" as roc_std_box : Str
+import "../../roc_std/src/roc_dict.rs" as roc_std_dict : Str
+import "../../roc_std/src/roc_hash.rs" as roc_std_hash : Str
 import "../../roc_std/src/roc_list.rs" as roc_std_list : Str
+import "../../roc_std/src/roc_set.rs" as roc_std_set : Str
 import "../../roc_std/src/roc_str.rs" as roc_std_str : Str
 import "../../roc_std/src/storage.rs" as roc_std_storage : Str
 
@@ -43,7 +46,10 @@ static_files = [
     { name: "roc_std/Cargo.toml", content: roc_std_cargo_toml },
     { name: "roc_std/src/lib.rs", content: roc_std_lib },
     { name: "roc_std/src/roc_box.rs", content: roc_std_box },
+    { name: "roc_std/src/roc_dict.rs", content: roc_std_dict },
+    { name: "roc_std/src/roc_hash.rs", content: roc_std_hash },
     { name: "roc_std/src/roc_list.rs", content: roc_std_list },
+    { name: "roc_std/src/roc_set.rs", content: roc_std_set },
     { name: "roc_std/src/roc_str.rs", content: roc_std_str },
     { name: "roc_std/src/storage.rs", content: roc_std_storage },
 ]
@@ -1809,16 +1815,12 @@ can_support_partial_eq_ord = \types, type ->
         Unsized -> Bool.false
         Unit | EmptyTagUnion | Bool | Num(_) | TagUnion(Enumeration(_)) -> Bool.true
         RocStr -> Bool.true
-        RocList(inner) | RocSet(inner) | RocBox(inner) ->
+        RocList(inner) | RocBox(inner) ->
             inner_type = Types.shape(types, inner)
             can_support_partial_eq_ord(types, inner_type)
 
-        RocDict(k, v) ->
-            k_type = Types.shape(types, k)
-            v_type = Types.shape(types, v)
-
-            can_support_partial_eq_ord(types, k_type) && can_support_partial_eq_ord(types, v_type)
-
+        # The entries of dicts and sets have no order, so they can't implement PartialOrd.
+        RocDict(_, _) | RocSet(_) -> Bool.false
         TagUnion(Recursive({ tags })) ->
             List.all(tags, \{ payload } ->
                 when payload is
@@ -2204,16 +2206,16 @@ type_name = \types, id ->
         Num(F32) -> "f32"
         Num(F64) -> "f64"
         Num(Dec) -> "roc_std:RocDec"
-        RocDict(_key, _value) ->
-            # key_name = type_name(types, key)
-            # value_name = type_name(types, value)
-            # "roc_std::RocDict<${key_name}, ${value_name}>"
-            crash("RocDict is not yet supported in rust")
+        RocDict(key, value) ->
+            key_name = type_name(types, key)
+            value_name = type_name(types, value)
+
+            "roc_std::RocDict<${key_name}, ${value_name}>"
 
-        RocSet(_elem) ->
-            # elem_name = type_name(types, elem)
-            # "roc_std::RocSet<${elem_name}>"
-            crash("RocSet is not yet supported in rust")
+        RocSet(elem) ->
+            elem_name = type_name(types, elem)
+
+            "roc_std::RocSet<${elem_name}>"
 
         RocList(elem) ->
             elem_name = type_name(types, elem)
@@ -2253,6 +2255,9 @@ arch_name = \arch ->
         Aarch64 ->
             "aarch64"
 
+        Riscv64 ->
+            "riscv64"
+
         Wasm32 ->
             "wasm32"
 
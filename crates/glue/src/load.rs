@@ -16,6 +16,7 @@ use roc_load::{ExecutionMode, FunctionKind, LoadConfig, LoadedModule, LoadingPro
 use roc_mono::ir::{generate_glue_procs, CrashTag, GlueProc, OptLevel};
 use roc_mono::layout::{GlobalLayoutInterner, LayoutCache, LayoutInterner};
 use roc_packaging::cache::{self, RocCacheDir};
+use roc_reporting::cli::{Diagnostics, ReportFormat};
 use roc_reporting::report::{RenderTarget, DEFAULT_PALETTE};
 use roc_target::{Architecture, Target, TargetFromTripleError::TripleUnsupported};
 use roc_types::subs::{Subs, Variable};
@@ -67,10 +68,12 @@ pub fn generate(
                 target,
                 BuildOrdering::BuildIfChecks,
                 Threading::AllAvailable,
+                ReportFormat::Human,
             );
 
             let arena = ManuallyDrop::new(Bump::new());
             let tempdir_res = tempfile::tempdir();
+            let mut diagnostics = Diagnostics::new(ReportFormat::Human);
 
             // we don't need a host for glue, we will generate a dylib
             // that will be loaded by the roc compiler/cli
@@ -93,6 +96,7 @@ pub fn generate(
                     load_config,
                     Some(dylib_dir.path()),
                     false,
+                    &mut diagnostics,
                 ),
                 Err(_) => {
                     eprintln!("`roc glue` was unable to create a tempdir.");
@@ -106,6 +110,7 @@ pub fn generate(
                     problems,
                     total_time,
                     expect_metadata: _,
+                    source_paths: _,
                 }) => {
                     // TODO: Should binary_path be update to deal with extensions?
                     use roc_target::OperatingSystem;
@@ -188,10 +193,16 @@ pub fn generate(
 
                     Ok(0)
                 }
-                Err(BuildFileError::ErrorModule { module, total_time }) => {
-                    handle_error_module(module, total_time, spec_path.as_os_str(), true)
+                Err(BuildFileError::ErrorModule { module, total_time }) => handle_error_module(
+                    module,
+                    total_time,
+                    spec_path.as_os_str(),
+                    true,
+                    &mut diagnostics,
+                ),
+                Err(BuildFileError::LoadingProblem(problem)) => {
+                    handle_loading_problem(problem, &mut diagnostics)
                 }
-                Err(BuildFileError::LoadingProblem(problem)) => handle_loading_problem(problem),
             };
 
             // Extend the lifetime of the tempdir to after we're done with everything,
@@ -430,11 +441,12 @@ pub fn load_types(
             palette: DEFAULT_PALETTE,
             threading,
             exec_mode: ExecutionMode::Check,
+            solved_modules: None,
         },
     )
     .unwrap_or_else(|problem| match problem {
-        LoadingProblem::FormattedReport(report, _) => {
-            eprintln!("{report}");
+        LoadingProblem::FormattedReport(report) => {
+            eprintln!("{}", report.text);
 
             process::exit(1);
         }
@@ -286,9 +286,10 @@ impl core::fmt::Debug for OperatingSystem {
 pub enum Architecture {
     Aarch32 = 0,
     Aarch64 = 1,
-    Wasm32 = 2,
-    X86x32 = 3,
-    X86x64 = 4,
+    Riscv64 = 2,
+    Wasm32 = 3,
+    X86x32 = 4,
+    X86x64 = 5,
 }
 roc_refcounted_noop_impl!(Architecture);
 
@@ -297,6 +298,7 @@ impl core::fmt::Debug for Architecture {
         match self {
             Self::Aarch32 => f.write_str("Architecture::Aarch32"),
             Self::Aarch64 => f.write_str("Architecture::Aarch64"),
+            Self::Riscv64 => f.write_str("Architecture::Riscv64"),
             Self::Wasm32 => f.write_str("Architecture::Wasm32"),
             Self::X86x32 => f.write_str("Architecture::X86x32"),
             Self::X86x64 => f.write_str("Architecture::X86x64"),
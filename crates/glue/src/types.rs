@@ -919,6 +919,7 @@ impl From<Architecture> for roc_type::Architecture {
         match arch {
             Architecture::Aarch32 => roc_type::Architecture::Aarch32,
             Architecture::Aarch64 => roc_type::Architecture::Aarch64,
+            Architecture::Riscv64 => roc_type::Architecture::Riscv64,
             Architecture::Wasm32 => roc_type::Architecture::Wasm32,
             Architecture::X86_32 => roc_type::Architecture::X86x32,
             Architecture::X86_64 => roc_type::Architecture::X86x64,
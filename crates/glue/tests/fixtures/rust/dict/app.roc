@@ -0,0 +1,9 @@
+app [main] { pf: platform "platform.roc" }
+
+main : Dict Str I64 -> Dict Str I64
+main = \dict ->
+    b = Dict.get(dict, "b") |> Result.with_default(-1)
+
+    dict
+    |> Dict.remove("a")
+    |> Dict.insert("d", b * 2)
@@ -0,0 +1,9 @@
+platform "test-platform"
+    requires {} { main : Dict Str I64 -> Dict Str I64 }
+    exposes []
+    packages {}
+    imports []
+    provides [main_for_host]
+
+main_for_host : Dict Str I64 -> Dict Str I64
+main_for_host = \dict -> main(dict)
@@ -162,6 +162,9 @@ mod glue_cli_tests {
             Answer was: RocOk(ManuallyDrop { value: "Hello World!" })
             Answer was: RocErr(ManuallyDrop { value: 42 })
         "#),
+        rust_dict:"rust/dict" => indoc!(r#"
+            Answer was: [None, Some(2), Some(3), Some(4)]
+        "#),
         rust_option:"rust/option" => indoc!(r#"
             Answer was: "Hello World!"
             Answer was: discriminant_U1::None
@@ -20,15 +20,23 @@ use roc_types::subs::{Subs, Variable};
 use tower_lsp::lsp_types::{Diagnostic, SemanticTokenType, Url};
 
 mod analysed_doc;
+mod code_actions;
 mod completion;
+mod inlay_hints;
 mod parse_ast;
+mod ranges;
+mod references;
 mod semantic_tokens;
+mod signature_help;
+mod symbols;
 mod tokens;
 mod utils;
 
 use crate::convert::diag::{IntoLspDiagnostic, ProblemFmt};
 
 pub(crate) use self::analysed_doc::{AnalyzedDocument, DocInfo};
+pub(crate) use self::references::Target;
+pub(crate) use self::symbols::SymbolMatch;
 use self::{analysed_doc::ModuleIdToUrl, tokens::Token};
 
 pub const HIGHLIGHT_TOKENS_LEGEND: &[SemanticTokenType] = Token::LEGEND;
@@ -89,6 +97,9 @@ pub(super) struct AnalyzedModule {
     subs: Subs,
     abilities: AbilitiesStore,
     declarations: Declarations,
+    // Kept around to offer quick fixes for them.
+    can_problems: Vec<roc_problem::can::Problem>,
+    type_problems: Vec<TypeError>,
     modules_info: Arc<ModulesInfo>,
     // ModuleIds are not stable between compilations, so a ModuleId visible to
     // one module may not be true global to the language server.
@@ -301,21 +312,33 @@ impl<'a> AnalyzedDocumentBuilder<'a> {
             declarations = self.declarations_by_id.remove(&module_id).unwrap();
         }
 
+        let can_problems = self.can_problems.remove(&module_id).unwrap_or_default();
+        let type_problems = self.type_problems.remove(&module_id).unwrap_or_default();
+
+        let line_info = LineInfo::new(&source);
+        let diagnostics = self.build_diagnostics(
+            &path,
+            &source,
+            &line_info,
+            module_id,
+            can_problems.clone(),
+            type_problems.clone(),
+        );
+
         let analyzed_module = AnalyzedModule {
             exposed_imports,
             imports_by_module: imports,
             subs,
             abilities,
             declarations,
+            can_problems,
+            type_problems,
             module_id,
             modules_info: self.modules_info.clone(),
             interns: self.interns.clone(),
             module_id_to_url: self.module_id_to_url.clone(),
         };
 
-        let line_info = LineInfo::new(&source);
-        let diagnostics = self.build_diagnostics(&path, &source, &line_info, module_id);
-
         AnalyzedDocument {
             doc_info: DocInfo {
                 url: path_to_url(&path),
@@ -356,6 +379,8 @@ impl<'a> AnalyzedDocumentBuilder<'a> {
         source: &str,
         line_info: &LineInfo,
         module_id: ModuleId,
+        can_problems: Vec<roc_problem::can::Problem>,
+        type_problems: Vec<TypeError>,
     ) -> Vec<Diagnostic> {
         let lines: Vec<_> = source.lines().collect();
 
@@ -368,10 +393,6 @@ impl<'a> AnalyzedDocumentBuilder<'a> {
             path: source_path,
         };
 
-        let can_problems = self.can_problems.remove(&module_id).unwrap_or_default();
-
-        let type_problems = self.type_problems.remove(&module_id).unwrap_or_default();
-
         for can_problem in can_problems {
             if let Some(diag) = can_problem.into_lsp_diagnostic(&fmt) {
                 all_problems.push(diag);
@@ -4,13 +4,17 @@ use std::collections::HashMap;
 
 use bumpalo::Bump;
 
+use roc_can::traverse::FoundSymbol;
 use roc_module::symbol::{ModuleId, Symbol};
 
-use roc_region::all::LineInfo;
+use roc_region::all::{LineInfo, Region};
+use roc_solve_problem::TypeError;
 
 use tower_lsp::lsp_types::{
-    CompletionItem, Diagnostic, GotoDefinitionResponse, Hover, HoverContents, LanguageString,
-    Location, MarkedString, Position, Range, SemanticTokens, SemanticTokensResult, TextEdit, Url,
+    CodeActionOrCommand, CodeLens, Command, CompletionItem, Diagnostic, DocumentSymbolResponse,
+    FoldingRange, GotoDefinitionResponse, Hover, HoverContents, InlayHint, LanguageString,
+    Location, MarkedString, Position, Range, SelectionRange, SemanticTokens, SemanticTokensResult,
+    SignatureHelp, SymbolInformation, SymbolKind, TextEdit, Url,
 };
 
 use crate::{
@@ -18,12 +22,23 @@ use crate::{
         field_completion, get_completion_items, get_module_completion_items,
         get_tag_completion_items,
     },
-    convert::{ToRange, ToRocPosition},
+    convert::{text_offset, ToRange, ToRocPosition},
+    test_runner::{RUN_MODULE_TESTS_COMMAND, RUN_TEST_COMMAND},
 };
 
 use super::{
+    code_actions::CodeActions,
+    inlay_hints::InlayHints,
     parse_ast::Ast,
+    references::{
+        field_references, field_shapes, module_qualifier, tag_references, tag_shapes,
+        type_references, value_references, Reference, Shape, StructuralTarget, Target, TypeTarget,
+        ValueTarget,
+    },
     semantic_tokens::arrange_semantic_tokens,
+    signature_help::signature_help,
+    symbols::{fuzzy_match_score, SymbolMatch},
+    tokens::Token,
     utils::{format_var_type, is_roc_identifier_char},
     AnalysisResult, AnalyzedModule,
 };
@@ -113,6 +128,184 @@ impl DocInfo {
         }
     }
 
+    /// Reformat just the top-level defs which overlap `range`, e.g. what was just pasted.
+    pub fn format_range(&self, range: Range) -> Option<Vec<TextEdit>> {
+        self.format_defs_in(range, |_| true)
+    }
+
+    /// Reformat the top-level def which ended on the line before `position`, after a newline
+    /// was typed there. Defs which carry on past that line are still being written.
+    pub fn format_on_type(&self, position: Position) -> Option<Vec<TextEdit>> {
+        let previous_line = position.line.checked_sub(1)?;
+        let line_range = Range::new(
+            Position::new(previous_line, 0),
+            Position::new(previous_line, u32::MAX),
+        );
+
+        self.format_defs_in(line_range, |def_range| def_range.end.line == previous_line)
+    }
+
+    fn format_defs_in(
+        &self,
+        range: Range,
+        include: impl Fn(&Range) -> bool,
+    ) -> Option<Vec<TextEdit>> {
+        let region = Region::new(
+            roc_region::all::Position::new(text_offset(&self.source, range.start) as u32),
+            roc_region::all::Position::new(text_offset(&self.source, range.end) as u32),
+        );
+        let arena = Bump::new();
+        let ast = Ast::parse(&arena, &self.source).ok()?;
+        let flags = MigrationFlags {
+            snakify: false,
+            parens_and_commas: false,
+        };
+
+        let edits: Vec<TextEdit> = (ast.fmt_defs_overlapping(region, flags).into_iter())
+            .filter_map(|def| {
+                let start = def.replaces.start().offset as usize;
+                let end = def.replaces.end().offset as usize;
+
+                (include(&def.region.to_range(&self.line_info))
+                    && self.source[start..end] != *def.formatted.as_str())
+                .then(|| {
+                    let range = def.replaces.to_range(&self.line_info);
+
+                    TextEdit::new(range, def.formatted.to_string())
+                })
+            })
+            .collect();
+
+        (!edits.is_empty()).then_some(edits)
+    }
+
+    pub fn document_symbols(&self) -> Option<DocumentSymbolResponse> {
+        let arena = Bump::new();
+        let ast = Ast::parse(&arena, &self.source).ok()?;
+
+        let symbols = (ast.symbols().iter())
+            .map(|symbol| symbol.to_document_symbol(&self.source, &self.line_info))
+            .collect();
+
+        Some(DocumentSymbolResponse::Nested(symbols))
+    }
+
+    /// The top-level symbols, and ability members, whose names match `query`.
+    #[allow(deprecated)] // SymbolInformation::deprecated
+    pub fn workspace_symbols(&self, query: &str) -> Vec<SymbolMatch> {
+        let arena = Bump::new();
+        let Ok(ast) = Ast::parse(&arena, &self.source) else {
+            return Vec::new();
+        };
+
+        let exposed_names = ast.exposed_names();
+        let is_exposed = |name: &str| exposed_names.iter().any(|exposed| exposed.value == name);
+        let module_name = (self.url.to_file_path().ok())
+            .and_then(|path| Some(path.file_stem()?.to_string_lossy().into_owned()));
+
+        let mut matches = Vec::new();
+
+        for symbol in ast.symbols() {
+            let exposed = is_exposed(symbol.name.value);
+            let members = (symbol.kind == SymbolKind::INTERFACE).then_some(&symbol.children);
+
+            for symbol in std::iter::once(&symbol).chain(members.into_iter().flatten()) {
+                let Some(score) = fuzzy_match_score(query, symbol.name.value) else {
+                    continue;
+                };
+
+                matches.push(SymbolMatch {
+                    score,
+                    exposed,
+                    symbol: SymbolInformation {
+                        name: symbol.name.value.to_string(),
+                        kind: symbol.kind,
+                        tags: None,
+                        deprecated: None,
+                        location: Location::new(
+                            self.url.clone(),
+                            symbol.name.region.to_range(&self.line_info),
+                        ),
+                        container_name: module_name.clone(),
+                    },
+                });
+            }
+        }
+
+        matches
+    }
+
+    /// Lenses to run each top-level `expect`, and all of them.
+    pub fn code_lenses(&self) -> Vec<CodeLens> {
+        let arena = Bump::new();
+        let Ok(ast) = Ast::parse(&arena, &self.source) else {
+            return Vec::new();
+        };
+
+        let expects = ast.top_level_expects();
+
+        if expects.is_empty() {
+            return Vec::new();
+        }
+
+        let url = serde_json::Value::from(self.url.as_str());
+        let lens = |range: Range, title: &str, command: &str, arguments| CodeLens {
+            range,
+            command: Some(Command::new(
+                title.to_string(),
+                command.to_string(),
+                Some(arguments),
+            )),
+            data: None,
+        };
+
+        let start = Range::new(Position::new(0, 0), Position::new(0, 0));
+        let mut lenses = vec![lens(
+            start,
+            "Run all tests in module",
+            RUN_MODULE_TESTS_COMMAND,
+            vec![url.clone()],
+        )];
+
+        for region in expects {
+            let range = region.to_range(&self.line_info);
+            let position = serde_json::json!(range.start);
+
+            lenses.push(lens(
+                range,
+                "Run test",
+                RUN_TEST_COMMAND,
+                vec![url.clone(), position],
+            ));
+        }
+
+        lenses
+    }
+
+    pub fn folding_ranges(&self) -> Option<Vec<FoldingRange>> {
+        let arena = Bump::new();
+        let ast = Ast::parse(&arena, &self.source).ok()?;
+
+        Some(ast.folding_ranges(&self.source, &self.line_info))
+    }
+
+    /// The selection ranges to expand through from each of `positions`, innermost first.
+    pub fn selection_ranges(&self, positions: &[Position]) -> Option<Vec<SelectionRange>> {
+        let arena = Bump::new();
+        let ast = Ast::parse(&arena, &self.source).ok()?;
+        let whole_document = self.whole_document_range();
+
+        let ranges = (positions.iter())
+            .map(|position| {
+                let position = position.to_roc_position(&self.line_info);
+
+                ast.selection_range(&self.line_info, position, whole_document)
+            })
+            .collect();
+
+        Some(ranges)
+    }
+
     pub fn semantic_tokens(&self) -> Option<SemanticTokensResult> {
         let source = &self.source;
         let arena = &Bump::new();
@@ -149,6 +342,11 @@ impl AnalyzedDocument {
         }
     }
 
+    pub fn is_builtin(&self) -> bool {
+        self.module()
+            .is_some_and(|module| module.module_id.is_builtin())
+    }
+
     pub fn type_checked(&self) -> bool {
         self.analysis_result.module.is_some()
     }
@@ -157,6 +355,27 @@ impl AnalyzedDocument {
         self.analysis_result.diagnostics.clone()
     }
 
+    /// The quick fixes for the problems in `range`, and the other code actions that apply
+    /// to it. `diagnostics` are the ones the client has for `range`.
+    pub(crate) fn code_actions(
+        &self,
+        range: Range,
+        diagnostics: &[Diagnostic],
+    ) -> Vec<CodeActionOrCommand> {
+        let Some(module) = self.module() else {
+            return Vec::new();
+        };
+
+        CodeActions::new(
+            self.url(),
+            &self.doc_info.source,
+            self.line_info(),
+            module,
+            diagnostics,
+        )
+        .in_range(range)
+    }
+
     pub fn symbol_at(&self, position: Position) -> Option<Symbol> {
         let line_info = self.line_info();
 
@@ -206,7 +425,10 @@ impl AnalyzedDocument {
             value: type_str,
         });
 
-        let content = vec![Some(type_content), docs_opt.map(MarkedString::String)]
+        let docs = docs_opt.map(MarkedString::String);
+        let hole_fits = self.typed_hole_fits(pos).map(MarkedString::String);
+
+        let content = vec![Some(type_content), docs, hole_fits]
             .into_iter()
             .flatten()
             .collect::<Vec<_>>();
@@ -217,6 +439,43 @@ impl AnalyzedDocument {
         })
     }
 
+    /// The names in scope that fit the typed hole at `pos`, if there's one there.
+    fn typed_hole_fits(&self, pos: roc_region::all::Position) -> Option<String> {
+        let AnalyzedModule {
+            type_problems,
+            interns,
+            ..
+        } = self.module()?;
+
+        let fits = type_problems.iter().find_map(|problem| match problem {
+            TypeError::TypedHole { region, fits, .. } if region.contains_pos(pos) => Some(fits),
+            _ => None,
+        })?;
+
+        // The innermost names first, leaving out the ones the compiler made up
+        let names = (fits.iter().rev())
+            .map(|symbol| symbol.as_str(interns))
+            .filter(|name| name.starts_with(|c: char| c.is_alphabetic()))
+            .map(|name| format!("`{name}`"))
+            .collect::<Vec<_>>();
+
+        if names.is_empty() {
+            Some("Nothing in scope has that type.".to_string())
+        } else {
+            Some(format!("In scope with that type: {}", names.join(", ")))
+        }
+    }
+
+    /// The inferred types of the unannotated defs, let-bindings and closure arguments in
+    /// `range`. Types longer than `max_length` are cut short.
+    pub fn inlay_hints(&self, range: Range, max_length: usize) -> Vec<InlayHint> {
+        let Some(module) = self.module() else {
+            return Vec::new();
+        };
+
+        InlayHints::new(module, self.line_info(), range, max_length).collect(&module.declarations)
+    }
+
     pub fn definition(&self, symbol: Symbol) -> Option<GotoDefinitionResponse> {
         let AnalyzedModule { declarations, .. } = self.module()?;
 
@@ -231,6 +490,256 @@ impl AnalyzedDocument {
         self.module()?.module_id_to_url.get(&module_id).cloned()
     }
 
+    /// The urls of the modules that this module imports.
+    pub(crate) fn imported_urls(&self) -> Vec<Url> {
+        let Some(module) = self.module() else {
+            return Vec::new();
+        };
+
+        (module.imports_by_module.keys())
+            .filter_map(|module_id| module.module_id_to_url.get(module_id).cloned())
+            .collect()
+    }
+
+    /// The name that other modules import this one by, which is the name of its file
+    /// if it hasn't been analyzed.
+    pub(crate) fn module_name(&self) -> Option<String> {
+        match self.module() {
+            Some(module) => Some(
+                module
+                    .interns
+                    .module_name(module.module_id)
+                    .as_str()
+                    .to_string(),
+            ),
+            None => {
+                let file_name = self.url().path_segments()?.next_back()?;
+
+                file_name.strip_suffix(".roc").map(str::to_string)
+            }
+        }
+    }
+
+    /// Whether this module defines a type alias, opaque type or ability named `name`.
+    pub(crate) fn defines_type(&self, name: &str) -> bool {
+        let arena = Bump::new();
+
+        Ast::parse(&arena, &self.doc_info.source)
+            .map(|ast| ast.defines_type(name))
+            .unwrap_or(false)
+    }
+
+    /// Works out what to find the references of, or rename, from the name at `position`.
+    pub(crate) fn reference_target(&self, position: Position) -> Option<Target> {
+        let source = self.doc_info.source.as_str();
+        let position = position.to_roc_position(self.line_info());
+
+        let arena = Bump::new();
+        let ast = Ast::parse(&arena, source).ok()?;
+
+        let token = (ast.semantic_tokens().into_iter())
+            .find(|token| token.region.contains_pos(position))?;
+        let text =
+            source.get(token.region.start().offset as usize..token.region.end().offset as usize)?;
+        let is_lowercase = |name: &str| name.starts_with(|c: char| c.is_ascii_lowercase());
+        let is_uppercase = |name: &str| name.starts_with(|c: char| c.is_ascii_uppercase());
+
+        // Tags and record fields are told apart from others with the same name by the shape
+        // of the tag union or record type they're in here.
+        let structural =
+            |name: &str, region: Region, shapes: Vec<(Region, Shape)>| StructuralTarget {
+                name: name.to_string(),
+                shape: (shapes.into_iter())
+                    .find(|(shape_region, _)| *shape_region == region)
+                    .map(|(_, shape)| shape),
+            };
+
+        match token.value {
+            Token::Tag => Some(Target::Tag(structural(
+                text,
+                token.region,
+                tag_shapes(text, self.module(), &ast),
+            ))),
+            Token::Field => Some(Target::Field(structural(
+                text,
+                token.region,
+                field_shapes(text, self.module(), &ast),
+            ))),
+            // Accessor functions like `.foo`, and record updaters like `&foo`
+            Token::Function => (text.get(1..))
+                .filter(|name| is_lowercase(name))
+                .map(|name| {
+                    let region =
+                        Region::new(token.region.start().bump_column(1), token.region.end());
+
+                    Target::Field(structural(
+                        name,
+                        region,
+                        field_shapes(name, self.module(), &ast),
+                    ))
+                }),
+            Token::Type | Token::Ability => {
+                let name = text.trim_start_matches('@');
+
+                if !is_uppercase(name) {
+                    return None;
+                }
+
+                let module_name =
+                    match module_qualifier(source, token.region.start().offset as usize) {
+                        Some(qualifier) => Some(qualifier.to_string()),
+                        None if ast.defines_type(name) => self.module_name(),
+                        None => (ast.imported_names().into_iter())
+                            .find(|(_, imported)| imported.value == name)
+                            .map(|(module_name, _)| module_name),
+                    };
+
+                Some(Target::Type(TypeTarget {
+                    name: name.to_string(),
+                    module_name,
+                }))
+            }
+            Token::Variable => {
+                let module = self.module()?;
+                let found = roc_can::traverse::find_closest_symbol_at(
+                    position,
+                    &module.declarations,
+                    &module.abilities,
+                )
+                .or_else(|| exposed_or_imported_symbol_at(&ast, position, module))?;
+
+                let target = ValueTarget::new(found, module, self.url().clone());
+                let references = value_references(&target, target.symbol, module, &ast, source);
+
+                // The closest symbol isn't necessarily the one that's named at the position,
+                // e.g. in a record field access.
+                (references.iter())
+                    .any(|reference| reference.region.contains_pos(position))
+                    .then_some(Target::Value(target))
+            }
+            _ => None,
+        }
+    }
+
+    /// Finds the references to `target` in this document.
+    pub(crate) fn references(&self, target: &Target) -> Vec<Reference> {
+        let source = self.doc_info.source.as_str();
+
+        let arena = Bump::new();
+        let Ok(ast) = Ast::parse(&arena, source) else {
+            return Vec::new();
+        };
+
+        match target {
+            Target::Value(value) => {
+                let Some(module) = self.module() else {
+                    return Vec::new();
+                };
+
+                let symbol = if self.url() == &value.origin {
+                    Some(value.symbol)
+                } else if value.top_level {
+                    value.symbol_in(module)
+                } else {
+                    None
+                };
+
+                symbol
+                    .map(|symbol| value_references(value, symbol, module, &ast, source))
+                    .unwrap_or_default()
+            }
+            Target::Tag(tag) => tag_references(tag, self.module(), &ast, source),
+            Target::Field(field) => field_references(field, self.module(), &ast, source),
+            Target::Type(ty) => self
+                .module_name()
+                .map(|module_name| type_references(ty, &module_name, &ast, source))
+                .unwrap_or_default(),
+        }
+    }
+
+    pub(crate) fn reference_locations(
+        &self,
+        target: &Target,
+        include_declaration: bool,
+    ) -> Vec<Location> {
+        (self.references(target).into_iter())
+            .filter(|reference| include_declaration || !reference.declaration)
+            .map(|reference| self.location(reference.region.to_range(self.line_info())))
+            .collect()
+    }
+
+    pub(crate) fn rename_edits(&self, target: &Target, new_name: &str) -> Vec<TextEdit> {
+        let old_name = target.name();
+
+        (self.references(target).into_iter())
+            .map(|reference| {
+                TextEdit::new(
+                    reference.region.to_range(self.line_info()),
+                    reference.new_text(old_name, new_name),
+                )
+            })
+            .collect()
+    }
+
+    /// Whether renaming `target` to `new_name` would clash with something this document
+    /// already uses that name for.
+    pub(crate) fn rename_conflicts(&self, target: &Target, new_name: &str) -> bool {
+        match target {
+            Target::Value(_) => self.module().is_some_and(|module| {
+                (module.interns.all_ident_ids.get(&module.module_id))
+                    .is_some_and(|ident_ids| ident_ids.get_id(new_name).is_some())
+            }),
+            // The new name can't already be in the same tag union or record type.
+            Target::Tag(structural) | Target::Field(structural) => {
+                (structural.shape.as_ref()).is_some_and(|shape| shape.names.contains(new_name))
+                    || !self.references(&target.renamed(new_name)).is_empty()
+            }
+            // Any type with the new name would clash, not just the ones from the same module.
+            Target::Type(_) => {
+                let source = self.doc_info.source.as_str();
+
+                let arena = Bump::new();
+                let Ok(ast) = Ast::parse(&arena, source) else {
+                    return true;
+                };
+
+                ast.defines_type(new_name)
+                    || (ast.semantic_tokens().into_iter()).any(|token| {
+                        matches!(token.value, Token::Type | Token::Ability)
+                            && source.get(
+                                token.region.start().offset as usize
+                                    ..token.region.end().offset as usize,
+                            ) == Some(new_name)
+                    })
+            }
+        }
+    }
+
+    /// The signature of the function being applied at `position` in `latest_doc`, which may
+    /// be newer than this document.
+    pub fn signature_help(
+        &self,
+        position: Position,
+        latest_doc: &DocInfo,
+    ) -> Option<SignatureHelp> {
+        let module = self.module()?;
+        let source = latest_doc.source.as_str();
+        let position = position.to_roc_position(&latest_doc.line_info);
+
+        let arena = Bump::new();
+        let ast = Ast::parse(&arena, source).ok()?;
+        let calls = ast.calls_at(source, position);
+
+        // Like for completion, names are looked up in this document's scopes by moving
+        // positions back by however much longer the latest document is.
+        let len_diff = source.len() as i32 - self.doc_info.source.len() as i32;
+        let scope_position = |position: roc_region::all::Position| {
+            roc_region::all::Position::new((position.offset as i32 - len_diff).max(0) as u32)
+        };
+
+        signature_help(module, calls, scope_position)
+    }
+
     pub fn completion_items(
         &self,
         position: Position,
@@ -325,3 +834,29 @@ impl AnalyzedDocument {
         }
     }
 }
+
+/// The symbol named at `position` in the module header's exposes, or in an import's exposing list.
+fn exposed_or_imported_symbol_at(
+    ast: &Ast,
+    position: roc_region::all::Position,
+    module: &AnalyzedModule,
+) -> Option<FoundSymbol> {
+    let interns = &module.interns;
+
+    let (module_id, name) = match (ast.exposed_names().into_iter())
+        .find(|exposed| exposed.region.contains_pos(position))
+    {
+        Some(exposed) => (module.module_id, exposed.value),
+        None => {
+            let (module_name, imported) = (ast.imported_names().into_iter())
+                .find(|(_, imported)| imported.region.contains_pos(position))?;
+            let module_id = interns.module_ids.get_id(&module_name.as_str().into())?;
+
+            (module_id, imported.value)
+        }
+    };
+
+    let ident_id = interns.all_ident_ids.get(&module_id)?.get_id(name)?;
+
+    Some(FoundSymbol::Symbol(Symbol::new(module_id, ident_id)))
+}
@@ -0,0 +1,436 @@
+//! Code actions: quick fixes for the problems found while checking a module and for failed
+//! snapshots, and adding the inferred type annotation to a top-level def.
+use std::collections::HashMap;
+
+use bumpalo::Bump;
+use roc_can::{
+    expr::{DeclarationTag, Declarations},
+    traverse::{find_declaration, FoundDeclaration},
+};
+use roc_exhaustive::Context;
+use roc_module::{
+    ident::ModuleName,
+    symbol::{ModuleId, Symbol},
+};
+use roc_problem::can::{Problem, RuntimeError};
+use roc_region::all::{LineInfo, Position, Region};
+use roc_reporting::{error::r#type::unhandled_pattern_to_source, report::RocDocAllocator};
+use roc_solve_problem::TypeError;
+use roc_types::subs::Subs;
+use tower_lsp::lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, Command, Diagnostic, NumberOrString, Range,
+    TextEdit, Url, WorkspaceEdit,
+};
+
+use crate::convert::ToRange;
+use crate::test_runner::{SNAPSHOT_DIAGNOSTIC_CODE, UPDATE_SNAPSHOT_COMMAND};
+
+use super::{parse_ast::Ast, utils::format_var_type, AnalyzedModule};
+
+/// What a new `when` branch does, until it's filled in.
+const NEW_BRANCH_BODY: &str = "crash \"TODO\"";
+
+pub(super) struct CodeActions<'a> {
+    url: &'a Url,
+    source: &'a str,
+    line_info: &'a LineInfo,
+    module: &'a AnalyzedModule,
+    /// The diagnostics that the client sent along; the quick fixes say which of them they fix.
+    diagnostics: &'a [Diagnostic],
+    actions: Vec<CodeActionOrCommand>,
+}
+
+impl<'a> CodeActions<'a> {
+    pub(super) fn new(
+        url: &'a Url,
+        source: &'a str,
+        line_info: &'a LineInfo,
+        module: &'a AnalyzedModule,
+        diagnostics: &'a [Diagnostic],
+    ) -> Self {
+        Self {
+            url,
+            source,
+            line_info,
+            module,
+            diagnostics,
+            actions: Vec::new(),
+        }
+    }
+
+    /// The code actions for everything in `range`.
+    pub(super) fn in_range(mut self, range: Range) -> Vec<CodeActionOrCommand> {
+        let line_info = self.line_info;
+        let overlaps = |problem_region: Region| {
+            let problem_range = problem_region.to_range(line_info);
+
+            problem_range.start <= range.end && range.start <= problem_range.end
+        };
+
+        for problem in &self.module.can_problems {
+            if problem.region().is_some_and(overlaps) {
+                self.fix_can_problem(problem);
+            }
+        }
+
+        for problem in &self.module.type_problems {
+            if problem.region().is_some_and(overlaps) {
+                self.fix_type_problem(problem);
+            }
+        }
+
+        self.update_snapshots(range);
+        self.add_annotation(range);
+
+        self.actions
+    }
+
+    fn fix_can_problem(&mut self, problem: &Problem) {
+        match problem {
+            Problem::UnusedDef(symbol, region) => self.remove_def(*symbol, *region),
+            Problem::UnusedImport(symbol, region) => self.remove_exposed_import(*symbol, *region),
+            Problem::UnusedModuleImport(module_id, region) => {
+                let title = format!("Remove unused import of `{}`", self.module_name(*module_id));
+
+                self.quick_fix(title, *region, vec![self.remove_lines(*region)]);
+            }
+            Problem::RuntimeError(RuntimeError::LookupNotInScope { loc_name, .. }) => {
+                self.import_exposing(loc_name.value.as_str(), loc_name.region);
+            }
+            Problem::RuntimeError(RuntimeError::ModuleNotImported {
+                module_name,
+                region,
+                module_exists: true,
+                ..
+            }) => {
+                if let Some(edit) = self.add_import(module_name.as_str()) {
+                    self.quick_fix(format!("Import `{module_name}`"), *region, vec![edit]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn fix_type_problem(&mut self, problem: &TypeError) {
+        if let TypeError::Exhaustive(roc_exhaustive::Error::Incomplete(
+            region,
+            Context::BadCase,
+            missing,
+        )) = problem
+        {
+            self.add_missing_branches(*region, missing);
+        }
+    }
+
+    /// Stores the current values of the snapshots in `range` that the test runner reported
+    /// as failed.
+    fn update_snapshots(&mut self, range: Range) {
+        let code = NumberOrString::String(SNAPSHOT_DIAGNOSTIC_CODE.to_string());
+        let diagnostics = self.diagnostics;
+
+        for diagnostic in diagnostics {
+            let overlaps =
+                diagnostic.range.start <= range.end && range.start <= diagnostic.range.end;
+
+            if diagnostic.code.as_ref() != Some(&code) || !overlaps {
+                continue;
+            }
+
+            let title = "Update snapshot".to_string();
+            let arguments = vec![
+                serde_json::Value::from(self.url.as_str()),
+                serde_json::json!(diagnostic.range.start),
+            ];
+
+            self.push(CodeAction {
+                title: title.clone(),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                command: Some(Command::new(
+                    title,
+                    UPDATE_SNAPSHOT_COMMAND.to_string(),
+                    Some(arguments),
+                )),
+                ..CodeAction::default()
+            });
+        }
+    }
+
+    fn remove_def(&mut self, symbol: Symbol, region: Region) {
+        // Defs that destructure aren't found, since only part of them is unused.
+        let Some(def_region) = def_region(symbol, &self.module.declarations) else {
+            return;
+        };
+
+        let title = format!("Remove unused def `{}`", self.symbol_name(symbol));
+
+        self.quick_fix(title, region, vec![self.remove_lines(def_region)]);
+    }
+
+    /// Removes `bar` from `import Foo exposing [bar, baz]`, along with the comma next to it.
+    /// The exposing list is removed when `bar` is the only name in it.
+    fn remove_exposed_import(&mut self, symbol: Symbol, region: Region) {
+        let source = self.source;
+        let start = region.start().offset as usize;
+        let end = region.end().offset as usize;
+
+        let after = &source[end..];
+        let before = &source[..start];
+        let after_trimmed = after.trim_start();
+        let before_trimmed = before.trim_end();
+
+        let (remove_start, remove_end) = if let Some(rest) = after_trimmed.strip_prefix(',') {
+            (start, source.len() - rest.trim_start().len())
+        } else if before_trimmed.ends_with(',') {
+            (before_trimmed.len() - 1, end)
+        } else {
+            let Some(exposing) = before_trimmed.strip_suffix('[') else {
+                return;
+            };
+            let Some(exposing) = exposing.trim_end().strip_suffix("exposing") else {
+                return;
+            };
+            let Some(rest) = after_trimmed.strip_prefix(']') else {
+                return;
+            };
+
+            (exposing.trim_end().len(), source.len() - rest.len())
+        };
+
+        let range = self.offsets_range(remove_start, remove_end);
+        let title = format!("Remove unused import of `{}`", self.symbol_name(symbol));
+
+        self.quick_fix(title, region, vec![TextEdit::new(range, String::new())]);
+    }
+
+    /// Offers to import `name` from each module that exposes it. When a module is already
+    /// imported, the name gets qualified with it instead.
+    fn import_exposing(&mut self, name: &str, region: Region) {
+        let module = self.module;
+        let mut candidates: Vec<_> = (module.modules_info.exposed_by_module.iter())
+            .filter(|(module_id, _)| {
+                **module_id != module.module_id
+                    && !module_id.is_builtin()
+                    && module.module_id_to_url.contains_key(module_id)
+            })
+            .filter(|(_, exposed)| {
+                (exposed.iter()).any(|(symbol, _)| symbol.as_str(&module.interns) == name)
+            })
+            .map(|(module_id, _)| (*module_id, self.module_name(*module_id).to_string()))
+            .collect();
+
+        candidates.sort_by(|(_, a), (_, b)| a.cmp(b));
+
+        for (module_id, module_name) in candidates {
+            if module.imports_by_module.contains_key(&module_id) {
+                let edit = TextEdit::new(
+                    region.to_range(self.line_info),
+                    format!("{module_name}.{name}"),
+                );
+
+                self.quick_fix(
+                    format!("Change to `{module_name}.{name}`"),
+                    region,
+                    vec![edit],
+                );
+            } else if let Some(edit) = self.add_import(&format!("{module_name} exposing [{name}]"))
+            {
+                let title = format!("Import `{name}` from `{module_name}`");
+
+                self.quick_fix(title, region, vec![edit]);
+            }
+        }
+    }
+
+    /// Adds `import <import>` after the existing imports.
+    fn add_import(&self, import: &str) -> Option<TextEdit> {
+        let arena = Bump::new();
+        let ast = Ast::parse(&arena, self.source).ok()?;
+
+        let imports_end = self.source[..ast.imports_end().offset as usize]
+            .trim_end()
+            .len();
+
+        let (offset, new_text) = match self.source[imports_end..].find('\n') {
+            Some(newline) => (imports_end + newline + 1, format!("import {import}\n")),
+            None => (self.source.len(), format!("\nimport {import}")),
+        };
+
+        Some(TextEdit::new(self.offsets_range(offset, offset), new_text))
+    }
+
+    /// Adds a branch that crashes for each pattern that the `when` doesn't handle.
+    fn add_missing_branches(&mut self, region: Region, missing: &[roc_exhaustive::Pattern]) {
+        let start_line = self.line_info.convert_pos(region.start()).line as usize;
+        let end_line = self.line_info.convert_pos(region.end()).line as usize;
+
+        // The new branches are indented like the first existing one.
+        let Some(indent) = (self.source.lines())
+            .take(end_line + 1)
+            .skip(start_line + 1)
+            .find(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+            .map(|line| &line[..line.len() - line.trim_start().len()])
+        else {
+            return;
+        };
+
+        let lines: Vec<_> = self.source.lines().collect();
+        let alloc = RocDocAllocator::new(&lines, self.module.module_id, &self.module.interns);
+
+        let new_text: String = (missing.iter())
+            .map(|pattern| {
+                let pattern = unhandled_pattern_to_source(&alloc, pattern.clone());
+
+                format!("\n{indent}{pattern} -> {NEW_BRANCH_BODY}")
+            })
+            .collect();
+
+        let end = region.end().offset as usize;
+        let title = match missing.len() {
+            1 => "Add missing branch".to_string(),
+            n => format!("Add {n} missing branches"),
+        };
+
+        self.quick_fix(
+            title,
+            region,
+            vec![TextEdit::new(self.offsets_range(end, end), new_text)],
+        );
+    }
+
+    /// Offers to annotate the top-level def named in `range` with its inferred type.
+    fn add_annotation(&mut self, range: Range) {
+        let AnalyzedModule {
+            declarations,
+            subs,
+            module_id,
+            interns,
+            ..
+        } = self.module;
+        // A copy of the module's `Subs` to print the types with, made once the first def that
+        // needs one is found. Printing only names variables temporarily, so one copy serves
+        // all the defs.
+        let mut printing_subs: Option<Subs> = None;
+
+        for (index, tag) in declarations.declarations.iter().enumerate() {
+            let is_named = matches!(
+                tag,
+                DeclarationTag::Value
+                    | DeclarationTag::Function(_)
+                    | DeclarationTag::Recursive(_)
+                    | DeclarationTag::TailRecursive(_)
+            );
+            let loc_symbol = declarations.symbols[index];
+            let name_range = loc_symbol.region.to_range(self.line_info);
+
+            if !is_named
+                || declarations.annotations[index].is_some()
+                || range.start < name_range.start
+                || name_range.end < range.end
+            {
+                continue;
+            }
+
+            let var = declarations.variables[index];
+            let printing_subs = printing_subs.get_or_insert_with(|| subs.clone());
+            let type_str = format_var_type(var, printing_subs, module_id, interns);
+
+            let name_start = loc_symbol.region.start().offset as usize;
+            let name = &self.source[name_start..loc_symbol.region.end().offset as usize];
+            let line_start = self.source[..name_start].rfind('\n').map_or(0, |i| i + 1);
+            let indent = &self.source[line_start..name_start];
+
+            let edit = TextEdit::new(
+                self.offsets_range(line_start, line_start),
+                format!("{indent}{name} : {type_str}\n"),
+            );
+
+            self.push(CodeAction {
+                title: "Add inferred type annotation".to_string(),
+                kind: Some(CodeActionKind::REFACTOR_REWRITE),
+                edit: Some(self.workspace_edit(vec![edit])),
+                ..CodeAction::default()
+            });
+        }
+    }
+
+    fn quick_fix(&mut self, title: String, problem_region: Region, edits: Vec<TextEdit>) {
+        let problem_range = problem_region.to_range(self.line_info);
+        let diagnostics: Vec<_> = (self.diagnostics.iter())
+            .filter(|diagnostic| diagnostic.range == problem_range)
+            .cloned()
+            .collect();
+
+        self.push(CodeAction {
+            title,
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: (!diagnostics.is_empty()).then_some(diagnostics),
+            edit: Some(self.workspace_edit(edits)),
+            ..CodeAction::default()
+        });
+    }
+
+    fn push(&mut self, action: CodeAction) {
+        self.actions.push(CodeActionOrCommand::CodeAction(action));
+    }
+
+    fn workspace_edit(&self, edits: Vec<TextEdit>) -> WorkspaceEdit {
+        WorkspaceEdit {
+            changes: Some(HashMap::from([(self.url.clone(), edits)])),
+            ..WorkspaceEdit::default()
+        }
+    }
+
+    /// Removes the lines that `region` is on. If that would leave two blank lines in a row,
+    /// the one after the region is removed too.
+    fn remove_lines(&self, region: Region) -> TextEdit {
+        let start = self.line_info.convert_pos(region.start()).line;
+        let mut end = self.line_info.convert_pos(region.end()).line + 1;
+
+        let is_blank = |line: u32| {
+            (self.source.lines().nth(line as usize)).is_some_and(|line| line.trim().is_empty())
+        };
+
+        if is_blank(end) && (start == 0 || is_blank(start - 1)) {
+            end += 1;
+        }
+
+        let range = Range::new(
+            tower_lsp::lsp_types::Position::new(start, 0),
+            tower_lsp::lsp_types::Position::new(end, 0),
+        );
+
+        TextEdit::new(range, String::new())
+    }
+
+    fn offsets_range(&self, start: usize, end: usize) -> Range {
+        Region::new(Position::new(start as u32), Position::new(end as u32)).to_range(self.line_info)
+    }
+
+    fn module_name(&self, module_id: ModuleId) -> &ModuleName {
+        self.module.interns.module_name(module_id)
+    }
+
+    fn symbol_name(&self, symbol: Symbol) -> &str {
+        symbol.as_str(&self.module.interns)
+    }
+}
+
+/// The region of the def of `symbol`, including its type annotation.
+fn def_region(symbol: Symbol, declarations: &Declarations) -> Option<Region> {
+    match find_declaration(symbol, declarations)? {
+        FoundDeclaration::Def(def) => Some(def.region()),
+        FoundDeclaration::Decl(decl) => {
+            let index = (declarations.symbols.iter()).position(|s| s.value == symbol)?;
+
+            let region = match &declarations.annotations[index] {
+                Some(annotation) if annotation.region.start() < decl.region().start() => {
+                    Region::span_across(&annotation.region, &decl.region())
+                }
+                _ => decl.region(),
+            };
+
+            Some(region)
+        }
+    }
+}
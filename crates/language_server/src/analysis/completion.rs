@@ -19,7 +19,7 @@ use super::{utils::format_var_type, ModulesInfo};
 mod formatting;
 mod visitor;
 
-fn get_completions(
+pub(super) fn get_completions(
     position: Position,
     decls: &Declarations,
     prefix: String,
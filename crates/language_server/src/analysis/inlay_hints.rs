@@ -0,0 +1,162 @@
+//! Inlay hints that show the inferred types of the defs, let-bindings and closure arguments
+//! that don't have a type annotation.
+use roc_can::{
+    def::Def,
+    expr::{ClosureData, Declarations, Expr},
+    pattern::Pattern,
+    traverse::{walk_decl, walk_def, walk_expr, DeclarationInfo, Visitor},
+};
+use roc_region::all::{LineInfo, Region};
+use roc_types::subs::{Subs, Variable};
+use tower_lsp::lsp_types::{InlayHint, InlayHintKind, InlayHintLabel, InlayHintTooltip, Range};
+
+use crate::convert::ToRange;
+
+use super::{utils::format_var_type, AnalyzedModule};
+
+pub(super) struct InlayHints<'a> {
+    module: &'a AnalyzedModule,
+    /// A copy of the module's `Subs` to print the types with. Printing only names variables
+    /// temporarily, so one copy serves all the hints.
+    subs: Subs,
+    line_info: &'a LineInfo,
+    /// Only the hints in this range are wanted.
+    range: Range,
+    /// Longer types are cut short, and shown in full in the tooltip.
+    max_length: usize,
+    /// The top-level defs that have a type annotation.
+    annotated: Vec<Region>,
+    /// The closures that are the bodies of let-bindings; the hint for the binding already
+    /// shows the types of their arguments.
+    bound_closures: Vec<Region>,
+    hints: Vec<InlayHint>,
+}
+
+impl<'a> InlayHints<'a> {
+    pub(super) fn new(
+        module: &'a AnalyzedModule,
+        line_info: &'a LineInfo,
+        range: Range,
+        max_length: usize,
+    ) -> Self {
+        Self {
+            module,
+            subs: module.subs.clone(),
+            line_info,
+            range,
+            max_length,
+            annotated: Vec::new(),
+            bound_closures: Vec::new(),
+            hints: Vec::new(),
+        }
+    }
+
+    pub(super) fn collect(mut self, declarations: &Declarations) -> Vec<InlayHint> {
+        self.annotated = (declarations.symbols.iter().zip(&declarations.annotations))
+            .filter(|(_, annotation)| annotation.is_some())
+            .map(|(loc_symbol, _)| loc_symbol.region)
+            .collect();
+
+        self.visit_decls(declarations);
+
+        self.hints
+            .sort_by_key(|hint| (hint.position.line, hint.position.character));
+
+        self.hints
+    }
+
+    /// Shows the type of `var` after the name at `region`.
+    fn hint(&mut self, region: Region, var: Variable) {
+        let position = region.to_range(self.line_info).end;
+
+        if position < self.range.start || position > self.range.end {
+            return;
+        }
+
+        let AnalyzedModule {
+            module_id, interns, ..
+        } = self.module;
+
+        let type_str = format_var_type(var, &mut self.subs, module_id, interns);
+
+        let (label, tooltip) = if type_str.chars().count() > self.max_length {
+            let shortened = (type_str.chars())
+                .take(self.max_length.saturating_sub(1))
+                .collect::<String>();
+
+            (
+                format!(": {}…", shortened.trim_end()),
+                Some(InlayHintTooltip::String(type_str)),
+            )
+        } else {
+            (format!(": {type_str}"), None)
+        };
+
+        self.hints.push(InlayHint {
+            position,
+            label: InlayHintLabel::String(label),
+            kind: Some(InlayHintKind::TYPE),
+            text_edits: None,
+            tooltip,
+            padding_left: None,
+            padding_right: None,
+            data: None,
+        });
+    }
+
+    fn closure_arguments(&mut self, closure: &ClosureData) {
+        for (var, _, argument) in closure.arguments.iter() {
+            if let Pattern::Identifier(_) = argument.value {
+                self.hint(argument.region, *var);
+            }
+        }
+    }
+}
+
+impl Visitor for InlayHints<'_> {
+    fn visit_decl(&mut self, decl: DeclarationInfo<'_>) {
+        match &decl {
+            DeclarationInfo::Value {
+                loc_symbol,
+                expr_var,
+                pattern: Pattern::Identifier(_),
+                ..
+            }
+            | DeclarationInfo::Function {
+                loc_symbol,
+                expr_var,
+                pattern: Pattern::Identifier(_),
+                ..
+            } if !self.annotated.contains(&loc_symbol.region) => {
+                self.hint(loc_symbol.region, *expr_var);
+            }
+            _ => {}
+        }
+
+        walk_decl(self, decl);
+    }
+
+    fn visit_def(&mut self, def: &Def) {
+        if def.annotation.is_none() {
+            if let Pattern::Identifier(_) = def.loc_pattern.value {
+                self.hint(def.loc_pattern.region, def.expr_var);
+            }
+        }
+
+        if let Expr::Closure(_) = def.loc_expr.value {
+            self.bound_closures.push(def.loc_expr.region);
+        }
+
+        walk_def(self, def);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+        if let Expr::Closure(closure) = expr {
+            if !self.bound_closures.contains(&region) {
+                self.closure_arguments(closure);
+            }
+        }
+
+        walk_expr(self, expr, var);
+    }
+}
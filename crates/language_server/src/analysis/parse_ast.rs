@@ -1,22 +1,41 @@
 use bumpalo::Bump;
 use roc_fmt::{Buf, MigrationFlags};
 use roc_parse::{
-    ast::{Defs, Header, SpacesBefore},
-    header::parse_module_defs,
+    ast::{
+        AssignedField, Collection, Defs, ExtractSpaces, Header, ImplementsAbility, Spaced,
+        SpacesBefore, TypeAnnotation, TypeDef, ValueDef,
+    },
+    header::{parse_module_defs, ExposedName},
     parser::SyntaxError,
 };
-use roc_region::all::Loc;
+use roc_region::all::{LineInfo, Loc, Position, Region};
+use tower_lsp::lsp_types::{self, FoldingRange, SelectionRange};
 
 use self::format::FormattedAst;
 
-use super::tokens::{IterTokens, Token};
+use super::{
+    ranges::{annotations, folding_ranges, selection_range},
+    signature_help::{calls_at, Call},
+    symbols::{defs_symbols, DefSymbol},
+    tokens::{IterTokens, Token},
+};
 
 mod format;
 
 pub struct Ast<'a> {
     arena: &'a Bump,
     module: SpacesBefore<'a, Header<'a>>,
+    header_end: Position,
     defs: Defs<'a>,
+    src_end: Position,
+}
+
+/// A top-level def formatted on its own.
+pub struct FormattedDef<'a> {
+    pub region: Region,
+    /// What the formatted def replaces: the def and what's after it, up to the next def.
+    pub replaces: Region,
+    pub formatted: FormattedAst<'a>,
 }
 
 impl<'a> Ast<'a> {
@@ -26,6 +45,7 @@ impl<'a> Ast<'a> {
         let (module, state) = parse_header(arena, State::new(src.as_bytes()))
             .map_err(|e| SyntaxError::Header(e.problem))?;
 
+        let header_end = state.pos();
         let (header, defs) = module.item.upgrade_header_imports(arena);
 
         let defs = parse_module_defs(arena, state, defs)?;
@@ -35,7 +55,9 @@ impl<'a> Ast<'a> {
                 before: module.before,
                 item: header,
             },
+            header_end,
             defs,
+            src_end: Position::new(src.len() as u32),
             arena,
         })
     }
@@ -52,10 +74,210 @@ impl<'a> Ast<'a> {
         FormattedAst::new(buf)
     }
 
+    /// Each top-level def which overlaps `region`, formatted on its own. Imports in the header
+    /// are left out, since they belong to the header.
+    pub fn fmt_defs_overlapping(
+        &self,
+        region: Region,
+        flags: MigrationFlags,
+    ) -> Vec<FormattedDef<'a>> {
+        let regions = &self.defs.regions;
+
+        (regions.iter().enumerate())
+            .filter(|(_, def_region)| {
+                def_region.start() >= self.header_end
+                    && def_region.start() <= region.end()
+                    && def_region.end() >= region.start()
+            })
+            .map(|(index, def_region)| {
+                let mut buf = Buf::new_in(self.arena, flags);
+
+                roc_fmt::def::fmt_def(&mut buf, &self.defs, index, 0);
+
+                let replaced_end = (regions.get(index + 1))
+                    .map_or(self.src_end, |next_region| next_region.start());
+
+                FormattedDef {
+                    region: *def_region,
+                    replaces: Region::new(def_region.start(), replaced_end),
+                    formatted: FormattedAst::new(buf),
+                }
+            })
+            .collect()
+    }
+
     pub fn semantic_tokens(&self) -> impl IntoIterator<Item = Loc<Token>> + '_ {
         let header_tokens = self.module.item.iter_tokens(self.arena);
         let body_tokens = self.defs.iter_tokens(self.arena);
 
         header_tokens.into_iter().chain(body_tokens)
     }
+
+    /// The defs, types and abilities of the module, with the defs nested in them.
+    pub fn symbols(&self) -> Vec<DefSymbol<'a>> {
+        defs_symbols(&self.defs, true)
+    }
+
+    /// The function applications that the cursor at `position` is in, innermost first.
+    pub fn calls_at(&self, source: &str, position: Position) -> Vec<Call<'a>> {
+        calls_at(&self.defs, source, position)
+    }
+
+    /// The ranges of lines that the editor can fold.
+    pub fn folding_ranges(&self, source: &str, line_info: &LineInfo) -> Vec<FoldingRange> {
+        folding_ranges(&self.defs, source, line_info)
+    }
+
+    /// The ranges around `position` to expand the selection through, ending at `whole_document`.
+    pub fn selection_range(
+        &self,
+        line_info: &LineInfo,
+        position: Position,
+        whole_document: lsp_types::Range,
+    ) -> SelectionRange {
+        selection_range(&self.defs, line_info, position, whole_document)
+    }
+
+    /// Where the top-level `expect`s are.
+    pub fn top_level_expects(&self) -> Vec<Region> {
+        (self.defs.defs().zip(&self.defs.regions))
+            .filter(|(def, _)| matches!(def, Err(ValueDef::Expect { .. })))
+            .map(|(_, region)| *region)
+            .collect()
+    }
+
+    /// The names the header exposes, or provides to the platform or host.
+    pub fn exposed_names(&self) -> Vec<Loc<&'a str>> {
+        let names = match &self.module.item {
+            Header::Module(header) => header.exposes,
+            Header::App(header) => header.provides,
+            Header::Hosted(header) => header.exposes.item,
+            Header::Platform(header) => header.provides.item,
+            Header::Package(_) => Collection::empty(),
+        };
+
+        names.iter().map(exposed_name).collect()
+    }
+
+    /// The names exposed by imports, like `bar` in `import Foo exposing [bar]`,
+    /// each with the name of the module it's imported from.
+    pub fn imported_names(&self) -> Vec<(String, Loc<&'a str>)> {
+        let mut imported = Vec::new();
+
+        for def in self.defs.defs() {
+            let Err(ValueDef::ModuleImport(import)) = def else {
+                continue;
+            };
+
+            let module_name = match import.name.value.package {
+                Some(package) => format!("{}.{}", package, import.name.value.name.as_str()),
+                None => import.name.value.name.as_str().to_string(),
+            };
+
+            for name in import
+                .exposed
+                .iter()
+                .flat_map(|exposed| exposed.item.iter())
+            {
+                imported.push((module_name.clone(), exposed_name(name)));
+            }
+        }
+
+        imported
+    }
+
+    /// Where the header and the import statements after it end, which is where new
+    /// imports go.
+    pub fn imports_end(&self) -> Position {
+        (self.defs.defs().zip(&self.defs.regions))
+            .filter(|(def, _)| {
+                matches!(
+                    def,
+                    Err(ValueDef::ModuleImport(_) | ValueDef::IngestedFileImport(_))
+                )
+            })
+            .map(|(_, region)| region.end())
+            // Imports in the header are upgraded to import statements, but they're still
+            // inside of the header.
+            .fold(self.header_end, Position::max)
+    }
+
+    /// The labels in the ability implementations of opaque types, like `is_eq` in
+    /// `implements [Eq { is_eq: my_is_eq }]`.
+    pub fn ability_impl_labels(&self) -> Vec<AbilityImplLabel<'a>> {
+        let mut labels = Vec::new();
+
+        for def in self.defs.defs() {
+            let Ok(TypeDef::Opaque {
+                derived: Some(derived),
+                ..
+            }) = def
+            else {
+                continue;
+            };
+
+            for implements in derived.item.value.iter() {
+                let ImplementsAbility::ImplementsAbility {
+                    ability,
+                    impls: Some(impls),
+                } = implements.value.extract_spaces().item
+                else {
+                    continue;
+                };
+
+                let TypeAnnotation::Apply(_, ability, _) = ability.value.extract_spaces().item
+                else {
+                    continue;
+                };
+
+                let fields = impls.value.extract_spaces().item;
+
+                for field in fields.iter() {
+                    let (label, punned) = match field.value.extract_spaces().item {
+                        AssignedField::RequiredValue(label, _, _)
+                        | AssignedField::OptionalValue(label, _, _)
+                        | AssignedField::IgnoredValue(label, _, _) => (label, false),
+                        AssignedField::LabelOnly(label) => (label, true),
+                        AssignedField::SpaceBefore(..) | AssignedField::SpaceAfter(..) => continue,
+                    };
+
+                    labels.push(AbilityImplLabel {
+                        ability,
+                        label,
+                        punned,
+                    });
+                }
+            }
+        }
+
+        labels
+    }
+
+    /// Whether the module defines a type alias, opaque type or ability with the given name.
+    pub fn defines_type(&self, name: &str) -> bool {
+        self.defs.defs().any(|def| match def {
+            Ok(TypeDef::Alias { header, .. })
+            | Ok(TypeDef::Opaque { header, .. })
+            | Ok(TypeDef::Ability { header, .. }) => header.name.value == name,
+            Err(_) => false,
+        })
+    }
+
+    /// The type annotations in the module, including those of nested defs, and the types that
+    /// aliases, opaque types and ability members are defined as.
+    pub fn type_annotations(&self) -> Vec<&Loc<TypeAnnotation<'_>>> {
+        annotations(&self.defs)
+    }
+}
+
+/// A label in the implementation of an ability, which refers to one of its members.
+pub struct AbilityImplLabel<'a> {
+    pub ability: &'a str,
+    pub label: Loc<&'a str>,
+    /// Whether the label is also the name of the implementation, like `is_eq` in `Eq { is_eq }`
+    pub punned: bool,
+}
+
+fn exposed_name<'a>(name: &Loc<Spaced<'a, ExposedName<'a>>>) -> Loc<&'a str> {
+    name.map(|spaced| (*spaced.item()).into())
 }
@@ -0,0 +1,398 @@
+//! Folding ranges and selection ranges, which follow the structure of the parse AST, and the
+//! type annotations found along the way.
+use roc_parse::ast::{
+    AssignedField, Defs, Expr, ExtractSpaces, Pattern, TypeAnnotation, TypeDef, ValueDef,
+};
+use roc_region::all::{LineInfo, Loc, Position, Region};
+use tower_lsp::lsp_types::{self, FoldingRange, FoldingRangeKind, SelectionRange};
+
+use crate::convert::ToRange;
+
+/// The kinds of AST nodes that are told apart when folding.
+#[derive(Debug, Clone, Copy)]
+enum Node<'a> {
+    Def,
+    Import,
+    /// A `when`, one of its branches, or a record, list or tuple literal.
+    Block,
+    Annotation(&'a Loc<TypeAnnotation<'a>>),
+    Other,
+}
+
+/// The folding ranges for the defs, `when` expressions and branches, and the record, list
+/// and tuple literals that span several lines, as well as for runs of imports and of doc
+/// comments.
+pub(super) fn folding_ranges(defs: &Defs, source: &str, line_info: &LineInfo) -> Vec<FoldingRange> {
+    let mut folds = Vec::new();
+    let mut imports: Option<Region> = None;
+
+    for (def, region) in defs.defs().zip(&defs.regions) {
+        if let Err(ValueDef::ModuleImport(_) | ValueDef::IngestedFileImport(_)) = def {
+            imports = Some(match imports {
+                Some(imports) => Region::span_across(&imports, region),
+                None => *region,
+            });
+        }
+    }
+
+    if let Some(imports) = imports {
+        let range = imports.to_range(line_info);
+        folds.push(fold(
+            range.start.line,
+            range.end.line,
+            Some(FoldingRangeKind::Imports),
+        ));
+    }
+
+    walk_defs(defs, &mut |region, node| {
+        if let Node::Def | Node::Block = node {
+            let range = region.to_range(line_info);
+            folds.push(fold(range.start.line, range.end.line, None));
+        }
+    });
+
+    let mut doc_comment_start = None;
+
+    for (line, text) in source.lines().enumerate() {
+        let line = line as u32;
+
+        match (text.trim_start().starts_with("##"), doc_comment_start) {
+            (true, None) => doc_comment_start = Some(line),
+            (false, Some(start)) => {
+                folds.push(fold(start, line - 1, Some(FoldingRangeKind::Comment)));
+                doc_comment_start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(start) = doc_comment_start {
+        let end = source.lines().count() as u32 - 1;
+        folds.push(fold(start, end, Some(FoldingRangeKind::Comment)));
+    }
+
+    folds.retain(|fold| fold.end_line > fold.start_line);
+    folds.sort_by_key(|fold| (fold.start_line, std::cmp::Reverse(fold.end_line)));
+    folds.dedup_by_key(|fold| (fold.start_line, fold.end_line));
+
+    folds
+}
+
+fn fold(start_line: u32, end_line: u32, kind: Option<FoldingRangeKind>) -> FoldingRange {
+    FoldingRange {
+        start_line,
+        end_line,
+        kind,
+        ..FoldingRange::default()
+    }
+}
+
+/// The selection range at `position`, which expands along the AST nodes around it up to
+/// `whole_document`.
+pub(super) fn selection_range(
+    defs: &Defs,
+    line_info: &LineInfo,
+    position: Position,
+    whole_document: lsp_types::Range,
+) -> SelectionRange {
+    let mut regions = Vec::new();
+
+    walk_defs(defs, &mut |region, _| {
+        if region.contains_pos(position) {
+            regions.push(region);
+        }
+    });
+
+    regions.sort_by_key(|region| region.end().offset - region.start().offset);
+
+    // Nodes that only touch the position at their edge can be siblings of each other, rather
+    // than nested.
+    let mut chain: Vec<Region> = Vec::new();
+
+    for region in regions {
+        let encloses_chain = match chain.last() {
+            Some(inner) => region.contains(inner) && region != *inner,
+            None => true,
+        };
+
+        if encloses_chain {
+            chain.push(region);
+        }
+    }
+
+    let mut selection = SelectionRange {
+        range: whole_document,
+        parent: None,
+    };
+
+    for region in chain.into_iter().rev() {
+        let range = region.to_range(line_info);
+
+        if range != selection.range {
+            selection = SelectionRange {
+                range,
+                parent: Some(Box::new(selection)),
+            };
+        }
+    }
+
+    selection
+}
+
+/// The type annotations of the defs in `defs`, including the nested ones, and the types that
+/// aliases, opaque types and ability members are defined as.
+pub(super) fn annotations<'a>(defs: &'a Defs<'a>) -> Vec<&'a Loc<TypeAnnotation<'a>>> {
+    let mut annotations = Vec::new();
+
+    walk_defs(defs, &mut |_, node| {
+        if let Node::Annotation(annotation) = node {
+            annotations.push(annotation);
+        }
+    });
+
+    annotations
+}
+
+fn walk_defs<'a>(defs: &'a Defs<'a>, visit: &mut impl FnMut(Region, Node<'a>)) {
+    for (def, region) in defs.defs().zip(&defs.regions) {
+        match def {
+            Ok(type_def) => {
+                visit(*region, Node::Def);
+
+                match type_def {
+                    TypeDef::Alias { header, ann } => {
+                        visit(header.name.region, Node::Other);
+                        visit(ann.region, Node::Annotation(ann));
+                    }
+                    TypeDef::Opaque { header, typ, .. } => {
+                        visit(header.name.region, Node::Other);
+                        visit(typ.region, Node::Annotation(typ));
+                    }
+                    TypeDef::Ability {
+                        header, members, ..
+                    } => {
+                        visit(header.name.region, Node::Other);
+
+                        for member in members.iter() {
+                            visit(member.region(), Node::Other);
+                            visit(member.typ.region, Node::Annotation(&member.typ));
+                        }
+                    }
+                }
+            }
+            Err(value_def) => match value_def {
+                ValueDef::ModuleImport(_) | ValueDef::IngestedFileImport(_) => {
+                    visit(*region, Node::Import)
+                }
+                ValueDef::Annotation(pattern, annotation) => {
+                    visit(*region, Node::Def);
+                    walk_pattern(pattern.region, &pattern.value, visit);
+                    visit(annotation.region, Node::Annotation(annotation));
+                }
+                ValueDef::Body(pattern, body) => {
+                    visit(*region, Node::Def);
+                    walk_pattern(pattern.region, &pattern.value, visit);
+                    walk_expr(body.region, &body.value, visit);
+                }
+                ValueDef::AnnotatedBody {
+                    ann_pattern,
+                    ann_type,
+                    body_pattern,
+                    body_expr,
+                    ..
+                } => {
+                    visit(*region, Node::Def);
+                    visit(
+                        Region::span_across(&ann_pattern.region, &ann_type.region),
+                        Node::Other,
+                    );
+                    walk_pattern(ann_pattern.region, &ann_pattern.value, visit);
+                    visit(ann_type.region, Node::Annotation(ann_type));
+                    visit(
+                        Region::span_across(&body_pattern.region, &body_expr.region),
+                        Node::Other,
+                    );
+                    walk_pattern(body_pattern.region, &body_pattern.value, visit);
+                    walk_expr(body_expr.region, &body_expr.value, visit);
+                }
+                ValueDef::Dbg { condition, .. } | ValueDef::Expect { condition, .. } => {
+                    visit(*region, Node::Def);
+                    walk_expr(condition.region, &condition.value, visit);
+                }
+                ValueDef::Stmt(expr) => walk_expr(expr.region, &expr.value, visit),
+                ValueDef::StmtAfterExpr => {}
+            },
+        }
+    }
+}
+
+fn walk_expr<'a>(region: Region, expr: &'a Expr<'a>, visit: &mut impl FnMut(Region, Node<'a>)) {
+    match expr {
+        Expr::SpaceBefore(expr, _) | Expr::SpaceAfter(expr, _) => {
+            return walk_expr(region, expr, visit);
+        }
+        Expr::When(..)
+        | Expr::Record(_)
+        | Expr::RecordUpdate { .. }
+        | Expr::RecordBuilder { .. }
+        | Expr::List(_)
+        | Expr::Tuple(_) => visit(region, Node::Block),
+        _ => visit(region, Node::Other),
+    }
+
+    match expr {
+        Expr::Defs(defs, final_expr) => {
+            walk_defs(defs, visit);
+            walk_expr(final_expr.region, &final_expr.value, visit);
+        }
+        Expr::Closure(arguments, body) => {
+            (arguments.iter())
+                .for_each(|argument| walk_pattern(argument.region, &argument.value, visit));
+            walk_expr(body.region, &body.value, visit);
+        }
+        Expr::When(condition, branches) => {
+            walk_expr(condition.region, &condition.value, visit);
+
+            for branch in branches.iter() {
+                if let Some(first) = branch.patterns.first() {
+                    visit(
+                        Region::span_across(&first.region, &branch.value.region),
+                        Node::Block,
+                    );
+                }
+
+                (branch.patterns.iter())
+                    .for_each(|pattern| walk_pattern(pattern.region, &pattern.value, visit));
+
+                if let Some(guard) = &branch.guard {
+                    walk_expr(guard.region, &guard.value, visit);
+                }
+
+                walk_expr(branch.value.region, &branch.value.value, visit);
+            }
+        }
+        Expr::If {
+            if_thens,
+            final_else,
+            ..
+        } => {
+            for (condition, then) in if_thens.iter() {
+                walk_expr(condition.region, &condition.value, visit);
+                walk_expr(then.region, &then.value, visit);
+            }
+
+            walk_expr(final_else.region, &final_else.value, visit);
+        }
+        Expr::Apply(function, arguments, _) => {
+            walk_expr(function.region, &function.value, visit);
+            (arguments.iter())
+                .for_each(|argument| walk_expr(argument.region, &argument.value, visit));
+        }
+        Expr::PncApply(function, arguments) => {
+            walk_expr(function.region, &function.value, visit);
+            (arguments.iter())
+                .for_each(|argument| walk_expr(argument.region, &argument.value, visit));
+        }
+        Expr::BinOps(operands, last) => {
+            (operands.iter())
+                .for_each(|(operand, _)| walk_expr(operand.region, &operand.value, visit));
+            walk_expr(last.region, &last.value, visit);
+        }
+        Expr::List(items) | Expr::Tuple(items) => {
+            (items.iter()).for_each(|item| walk_expr(item.region, &item.value, visit))
+        }
+        Expr::Record(fields) => walk_fields(fields.items, visit),
+        Expr::RecordUpdate { update, fields } => {
+            walk_expr(update.region, &update.value, visit);
+            walk_fields(fields.items, visit);
+        }
+        Expr::RecordBuilder { mapper, fields } => {
+            walk_expr(mapper.region, &mapper.value, visit);
+            walk_fields(fields.items, visit);
+        }
+        Expr::DbgStmt {
+            first,
+            extra_args,
+            continuation,
+            ..
+        } => {
+            walk_expr(first.region, &first.value, visit);
+            (extra_args.iter())
+                .for_each(|argument| walk_expr(argument.region, &argument.value, visit));
+            walk_expr(continuation.region, &continuation.value, visit);
+        }
+        Expr::LowLevelDbg(_, message, continuation) => {
+            walk_expr(message.region, &message.value, visit);
+            walk_expr(continuation.region, &continuation.value, visit);
+        }
+        Expr::Return(value, after) => {
+            walk_expr(value.region, &value.value, visit);
+
+            if let Some(after) = after {
+                walk_expr(after.region, &after.value, visit);
+            }
+        }
+        Expr::UnaryOp(expr, _) | Expr::LowLevelTry(expr, _) => {
+            walk_expr(expr.region, &expr.value, visit)
+        }
+        Expr::RecordAccess(expr, _)
+        | Expr::TupleAccess(expr, _)
+        | Expr::TrySuffix(expr)
+        | Expr::ParensAround(expr) => walk_expr(region, expr, visit),
+        _ => {}
+    }
+}
+
+fn walk_fields<'a>(
+    fields: &'a [Loc<AssignedField<'a, Expr<'a>>>],
+    visit: &mut impl FnMut(Region, Node<'a>),
+) {
+    for field in fields {
+        visit(field.region, Node::Other);
+
+        match field.value.extract_spaces().item {
+            AssignedField::RequiredValue(_, _, value)
+            | AssignedField::OptionalValue(_, _, value)
+            | AssignedField::IgnoredValue(_, _, value) => {
+                walk_expr(value.region, &value.value, visit)
+            }
+            AssignedField::LabelOnly(_)
+            | AssignedField::SpaceBefore(..)
+            | AssignedField::SpaceAfter(..) => {}
+        }
+    }
+}
+
+fn walk_pattern<'a>(
+    region: Region,
+    pattern: &'a Pattern<'a>,
+    visit: &mut impl FnMut(Region, Node<'a>),
+) {
+    if let Pattern::SpaceBefore(pattern, _) | Pattern::SpaceAfter(pattern, _) = pattern {
+        return walk_pattern(region, pattern, visit);
+    }
+
+    visit(region, Node::Other);
+
+    match pattern {
+        Pattern::Apply(function, arguments) => {
+            walk_pattern(function.region, &function.value, visit);
+            (arguments.iter())
+                .for_each(|argument| walk_pattern(argument.region, &argument.value, visit));
+        }
+        Pattern::PncApply(function, arguments) => {
+            walk_pattern(function.region, &function.value, visit);
+            (arguments.iter())
+                .for_each(|argument| walk_pattern(argument.region, &argument.value, visit));
+        }
+        Pattern::RecordDestructure(patterns)
+        | Pattern::Tuple(patterns)
+        | Pattern::List(patterns) => (patterns.iter())
+            .for_each(|pattern| walk_pattern(pattern.region, &pattern.value, visit)),
+        Pattern::RequiredField(_, pattern) | Pattern::As(pattern, _) => {
+            walk_pattern(pattern.region, &pattern.value, visit)
+        }
+        Pattern::OptionalField(_, default) => walk_expr(default.region, &default.value, visit),
+        _ => {}
+    }
+}
@@ -0,0 +1,786 @@
+//! Finding the references to values, tags, record fields and types, for "find references"
+//! and "rename".
+//!
+//! Values are found by symbol, using the canonical AST. Tags and record fields are found by
+//! name, using the semantic tokens of the parsed source, but only in tag unions and record
+//! types with the same [Shape] as the one the search started in. Types are found by name too,
+//! but only where the name refers to the module that defines it.
+use std::collections::BTreeSet;
+
+use roc_can::{
+    abilities::AbilitiesStore,
+    traverse::{
+        find_field_puns, find_field_uses, find_symbol_references, find_tag_uses, FoundSymbol,
+        SymbolReference,
+    },
+};
+use roc_module::{ident::ModuleName, symbol::Symbol};
+use roc_parse::ast::{AssignedField, ExtractSpaces, Tag, TypeAnnotation};
+use roc_region::all::{Loc, Position, Region};
+use roc_types::{
+    subs::{Content, FlatType, GetSubsSlice, Subs, Variable},
+    types::MemberImpl,
+};
+use tower_lsp::lsp_types::Url;
+
+use super::{parse_ast::Ast, tokens::Token, AnalyzedModule};
+
+/// What to find the references of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Target {
+    Value(ValueTarget),
+    Tag(StructuralTarget),
+    Field(StructuralTarget),
+    Type(TypeTarget),
+}
+
+impl Target {
+    pub(crate) fn name(&self) -> &str {
+        match self {
+            Target::Value(value) => &value.ident,
+            Target::Type(ty) => &ty.name,
+            Target::Tag(structural) | Target::Field(structural) => &structural.name,
+        }
+    }
+
+    /// Whether `new_name` can replace the name of the target, e.g. tags have to stay
+    /// capitalized, and effectful functions have to keep their `!`.
+    pub(crate) fn is_valid_new_name(&self, new_name: &str) -> bool {
+        match self {
+            Target::Value(_) | Target::Field(_) => {
+                let old_name = self.name();
+                let new_ident = new_name.strip_suffix('!').unwrap_or(new_name);
+
+                is_lowercase_ident(new_ident)
+                    && new_name.ends_with('!') == old_name.ends_with('!')
+                    && roc_parse::keyword::is_allowed_identifier(new_name)
+            }
+            Target::Tag(_) | Target::Type(_) => is_uppercase_ident(new_name),
+        }
+    }
+
+    /// The same kind of target, but with a different name.
+    pub(crate) fn renamed(&self, new_name: &str) -> Target {
+        match self {
+            Target::Value(value) => Target::Value(ValueTarget {
+                ident: new_name.to_string(),
+                ..value.clone()
+            }),
+            Target::Tag(structural) => Target::Tag(structural.renamed(new_name)),
+            Target::Field(structural) => Target::Field(structural.renamed(new_name)),
+            Target::Type(ty) => Target::Type(TypeTarget {
+                name: new_name.to_string(),
+                ..ty.clone()
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ValueTarget {
+    /// The document the search started from.
+    pub(crate) origin: Url,
+    /// The symbol in the analysis of [Self::origin].
+    pub(crate) symbol: Symbol,
+    pub(crate) module_name: ModuleName,
+    pub(crate) ident: String,
+    /// Whether other modules can refer to the value, i.e. whether it's a top-level def
+    /// or an ability member.
+    pub(crate) top_level: bool,
+    /// The name of the ability that the value is a member of, if any.
+    pub(crate) ability: Option<String>,
+}
+
+impl ValueTarget {
+    pub(super) fn new(found: FoundSymbol, module: &AnalyzedModule, origin: Url) -> Self {
+        let AnalyzedModule {
+            interns,
+            abilities,
+            declarations,
+            module_id,
+            ..
+        } = module;
+
+        // Renaming a specialization means renaming the member it specializes.
+        let symbol = match found {
+            FoundSymbol::Specialization(_, specialization) => {
+                member_of_specialization(specialization, abilities).unwrap_or(specialization)
+            }
+            FoundSymbol::AbilityMember(_, member) | FoundSymbol::Symbol(member) => member,
+        };
+
+        let ability = abilities
+            .member_def(symbol)
+            .map(|member| member.parent_ability.as_str(interns).to_string());
+
+        let top_level = symbol.module_id() != *module_id
+            || ability.is_some()
+            || declarations
+                .symbols
+                .iter()
+                .any(|loc_symbol| loc_symbol.value == symbol);
+
+        Self {
+            origin,
+            symbol,
+            module_name: interns.module_name(symbol.module_id()).clone(),
+            ident: symbol.as_str(interns).to_string(),
+            top_level,
+            ability,
+        }
+    }
+
+    /// Looks the value up by name in the analysis of another document, since each analysis
+    /// has its own symbols.
+    pub(super) fn symbol_in(&self, module: &AnalyzedModule) -> Option<Symbol> {
+        let interns = &module.interns;
+        let module_id = interns.module_ids.get_id(&self.module_name)?;
+        let ident_id = interns.all_ident_ids.get(&module_id)?.get_id(&self.ident)?;
+
+        Some(Symbol::new(module_id, ident_id))
+    }
+
+    pub(crate) fn is_builtin(&self) -> bool {
+        self.symbol.module_id().is_builtin()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TypeTarget {
+    pub(crate) name: String,
+    /// The name of the module that defines the type, as it was referred to where the search
+    /// started, or None if it's not defined in a module we know of (e.g. a builtin).
+    pub(crate) module_name: Option<String>,
+}
+
+impl TypeTarget {
+    /// Whether `module_name` (e.g. from a qualifier like `Foo.` in `Foo.Bar`) names the module
+    /// that defines the type. Modules from packages are compared without their package
+    /// shorthand, which can differ from one module to the next.
+    pub(crate) fn is_defined_in(&self, module_name: &str) -> bool {
+        let unqualified = |name: &str| name.rsplit('.').next().unwrap_or(name).to_string();
+
+        (self.module_name.as_deref())
+            .is_some_and(|home| unqualified(home) == unqualified(module_name))
+    }
+}
+
+/// A tag or record field, which is only the same as another with the same name if they're in
+/// tag unions or record types of the same shape.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct StructuralTarget {
+    pub(crate) name: String,
+    /// The shape of the tag union or record type where the search started, or None if it isn't
+    /// known, e.g. because the module doesn't typecheck.
+    pub(crate) shape: Option<Shape>,
+}
+
+impl StructuralTarget {
+    fn renamed(&self, new_name: &str) -> Self {
+        Self {
+            name: new_name.to_string(),
+            shape: (self.shape.as_ref()).map(|shape| shape.renamed(&self.name, new_name)),
+        }
+    }
+}
+
+/// The tags of a tag union, or the fields of a record type. An open one, like `[Red]a` or
+/// `{ name : Str }a`, can be part of any bigger one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Shape {
+    pub(crate) names: BTreeSet<String>,
+    pub(crate) open: bool,
+}
+
+impl Shape {
+    /// Whether a tag or field in a tag union or record type of this shape can be the same as
+    /// one with the same name in `other`.
+    fn matches(&self, other: &Shape) -> bool {
+        match (self.open, other.open) {
+            (false, false) => self.names == other.names,
+            (true, false) => self.names.is_subset(&other.names),
+            (false, true) => other.names.is_subset(&self.names),
+            (true, true) => {
+                self.names.is_subset(&other.names) || other.names.is_subset(&self.names)
+            }
+        }
+    }
+
+    fn renamed(&self, old_name: &str, new_name: &str) -> Self {
+        let mut names = self.names.clone();
+
+        if names.remove(old_name) {
+            names.insert(new_name.to_string());
+        }
+
+        Self {
+            names,
+            open: self.open,
+        }
+    }
+}
+
+fn member_of_specialization(specialization: Symbol, abilities: &AbilitiesStore) -> Option<Symbol> {
+    abilities.iter_declared_implementations().find_map(
+        |(impl_key, member_impl)| match member_impl {
+            MemberImpl::Impl(symbol) if *symbol == specialization => Some(impl_key.ability_member),
+            _ => None,
+        },
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ReferenceKind {
+    Name,
+    /// A punned record field like `{ foo }`, whose value is the target. Renaming it
+    /// means spelling out the label, like `{ foo: bar }`.
+    PunnedValue,
+    /// A punned record field like `{ foo }`, whose label is the target. Renaming it
+    /// means spelling out the value, like `{ bar: foo }`.
+    PunnedLabel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Reference {
+    /// The region of the name, without any module qualifier.
+    pub(crate) region: Region,
+    pub(crate) kind: ReferenceKind,
+    /// Whether this is where the target is defined or annotated.
+    pub(crate) declaration: bool,
+}
+
+impl Reference {
+    /// The text that replaces the name when renaming `old_name` to `new_name`.
+    pub(crate) fn new_text(&self, old_name: &str, new_name: &str) -> String {
+        match self.kind {
+            ReferenceKind::Name => new_name.to_string(),
+            ReferenceKind::PunnedValue => format!("{old_name}: {new_name}"),
+            ReferenceKind::PunnedLabel => format!("{new_name}: {old_name}"),
+        }
+    }
+}
+
+pub(super) fn value_references(
+    target: &ValueTarget,
+    symbol: Symbol,
+    module: &AnalyzedModule,
+    ast: &Ast,
+    source: &str,
+) -> Vec<Reference> {
+    let name = target.ident.as_str();
+
+    let mut references: Vec<Reference> = find_symbol_references(symbol, &module.declarations)
+        .into_iter()
+        .filter_map(|reference| symbol_reference(source, reference, name))
+        .collect();
+
+    if symbol.module_id() == module.module_id {
+        if let Some(member) = module.abilities.member_def(symbol) {
+            references.extend(reference_at(
+                source,
+                member.region.start().offset,
+                name,
+                ReferenceKind::Name,
+                true,
+            ));
+        }
+
+        if target.top_level {
+            for exposed in ast.exposed_names() {
+                if exposed.value == name {
+                    references.extend(reference_at(
+                        source,
+                        exposed.region.start().offset,
+                        name,
+                        ReferenceKind::Name,
+                        false,
+                    ));
+                }
+            }
+        }
+    } else {
+        for (module_name, imported) in ast.imported_names() {
+            if imported.value == name && module_name == target.module_name.as_str() {
+                references.extend(reference_at(
+                    source,
+                    imported.region.start().offset,
+                    name,
+                    ReferenceKind::Name,
+                    false,
+                ));
+            }
+        }
+    }
+
+    if let Some(ability) = &target.ability {
+        for impl_label in ast.ability_impl_labels() {
+            if impl_label.ability == ability && impl_label.label.value == name {
+                let kind = if impl_label.punned {
+                    ReferenceKind::PunnedLabel
+                } else {
+                    ReferenceKind::Name
+                };
+
+                references.extend(reference_at(
+                    source,
+                    impl_label.label.region.start().offset,
+                    name,
+                    kind,
+                    false,
+                ));
+            }
+        }
+    }
+
+    sorted(references)
+}
+
+pub(super) fn tag_references(
+    target: &StructuralTarget,
+    module: Option<&AnalyzedModule>,
+    ast: &Ast,
+    source: &str,
+) -> Vec<Reference> {
+    let name = target.name.as_str();
+    let references = token_references(ast, source, |token, text| match token {
+        Token::Tag if text == name => Some(0),
+        _ => None,
+    });
+
+    sorted(in_shape(references, target, &tag_shapes(name, module, ast)))
+}
+
+pub(super) fn field_references(
+    target: &StructuralTarget,
+    module: Option<&AnalyzedModule>,
+    ast: &Ast,
+    source: &str,
+) -> Vec<Reference> {
+    let name = target.name.as_str();
+    let mut references = token_references(ast, source, |token, text| match token {
+        Token::Field if text == name => Some(0),
+        // Accessor functions like `.foo`, and record updaters like `&foo`
+        Token::Function if text.len() == name.len() + 1 && text.ends_with(name) => Some(1),
+        _ => None,
+    });
+
+    if let Some(module) = module {
+        for region in find_field_puns(name, &module.declarations) {
+            references.extend(reference_at(
+                source,
+                region.start().offset,
+                name,
+                ReferenceKind::PunnedLabel,
+                false,
+            ));
+        }
+    }
+
+    sorted(in_shape(
+        references,
+        target,
+        &field_shapes(name, module, ast),
+    ))
+}
+
+/// The shapes of the tag unions that the tags named `name` are in, along with the regions of
+/// those names.
+pub(super) fn tag_shapes(
+    name: &str,
+    module: Option<&AnalyzedModule>,
+    ast: &Ast,
+) -> Vec<(Region, Shape)> {
+    let mut shapes: Vec<_> = (AnnotatedShapes::new(ast).tags.into_iter())
+        .filter(|(tag, _)| tag.value == name)
+        .map(|(tag, shape)| (tag.region, shape))
+        .collect();
+
+    if let Some(module) = module {
+        shapes.extend(
+            (find_tag_uses(name, &module.declarations).into_iter())
+                .filter_map(|(region, var)| Some((region, solved_shape(&module.subs, var)?))),
+        );
+    }
+
+    shapes
+}
+
+/// The shapes of the record types that the fields named `name` are in, along with the regions
+/// of those names.
+pub(super) fn field_shapes(
+    name: &str,
+    module: Option<&AnalyzedModule>,
+    ast: &Ast,
+) -> Vec<(Region, Shape)> {
+    let mut shapes: Vec<_> = (AnnotatedShapes::new(ast).fields.into_iter())
+        .filter(|(field, _)| field.value == name)
+        .map(|(field, shape)| (field.region, shape))
+        .collect();
+
+    if let Some(module) = module {
+        shapes.extend(
+            (find_field_uses(name, &module.declarations).into_iter())
+                .filter_map(|(region, var)| Some((region, solved_shape(&module.subs, var)?))),
+        );
+    }
+
+    shapes
+}
+
+/// Keeps the references in a tag union or record type that can be the target's. When the
+/// target's shape isn't known, they're all kept, and when a reference's isn't, it's left out.
+fn in_shape(
+    references: Vec<Reference>,
+    target: &StructuralTarget,
+    shapes: &[(Region, Shape)],
+) -> Vec<Reference> {
+    let Some(target_shape) = &target.shape else {
+        return references;
+    };
+
+    (references.into_iter())
+        .filter(|reference| {
+            (shapes.iter())
+                .any(|(region, shape)| *region == reference.region && shape.matches(target_shape))
+        })
+        .collect()
+}
+
+/// The shape of the tag union or record type that `var` was solved to, following its extensions
+/// and any aliases.
+fn solved_shape(subs: &Subs, mut var: Variable) -> Option<Shape> {
+    let mut names = BTreeSet::new();
+
+    loop {
+        match subs.get_content_without_compacting(var) {
+            Content::Alias(_, _, real_var, _) => var = *real_var,
+            Content::RecursionVar { structure, .. } => var = *structure,
+            Content::Structure(FlatType::TagUnion(tags, ext))
+            | Content::Structure(FlatType::RecursiveTagUnion(_, tags, ext)) => {
+                let tags = subs.get_subs_slice(tags.labels());
+                names.extend(tags.iter().map(|tag| tag.0.as_str().to_string()));
+                var = ext.var();
+            }
+            Content::Structure(FlatType::FunctionOrTagUnion(tags, _, ext)) => {
+                let tags = subs.get_subs_slice(*tags);
+                names.extend(tags.iter().map(|tag| tag.0.as_str().to_string()));
+                var = ext.var();
+            }
+            Content::Structure(FlatType::Record(fields, ext)) => {
+                let fields = subs.get_subs_slice(fields.field_names());
+                names.extend(fields.iter().map(|field| field.as_str().to_string()));
+                var = *ext;
+            }
+            Content::Structure(FlatType::EmptyTagUnion | FlatType::EmptyRecord) => {
+                return Some(Shape { names, open: false });
+            }
+            Content::FlexVar(_)
+            | Content::RigidVar(_)
+            | Content::FlexAbleVar(..)
+            | Content::RigidAbleVar(..)
+                if !names.is_empty() =>
+            {
+                return Some(Shape { names, open: true });
+            }
+            _ => return None,
+        }
+    }
+}
+
+/// The tags and record fields in the type annotations of a module, with the shapes of the tag
+/// unions and record types they're in.
+struct AnnotatedShapes<'a> {
+    tags: Vec<(Loc<&'a str>, Shape)>,
+    fields: Vec<(Loc<&'a str>, Shape)>,
+}
+
+impl<'a> AnnotatedShapes<'a> {
+    fn new(ast: &'a Ast) -> Self {
+        let mut shapes = Self {
+            tags: Vec::new(),
+            fields: Vec::new(),
+        };
+
+        for annotation in ast.type_annotations() {
+            shapes.add(&annotation.value);
+        }
+
+        shapes
+    }
+
+    fn add(&mut self, annotation: &TypeAnnotation<'a>) {
+        match annotation {
+            TypeAnnotation::TagUnion { tags, ext } => {
+                let tags: Vec<_> = (tags.iter())
+                    .filter_map(|tag| match tag.value.extract_spaces().item {
+                        Tag::Apply { name, args } => Some((name, args)),
+                        Tag::SpaceBefore(..) | Tag::SpaceAfter(..) => None,
+                    })
+                    .collect();
+                let shape = Shape {
+                    names: (tags.iter())
+                        .map(|(name, _)| name.value.to_string())
+                        .collect(),
+                    open: ext.is_some(),
+                };
+
+                for (name, args) in tags {
+                    self.tags.push((name, shape.clone()));
+                    args.iter().for_each(|arg| self.add(&arg.value));
+                }
+
+                if let Some(ext) = ext {
+                    self.add(&ext.value);
+                }
+            }
+            TypeAnnotation::Record { fields, ext } => {
+                let fields: Vec<_> = (fields.iter())
+                    .filter_map(|field| match field.value.extract_spaces().item {
+                        AssignedField::RequiredValue(label, _, value)
+                        | AssignedField::OptionalValue(label, _, value)
+                        | AssignedField::IgnoredValue(label, _, value) => {
+                            Some((label, Some(value)))
+                        }
+                        AssignedField::LabelOnly(label) => Some((label, None)),
+                        AssignedField::SpaceBefore(..) | AssignedField::SpaceAfter(..) => None,
+                    })
+                    .collect();
+                let shape = Shape {
+                    names: (fields.iter())
+                        .map(|(label, _)| label.value.to_string())
+                        .collect(),
+                    open: ext.is_some(),
+                };
+
+                for (label, value) in fields {
+                    self.fields.push((label, shape.clone()));
+                    if let Some(value) = value {
+                        self.add(&value.value);
+                    }
+                }
+
+                if let Some(ext) = ext {
+                    self.add(&ext.value);
+                }
+            }
+            TypeAnnotation::Function(params, _, ret) => {
+                params.iter().for_each(|param| self.add(&param.value));
+                self.add(&ret.value);
+            }
+            TypeAnnotation::Apply(_, _, args) => args.iter().for_each(|arg| self.add(&arg.value)),
+            TypeAnnotation::Tuple { elems, ext } => {
+                elems.iter().for_each(|elem| self.add(&elem.value));
+                if let Some(ext) = ext {
+                    self.add(&ext.value);
+                }
+            }
+            TypeAnnotation::As(annotation, _, _) | TypeAnnotation::Where(annotation, _) => {
+                self.add(&annotation.value)
+            }
+            TypeAnnotation::SpaceBefore(annotation, _)
+            | TypeAnnotation::SpaceAfter(annotation, _) => self.add(annotation),
+            TypeAnnotation::BoundVariable(_)
+            | TypeAnnotation::Inferred
+            | TypeAnnotation::Wildcard
+            | TypeAnnotation::Malformed(_) => {}
+        }
+    }
+}
+
+/// The references to the type in a module named `module_name`, which is either the module that
+/// defines the type, or one that imports it.
+pub(super) fn type_references(
+    target: &TypeTarget,
+    module_name: &str,
+    ast: &Ast,
+    source: &str,
+) -> Vec<Reference> {
+    let name = target.name.as_str();
+    let is_home = target.is_defined_in(module_name);
+
+    // Unqualified names refer to the type if it's defined here, or exposed by an import
+    // (unless this module has a type with the same name, which it would shadow).
+    let unqualified_refers = is_home
+        || (!ast.defines_type(name)
+            && (ast.imported_names().iter()).any(|(imported_from, imported)| {
+                imported.value == name && target.is_defined_in(imported_from)
+            }));
+
+    let mut references = token_references(ast, source, |token, text| match token {
+        Token::Type | Token::Ability if text == name => Some(0),
+        // Opaque references like `@Foo`, which only the defining module can use
+        Token::Type if is_home && text.strip_prefix('@') == Some(name) => Some(1),
+        _ => None,
+    });
+
+    references.retain(|reference| {
+        match module_qualifier(source, reference.region.start().offset as usize) {
+            Some(qualifier) => target.is_defined_in(qualifier),
+            None => unqualified_refers,
+        }
+    });
+
+    for (imported_from, imported) in ast.imported_names() {
+        if imported.value == name && target.is_defined_in(&imported_from) {
+            references.extend(reference_at(
+                source,
+                imported.region.start().offset,
+                name,
+                ReferenceKind::Name,
+                false,
+            ));
+        }
+    }
+
+    sorted(references)
+}
+
+/// The module name that qualifies the name starting at `start`, like `Foo` in `Foo.Bar`
+/// or `pf.Foo` in `pf.Foo.Bar`.
+pub(super) fn module_qualifier(source: &str, start: usize) -> Option<&str> {
+    let before = source.get(..start)?.strip_suffix('.')?;
+    let qualifier_start = before
+        .trim_end_matches(|c: char| c.is_alphanumeric() || c == '_' || c == '.')
+        .len();
+
+    Some(&before[qualifier_start..]).filter(|qualifier| !qualifier.is_empty())
+}
+
+/// Finds the tokens that `name_offset` accepts, which returns how far into the token
+/// the name starts.
+fn token_references(
+    ast: &Ast,
+    source: &str,
+    name_offset: impl Fn(&Token, &str) -> Option<u32>,
+) -> Vec<Reference> {
+    ast.semantic_tokens()
+        .into_iter()
+        .filter_map(|token| {
+            let region = token.region;
+            let text = source.get(region.start().offset as usize..region.end().offset as usize)?;
+            let offset = name_offset(&token.value, text)?;
+            let start = Position::new(region.start().offset + offset);
+
+            Some(Reference {
+                region: Region::new(start, region.end()),
+                kind: ReferenceKind::Name,
+                declaration: false,
+            })
+        })
+        .collect()
+}
+
+/// Narrows down a reference found in the canonical AST to the name of the symbol.
+fn symbol_reference(source: &str, reference: SymbolReference, name: &str) -> Option<Reference> {
+    let offset = |position: Position| position.offset as usize;
+
+    let (start, kind, declaration) = match reference {
+        SymbolReference::Definition(region) => (offset(region.start()), ReferenceKind::Name, true),
+        SymbolReference::Name(region) => {
+            let text = source.get(offset(region.start())..offset(region.end()))?;
+
+            (
+                offset(region.start()) + unqualified_name_start(text),
+                ReferenceKind::Name,
+                false,
+            )
+        }
+        SymbolReference::PunnedField(region) => {
+            (offset(region.start()), ReferenceKind::PunnedValue, false)
+        }
+        SymbolReference::Annotation(region) => {
+            // `name : Type`, where the region is the type
+            let before = source.get(..offset(region.start()))?.trim_end();
+            let before = before.strip_suffix(':')?.trim_end();
+
+            (
+                before.len().checked_sub(name.len())?,
+                ReferenceKind::Name,
+                true,
+            )
+        }
+        SymbolReference::RecordUpdate(region) => {
+            // `{ name & field: value }`
+            let after_brace = source.get(offset(region.start())..)?.strip_prefix('{')?;
+            let spaces = after_brace.len() - after_brace.trim_start().len();
+
+            (
+                offset(region.start()) + 1 + spaces,
+                ReferenceKind::Name,
+                false,
+            )
+        }
+        SymbolReference::As(region) => (
+            offset(region.end()).checked_sub(name.len())?,
+            ReferenceKind::Name,
+            true,
+        ),
+    };
+
+    reference_at(source, start as u32, name, kind, declaration)
+}
+
+/// Where the name starts in a possibly qualified reference, like `Foo.name`, `pf.Foo.name`
+/// or `name.field`: right after the last module name, if there is one.
+fn unqualified_name_start(text: &str) -> usize {
+    let mut start = 0;
+    let mut name_start = 0;
+
+    for segment in text.split('.') {
+        if segment.starts_with(|c: char| c.is_uppercase()) {
+            name_start = start + segment.len() + 1;
+        }
+
+        start += segment.len() + 1;
+    }
+
+    name_start.min(text.len())
+}
+
+/// A reference to `name` at `start`, if that's really where the name is in the source.
+fn reference_at(
+    source: &str,
+    start: u32,
+    name: &str,
+    kind: ReferenceKind,
+    declaration: bool,
+) -> Option<Reference> {
+    let end = start + name.len() as u32;
+    let text = source.get(start as usize..end as usize)?;
+    let is_whole_name = !source[end as usize..].starts_with(is_ident_char);
+
+    (text == name && is_whole_name).then_some(Reference {
+        region: Region::new(Position::new(start), Position::new(end)),
+        kind,
+        declaration,
+    })
+}
+
+fn sorted(mut references: Vec<Reference>) -> Vec<Reference> {
+    // When a name is found as both a punned field and a plain name, the pun wins.
+    references.sort_by_key(|reference| {
+        (
+            reference.region.start(),
+            reference.kind == ReferenceKind::Name,
+        )
+    });
+    references.dedup_by_key(|reference| reference.region);
+
+    references
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '!'
+}
+
+fn is_lowercase_ident(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase()) && name.chars().all(is_ident_char_no_bang)
+}
+
+fn is_uppercase_ident(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase()) && name.chars().all(is_ident_char_no_bang)
+}
+
+fn is_ident_char_no_bang(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
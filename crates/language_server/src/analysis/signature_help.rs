@@ -0,0 +1,365 @@
+//! Signature help: the type of the function that's being applied at the cursor, with the
+//! argument that's being written highlighted.
+use roc_module::{called_via::BinOp, symbol::Symbol};
+use roc_parse::ast::{AssignedField, Defs, Expr, ExtractSpaces, ValueDef};
+use roc_region::all::{Loc, Position, Region};
+use roc_types::subs::Variable;
+use tower_lsp::lsp_types::{
+    Documentation, MarkupContent, MarkupKind, ParameterInformation, ParameterLabel, SignatureHelp,
+    SignatureInformation,
+};
+
+use super::{completion::get_completions, utils::format_var_type, AnalyzedModule};
+
+/// A function application that the cursor is in.
+pub(super) struct Call<'a> {
+    module_name: &'a str,
+    ident: &'a str,
+    /// Where the function is named.
+    region: Region,
+    /// The argument that the cursor is in, counting one that's piped in.
+    active_argument: u32,
+}
+
+/// The applications that the cursor at `position` is in, innermost first. A function name
+/// followed by whitespace counts as an application that has no arguments yet.
+pub(super) fn calls_at<'a>(defs: &Defs<'a>, source: &str, position: Position) -> Vec<Call<'a>> {
+    let finder = CallFinder { source, position };
+    let mut calls = Vec::new();
+
+    finder.defs(defs, &mut calls);
+
+    calls
+}
+
+struct CallFinder<'s> {
+    source: &'s str,
+    position: Position,
+}
+
+impl CallFinder<'_> {
+    /// Whether the cursor is in `region`, or in the whitespace right after it on the same line.
+    fn covers(&self, region: Region) -> bool {
+        if self.position < region.start() {
+            return false;
+        }
+
+        self.position <= region.end()
+            || (self.source)
+                .get(region.end().offset as usize..self.position.offset as usize)
+                .is_some_and(|between| between.chars().all(|c| c == ' ' || c == '\t'))
+    }
+
+    fn defs<'a>(&self, defs: &Defs<'a>, calls: &mut Vec<Call<'a>>) {
+        for (def, region) in defs.defs().zip(&defs.regions) {
+            if !self.covers(*region) {
+                continue;
+            }
+
+            match def {
+                Err(ValueDef::Body(_, expr))
+                | Err(ValueDef::AnnotatedBody {
+                    body_expr: expr, ..
+                })
+                | Err(ValueDef::Dbg {
+                    condition: expr, ..
+                })
+                | Err(ValueDef::Expect {
+                    condition: expr, ..
+                })
+                | Err(ValueDef::Stmt(expr)) => self.expr(&expr.value, expr.region, false, calls),
+                _ => {}
+            }
+        }
+    }
+
+    fn expr<'a>(&self, expr: &Expr<'a>, region: Region, piped: bool, calls: &mut Vec<Call<'a>>) {
+        if !self.covers(region) {
+            return;
+        }
+
+        let piped_argument = piped as u32;
+
+        match expr {
+            Expr::Var { module_name, ident } if self.position > region.end() => {
+                calls.push(Call {
+                    module_name,
+                    ident,
+                    region,
+                    active_argument: piped_argument,
+                });
+            }
+            Expr::Apply(function, arguments, _) => {
+                (arguments.iter()).for_each(|argument| self.argument(argument, calls));
+
+                if let Expr::Var { module_name, ident } = function.value.extract_spaces().item {
+                    let index = (arguments.iter())
+                        .position(|argument| self.position <= argument.region.end())
+                        .unwrap_or(arguments.len());
+
+                    calls.push(Call {
+                        module_name,
+                        ident,
+                        region: function.region,
+                        active_argument: index as u32 + piped_argument,
+                    });
+                }
+            }
+            Expr::PncApply(function, arguments) => {
+                // Only inside of the parens
+                if self.position <= function.region.end() || self.position >= region.end() {
+                    return;
+                }
+
+                (arguments.iter()).for_each(|argument| self.argument(argument, calls));
+
+                if let Expr::Var { module_name, ident } = function.value.extract_spaces().item {
+                    let index = (arguments.iter())
+                        .position(|argument| self.position <= argument.region.end())
+                        .unwrap_or(arguments.len());
+
+                    calls.push(Call {
+                        module_name,
+                        ident,
+                        region: function.region,
+                        active_argument: index as u32 + piped_argument,
+                    });
+                }
+            }
+            Expr::BinOps(operands, last) => {
+                let mut piped = false;
+
+                for (operand, operator) in operands.iter() {
+                    self.expr(&operand.value, operand.region, piped, calls);
+                    piped = operator.value == BinOp::Pizza;
+                }
+
+                self.expr(&last.value, last.region, piped, calls);
+            }
+            Expr::Defs(defs, final_expr) => {
+                self.defs(defs, calls);
+                self.expr(&final_expr.value, final_expr.region, false, calls);
+            }
+            Expr::Closure(_, body) => self.expr(&body.value, body.region, false, calls),
+            Expr::When(condition, branches) => {
+                self.expr(&condition.value, condition.region, false, calls);
+
+                for branch in branches.iter() {
+                    if let Some(guard) = &branch.guard {
+                        self.expr(&guard.value, guard.region, false, calls);
+                    }
+
+                    self.expr(&branch.value.value, branch.value.region, false, calls);
+                }
+            }
+            Expr::If {
+                if_thens,
+                final_else,
+                ..
+            } => {
+                for (condition, then) in if_thens.iter() {
+                    self.expr(&condition.value, condition.region, false, calls);
+                    self.expr(&then.value, then.region, false, calls);
+                }
+
+                self.expr(&final_else.value, final_else.region, false, calls);
+            }
+            Expr::List(items) | Expr::Tuple(items) => {
+                (items.iter()).for_each(|item| self.expr(&item.value, item.region, false, calls))
+            }
+            Expr::Record(fields) => self.fields(fields.items, calls),
+            Expr::RecordUpdate { update, fields } => {
+                self.expr(&update.value, update.region, false, calls);
+                self.fields(fields.items, calls);
+            }
+            Expr::RecordBuilder { mapper, fields } => {
+                self.expr(&mapper.value, mapper.region, false, calls);
+                self.fields(fields.items, calls);
+            }
+            Expr::DbgStmt {
+                first,
+                extra_args,
+                continuation,
+                ..
+            } => {
+                self.expr(&first.value, first.region, false, calls);
+                (extra_args.iter())
+                    .for_each(|argument| self.expr(&argument.value, argument.region, false, calls));
+                self.expr(&continuation.value, continuation.region, false, calls);
+            }
+            Expr::LowLevelDbg(_, message, continuation) => {
+                self.expr(&message.value, message.region, false, calls);
+                self.expr(&continuation.value, continuation.region, false, calls);
+            }
+            Expr::Return(value, after) => {
+                self.expr(&value.value, value.region, false, calls);
+
+                if let Some(after) = after {
+                    self.expr(&after.value, after.region, false, calls);
+                }
+            }
+            Expr::UnaryOp(expr, _) | Expr::LowLevelTry(expr, _) => {
+                self.expr(&expr.value, expr.region, false, calls)
+            }
+            Expr::RecordAccess(expr, _) | Expr::TupleAccess(expr, _) | Expr::TrySuffix(expr) => {
+                self.expr(expr, region, false, calls)
+            }
+            Expr::ParensAround(expr) | Expr::SpaceBefore(expr, _) | Expr::SpaceAfter(expr, _) => {
+                self.expr(expr, region, piped, calls)
+            }
+            _ => {}
+        }
+    }
+
+    /// An argument that's just a name is a value being passed, rather than a function that's
+    /// about to be applied.
+    fn argument<'a>(&self, argument: &Loc<Expr<'a>>, calls: &mut Vec<Call<'a>>) {
+        if !matches!(argument.value.extract_spaces().item, Expr::Var { .. }) {
+            self.expr(&argument.value, argument.region, false, calls);
+        }
+    }
+
+    fn fields<'a>(&self, fields: &[Loc<AssignedField<'a, Expr<'a>>>], calls: &mut Vec<Call<'a>>) {
+        for field in fields {
+            match field.value.extract_spaces().item {
+                AssignedField::RequiredValue(_, _, value)
+                | AssignedField::OptionalValue(_, _, value)
+                | AssignedField::IgnoredValue(_, _, value) => {
+                    self.expr(&value.value, value.region, false, calls)
+                }
+                AssignedField::LabelOnly(_)
+                | AssignedField::SpaceBefore(..)
+                | AssignedField::SpaceAfter(..) => {}
+            }
+        }
+    }
+}
+
+/// The signature of the innermost of `calls` that applies a function we know the type of.
+/// `scope_position` moves a position in the source that `calls` were found in to the
+/// analyzed source, where the names in scope are looked up.
+pub(super) fn signature_help(
+    module: &AnalyzedModule,
+    calls: Vec<Call>,
+    scope_position: impl Fn(Position) -> Position,
+) -> Option<SignatureHelp> {
+    calls.into_iter().find_map(|call| {
+        let (symbol, var) = resolve(module, &call, scope_position(call.region.start()))?;
+
+        let AnalyzedModule {
+            subs,
+            module_id,
+            interns,
+            modules_info,
+            ..
+        } = module;
+
+        let type_str = if symbol.module_id() == *module_id {
+            format_var_type(var, &mut subs.clone(), module_id, interns)
+        } else {
+            modules_info.with_subs(&symbol.module_id(), |subs| {
+                format_var_type(var, subs, module_id, interns)
+            })?
+        };
+
+        let name = match call.module_name {
+            "" => call.ident.to_string(),
+            module_name => format!("{}.{}", module_name, call.ident),
+        };
+        let label = format!("{name} : {type_str}");
+        let prefix_len = label.len() - type_str.len();
+
+        let utf16_offset =
+            |offset: usize| label[..prefix_len + offset].encode_utf16().count() as u32;
+        let parameters = (parameter_ranges(&type_str)?.into_iter())
+            .map(|(start, end)| ParameterInformation {
+                label: ParameterLabel::LabelOffsets([utf16_offset(start), utf16_offset(end)]),
+                documentation: None,
+            })
+            .collect::<Vec<_>>();
+
+        let documentation = (modules_info.get_docs(&symbol.module_id()))
+            .and_then(|docs| docs.get_doc_for_symbol(&symbol))
+            .map(|docs| {
+                Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: docs,
+                })
+            });
+
+        Some(SignatureHelp {
+            signatures: vec![SignatureInformation {
+                label,
+                documentation,
+                parameters: Some(parameters),
+                active_parameter: Some(call.active_argument),
+            }],
+            active_signature: Some(0),
+            active_parameter: Some(call.active_argument),
+        })
+    })
+}
+
+/// The symbol that `call` applies, and its type.
+fn resolve(
+    module: &AnalyzedModule,
+    call: &Call,
+    scope_position: Position,
+) -> Option<(Symbol, Variable)> {
+    let AnalyzedModule {
+        interns,
+        declarations,
+        exposed_imports,
+        imports_by_module,
+        ..
+    } = module;
+
+    let is_named = |symbol: &Symbol| symbol.as_str(interns) == call.ident;
+
+    if call.module_name.is_empty() {
+        get_completions(
+            scope_position,
+            declarations,
+            call.ident.to_string(),
+            interns,
+        )
+        .into_iter()
+        .chain(exposed_imports.iter().copied())
+        .find(|(symbol, _)| is_named(symbol))
+    } else {
+        let (_, exposed) = (imports_by_module.iter())
+            .find(|(module_id, _)| module_id.to_ident_str(interns).as_str() == call.module_name)?;
+
+        exposed.iter().copied().find(|(symbol, _)| is_named(symbol))
+    }
+}
+
+/// The byte ranges of the parameters in a printed function type, or None if it's not a
+/// function type.
+fn parameter_ranges(type_str: &str) -> Option<Vec<(usize, usize)>> {
+    let mut depth = 0;
+    let mut ranges = Vec::new();
+    let mut start = 0;
+
+    for (index, c) in type_str.char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                ranges.push((start, index));
+                start = index + 2;
+            }
+            ' ' if depth == 0
+                && (type_str[index..].starts_with(" -> ")
+                    || type_str[index..].starts_with(" => ")) =>
+            {
+                ranges.push((start, index));
+
+                return Some(ranges);
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
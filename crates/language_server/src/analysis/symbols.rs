@@ -0,0 +1,331 @@
+//! The symbols that a module defines, for the outline of a document, and for searching for
+//! symbols by name across the workspace.
+use roc_parse::ast::{
+    AssignedField, Defs, Expr, ExtractSpaces, Pattern, TypeAnnotation, TypeDef, ValueDef,
+};
+use roc_region::all::{LineInfo, Loc, Region};
+use tower_lsp::lsp_types::{DocumentSymbol, SymbolInformation, SymbolKind};
+
+use crate::convert::ToRange;
+
+use super::tokens::subregion;
+
+/// A def, type or ability member, with the symbols defined inside of it.
+pub struct DefSymbol<'a> {
+    pub name: Loc<&'a str>,
+    pub kind: SymbolKind,
+    /// The whole def, including its annotation.
+    pub region: Region,
+    /// The type annotation of the def, if it has one.
+    pub annotation: Option<Region>,
+    pub children: Vec<DefSymbol<'a>>,
+}
+
+impl<'a> DefSymbol<'a> {
+    #[allow(deprecated)] // DocumentSymbol::deprecated
+    pub fn to_document_symbol(&self, source: &str, line_info: &LineInfo) -> DocumentSymbol {
+        let detail = self.annotation.map(|annotation| {
+            let text =
+                &source[annotation.start().offset as usize..annotation.end().offset as usize];
+
+            text.split_whitespace().collect::<Vec<_>>().join(" ")
+        });
+
+        let children = (self.children.iter())
+            .map(|child| child.to_document_symbol(source, line_info))
+            .collect::<Vec<_>>();
+
+        DocumentSymbol {
+            name: self.name.value.to_string(),
+            detail,
+            kind: self.kind,
+            tags: None,
+            deprecated: None,
+            range: self.region.to_range(line_info),
+            selection_range: self.name.region.to_range(line_info),
+            children: (!children.is_empty()).then_some(children),
+        }
+    }
+}
+
+/// The symbols for the defs, types and abilities in `defs`.
+pub fn defs_symbols<'a>(defs: &Defs<'a>, top_level: bool) -> Vec<DefSymbol<'a>> {
+    let mut symbols = Vec::new();
+
+    for (def, region) in defs.defs().zip(&defs.regions) {
+        match def {
+            Ok(type_def) => symbols.push(type_def_symbol(type_def, *region)),
+            Err(value_def) => value_def_symbols(value_def, *region, top_level, &mut symbols),
+        }
+    }
+
+    symbols
+}
+
+fn type_def_symbol<'a>(type_def: &TypeDef<'a>, region: Region) -> DefSymbol<'a> {
+    match type_def {
+        TypeDef::Alias { header, .. } => DefSymbol {
+            name: header.name,
+            kind: SymbolKind::TYPE_PARAMETER,
+            region,
+            annotation: None,
+            children: Vec::new(),
+        },
+        TypeDef::Opaque { header, .. } => DefSymbol {
+            name: header.name,
+            kind: SymbolKind::STRUCT,
+            region,
+            annotation: None,
+            children: Vec::new(),
+        },
+        TypeDef::Ability {
+            header, members, ..
+        } => DefSymbol {
+            name: header.name,
+            kind: SymbolKind::INTERFACE,
+            region,
+            annotation: None,
+            children: (members.iter())
+                .map(|member| DefSymbol {
+                    name: member.name.map(|name| *name.item()),
+                    kind: SymbolKind::METHOD,
+                    region: member.region(),
+                    annotation: Some(member.typ.region),
+                    children: Vec::new(),
+                })
+                .collect(),
+        },
+    }
+}
+
+fn value_def_symbols<'a>(
+    value_def: &ValueDef<'a>,
+    region: Region,
+    top_level: bool,
+    symbols: &mut Vec<DefSymbol<'a>>,
+) {
+    let (pattern, annotation, body) = match value_def {
+        ValueDef::Annotation(pattern, annotation) => (pattern, Some(annotation), None),
+        ValueDef::Body(pattern, body) => (*pattern, None, Some(*body)),
+        ValueDef::AnnotatedBody {
+            ann_pattern,
+            ann_type,
+            body_expr,
+            ..
+        } => (*ann_pattern, Some(*ann_type), Some(*body_expr)),
+        ValueDef::Dbg { .. }
+        | ValueDef::Expect { .. }
+        | ValueDef::ModuleImport(_)
+        | ValueDef::IngestedFileImport(_)
+        | ValueDef::Stmt(_)
+        | ValueDef::StmtAfterExpr => return,
+    };
+
+    let is_function = match (body, annotation) {
+        (Some(body), _) => matches!(body.value.extract_spaces().item, Expr::Closure(..)),
+        (None, Some(annotation)) => matches!(
+            annotation.value.extract_spaces().item,
+            TypeAnnotation::Function(..)
+        ),
+        (None, None) => false,
+    };
+
+    let kind = if is_function {
+        SymbolKind::FUNCTION
+    } else if top_level {
+        SymbolKind::CONSTANT
+    } else {
+        SymbolKind::VARIABLE
+    };
+
+    let mut names = Vec::new();
+    pattern_names(pattern, &mut names);
+
+    let mut children = Vec::new();
+
+    if let Some(body) = body {
+        expr_symbols(&body.value, &mut children);
+    }
+
+    match names.as_slice() {
+        [name] => symbols.push(DefSymbol {
+            name: *name,
+            kind,
+            region,
+            annotation: annotation.map(|annotation| annotation.region),
+            children,
+        }),
+        // A destructure; the defs in its body can't be nested under one of the names.
+        _ => {
+            symbols.extend(names.into_iter().map(|name| DefSymbol {
+                name,
+                kind,
+                region,
+                annotation: None,
+                children: Vec::new(),
+            }));
+            symbols.extend(children);
+        }
+    }
+}
+
+/// The names that `pattern` introduces.
+fn pattern_names<'a>(loc_pattern: &Loc<Pattern<'a>>, names: &mut Vec<Loc<&'a str>>) {
+    let region = loc_pattern.region;
+
+    match loc_pattern.value {
+        Pattern::Identifier { ident } => {
+            names.push(Loc::at(subregion(region, 0, ident.len() as u32), ident))
+        }
+        Pattern::OptionalField(label, _) => {
+            names.push(Loc::at(subregion(region, 0, label.len() as u32), label))
+        }
+        Pattern::RequiredField(_, pattern) => pattern_names(pattern, names),
+        Pattern::As(pattern, pattern_as) => {
+            pattern_names(pattern, names);
+            names.push(pattern_as.identifier);
+        }
+        Pattern::Apply(_, arguments) => {
+            (arguments.iter()).for_each(|argument| pattern_names(argument, names))
+        }
+        Pattern::PncApply(_, arguments) => {
+            (arguments.iter()).for_each(|argument| pattern_names(argument, names))
+        }
+        Pattern::RecordDestructure(patterns)
+        | Pattern::Tuple(patterns)
+        | Pattern::List(patterns) => {
+            (patterns.iter()).for_each(|pattern| pattern_names(pattern, names))
+        }
+        Pattern::SpaceBefore(pattern, _) | Pattern::SpaceAfter(pattern, _) => {
+            pattern_names(&Loc::at(region, *pattern), names)
+        }
+        _ => {}
+    }
+}
+
+/// The symbols for the defs nested anywhere in `expr`.
+fn expr_symbols<'a>(expr: &Expr<'a>, symbols: &mut Vec<DefSymbol<'a>>) {
+    match expr {
+        Expr::Defs(defs, final_expr) => {
+            symbols.extend(defs_symbols(defs, false));
+            expr_symbols(&final_expr.value, symbols);
+        }
+        Expr::Closure(_, body) => expr_symbols(&body.value, symbols),
+        Expr::When(condition, branches) => {
+            expr_symbols(&condition.value, symbols);
+
+            for branch in branches.iter() {
+                if let Some(guard) = &branch.guard {
+                    expr_symbols(&guard.value, symbols);
+                }
+
+                expr_symbols(&branch.value.value, symbols);
+            }
+        }
+        Expr::If {
+            if_thens,
+            final_else,
+            ..
+        } => {
+            for (condition, then) in if_thens.iter() {
+                expr_symbols(&condition.value, symbols);
+                expr_symbols(&then.value, symbols);
+            }
+
+            expr_symbols(&final_else.value, symbols);
+        }
+        Expr::Apply(function, arguments, _) => {
+            expr_symbols(&function.value, symbols);
+            (arguments.iter()).for_each(|argument| expr_symbols(&argument.value, symbols));
+        }
+        Expr::PncApply(function, arguments) => {
+            expr_symbols(&function.value, symbols);
+            (arguments.iter()).for_each(|argument| expr_symbols(&argument.value, symbols));
+        }
+        Expr::BinOps(operands, last) => {
+            (operands.iter()).for_each(|(operand, _)| expr_symbols(&operand.value, symbols));
+            expr_symbols(&last.value, symbols);
+        }
+        Expr::List(items) | Expr::Tuple(items) => {
+            (items.iter()).for_each(|item| expr_symbols(&item.value, symbols))
+        }
+        Expr::Record(fields) => {
+            for field in fields.iter() {
+                match field.value.extract_spaces().item {
+                    AssignedField::RequiredValue(_, _, value)
+                    | AssignedField::OptionalValue(_, _, value)
+                    | AssignedField::IgnoredValue(_, _, value) => {
+                        expr_symbols(&value.value, symbols)
+                    }
+                    AssignedField::LabelOnly(_)
+                    | AssignedField::SpaceBefore(..)
+                    | AssignedField::SpaceAfter(..) => {}
+                }
+            }
+        }
+        Expr::UnaryOp(expr, _)
+        | Expr::LowLevelTry(expr, _)
+        | Expr::Return(expr, _)
+        | Expr::LowLevelDbg(_, _, expr) => expr_symbols(&expr.value, symbols),
+        Expr::RecordAccess(expr, _)
+        | Expr::TupleAccess(expr, _)
+        | Expr::TrySuffix(expr)
+        | Expr::ParensAround(expr)
+        | Expr::SpaceBefore(expr, _)
+        | Expr::SpaceAfter(expr, _) => expr_symbols(expr, symbols),
+        _ => {}
+    }
+}
+
+/// How well `name` matches `query`, or None if it doesn't. The characters of `query` have to
+/// appear in `name` in order, ignoring case; consecutive characters, and characters at the
+/// start of a word, make a better match.
+pub fn fuzzy_match_score(query: &str, name: &str) -> Option<u32> {
+    let mut score = 0;
+    let mut name_chars = name.char_indices();
+    let mut previous: Option<(usize, char)> = None;
+    let mut last_match: Option<usize> = None;
+
+    for query_char in query.chars() {
+        loop {
+            let (index, name_char) = name_chars.next()?;
+            let before = previous;
+            previous = Some((index, name_char));
+
+            if !name_char.to_lowercase().eq(query_char.to_lowercase()) {
+                continue;
+            }
+
+            let starts_word = match before {
+                None => true,
+                Some((_, before)) => {
+                    !before.is_alphanumeric() || (before.is_lowercase() && name_char.is_uppercase())
+                }
+            };
+
+            score += 1;
+
+            if starts_word {
+                score += 2;
+            }
+
+            if last_match.is_some() && last_match == before.map(|(index, _)| index) {
+                score += 3;
+            }
+
+            last_match = Some(index);
+
+            break;
+        }
+    }
+
+    Some(score)
+}
+
+/// A top-level symbol that matches a workspace symbol search.
+pub struct SymbolMatch {
+    pub score: u32,
+    /// Whether the module exposes the symbol, which makes it more likely to be what's wanted.
+    pub exposed: bool,
+    pub symbol: SymbolInformation,
+}
@@ -16,7 +16,7 @@ use roc_parse::{
     },
     ident::{Accessor, UppercaseIdent},
 };
-use roc_region::all::{Loc, Region};
+use roc_region::all::{Loc, Position, Region};
 use tower_lsp::lsp_types::SemanticTokenType;
 
 macro_rules! tokens {
@@ -73,6 +73,20 @@ fn field_token(region: Region, arena: &Bump) -> BumpVec<Loc<Token>> {
     onetoken(Token::Field, region, arena)
 }
 
+/// The `len` bytes of `region` that start `offset` bytes after its start.
+pub(super) fn subregion(region: Region, offset: u32, len: u32) -> Region {
+    let start = region.start().offset + offset;
+
+    Region::new(Position::new(start), Position::new(start + len))
+}
+
+/// The last `len` bytes of `region`.
+fn suffix_region(region: Region, len: u32) -> Region {
+    let end = region.end().offset;
+
+    Region::new(Position::new(end.saturating_sub(len)), region.end())
+}
+
 trait HasToken {
     fn token(&self) -> Token;
 }
@@ -390,7 +404,19 @@ impl IterTokens for Loc<TypeAnnotation<'_>> {
                     .chain(ret.iter_tokens(arena))
                     .collect_in(arena)
             }
-            TypeAnnotation::Apply(_mod, _type, args) => args.iter_tokens(arena),
+            TypeAnnotation::Apply(module, name, args) => {
+                // The region starts with the (possibly qualified) name, like `Dict.Dict k v`
+                let offset = if module.is_empty() {
+                    0
+                } else {
+                    module.len() as u32 + 1
+                };
+                let name_region = subregion(self.region, offset, name.len() as u32);
+
+                (onetoken(Token::Type, name_region, arena).into_iter())
+                    .chain(args.iter_tokens(arena))
+                    .collect_in(arena)
+            }
             TypeAnnotation::BoundVariable(_) => onetoken(Token::Type, self.region, arena),
             TypeAnnotation::As(ty, _, as_ty) => (ty.iter_tokens(arena).into_iter())
                 .chain(as_ty.iter_tokens(arena))
@@ -651,7 +677,16 @@ impl IterTokens for Loc<Expr<'_>> {
             Expr::NonBase10Int { .. } => onetoken(Token::Number, region, arena),
             Expr::Str(_) => onetoken(Token::String, region, arena),
             Expr::SingleQuote(_) => onetoken(Token::String, region, arena),
-            Expr::RecordAccess(rcd, _field) => Loc::at(region, *rcd).iter_tokens(arena),
+            Expr::RecordAccess(rcd, field) => {
+                // `rcd.field`, so the record ends right before the dot
+                let field_region = suffix_region(region, field.len() as u32);
+                let rcd_end = field_region.start().offset.saturating_sub(1);
+                let rcd_region = Region::new(region.start(), Position::new(rcd_end));
+
+                (Loc::at(rcd_region, *rcd).iter_tokens(arena).into_iter())
+                    .chain(field_token(field_region, arena))
+                    .collect_in(arena)
+            }
             Expr::AccessorFunction(accessor) => Loc::at(region, accessor).iter_tokens(arena),
             Expr::RecordUpdater(updater) => Loc::at(region, updater).iter_tokens(arena),
             Expr::TupleAccess(tup, _field) => Loc::at(region, *tup).iter_tokens(arena),
@@ -667,6 +702,7 @@ impl IterTokens for Loc<Expr<'_>> {
                 .collect_in(arena),
             Expr::Var { .. } => onetoken(Token::Variable, region, arena),
             Expr::Underscore(_) => onetoken(Token::Variable, region, arena),
+            Expr::TypedHole(_) => onetoken(Token::Variable, region, arena),
             Expr::Crash => onetoken(Token::Keyword, region, arena),
             Expr::Tag(_) => onetoken(Token::Tag, region, arena),
             Expr::OpaqueRef(_) => onetoken(Token::Type, region, arena),
@@ -770,8 +806,16 @@ impl IterTokens for Loc<Pattern<'_>> {
                 .chain(p2.iter_tokens(arena))
                 .collect_in(arena),
             Pattern::RecordDestructure(ps) => ps.iter_tokens(arena),
-            Pattern::RequiredField(_field, p) => p.iter_tokens(arena),
-            Pattern::OptionalField(_field, p) => p.iter_tokens(arena),
+            Pattern::RequiredField(field, p) => {
+                (field_token(subregion(region, 0, field.len() as u32), arena).into_iter())
+                    .chain(p.iter_tokens(arena))
+                    .collect_in(arena)
+            }
+            Pattern::OptionalField(field, e) => {
+                (field_token(subregion(region, 0, field.len() as u32), arena).into_iter())
+                    .chain(e.iter_tokens(arena))
+                    .collect_in(arena)
+            }
             Pattern::NumLiteral(_) => onetoken(Token::Number, region, arena),
             Pattern::NonBase10Literal { .. } => onetoken(Token::Number, region, arena),
             Pattern::FloatLiteral(_) => onetoken(Token::Number, region, arena),
@@ -11,17 +11,21 @@ impl ToRange for Region {
     type Feed = LineInfo;
 
     fn to_range(&self, line_info: &LineInfo) -> Range {
-        let LineColumnRegion { start, end } = line_info.convert_region(*self);
-        Range {
-            start: Position {
-                line: start.line,
-                character: start.column,
-            },
-            end: Position {
-                line: end.line,
-                character: end.column,
-            },
-        }
+        line_column_region_to_range(line_info.convert_region(*self))
+    }
+}
+
+pub(crate) fn line_column_region_to_range(region: LineColumnRegion) -> Range {
+    let LineColumnRegion { start, end } = region;
+    Range {
+        start: Position {
+            line: start.line,
+            character: start.column,
+        },
+        end: Position {
+            line: end.line,
+            character: end.column,
+        },
     }
 }
 
@@ -68,6 +72,33 @@ impl ToRocPosition for tower_lsp::lsp_types::Position {
     }
 }
 
+/// The byte offset of `position` in `text`, counting the character of the position in UTF-16
+/// code units, which is what clients do unless told otherwise. Positions past the end of a
+/// line, or of the text, are moved back to it.
+pub(crate) fn text_offset(text: &str, position: Position) -> usize {
+    let line_start = match position.line {
+        0 => 0,
+        line => match text.match_indices('\n').nth(line as usize - 1) {
+            Some((newline, _)) => newline + 1,
+            None => return text.len(),
+        },
+    };
+
+    let line = &text[line_start..];
+    let line = &line[..line.find('\n').unwrap_or(line.len())];
+    let mut utf16_column = 0;
+
+    for (index, char) in line.char_indices() {
+        if utf16_column >= position.character as usize {
+            return line_start + index;
+        }
+
+        utf16_column += char.len_utf16();
+    }
+
+    line_start + line.len()
+}
+
 pub(crate) mod diag {
     use std::path::Path;
 
@@ -76,10 +107,10 @@ pub(crate) mod diag {
     use roc_solve_problem::TypeError;
 
     use roc_problem::Severity;
-    use roc_reporting::report::RocDocAllocator;
+    use roc_reporting::report::{RenderedReport, RocDocAllocator};
     use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
 
-    use super::ToRange;
+    use super::{line_column_region_to_range, ToRange};
 
     pub trait IntoLspSeverity {
         fn into_lsp_severity(self) -> DiagnosticSeverity;
@@ -105,13 +136,19 @@ pub(crate) mod diag {
         type Feed = LineInfo;
 
         fn into_lsp_diagnostic(self, line_info: &LineInfo) -> Option<Diagnostic> {
-            let range = self
-                .get_region()
-                .unwrap_or(Region::new(
-                    roc_region::all::Position::new(0),
-                    roc_region::all::Position::new(10_000_000),
-                ))
-                .to_range(line_info);
+            let range = match self {
+                LoadingProblem::FormattedReport(RenderedReport {
+                    region: Some(region),
+                    ..
+                }) => line_column_region_to_range(*region),
+                _ => self
+                    .get_region()
+                    .unwrap_or(Region::new(
+                        roc_region::all::Position::new(0),
+                        roc_region::all::Position::new(10_000_000),
+                    ))
+                    .to_range(line_info),
+            };
 
             let msg = match self {
                 LoadingProblem::FileProblem { filename, error } => {
@@ -149,7 +186,7 @@ pub(crate) mod diag {
                 LoadingProblem::TriedToImportAppModule => {
                     "Attempted to import app module".to_string()
                 }
-                LoadingProblem::FormattedReport(report, _region) => report.clone(),
+                LoadingProblem::FormattedReport(report) => report.text.clone(),
                 LoadingProblem::ImportCycle(_, _) => {
                     "Circular dependency between modules".to_string()
                 }
@@ -9,11 +9,17 @@ use std::{
 use tokio::sync::{Mutex, MutexGuard};
 
 use tower_lsp::lsp_types::{
-    CompletionResponse, Diagnostic, GotoDefinitionResponse, Hover, Position, SemanticTokensResult,
-    TextEdit, Url,
+    CodeActionContext, CodeActionOrCommand, CodeActionResponse, CodeLens, CompletionResponse,
+    Diagnostic, DocumentSymbolResponse, FoldingRange, GotoDefinitionResponse, Hover, InlayHint,
+    Location, Position, PrepareRenameResponse, Range, SelectionRange, SemanticTokensResult,
+    SignatureHelp, SymbolInformation, TextEdit, Url, WorkspaceEdit,
 };
 
-use crate::analysis::{AnalyzedDocument, DocInfo};
+use crate::analysis::{AnalyzedDocument, DocInfo, SymbolMatch, Target};
+
+/// The most symbols to return from a workspace symbol search, which can match most of the
+/// workspace when the query is short.
+const MAX_WORKSPACE_SYMBOLS: usize = 256;
 
 #[derive(Debug)]
 pub(crate) struct DocumentPair {
@@ -66,11 +72,14 @@ impl Registry {
         self.documents.lock().await.get(url).map(|x| x.info.version)
     }
 
+    /// Returns the diagnostics of `document` when they have to be published, which is when
+    /// it's the latest analysis of a document other than the one being updated, and they
+    /// changed. The diagnostics of the document being updated are always published.
     fn update_document(
         documents: &mut MutexGuard<'_, HashMap<Url, DocumentPair>>,
         document: Arc<AnalyzedDocument>,
         updating_url: &Url,
-    ) {
+    ) -> Option<Vec<Diagnostic>> {
         if &document.doc_info.url == updating_url {
             //Write the newly analysed document into the oncelock that any request requiring the latest document will be waiting on
             if let Some(a) = documents.get_mut(updating_url) {
@@ -80,8 +89,13 @@ impl Registry {
         }
 
         let url = document.url().clone();
-        match documents.get_mut(&url) {
+        let diagnostics = document.diagnostics();
+        let changed = match documents.get_mut(&url) {
             Some(old_doc) => {
+                let old_diagnostics = (old_doc.latest_document.get())
+                    .unwrap_or(&old_doc.last_good_document)
+                    .diagnostics();
+
                 //If the latest doc_info has a version higher than what we are setting we shouldn't overwrite the document, but we can update the last_good_document if the parse went well
                 if old_doc.info.version > document.doc_info.version {
                     if document.type_checked() {
@@ -91,33 +105,59 @@ impl Registry {
                             last_good_document: document,
                         };
                     }
-                } else if document.type_checked() {
-                    *old_doc = DocumentPair::new(document.clone(), document);
+
+                    false
                 } else {
-                    debug!(
-                        "Document typechecking failed at version {:?}, not updating last_good_document",
-                        &document.doc_info.version
-                    );
-                    *old_doc = DocumentPair::new(document, old_doc.last_good_document.clone());
+                    if document.type_checked() {
+                        *old_doc = DocumentPair::new(document.clone(), document);
+                    } else {
+                        debug!(
+                            "Document typechecking failed at version {:?}, not updating last_good_document",
+                            &document.doc_info.version
+                        );
+                        *old_doc = DocumentPair::new(document, old_doc.last_good_document.clone());
+                    }
+
+                    old_diagnostics != diagnostics
                 }
             }
             None => {
                 documents.insert(url.clone(), DocumentPair::new(document.clone(), document));
+
+                !diagnostics.is_empty()
             }
-        }
+        };
+
+        (changed && &url != updating_url).then_some(diagnostics)
     }
 
-    pub async fn apply_changes<'a>(&self, analysed_docs: Vec<AnalyzedDocument>, updating_url: Url) {
+    /// Records the analysed documents, and returns the diagnostics to publish for the
+    /// documents other than `updating_url`.
+    pub async fn apply_changes<'a>(
+        &self,
+        analysed_docs: Vec<AnalyzedDocument>,
+        updating_url: Url,
+    ) -> Vec<(Url, Vec<Diagnostic>)> {
         let mut documents = self.documents.lock().await;
         debug!(
             "Finished doc analysis for doc: {}",
             updating_url.to_string()
         );
 
+        let mut changed_diagnostics = Vec::new();
+
         for document in analysed_docs {
             let document = Arc::new(document);
-            Registry::update_document(&mut documents, document, &updating_url);
+            let url = document.url().clone();
+
+            if let Some(diagnostics) =
+                Registry::update_document(&mut documents, document, &updating_url)
+            {
+                changed_diagnostics.push((url, diagnostics));
+            }
         }
+
+        changed_diagnostics
     }
 
     pub async fn apply_doc_info_changes(&self, url: Url, info: DocInfo) {
@@ -164,6 +204,168 @@ impl Registry {
         .ok()
     }
 
+    /// The latest analysis of every document, or the last good one if it's not ready yet.
+    async fn all_documents(&self) -> Vec<Arc<AnalyzedDocument>> {
+        let documents = self.documents.lock().await;
+
+        (documents.values())
+            .map(|pair| {
+                pair.latest_document
+                    .get()
+                    .unwrap_or(&pair.last_good_document)
+                    .clone()
+            })
+            .collect()
+    }
+
+    /// The documents that can refer to `target`, starting with `origin`, which is where
+    /// `target` was found. Also returns whether `target` can be renamed, which it can
+    /// only be if it's defined in one of them, or for tags and record fields, if the shape
+    /// of the tag union or record type they're in is known.
+    async fn documents_referring_to(
+        &self,
+        origin: &Arc<AnalyzedDocument>,
+        target: &Target,
+    ) -> (Vec<Arc<AnalyzedDocument>>, bool) {
+        let mut documents = vec![origin.clone()];
+
+        documents.extend(
+            (self.all_documents().await.into_iter()).filter(|doc| doc.url() != origin.url()),
+        );
+
+        match target {
+            Target::Value(value) => {
+                if !value.top_level {
+                    documents.truncate(1);
+                }
+
+                (documents, !value.is_builtin())
+            }
+            Target::Type(ty) => {
+                // Different modules can define types with the same name, so only look
+                // in the module that defines this one, and the modules that import it.
+                let home = (documents.iter())
+                    .find(|doc| {
+                        doc.module_name()
+                            .is_some_and(|module_name| ty.is_defined_in(&module_name))
+                            && doc.defines_type(&ty.name)
+                    })
+                    .map(|doc| doc.url().clone());
+
+                match home {
+                    Some(home) => {
+                        documents.retain(|doc| {
+                            doc.url() == &home || doc.imported_urls().contains(&home)
+                        });
+
+                        (documents, true)
+                    }
+                    // A builtin type
+                    None => (documents, false),
+                }
+            }
+            // Tags and record fields are structural, so any module can have ones in a tag
+            // union or record type of the same shape.
+            Target::Tag(structural) | Target::Field(structural) => {
+                (documents, structural.shape.is_some())
+            }
+        }
+    }
+
+    pub async fn references(
+        &self,
+        url: &Url,
+        position: Position,
+        include_declaration: bool,
+    ) -> Option<Vec<Location>> {
+        let document = self.latest_document_by_url(url).await?;
+        let target = document.reference_target(position)?;
+        let (documents, _) = self.documents_referring_to(&document, &target).await;
+
+        let locations = (documents.iter())
+            .flat_map(|doc| doc.reference_locations(&target, include_declaration))
+            .collect();
+
+        Some(locations)
+    }
+
+    pub async fn prepare_rename(
+        &self,
+        url: &Url,
+        position: Position,
+    ) -> Option<PrepareRenameResponse> {
+        let document = self.latest_document_by_url(url).await?;
+        let target = document.reference_target(position)?;
+        let (_, renamable) = self.documents_referring_to(&document, &target).await;
+
+        if !renamable {
+            return None;
+        }
+
+        (document.reference_locations(&target, true).into_iter())
+            .find(|location| location.range.start <= position && position <= location.range.end)
+            .map(|location| PrepareRenameResponse::Range(location.range))
+    }
+
+    /// Renames whatever is named at `position` everywhere it's referred to, or explains
+    /// why it can't be renamed.
+    pub async fn rename(
+        &self,
+        url: &Url,
+        position: Position,
+        new_name: &str,
+    ) -> Result<WorkspaceEdit, String> {
+        let document = self
+            .latest_document_by_url(url)
+            .await
+            .ok_or("This document hasn't been analyzed yet.")?;
+        let target = document
+            .reference_target(position)
+            .ok_or("There's nothing that can be renamed here.")?;
+        let (documents, renamable) = self.documents_referring_to(&document, &target).await;
+
+        if !renamable {
+            return Err(match target {
+                Target::Tag(_) | Target::Field(_) => format!(
+                    "`{}` can't be renamed, because the type it's in isn't known, so other tags and record fields with the same name can't be told apart from it.",
+                    target.name()
+                ),
+                Target::Value(_) | Target::Type(_) => {
+                    format!("`{}` is a builtin, so it can't be renamed.", target.name())
+                }
+            });
+        }
+
+        if !target.is_valid_new_name(new_name) {
+            return Err(format!("`{new_name}` isn't a valid name here."));
+        }
+
+        let mut changes = HashMap::new();
+
+        for doc in documents {
+            let edits = doc.rename_edits(&target, new_name);
+
+            if edits.is_empty() {
+                continue;
+            }
+
+            if doc.rename_conflicts(&target, new_name) {
+                return Err(format!(
+                    "`{new_name}` is already used in {}, so renaming `{}` would change what it refers to.",
+                    doc.url(),
+                    target.name()
+                ));
+            }
+
+            changes.insert(doc.url().clone(), edits);
+        }
+
+        Ok(WorkspaceEdit {
+            changes: Some(changes),
+            ..WorkspaceEdit::default()
+        })
+    }
+
     pub async fn diagnostics(&self, url: &Url) -> Vec<Diagnostic> {
         let Some(document) = self.latest_document_by_url(url).await else {
             return vec![];
@@ -171,10 +373,49 @@ impl Registry {
         document.diagnostics()
     }
 
+    pub async fn code_actions(
+        &self,
+        url: &Url,
+        range: Range,
+        context: &CodeActionContext,
+    ) -> Option<CodeActionResponse> {
+        let document = self.latest_document_by_url(url).await?;
+        let mut actions = document.code_actions(range, &context.diagnostics);
+
+        // Kinds are hierarchical, e.g. asking for `refactor` includes `refactor.rewrite`.
+        if let Some(only) = &context.only {
+            actions.retain(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => {
+                    action.kind.as_ref().is_some_and(|kind| {
+                        only.iter().any(|only| {
+                            kind.as_str()
+                                .strip_prefix(only.as_str())
+                                .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+                        })
+                    })
+                }
+                CodeActionOrCommand::Command(_) => true,
+            });
+        }
+
+        Some(actions)
+    }
+
     pub async fn hover(&self, url: &Url, position: Position) -> Option<Hover> {
         self.latest_document_by_url(url).await?.hover(position)
     }
 
+    pub async fn inlay_hints(
+        &self,
+        url: &Url,
+        range: Range,
+        max_length: usize,
+    ) -> Option<Vec<InlayHint>> {
+        let document = self.latest_document_by_url(url).await?;
+
+        Some(document.inlay_hints(range, max_length))
+    }
+
     pub async fn goto_definition(
         &self,
         url: &Url,
@@ -192,10 +433,77 @@ impl Registry {
         document.format()
     }
 
+    pub async fn range_formatting(&self, url: &Url, range: Range) -> Option<Vec<TextEdit>> {
+        let document = self.document_info_by_url(url).await?;
+        document.format_range(range)
+    }
+
+    pub async fn on_type_formatting(&self, url: &Url, position: Position) -> Option<Vec<TextEdit>> {
+        let document = self.document_info_by_url(url).await?;
+        document.format_on_type(position)
+    }
+
     pub async fn semantic_tokens(&self, url: &Url) -> Option<SemanticTokensResult> {
         let document = self.document_info_by_url(url).await?;
         document.semantic_tokens()
     }
+
+    pub async fn folding_ranges(&self, url: &Url) -> Option<Vec<FoldingRange>> {
+        let document = self.document_info_by_url(url).await?;
+        document.folding_ranges()
+    }
+
+    pub async fn selection_ranges(
+        &self,
+        url: &Url,
+        positions: &[Position],
+    ) -> Option<Vec<SelectionRange>> {
+        let document = self.document_info_by_url(url).await?;
+        document.selection_ranges(positions)
+    }
+
+    pub async fn code_lenses(&self, url: &Url) -> Option<Vec<CodeLens>> {
+        let document = self.document_info_by_url(url).await?;
+        Some(document.code_lenses())
+    }
+
+    pub async fn document_symbols(&self, url: &Url) -> Option<DocumentSymbolResponse> {
+        let document = self.document_info_by_url(url).await?;
+        document.document_symbols()
+    }
+
+    /// The symbols in all the documents that match `query`, best matches first. The builtins
+    /// are left out, since they aren't files in the workspace.
+    pub async fn workspace_symbols(&self, query: &str) -> Vec<SymbolInformation> {
+        let infos: Vec<DocInfo> = (self.documents.lock().await.values())
+            .filter(|pair| !pair.last_good_document.is_builtin())
+            .map(|pair| pair.info.clone())
+            .collect();
+
+        let mut matches: Vec<SymbolMatch> = (infos.iter())
+            .flat_map(|info| info.workspace_symbols(query))
+            .collect();
+
+        matches.sort_by(|a, b| {
+            (b.score.cmp(&a.score))
+                .then(b.exposed.cmp(&a.exposed))
+                .then(a.symbol.name.len().cmp(&b.symbol.name.len()))
+                .then_with(|| a.symbol.name.cmp(&b.symbol.name))
+        });
+
+        (matches.into_iter())
+            .take(MAX_WORKSPACE_SYMBOLS)
+            .map(|symbol_match| symbol_match.symbol)
+            .collect()
+    }
+
+    pub async fn signature_help(&self, url: &Url, position: Position) -> Option<SignatureHelp> {
+        let lock = self.documents.lock().await;
+        let pair = lock.get(url)?;
+
+        pair.last_good_document.signature_help(position, &pair.info)
+    }
+
     pub async fn completion_items(
         &self,
         url: &Url,
@@ -2,8 +2,10 @@ use analysis::HIGHLIGHT_TOKENS_LEGEND;
 
 use log::{debug, trace};
 use registry::{Registry, RegistryConfig};
+use std::collections::HashMap;
 use std::future::Future;
 use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::PathBuf;
 use std::time::Duration;
 
 use tower_lsp::jsonrpc::{self, Result};
@@ -11,10 +13,16 @@ use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer, LspService, Server};
 
 use crate::analysis::{global_analysis, DocInfo};
+use crate::convert::text_offset;
+use crate::test_runner::{
+    default_roc_path, run_expects, RUN_MODULE_TESTS_COMMAND, RUN_TEST_COMMAND,
+    SNAPSHOT_DIAGNOSTIC_CODE, UPDATE_SNAPSHOT_COMMAND,
+};
 
 mod analysis;
 mod convert;
 mod registry;
+mod test_runner;
 
 struct RocServer {
     pub state: RocServerState,
@@ -23,12 +31,18 @@ struct RocServer {
 
 struct RocServerConfig {
     pub debounce_ms: Duration,
+    /// Inferred types longer than this are cut short in inlay hints.
+    pub inlay_hint_max_length: usize,
+    /// The `roc` binary that runs the tests.
+    pub roc_path: PathBuf,
 }
 
 impl Default for RocServerConfig {
     fn default() -> Self {
         Self {
             debounce_ms: Duration::from_millis(100),
+            inlay_hint_max_length: 40,
+            roc_path: default_roc_path(),
         }
     }
 }
@@ -37,6 +51,14 @@ impl Default for RocServerConfig {
 struct RocServerState {
     registry: Registry,
     config: RocServerConfig,
+    /// Each open document, which incremental changes get applied to.
+    open_documents: tokio::sync::Mutex<HashMap<Url, OpenDocument>>,
+}
+
+/// The latest text of an open document.
+struct OpenDocument {
+    text: String,
+    version: i32,
 }
 
 impl std::panic::RefUnwindSafe for RocServer {}
@@ -56,6 +78,11 @@ impl RocServer {
         };
         let config = RocServerConfig {
             debounce_ms: Duration::from_millis(read_env_num("ROCLS_DEBOUNCE_MS").unwrap_or(100)),
+            inlay_hint_max_length: read_env_num("ROCLS_INLAY_HINT_MAX_LENGTH").unwrap_or(40)
+                as usize,
+            roc_path: std::env::var_os("ROCLS_ROC_PATH")
+                .map(PathBuf::from)
+                .unwrap_or_else(default_roc_path),
         };
         Self {
             state: RocServerState::new(config, Registry::new(registry_config)),
@@ -64,14 +91,11 @@ impl RocServer {
     }
 
     pub fn capabilities() -> ServerCapabilities {
-        let text_document_sync = TextDocumentSyncCapability::Options(
-            // TODO: later on make this incremental
-            TextDocumentSyncOptions {
-                open_close: Some(true),
-                change: Some(TextDocumentSyncKind::FULL),
-                ..TextDocumentSyncOptions::default()
-            },
-        );
+        let text_document_sync = TextDocumentSyncCapability::Options(TextDocumentSyncOptions {
+            open_close: Some(true),
+            change: Some(TextDocumentSyncKind::INCREMENTAL),
+            ..TextDocumentSyncOptions::default()
+        });
         let hover_provider = HoverProviderCapability::Simple(true);
         let definition_provider = DefinitionOptions {
             work_done_progress_options: WorkDoneProgressOptions {
@@ -83,6 +107,16 @@ impl RocServer {
                 work_done_progress: None,
             },
         };
+        let document_range_formatting_provider = DocumentRangeFormattingOptions {
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        };
+        // Typing a newline usually means a def has just been finished.
+        let document_on_type_formatting_provider = DocumentOnTypeFormattingOptions {
+            first_trigger_character: "\n".to_string(),
+            more_trigger_character: None,
+        };
         let semantic_tokens_provider =
             SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
                 work_done_progress_options: WorkDoneProgressOptions {
@@ -103,13 +137,64 @@ impl RocServer {
                 work_done_progress: None,
             },
         };
+        let signature_help_provider = SignatureHelpOptions {
+            trigger_characters: Some(vec![" ".to_string(), "(".to_string(), ",".to_string()]),
+            retrigger_characters: None,
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        };
+        let rename_provider = RenameOptions {
+            prepare_provider: Some(true),
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        };
+        let code_action_provider = CodeActionOptions {
+            code_action_kinds: Some(vec![
+                CodeActionKind::QUICKFIX,
+                CodeActionKind::REFACTOR_REWRITE,
+            ]),
+            resolve_provider: Some(false),
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        };
+        let code_lens_provider = CodeLensOptions {
+            resolve_provider: Some(false),
+        };
+        let execute_command_provider = ExecuteCommandOptions {
+            commands: vec![
+                RUN_TEST_COMMAND.to_string(),
+                RUN_MODULE_TESTS_COMMAND.to_string(),
+                UPDATE_SNAPSHOT_COMMAND.to_string(),
+            ],
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        };
         ServerCapabilities {
             text_document_sync: Some(text_document_sync),
             hover_provider: Some(hover_provider),
             definition_provider: Some(OneOf::Right(definition_provider)),
             document_formatting_provider: Some(OneOf::Right(document_formatting_provider)),
+            document_range_formatting_provider: Some(OneOf::Right(
+                document_range_formatting_provider,
+            )),
+            document_on_type_formatting_provider: Some(document_on_type_formatting_provider),
             semantic_tokens_provider: Some(semantic_tokens_provider),
             completion_provider: Some(completion_provider),
+            signature_help_provider: Some(signature_help_provider),
+            references_provider: Some(OneOf::Left(true)),
+            rename_provider: Some(OneOf::Right(rename_provider)),
+            code_action_provider: Some(CodeActionProviderCapability::Options(code_action_provider)),
+            document_symbol_provider: Some(OneOf::Left(true)),
+            workspace_symbol_provider: Some(OneOf::Left(true)),
+            inlay_hint_provider: Some(OneOf::Left(true)),
+            code_lens_provider: Some(code_lens_provider),
+            execute_command_provider: Some(execute_command_provider),
+            folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+            selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
             ..ServerCapabilities::default()
         }
     }
@@ -119,10 +204,13 @@ impl RocServer {
         let updating_result = self.state.change(&fi, text, version).await;
 
         //The analysis task can be cancelled by another change coming in which will update the watched variable
-        if let Err(e) = updating_result {
-            debug!("Cancelled change. Reason:{:?}", e);
-            return;
-        }
+        let changed_diagnostics = match updating_result {
+            Ok(changed_diagnostics) => changed_diagnostics,
+            Err(e) => {
+                debug!("Cancelled change. Reason:{:?}", e);
+                return;
+            }
+        };
 
         debug!("Applied_changes getting and returning diagnostics");
 
@@ -131,23 +219,223 @@ impl RocServer {
         self.client
             .publish_diagnostics(fi, diagnostics, Some(version))
             .await;
+
+        // Other modules were analysed along with this one, and a change to this one
+        // can fix or cause problems in them.
+        for (url, diagnostics) in changed_diagnostics {
+            self.client
+                .publish_diagnostics(url, diagnostics, None)
+                .await;
+        }
+    }
+
+    /// Runs the tests in a document, or only the one at `position`, and reports the
+    /// failures as diagnostics. With `update_snapshots`, the snapshots that run are stored.
+    async fn run_tests(&self, url: Url, position: Option<Position>, update_snapshots: bool) {
+        let report = match self.state.run_tests(&url, position, update_snapshots).await {
+            Ok(report) => report,
+            Err(message) => {
+                self.client.show_message(MessageType::ERROR, message).await;
+                return;
+            }
+        };
+
+        let message_type = match report.failed {
+            0 => MessageType::INFO,
+            _ => MessageType::ERROR,
+        };
+        let message = match (report.passed, report.failed) {
+            (0, 0) => "No tests were found.".to_string(),
+            (passed, failed) => format!("{failed} failed and {passed} passed."),
+        };
+
+        self.client
+            .publish_diagnostics(url, report.diagnostics, None)
+            .await;
+        self.client.show_message(message_type, message).await;
+    }
+
+    /// Records a change to part of a document's content.
+    async fn change_incrementally(
+        &self,
+        fi: Url,
+        changes: Vec<TextDocumentContentChangeEvent>,
+        version: i32,
+    ) {
+        match self
+            .state
+            .apply_content_changes(&fi, changes, version)
+            .await
+        {
+            Ok(text) => self.change(fi, text, version).await,
+            Err(message) => self.client.log_message(MessageType::ERROR, message).await,
+        }
+    }
+}
+
+/// Applies a change from the client to the text of a document.
+fn apply_content_change(text: &mut String, change: TextDocumentContentChangeEvent) {
+    match change.range {
+        Some(range) => {
+            let start = text_offset(text, range.start);
+            let end = text_offset(text, range.end).max(start);
+
+            text.replace_range(start..end, &change.text);
+        }
+        None => *text = change.text,
     }
 }
 
+/// The results of running the tests in a document.
+struct TestReport {
+    passed: usize,
+    failed: usize,
+    /// The document's diagnostics, along with one for each test that failed.
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// The document and position that a command to run tests was given.
+fn test_command_arguments(
+    command: &str,
+    arguments: Vec<serde_json::Value>,
+) -> std::result::Result<(Url, Option<Position>), String> {
+    let mut arguments = arguments.into_iter();
+    let mut next = || {
+        arguments
+            .next()
+            .ok_or(format!("{command} is missing arguments"))
+    };
+
+    let url: Url = serde_json::from_value(next()?).map_err(|err| err.to_string())?;
+    let position = match command {
+        RUN_TEST_COMMAND | UPDATE_SNAPSHOT_COMMAND => {
+            Some(serde_json::from_value(next()?).map_err(|err| err.to_string())?)
+        }
+        _ => None,
+    };
+
+    Ok((url, position))
+}
+
 impl RocServerState {
     pub fn new(config: RocServerConfig, registry: Registry) -> RocServerState {
-        Self { config, registry }
+        Self {
+            config,
+            registry,
+            open_documents: Default::default(),
+        }
+    }
+
+    /// Runs the tests in the saved version of a document, or only the one at `position`.
+    async fn run_tests(
+        &self,
+        url: &Url,
+        position: Option<Position>,
+        update_snapshots: bool,
+    ) -> std::result::Result<TestReport, String> {
+        let path = (url.to_file_path()).map_err(|()| format!("{url} isn't a file"))?;
+
+        let roc_path = self.config.roc_path.clone();
+        let results = tokio::task::spawn_blocking(move || {
+            run_expects(&roc_path, &path, position, update_snapshots)
+        })
+        .await
+        .map_err(|err| format!("Running the tests failed. reason:{:?}", err))??;
+
+        let mut diagnostics = self.registry.diagnostics(url).await;
+        let mut passed = 0;
+        let mut failed = 0;
+
+        for result in results {
+            let Some(failure) = result.failure else {
+                passed += 1;
+                continue;
+            };
+
+            failed += 1;
+            diagnostics.push(Diagnostic {
+                range: result.range,
+                severity: Some(DiagnosticSeverity::ERROR),
+                source: Some("test".to_string()),
+                code: (result.snapshot)
+                    .then(|| NumberOrString::String(SNAPSHOT_DIAGNOSTIC_CODE.to_string())),
+                message: failure,
+                ..Diagnostic::default()
+            });
+        }
+
+        Ok(TestReport {
+            passed,
+            failed,
+            diagnostics,
+        })
+    }
+
+    async fn close(&self, fi: Url) {
+        self.open_documents.lock().await.remove(&fi);
     }
 
-    async fn close(&self, _fi: Url) {}
+    /// Applies the changes to the open document and returns its new text.
+    ///
+    /// The changes are relative to the text they get applied to, so the lock is held until
+    /// the new text is stored; otherwise concurrent changes could be applied to the same old
+    /// text, and all but one of them would be lost. Versions only have to increase, and some
+    /// clients skip numbers, so changes are applied in the order they arrive, which is the
+    /// order they were sent in; changes to a version that was already applied are dropped.
+    pub async fn apply_content_changes(
+        &self,
+        fi: &Url,
+        changes: Vec<TextDocumentContentChangeEvent>,
+        version: i32,
+    ) -> std::result::Result<String, String> {
+        let mut open_documents = self.open_documents.lock().await;
+        let document = open_documents
+            .get_mut(fi)
+            .ok_or_else(|| format!("Got changes to {fi}, which isn't open."))?;
+
+        if version <= document.version {
+            return Err(format!(
+                "Got changes to version {version} of {fi}, but version {} was already applied.",
+                document.version
+            ));
+        }
+
+        for change in changes {
+            apply_content_change(&mut document.text, change);
+        }
+
+        document.version = version;
+
+        Ok(document.text.clone())
+    }
 
+    /// Analyses the new version of the document, and returns the diagnostics to publish for
+    /// the other documents that were analysed along with it.
     pub async fn change(
         &self,
         fi: &Url,
         text: String,
         version: i32,
-    ) -> std::result::Result<(), String> {
+    ) -> std::result::Result<Vec<(Url, Vec<Diagnostic>)>, String> {
         debug!("V{:?}:starting change", version);
+        {
+            let mut open_documents = self.open_documents.lock().await;
+
+            match open_documents.get(fi) {
+                Some(document) if document.version > version => {
+                    return Err("A newer version is already open, skipping analysis".to_string());
+                }
+                _ => {
+                    let document = OpenDocument {
+                        text: text.clone(),
+                        version,
+                    };
+
+                    open_documents.insert(fi.clone(), document);
+                }
+            }
+        }
+
         let doc_info = DocInfo::new(fi.clone(), text, version);
 
         self.registry
@@ -209,8 +497,7 @@ impl RocServerState {
                 version
             );
 
-            inner_ref.registry.apply_changes(results, fi.clone()).await;
-            Ok(())
+            Ok(inner_ref.registry.apply_changes(results, fi.clone()).await)
         }
         .await;
         debug!("V{:?}:finished document change process", version);
@@ -246,15 +533,8 @@ impl LanguageServer for RocServer {
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
         let VersionedTextDocumentIdentifier { uri, version, .. } = params.text_document;
 
-        // NOTE: We specify that we expect full-content syncs in the server capabilities,
-        // so here we assume the only change passed is a change of the entire document's content.
-        let TextDocumentContentChangeEvent { text, .. } = params
-            .content_changes
-            .into_iter()
-            .last()
-            .expect("textDocument change event had no changes ");
-
-        let _res = unwind_async(self.change(uri, text, version)).await;
+        let _res =
+            unwind_async(self.change_incrementally(uri, params.content_changes, version)).await;
         if let Err(e) = _res {
             self.client.log_message(MessageType::ERROR, e.message).await
         }
@@ -314,6 +594,47 @@ impl LanguageServer for RocServer {
         unwind_async(self.state.registry.formatting(&text_document.uri)).await
     }
 
+    async fn range_formatting(
+        &self,
+        params: DocumentRangeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        let DocumentRangeFormattingParams {
+            text_document,
+            range,
+            options: _,
+            work_done_progress_params: _,
+        } = params;
+
+        unwind_async(
+            self.state
+                .registry
+                .range_formatting(&text_document.uri, range),
+        )
+        .await
+    }
+
+    async fn on_type_formatting(
+        &self,
+        params: DocumentOnTypeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        let DocumentOnTypeFormattingParams {
+            text_document_position:
+                TextDocumentPositionParams {
+                    text_document,
+                    position,
+                },
+            ch: _,
+            options: _,
+        } = params;
+
+        unwind_async(
+            self.state
+                .registry
+                .on_type_formatting(&text_document.uri, position),
+        )
+        .await
+    }
+
     async fn semantic_tokens_full(
         &self,
         params: SemanticTokensParams,
@@ -327,6 +648,116 @@ impl LanguageServer for RocServer {
         unwind_async(self.state.registry.semantic_tokens(&text_document.uri)).await
     }
 
+    async fn folding_range(&self, params: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>> {
+        let FoldingRangeParams {
+            text_document,
+            work_done_progress_params: _,
+            partial_result_params: _,
+        } = params;
+
+        unwind_async(self.state.registry.folding_ranges(&text_document.uri)).await
+    }
+
+    async fn selection_range(
+        &self,
+        params: SelectionRangeParams,
+    ) -> Result<Option<Vec<SelectionRange>>> {
+        let SelectionRangeParams {
+            text_document,
+            positions,
+            work_done_progress_params: _,
+            partial_result_params: _,
+        } = params;
+
+        unwind_async(
+            self.state
+                .registry
+                .selection_ranges(&text_document.uri, &positions),
+        )
+        .await
+    }
+
+    async fn document_symbol(
+        &self,
+        params: DocumentSymbolParams,
+    ) -> Result<Option<DocumentSymbolResponse>> {
+        let DocumentSymbolParams {
+            text_document,
+            work_done_progress_params: _,
+            partial_result_params: _,
+        } = params;
+
+        unwind_async(self.state.registry.document_symbols(&text_document.uri)).await
+    }
+
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> Result<Option<Vec<SymbolInformation>>> {
+        let WorkspaceSymbolParams {
+            query,
+            work_done_progress_params: _,
+            partial_result_params: _,
+        } = params;
+
+        unwind_async(self.state.registry.workspace_symbols(&query))
+            .await
+            .map(Some)
+    }
+
+    async fn code_lens(&self, params: CodeLensParams) -> Result<Option<Vec<CodeLens>>> {
+        let CodeLensParams {
+            text_document,
+            work_done_progress_params: _,
+            partial_result_params: _,
+        } = params;
+
+        unwind_async(self.state.registry.code_lenses(&text_document.uri)).await
+    }
+
+    async fn execute_command(
+        &self,
+        params: ExecuteCommandParams,
+    ) -> Result<Option<serde_json::Value>> {
+        let ExecuteCommandParams {
+            command,
+            arguments,
+            work_done_progress_params: _,
+        } = params;
+
+        let update_snapshots = match command.as_str() {
+            RUN_TEST_COMMAND | RUN_MODULE_TESTS_COMMAND => false,
+            UPDATE_SNAPSHOT_COMMAND => true,
+            _ => {
+                return Err(jsonrpc::Error::invalid_params(format!(
+                    "Unknown command: {command}"
+                )))
+            }
+        };
+
+        let (url, position) =
+            test_command_arguments(&command, arguments).map_err(jsonrpc::Error::invalid_params)?;
+
+        self.run_tests(url, position, update_snapshots).await;
+
+        Ok(None)
+    }
+
+    async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
+        let InlayHintParams {
+            text_document,
+            range,
+            work_done_progress_params: _,
+        } = params;
+
+        unwind_async(self.state.registry.inlay_hints(
+            &text_document.uri,
+            range,
+            self.state.config.inlay_hint_max_length,
+        ))
+        .await
+    }
+
     async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
         let doc = params.text_document_position;
         trace!("Got completion request.");
@@ -338,6 +769,100 @@ impl LanguageServer for RocServer {
         )
         .await
     }
+
+    async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
+        let SignatureHelpParams {
+            context: _,
+            text_document_position_params:
+                TextDocumentPositionParams {
+                    text_document,
+                    position,
+                },
+            work_done_progress_params: _,
+        } = params;
+
+        unwind_async(
+            self.state
+                .registry
+                .signature_help(&text_document.uri, position),
+        )
+        .await
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let CodeActionParams {
+            text_document,
+            range,
+            context,
+            work_done_progress_params: _,
+            partial_result_params: _,
+        } = params;
+
+        unwind_async(
+            self.state
+                .registry
+                .code_actions(&text_document.uri, range, &context),
+        )
+        .await
+    }
+
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        let ReferenceParams {
+            text_document_position:
+                TextDocumentPositionParams {
+                    text_document,
+                    position,
+                },
+            context,
+            work_done_progress_params: _,
+            partial_result_params: _,
+        } = params;
+
+        unwind_async(self.state.registry.references(
+            &text_document.uri,
+            position,
+            context.include_declaration,
+        ))
+        .await
+    }
+
+    async fn prepare_rename(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>> {
+        let TextDocumentPositionParams {
+            text_document,
+            position,
+        } = params;
+
+        unwind_async(
+            self.state
+                .registry
+                .prepare_rename(&text_document.uri, position),
+        )
+        .await
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let RenameParams {
+            text_document_position:
+                TextDocumentPositionParams {
+                    text_document,
+                    position,
+                },
+            new_name,
+            work_done_progress_params: _,
+        } = params;
+
+        unwind_async(
+            self.state
+                .registry
+                .rename(&text_document.uri, position, &new_name),
+        )
+        .await?
+        .map(Some)
+        .map_err(jsonrpc::Error::invalid_params)
+    }
 }
 
 async fn unwind_async<Fut, T>(future: Fut) -> tower_lsp::jsonrpc::Result<T>
@@ -644,4 +1169,1081 @@ mod tests {
         "#]]
         .assert_debug_eq(&actual);
     }
+
+    /// Renders ranges as `line:character-line:character`, to keep expectations short
+    fn range_string(range: Range) -> String {
+        format!(
+            "{}:{}-{}:{}",
+            range.start.line, range.start.character, range.end.line, range.end.character
+        )
+    }
+
+    async fn reference_ranges(
+        doc: &str,
+        position: Position,
+        include_declaration: bool,
+    ) -> Option<Vec<String>> {
+        let (inner, url) = test_setup(DOC_LIT.to_string() + doc).await;
+
+        let locations = inner
+            .registry
+            .references(&url, position, include_declaration)
+            .await?;
+
+        Some(
+            locations
+                .into_iter()
+                .map(|location| range_string(location.range))
+                .collect(),
+        )
+    }
+
+    /// Renames, and returns the edits to the test document
+    async fn rename_edits(
+        doc: &str,
+        position: Position,
+        new_name: &str,
+    ) -> std::result::Result<Vec<(String, String)>, String> {
+        let (inner, url) = test_setup(DOC_LIT.to_string() + doc).await;
+
+        let mut changes = inner
+            .registry
+            .rename(&url, position, new_name)
+            .await?
+            .changes
+            .unwrap_or_default();
+
+        let mut edits = changes.remove(&url).unwrap_or_default();
+        edits.sort_by_key(|edit| edit.range.start);
+
+        Ok(edits
+            .into_iter()
+            .map(|edit| (range_string(edit.range), edit.new_text))
+            .collect())
+    }
+
+    #[tokio::test]
+    async fn test_references_top_level_def() {
+        let doc = indoc! {r"
+            helper : U64 -> U64
+            helper = \num -> num + 1
+
+            main = helper (helper 1)
+            "};
+
+        let with_declaration = reference_ranges(doc, Position::new(6, 9), true).await;
+        let without_declaration = reference_ranges(doc, Position::new(6, 9), false).await;
+
+        expect![[r#"
+            [
+                Some(
+                    [
+                        "3:0-3:6",
+                        "4:0-4:6",
+                        "6:7-6:13",
+                        "6:15-6:21",
+                    ],
+                ),
+                Some(
+                    [
+                        "6:7-6:13",
+                        "6:15-6:21",
+                    ],
+                ),
+            ]
+        "#]]
+        .assert_debug_eq(&[with_declaration, without_declaration]);
+    }
+
+    #[tokio::test]
+    async fn test_rename_local_in_punned_field() {
+        let doc = indoc! {r"
+            main =
+                value = 1
+                { value, other: value }
+            "};
+
+        let actual = rename_edits(doc, Position::new(4, 4), "count").await;
+
+        expect![[r#"
+            Ok(
+                [
+                    (
+                        "4:4-4:9",
+                        "count",
+                    ),
+                    (
+                        "5:6-5:11",
+                        "value: count",
+                    ),
+                    (
+                        "5:20-5:25",
+                        "count",
+                    ),
+                ],
+            )
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    async fn prepare_rename_range(doc: &str, position: Position) -> Option<String> {
+        let (inner, url) = test_setup(DOC_LIT.to_string() + doc).await;
+
+        match inner.registry.prepare_rename(&url, position).await? {
+            PrepareRenameResponse::Range(range) => Some(range_string(range)),
+            other => panic!("Expected a range, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rename_tag_in_its_tag_union() {
+        let doc = indoc! {r#"
+            Color : [Red, Green]
+            Light : [Red, Amber, Green]
+
+            paint : Color -> Str
+            paint = \color ->
+                when color is
+                    Red -> "red"
+                    Green -> "green"
+
+            stop : Light
+            stop = Red
+            "#};
+
+        let prepared = prepare_rename_range(doc, Position::new(9, 9)).await;
+        let actual = rename_edits(doc, Position::new(9, 9), "Crimson").await;
+
+        assert_eq!(prepared.as_deref(), Some("9:8-9:11"));
+        expect![[r#"
+            Ok(
+                [
+                    (
+                        "3:9-3:12",
+                        "Crimson",
+                    ),
+                    (
+                        "9:8-9:11",
+                        "Crimson",
+                    ),
+                ],
+            )
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_rename_field_in_its_record_type() {
+        let doc = indoc! {r#"
+            Person : { name : Str, age : U64 }
+            Pet : { name : Str }
+
+            greet : Person -> Str
+            greet = \person -> person.name
+
+            pet : Pet
+            pet = { name: "Rex" }
+            "#};
+
+        let prepared = prepare_rename_range(doc, Position::new(7, 27)).await;
+        let renamed = rename_edits(doc, Position::new(7, 27), "title").await;
+        let taken = rename_edits(doc, Position::new(7, 27), "age").await;
+
+        assert_eq!(prepared.as_deref(), Some("7:26-7:30"));
+        expect![[r#"
+            [
+                Ok(
+                    [
+                        (
+                            "3:11-3:15",
+                            "title",
+                        ),
+                        (
+                            "7:26-7:30",
+                            "title",
+                        ),
+                    ],
+                ),
+                Err(
+                    "`age` is already used in file:///Test.roc, so renaming `name` would change what it refers to.",
+                ),
+            ]
+        "#]]
+        .assert_debug_eq(&[renamed, taken]);
+    }
+
+    #[tokio::test]
+    async fn test_rename_type_skips_other_modules_types() {
+        let doc = indoc! {r"
+            Color : [Red, Blue]
+
+            paint : Color, Str.Color -> Color
+            paint = \color, _ -> color
+            "};
+
+        let prepared = prepare_rename_range(doc, Position::new(3, 2)).await;
+        let actual = rename_edits(doc, Position::new(3, 2), "Shade").await;
+
+        expect![[r#"
+            Some(
+                "3:0-3:5",
+            )
+        "#]]
+        .assert_debug_eq(&prepared);
+        expect![[r#"
+            Ok(
+                [
+                    (
+                        "3:0-3:5",
+                        "Shade",
+                    ),
+                    (
+                        "5:8-5:13",
+                        "Shade",
+                    ),
+                    (
+                        "5:28-5:33",
+                        "Shade",
+                    ),
+                ],
+            )
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_rename_rejects_bad_names() {
+        let doc = indoc! {r"
+            helper = \num -> num + 1
+
+            other = 2
+
+            main = helper other
+            "};
+
+        let invalid = rename_edits(doc, Position::new(3, 0), "Helper").await;
+        let taken = rename_edits(doc, Position::new(3, 0), "other").await;
+
+        expect![[r#"
+            [
+                Err(
+                    "`Helper` isn't a valid name here.",
+                ),
+                Err(
+                    "`other` is already used in file:///Test.roc, so renaming `helper` would change what it refers to.",
+                ),
+            ]
+        "#]]
+        .assert_debug_eq(&[invalid, taken]);
+    }
+
+    /// Gets the code actions for `range`, and returns their titles and edits to the test document
+    async fn code_actions(doc: &str, range: Range) -> Vec<(String, Vec<(String, String)>)> {
+        let (inner, url) = test_setup(DOC_LIT.to_string() + doc).await;
+        let context = CodeActionContext::default();
+
+        let actions = (inner.registry.code_actions(&url, range, &context).await).unwrap();
+
+        actions
+            .into_iter()
+            .map(|action| {
+                let CodeActionOrCommand::CodeAction(action) = action else {
+                    panic!("Expected a code action, got {action:?}");
+                };
+                let mut changes = action
+                    .edit
+                    .and_then(|edit| edit.changes)
+                    .unwrap_or_default();
+                let edits = (changes.remove(&url).unwrap_or_default().into_iter())
+                    .map(|edit| (range_string(edit.range), edit.new_text))
+                    .collect();
+
+                (action.title, edits)
+            })
+            .collect()
+    }
+
+    fn cursor(line: u32, character: u32) -> Range {
+        Range::new(
+            Position::new(line, character),
+            Position::new(line, character),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_code_action_removes_unused_def() {
+        let doc = indoc! {r"
+            main =
+                unused = 1
+
+                2
+            "};
+
+        let actual = code_actions(doc, cursor(4, 6)).await;
+
+        expect![[r#"
+            [
+                (
+                    "Remove unused def `unused`",
+                    [
+                        (
+                            "4:0-5:0",
+                            "",
+                        ),
+                    ],
+                ),
+            ]
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_code_action_adds_missing_branches() {
+        let doc = indoc! {r"
+            to_num : [Red, Green, Blue] -> U8
+            to_num = \color ->
+                when color is
+                    Red -> 1
+            "};
+
+        let actual = code_actions(doc, cursor(5, 10)).await;
+
+        expect![[r#"
+            [
+                (
+                    "Add 2 missing branches",
+                    [
+                        (
+                            "6:16-6:16",
+                            "\n        Blue -> crash \"TODO\"\n        Green -> crash \"TODO\"",
+                        ),
+                    ],
+                ),
+            ]
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_code_action_updates_failed_snapshot() {
+        let doc = indoc! {r"
+            expect Inspect.snapshot (1 + 1)
+            "};
+
+        let (inner, url) = test_setup(DOC_LIT.to_string() + doc).await;
+        let snapshot_failure = Diagnostic {
+            range: Range::new(Position::new(3, 0), Position::new(3, 31)),
+            source: Some("test".to_string()),
+            code: Some(NumberOrString::String(SNAPSHOT_DIAGNOSTIC_CODE.to_string())),
+            message: "This snapshot isn't stored yet".to_string(),
+            ..Diagnostic::default()
+        };
+        let context = CodeActionContext {
+            diagnostics: vec![snapshot_failure],
+            ..CodeActionContext::default()
+        };
+
+        let range = cursor(3, 4);
+
+        let actions = (inner.registry.code_actions(&url, range, &context).await).unwrap();
+        let commands: Vec<_> = (actions.into_iter())
+            .filter_map(|action| match action {
+                CodeActionOrCommand::CodeAction(CodeAction {
+                    command: Some(command),
+                    ..
+                }) => {
+                    let arguments = command.arguments.unwrap_or_default();
+
+                    Some(format!(
+                        "{}: {} {} {}:{}",
+                        command.title,
+                        command.command,
+                        arguments[0],
+                        arguments[1]["line"],
+                        arguments[1]["character"],
+                    ))
+                }
+                _ => None,
+            })
+            .collect();
+
+        expect![[r#"
+            [
+                "Update snapshot: roc.updateSnapshot \"file:///Test.roc\" 3:0",
+            ]
+        "#]]
+        .assert_debug_eq(&commands);
+    }
+
+    #[tokio::test]
+    async fn test_code_action_adds_inferred_annotation() {
+        let doc = indoc! {r"
+            helper = \num -> num + 1
+            "};
+
+        let actual = code_actions(doc, cursor(3, 2)).await;
+
+        expect![[r#"
+            [
+                (
+                    "Remove unused def `helper`",
+                    [
+                        (
+                            "3:0-4:0",
+                            "",
+                        ),
+                    ],
+                ),
+                (
+                    "Add inferred type annotation",
+                    [
+                        (
+                            "3:0-3:0",
+                            "helper : Num a -> Num a\n",
+                        ),
+                    ],
+                ),
+            ]
+        "#]]
+        .assert_debug_eq(&actual);
+    }
+
+    #[tokio::test]
+    async fn test_incremental_change() {
+        let (inner, url) = test_setup("main = \"héllo\"\nother = 1\n".to_string()).await;
+
+        // Positions count UTF-16 code units.
+        let change =
+            |start: (u32, u32), end: (u32, u32), text: &str| TextDocumentContentChangeEvent {
+                range: Some(Range::new(
+                    Position::new(start.0, start.1),
+                    Position::new(end.0, end.1),
+                )),
+                range_length: None,
+                text: text.to_string(),
+            };
+
+        let text = inner
+            .apply_content_changes(
+                &url,
+                vec![
+                    change((0, 9), (0, 13), "ey"),
+                    change((1, 8), (1, 9), "2"),
+                    change((2, 0), (2, 0), "last = 3\n"),
+                ],
+                1,
+            )
+            .await;
+
+        expect![[r#"
+            Ok(
+                "main = \"hey\"\nother = 2\nlast = 3\n",
+            )
+        "#]]
+        .assert_debug_eq(&text);
+    }
+
+    #[tokio::test]
+    async fn test_incremental_changes_build_on_each_other() {
+        let (inner, url) = test_setup("a = 1\n".to_string()).await;
+
+        let append = |line: u32, text: &str| {
+            vec![TextDocumentContentChangeEvent {
+                range: Some(Range::new(Position::new(line, 0), Position::new(line, 0))),
+                range_length: None,
+                text: text.to_string(),
+            }]
+        };
+
+        let (first, second) = tokio::join!(
+            inner.apply_content_changes(&url, append(1, "b = 2\n"), 1),
+            inner.apply_content_changes(&url, append(2, "c = 3\n"), 2),
+        );
+        // A change that arrives after a newer one was applied can't be applied anymore.
+        let stale = inner
+            .apply_content_changes(&url, append(3, "d = 4\n"), 2)
+            .await;
+
+        expect![[r#"
+            (
+                Ok(
+                    "a = 1\nb = 2\n",
+                ),
+                Ok(
+                    "a = 1\nb = 2\nc = 3\n",
+                ),
+                Err(
+                    "Got changes to version 2 of file:///Test.roc, but version 2 was already applied.",
+                ),
+            )
+        "#]]
+        .assert_debug_eq(&(first, second, stale));
+    }
+
+    #[tokio::test]
+    async fn test_incremental_changes_skip_versions() {
+        let (inner, url) = test_setup("a = 1\n".to_string()).await;
+
+        let append = |line: u32, text: &str| {
+            vec![TextDocumentContentChangeEvent {
+                range: Some(Range::new(Position::new(line, 0), Position::new(line, 0))),
+                range_length: None,
+                text: text.to_string(),
+            }]
+        };
+
+        // Versions only have to increase, like neovim's changedtick.
+        let v3 = inner
+            .apply_content_changes(&url, append(1, "b = 2\n"), 3)
+            .await;
+        let v7 = inner
+            .apply_content_changes(&url, append(2, "c = 3\n"), 7)
+            .await;
+        let v8 = inner
+            .apply_content_changes(&url, append(3, "d = 4\n"), 8)
+            .await;
+
+        expect![[r#"
+            (
+                Ok(
+                    "a = 1\nb = 2\n",
+                ),
+                Ok(
+                    "a = 1\nb = 2\nc = 3\n",
+                ),
+                Ok(
+                    "a = 1\nb = 2\nc = 3\nd = 4\n",
+                ),
+            )
+        "#]]
+        .assert_debug_eq(&(v3, v7, v8));
+    }
+
+    /// Renders document symbols as an indented outline, to keep expectations short
+    fn outline(symbols: &[DocumentSymbol], depth: usize, lines: &mut Vec<String>) {
+        for symbol in symbols {
+            lines.push(format!(
+                "{}{} {:?} {} {}",
+                "  ".repeat(depth),
+                symbol.name,
+                symbol.kind,
+                range_string(symbol.selection_range),
+                symbol.detail.as_deref().unwrap_or_default(),
+            ));
+
+            outline(
+                symbol.children.as_deref().unwrap_or_default(),
+                depth + 1,
+                lines,
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_document_symbols() {
+        let doc = indoc! {r"
+            Color : [Red, Green]
+
+            Age := U64
+
+            Describe implements
+                describe : a -> Str where a implements Describe
+
+            helper : U64 -> U64
+            helper = \num ->
+                double = \n -> n * 2
+                offset = 1
+
+                double num + offset
+
+            { x, y } = { x: 1, y: 2 }
+            "};
+
+        let (inner, url) = test_setup(DOC_LIT.to_string() + doc).await;
+
+        let Some(DocumentSymbolResponse::Nested(symbols)) =
+            inner.registry.document_symbols(&url).await
+        else {
+            panic!("Expected nested document symbols");
+        };
+
+        let mut lines = Vec::new();
+        outline(&symbols, 0, &mut lines);
+
+        expect![[r#"
+            [
+                "Color TypeParameter 3:0-3:5 ",
+                "Age Struct 5:0-5:3 ",
+                "Describe Interface 7:0-7:8 ",
+                "  describe Method 8:4-8:12 a -> Str where a implements Describe",
+                "helper Function 10:0-10:6 U64 -> U64",
+                "  double Function 12:4-12:10 ",
+                "  offset Variable 13:4-13:10 ",
+                "x Constant 17:2-17:3 ",
+                "y Constant 17:5-17:6 ",
+            ]
+        "#]]
+        .assert_debug_eq(&lines);
+    }
+
+    #[tokio::test]
+    async fn test_workspace_symbols() {
+        let doc = indoc! {r"
+            parse_number = \str -> str
+
+            print_name = \name -> name
+
+            number = 1
+            "};
+
+        let (inner, _url) = test_setup(DOC_LIT.to_string() + doc).await;
+
+        let names = |symbols: Vec<SymbolInformation>| -> Vec<String> {
+            (symbols.into_iter())
+                .map(|symbol| format!("{} {:?}", symbol.name, symbol.container_name))
+                .collect()
+        };
+
+        let pn = names(inner.registry.workspace_symbols("pn").await);
+        let num = names(inner.registry.workspace_symbols("num").await);
+
+        expect![[r#"
+            [
+                [
+                    "parse_number Some(\"Test\")",
+                    "print_name Some(\"Test\")",
+                ],
+                [
+                    "number Some(\"Test\")",
+                    "parse_number Some(\"Test\")",
+                ],
+            ]
+        "#]]
+        .assert_debug_eq(&[pn, num]);
+    }
+
+    #[tokio::test]
+    async fn test_inlay_hints() {
+        let doc = indoc! {r#"
+            greeting = "Hello"
+
+            annotated : U64
+            annotated = 1
+
+            add = \a, b -> a + b
+
+            total =
+                numbers = [1, 2, 3]
+                double = \n -> n * 2
+
+                List.map numbers \number -> double number
+                |> List.walk 0 \sum, n -> sum + n
+            "#};
+
+        let (inner, url) = test_setup(DOC_LIT.to_string() + doc).await;
+
+        let whole_document = Range::new(Position::new(0, 0), Position::new(20, 0));
+        let hints = |max_length| {
+            let inner = &inner;
+            let url = &url;
+
+            async move {
+                (inner
+                    .registry
+                    .inlay_hints(url, whole_document, max_length)
+                    .await)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|hint| {
+                        let InlayHintLabel::String(label) = hint.label else {
+                            panic!("Expected a string label");
+                        };
+
+                        format!(
+                            "{}:{} {}",
+                            hint.position.line, hint.position.character, label
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        expect![[r#"
+            [
+                "3:8 : Str",
+                "8:3 : Num a, Num a -> Num a",
+                "10:5 : Num *",
+                "11:11 : List (Num *)",
+                "12:10 : Num a -> Num a",
+                "14:28 : Num *",
+                "15:23 : Num *",
+                "15:26 : Num *",
+            ]
+        "#]]
+        .assert_debug_eq(&hints(40).await);
+        expect![[r#"
+            [
+                "3:8 : Str",
+                "8:3 : Num a, Num…",
+                "10:5 : Num *",
+                "11:11 : List (Num *)",
+                "12:10 : Num a -> Nu…",
+                "14:28 : Num *",
+                "15:23 : Num *",
+                "15:26 : Num *",
+            ]
+        "#]]
+        .assert_debug_eq(&hints(12).await);
+    }
+
+    /// Renders the signature help at a position as the signature, with the active parameter
+    /// in brackets, and the documentation
+    async fn signature_at(inner: &RocServerState, url: &Url, line: u32, character: u32) -> String {
+        let Some(help) = (inner.registry)
+            .signature_help(url, Position::new(line, character))
+            .await
+        else {
+            return "None".to_string();
+        };
+
+        let signature = &help.signatures[0];
+        let active = help.active_parameter.unwrap() as usize;
+        let mut label = signature.label.clone();
+
+        if let Some(ParameterLabel::LabelOffsets([start, end])) = (signature.parameters)
+            .as_ref()
+            .and_then(|parameters| parameters.get(active))
+            .map(|parameter| &parameter.label)
+        {
+            label.insert(*end as usize, ']');
+            label.insert(*start as usize, '[');
+        }
+
+        match &signature.documentation {
+            Some(Documentation::MarkupContent(docs)) => format!("{} -- {}", label, docs.value),
+            _ => label,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signature_help() {
+        let doc = indoc! {r#"
+            ## Repeats a name.
+            greet : Str, U64 -> Str
+            greet = \name, times -> Str.repeat name times
+
+            main =
+                first = greet "Hi" 3
+                second = Str.concat "a" "b"
+                third = "Hi" |> greet 2
+                [first, second, third]
+            "#};
+
+        let (inner, url) = test_setup(DOC_LIT.to_string() + doc).await;
+
+        let signatures = [
+            signature_at(&inner, &url, 8, 19).await,
+            signature_at(&inner, &url, 8, 23).await,
+            signature_at(&inner, &url, 9, 25).await,
+            signature_at(&inner, &url, 10, 26).await,
+            signature_at(&inner, &url, 11, 5).await,
+        ];
+
+        expect![[r#"
+            [
+                "greet : [Str], U64 -> Str -- Repeats a name.\n",
+                "greet : Str, [U64] -> Str -- Repeats a name.\n",
+                "Str.concat : [Str], Str -> Str -- Concatenates two strings together.\n```roc\nexpect Str.concat(\"ab\", \"cd\") == \"abcd\"\nexpect Str.concat(\"hello\", \"\") == \"hello\"\nexpect Str.concat(\"\", \"\") == \"\"\n```\n",
+                "greet : Str, [U64] -> Str -- Repeats a name.\n",
+                "None",
+            ]
+        "#]].assert_debug_eq(&signatures);
+    }
+
+    #[tokio::test]
+    async fn test_hover_typed_hole() {
+        let doc = indoc! {r"
+            greet : Str -> Str
+            greet = \name -> Str.concat name ?rest
+            "};
+
+        let (inner, url) = test_setup(DOC_LIT.to_string() + doc).await;
+
+        let hover = (inner.registry.hover(&url, Position::new(4, 35)).await).unwrap();
+        let HoverContents::Array(contents) = hover.contents else {
+            panic!("Expected the hover to have several parts");
+        };
+        let contents = (contents.into_iter())
+            .map(|content| match content {
+                MarkedString::String(string) => string,
+                MarkedString::LanguageString(string) => string.value,
+            })
+            .collect::<Vec<_>>();
+
+        expect![[r#"
+            (
+                "4:33-4:38",
+                [
+                    "Str",
+                    "In scope with that type: `name`",
+                ],
+            )
+        "#]]
+        .assert_debug_eq(&(range_string(hover.range.unwrap()), contents));
+    }
+
+    #[tokio::test]
+    async fn test_code_lenses() {
+        let doc = indoc! {r"
+            double = \n -> n * 2
+
+            expect double 2 == 4
+
+            # Doubling zero
+            expect
+                double 0 == 0
+            "};
+
+        let (inner, url) = test_setup(DOC_LIT.to_string() + doc).await;
+
+        let lenses = (inner.registry.code_lenses(&url).await)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|lens| {
+                let command = lens.command.unwrap();
+                let arguments = (command.arguments.unwrap_or_default().iter())
+                    .map(|argument| argument.to_string())
+                    .collect::<Vec<_>>();
+
+                format!(
+                    "{} {} {} {}",
+                    range_string(lens.range),
+                    command.title,
+                    command.command,
+                    arguments.join(" ")
+                )
+            })
+            .collect::<Vec<_>>();
+
+        expect![[r#"
+            [
+                "0:0-0:0 Run all tests in module roc.runModuleTests \"file:///Test.roc\"",
+                "5:0-5:20 Run test roc.runTest \"file:///Test.roc\" {\"character\":0,\"line\":5}",
+                "8:0-9:17 Run test roc.runTest \"file:///Test.roc\" {\"character\":0,\"line\":8}",
+            ]
+        "#]]
+        .assert_debug_eq(&lenses);
+    }
+
+    #[test]
+    fn test_filter_matches_one_test() {
+        let filter = regex::Regex::new(&test_runner::test_filter("Test.roc", 1)).unwrap();
+
+        assert!(filter.is_match("Test.roc:1"));
+        assert!(!filter.is_match("Test.roc:12"));
+        assert!(!filter.is_match("MyTest.roc:1"));
+        assert!(!filter.is_match("Test_roc:1"));
+    }
+
+    #[test]
+    fn test_expect_results_from_report() {
+        let report = r#"[
+            {"module": "Test.roc", "line": 6, "name": "Test.roc:6", "source": "expect double 2 == 4",
+             "passed": true, "failure": null, "snapshot": null, "duration_ms": 1},
+            {"module": "Test.roc", "line": 9, "name": "Test.roc:9", "source": "expect\n    double 0 == 1",
+             "passed": false, "failure": "This expectation failed", "snapshot": null, "duration_ms": 1},
+            {"module": "Test.roc", "line": 12, "name": "Test.roc:12", "source": "expect Inspect.snapshot(double 3)",
+             "passed": false, "failure": "This snapshot isn't stored yet",
+             "snapshot": {"stored": null, "current": "6"}, "duration_ms": 1},
+            {"module": "Other.roc", "line": 9, "name": "Other.roc:9", "source": "expect 1 == 1",
+             "passed": true, "failure": null, "snapshot": null, "duration_ms": 0}
+        ]"#;
+
+        let results = |position| {
+            let results = test_runner::expect_results(report, "Test.roc", position).unwrap();
+
+            (results.iter())
+                .map(|result| {
+                    let range = range_string(result.range);
+
+                    format!("{range} {:?} snapshot: {}", result.failure, result.snapshot)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        expect![[r#"
+            [
+                "5:0-5:20 None snapshot: false",
+                "8:0-9:17 Some(\"This expectation failed\") snapshot: false",
+                "11:0-11:33 Some(\"This snapshot isn't stored yet\") snapshot: true",
+            ]
+        "#]]
+        .assert_debug_eq(&results(None));
+
+        expect![[r#"
+            [
+                "8:0-9:17 Some(\"This expectation failed\") snapshot: false",
+            ]
+        "#]]
+        .assert_debug_eq(&results(Some(Position::new(9, 4))));
+    }
+
+    #[tokio::test]
+    async fn test_folding_ranges() {
+        let doc = indoc! {r#"
+            import Foo
+            import Bar
+
+            ## Shows a number,
+            ## or says that there isn't one.
+            show = \maybe ->
+                when maybe is
+                    Ok n ->
+                        Num.toStr n
+
+                    Err _ ->
+                        "none"
+
+            config = {
+                name: "roc",
+                size: 1,
+            }
+            "#};
+
+        let (inner, url) = test_setup(DOC_LIT.to_string() + doc).await;
+
+        let folds = (inner.registry.folding_ranges(&url).await)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|fold| format!("{}-{} {:?}", fold.start_line, fold.end_line, fold.kind))
+            .collect::<Vec<_>>();
+
+        expect![[r#"
+            [
+                "3-4 Some(Imports)",
+                "6-7 Some(Comment)",
+                "8-14 None",
+                "9-14 None",
+                "10-11 None",
+                "13-14 None",
+                "16-19 None",
+            ]
+        "#]]
+        .assert_debug_eq(&folds);
+    }
+
+    #[tokio::test]
+    async fn test_range_formatting() {
+        let doc = indoc! {r#"
+            # Leave this comment alone
+            first   =    1
+            # This one too
+
+            second =   [1,2,   3]
+
+            third  =  3
+            "#};
+
+        let (inner, url) = test_setup(DOC_LIT.to_string() + doc).await;
+
+        let edits = |edits: Option<Vec<TextEdit>>| {
+            edits
+                .unwrap_or_default()
+                .into_iter()
+                .map(|edit| format!("{} {:?}", range_string(edit.range), edit.new_text))
+                .collect::<Vec<_>>()
+        };
+
+        // The selection only has to overlap a def to reformat all of it.
+        let range = Range::new(Position::new(4, 4), Position::new(7, 3));
+
+        expect![[r#"
+            [
+                "4:0-7:0 \"first = 1\\n# This one too\\n\\n\"",
+                "7:0-9:0 \"second = [1, 2, 3]\\n\\n\"",
+            ]
+        "#]]
+        .assert_debug_eq(&edits(inner.registry.range_formatting(&url, range).await));
+
+        // After a newline, only the def which ended on the line before is reformatted.
+        expect![[r#"
+            [
+                "9:0-10:0 \"third = 3\\n\"",
+            ]
+        "#]]
+        .assert_debug_eq(&edits(
+            inner
+                .registry
+                .on_type_formatting(&url, Position::new(10, 0))
+                .await,
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_format_on_type_keeps_comments() {
+        let doc = indoc! {r"
+            first   =   1 # One
+            # Keep this
+            second = 2
+            "};
+
+        let (inner, url) = test_setup(DOC_LIT.to_string() + doc).await;
+
+        let edits = (inner.registry)
+            .on_type_formatting(&url, Position::new(4, 0))
+            .await
+            .unwrap_or_default();
+
+        let mut source = DOC_LIT.to_string() + doc;
+        for edit in edits.iter().rev() {
+            let start = text_offset(&source, edit.range.start);
+            let end = text_offset(&source, edit.range.end);
+
+            source.replace_range(start..end, &edit.new_text);
+        }
+
+        // The comments after the def are reformatted with it, so they're still there once.
+        expect![[r#"
+            "first = 1 # One\n# Keep this\nsecond = 2\n"
+        "#]]
+        .assert_debug_eq(&&source[DOC_LIT.len()..]);
+    }
+
+    #[tokio::test]
+    async fn test_selection_ranges() {
+        let doc = indoc! {r"
+            main =
+                list = [1, 2 + 3, 4]
+
+                List.len list
+            "};
+
+        let (inner, url) = test_setup(DOC_LIT.to_string() + doc).await;
+
+        let selections = (inner.registry)
+            .selection_ranges(&url, &[Position::new(4, 19), Position::new(6, 6)])
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|selection| {
+                let mut ranges = vec![range_string(selection.range)];
+                let mut parent = selection.parent;
+
+                while let Some(selection) = parent {
+                    ranges.push(range_string(selection.range));
+                    parent = selection.parent;
+                }
+
+                ranges.join(" < ")
+            })
+            .collect::<Vec<_>>();
+
+        expect![[r#"
+            [
+                "4:19-4:20 < 4:15-4:20 < 4:11-4:24 < 4:4-4:24 < 4:4-6:17 < 3:0-6:17 < 0:0-8:0",
+                "6:4-6:12 < 6:4-6:17 < 4:4-6:17 < 3:0-6:17 < 0:0-8:0",
+            ]
+        "#]]
+        .assert_debug_eq(&selections);
+    }
 }
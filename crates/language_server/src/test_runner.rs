@@ -0,0 +1,153 @@
+//! Runs the top-level `expect`s of a module with `roc test`, so they can be run from code
+//! lenses in the editor. They run in a child process, so a test that crashes can't take the
+//! language server down with it.
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde_json::Value;
+use tower_lsp::lsp_types::{Position, Range};
+
+/// Runs the top-level `expect` at a position; its arguments are the document's url and the
+/// position.
+pub(crate) const RUN_TEST_COMMAND: &str = "roc.runTest";
+/// Runs all of the top-level `expect`s in a module; its argument is the document's url.
+pub(crate) const RUN_MODULE_TESTS_COMMAND: &str = "roc.runModuleTests";
+/// Stores the current value of the `expect Inspect.snapshot(value)` at a position; its
+/// arguments are the document's url and the position.
+pub(crate) const UPDATE_SNAPSHOT_COMMAND: &str = "roc.updateSnapshot";
+
+/// The code of the diagnostics of failed snapshots, which can be updated with a code action.
+pub(crate) const SNAPSHOT_DIAGNOSTIC_CODE: &str = "snapshot";
+
+#[derive(Debug)]
+pub(crate) struct ExpectResult {
+    pub range: Range,
+    /// How the `expect` failed, rendered like `roc test` does, or None if it passed.
+    pub failure: Option<String>,
+    /// Whether the `expect` is a snapshot that got compared with the stored one.
+    pub snapshot: bool,
+}
+
+/// The `roc` binary next to the language server, as they're released together, or else the
+/// one on the `PATH`.
+pub(crate) fn default_roc_path() -> PathBuf {
+    std::env::current_exe()
+        .ok()
+        .map(|exe| exe.with_file_name(format!("roc{}", std::env::consts::EXE_SUFFIX)))
+        .filter(|roc| roc.is_file())
+        .unwrap_or_else(|| PathBuf::from("roc"))
+}
+
+/// Runs the top-level `expect`s in the module at `path` with the `roc` binary at `roc`, or
+/// only the one at `position`. The module is loaded from disk, so changes that haven't been
+/// saved aren't tested. With `update_snapshots`, the snapshots that run are stored rather
+/// than compared.
+pub(crate) fn run_expects(
+    roc: &Path,
+    path: &Path,
+    position: Option<Position>,
+    update_snapshots: bool,
+) -> Result<Vec<ExpectResult>, String> {
+    static REPORTS: AtomicUsize = AtomicUsize::new(0);
+
+    let file_name = (path.file_name())
+        .map(|file_name| file_name.to_string_lossy().to_string())
+        .ok_or_else(|| format!("{} isn't a file", path.display()))?;
+    let report_path = std::env::temp_dir().join(format!(
+        "rocls-test-report-{}-{}.json",
+        std::process::id(),
+        REPORTS.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let mut command = Command::new(roc);
+    command
+        .arg("test")
+        .args(["--format", "json", "--reporter", "json", "--output"])
+        .arg(&report_path);
+
+    if update_snapshots {
+        command.arg("--update-snapshots");
+    }
+
+    if let Some(position) = position {
+        command
+            .arg("--filter")
+            .arg(test_filter(&file_name, position.line + 1));
+    }
+
+    let output = (command.arg(path).output())
+        .map_err(|err| format!("Couldn't run `{} test`: {err}", roc.display()))?;
+
+    let report = std::fs::read_to_string(&report_path);
+    let _ = std::fs::remove_file(&report_path);
+
+    match report {
+        Ok(report) => expect_results(&report, &file_name, position),
+        // The problems are printed to stdout instead of running the tests.
+        Err(_) if has_errors(&output.stdout) => {
+            Err("The tests can't run until the errors in the module are fixed".to_string())
+        }
+        Err(_) => Err(format!(
+            "Couldn't run the tests: {}",
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        )),
+    }
+}
+
+/// The `roc test --filter` that only runs the test that starts on `line` of the module named
+/// `file_name`. Tests are named after where they start, like `Module.roc:12`, and the filter is
+/// a regex, so it's anchored to keep `Module.roc:1` from also running `Module.roc:12`.
+pub(crate) fn test_filter(file_name: &str, line: u32) -> String {
+    format!("^{}$", regex::escape(&format!("{file_name}:{line}")))
+}
+
+/// The results of the tests in the module named `file_name` in a `roc test --reporter json`
+/// report, or only of the one at `position`.
+pub(crate) fn expect_results(
+    report: &str,
+    file_name: &str,
+    position: Option<Position>,
+) -> Result<Vec<ExpectResult>, String> {
+    let records: Vec<Value> = serde_json::from_str(report)
+        .map_err(|err| format!("Couldn't read the report of the tests: {err}"))?;
+
+    let results = (records.iter())
+        .filter(|record| record["module"].as_str() == Some(file_name))
+        .filter_map(|record| {
+            let line = record["line"].as_u64()?.checked_sub(1)? as u32;
+            let source = record["source"].as_str()?;
+
+            Some(ExpectResult {
+                range: source_range(line, source),
+                failure: record["failure"].as_str().map(str::to_string),
+                snapshot: record["snapshot"].is_object(),
+            })
+        })
+        .filter(|result| {
+            position.map_or(true, |position| {
+                result.range.start <= position && position <= result.range.end
+            })
+        })
+        .collect();
+
+    Ok(results)
+}
+
+/// The range of a top-level `expect` whose source starts at the beginning of `line`.
+fn source_range(line: u32, source: &str) -> Range {
+    let last_line = source.rsplit('\n').next().unwrap_or_default();
+    let end = Position::new(
+        line + source.matches('\n').count() as u32,
+        last_line.encode_utf16().count() as u32,
+    );
+
+    Range::new(Position::new(line, 0), end)
+}
+
+/// Whether `roc test --format json` printed any errors, rather than only warnings.
+fn has_errors(stdout: &[u8]) -> bool {
+    serde_json::from_slice::<Vec<Value>>(stdout).is_ok_and(|problems| {
+        (problems.iter()).any(|problem| problem["severity"].as_str() != Some("warning"))
+    })
+}
@@ -9,6 +9,7 @@ use object::{
 };
 use roc_collections::all::MutMap;
 use roc_error_macros::{internal_error, user_error};
+use roc_target::Architecture;
 use serde::{Deserialize, Serialize};
 use std::{
     ffi::{c_char, CStr},
@@ -29,6 +30,65 @@ const MIN_SECTION_ALIGNMENT: usize = 0x40;
 // TODO: Analyze if this offset is always correct.
 const PLT_ADDRESS_OFFSET: u64 = 0x10;
 
+/// The parts of ELF surgery that depend on the instruction set of the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ElfArch {
+    architecture: Architecture,
+    object_architecture: object::Architecture,
+    e_machine: u16,
+    r_none: u32,
+    r_relative: u32,
+    r_glob_dat: u32,
+    r_jump_slot: u32,
+    /// The first PLT entry calls the dynamic linker, the ones after it call functions.
+    plt_header_size: u64,
+    /// The data after the program headers is moved by a multiple of this to make room for more.
+    shift_alignment: u64,
+}
+
+impl ElfArch {
+    fn new(architecture: Architecture) -> Self {
+        match architecture {
+            Architecture::X86_64 => ElfArch {
+                architecture,
+                object_architecture: object::Architecture::X86_64,
+                e_machine: elf::EM_X86_64,
+                r_none: elf::R_X86_64_NONE,
+                r_relative: elf::R_X86_64_RELATIVE,
+                r_glob_dat: elf::R_X86_64_GLOB_DAT,
+                r_jump_slot: elf::R_X86_64_JUMP_SLOT,
+                plt_header_size: PLT_ADDRESS_OFFSET,
+                shift_alignment: MIN_SECTION_ALIGNMENT as u64,
+            },
+            Architecture::Aarch64 => ElfArch {
+                architecture,
+                object_architecture: object::Architecture::Aarch64,
+                e_machine: elf::EM_AARCH64,
+                r_none: elf::R_AARCH64_NONE,
+                r_relative: elf::R_AARCH64_RELATIVE,
+                r_glob_dat: elf::R_AARCH64_GLOB_DAT,
+                r_jump_slot: elf::R_AARCH64_JUMP_SLOT,
+                plt_header_size: 2 * PLT_ADDRESS_OFFSET,
+                // `adrp` addresses 4KiB pages, so the host's own `adrp`/`add` pairs only keep
+                // pointing at the right data if we move everything by whole pages.
+                shift_alignment: 0x1000,
+            },
+            other => internal_error!("The surgical linker does not support ELF for {other}"),
+        }
+    }
+
+    /// Checks that a host or app was compiled for this architecture, since with cross-compilation
+    /// it's easy to pass in a file for the wrong one.
+    fn check_machine(&self, e_machine: u16, what: &str) {
+        if e_machine != self.e_machine {
+            user_error!(
+                "The {what} is not an {} ELF file (its e_machine is {e_machine}).\nProbably a host for a different target was given.",
+                self.architecture
+            );
+        }
+    }
+}
+
 struct ElfDynamicDeps {
     got_app_syms: Vec<(String, usize)>,
     got_sections: Vec<(usize, usize)>,
@@ -143,13 +203,18 @@ fn collect_roc_definitions<'a>(object: &object::File<'a, &'a [u8]>) -> MutMap<St
 }
 
 struct Surgeries<'a> {
+    arch: ElfArch,
     surgeries: MutMap<String, Vec<SurgeryEntry>>,
     app_func_addresses: MutMap<u64, &'a str>,
     indirect_warning_given: bool,
 }
 
 impl<'a> Surgeries<'a> {
-    fn new(application_symbols: &[Symbol], app_func_addresses: MutMap<u64, &'a str>) -> Self {
+    fn new(
+        arch: ElfArch,
+        application_symbols: &[Symbol],
+        app_func_addresses: MutMap<u64, &'a str>,
+    ) -> Self {
         let mut surgeries = MutMap::default();
 
         // for each symbol that the host expects from the application
@@ -160,6 +225,7 @@ impl<'a> Surgeries<'a> {
         }
 
         Self {
+            arch,
             surgeries,
             app_func_addresses,
             indirect_warning_given: false,
@@ -220,6 +286,19 @@ impl<'a> Surgeries<'a> {
                 internal_error!("Failed to load text section, {:+x?}: {}", sec, err);
             }
         };
+
+        if self.arch.architecture == Architecture::Aarch64 {
+            self.append_aarch64_branches(
+                object_bytes,
+                sec,
+                file_offset,
+                compressed,
+                &data,
+                verbose,
+            );
+            return;
+        }
+
         let mut decoder = Decoder::with_ip(64, &data, sec.address(), DecoderOptions::NONE);
         let mut inst = Instruction::default();
 
@@ -310,10 +389,161 @@ impl<'a> Surgeries<'a> {
             }
         }
     }
+
+    /// Every aarch64 instruction is 4 bytes, so unlike on x86 we can look at each of them without
+    /// decoding the ones before. Only `b` and `bl` call PLT entries directly; anything else first
+    /// loads the address into a register, which we can't follow.
+    fn append_aarch64_branches(
+        &mut self,
+        object_bytes: &[u8],
+        sec: &Section,
+        file_offset: u64,
+        compressed: bool,
+        data: &[u8],
+        verbose: bool,
+    ) {
+        for (i, bytes) in data.chunks_exact(4).enumerate() {
+            let instruction = u32::from_le_bytes(bytes.try_into().unwrap());
+            let address = sec.address() + 4 * i as u64;
+
+            let Some(target) = aarch64_branch_target(instruction, address) else {
+                continue;
+            };
+
+            if let Some(func_name) = self.app_func_addresses.get(&target) {
+                if compressed {
+                    internal_error!(
+                        "Surgical linking does not work with compressed text sections: {:+x?}",
+                        sec
+                    );
+                }
+
+                let offset = file_offset + 4 * i as u64;
+                if verbose {
+                    println!("Found branch from {address:+x} to {target:+x}({func_name})");
+                    println!("\tNeed to surgically replace the branch at file offset {offset:+x}");
+                    println!(
+                        "\tIts current value is {:+x?}",
+                        &object_bytes[offset as usize..][..4]
+                    )
+                }
+
+                // unlike on x86, the branch offset is relative to the branch itself
+                self.surgeries
+                    .get_mut(*func_name)
+                    .unwrap()
+                    .push(SurgeryEntry {
+                        file_offset: offset,
+                        virtual_offset: VirtualOffset::Relative(address),
+                        size: 4,
+                    });
+            }
+        }
+    }
+}
+
+/// `b` and `bl` store the distance to their target in instructions, in their low 26 bits.
+const AARCH64_BRANCH_MASK: u32 = 0x7C00_0000;
+const AARCH64_B: u32 = 0x1400_0000;
+const AARCH64_NOP: u32 = 0xD503_201F;
+
+fn aarch64_branch_target(instruction: u32, address: u64) -> Option<u64> {
+    if instruction & AARCH64_BRANCH_MASK != AARCH64_B {
+        return None;
+    }
+
+    // sign-extend the 26-bit immediate, and multiply it by the instruction size
+    let offset = (((instruction << 6) as i32) >> 4) as i64;
+
+    Some(address.wrapping_add_signed(offset))
+}
+
+/// Replaces the `width` bits of an instruction starting at bit `shift` with `value`.
+fn aarch64_set_imm(instruction: u32, value: u32, shift: u32, width: u32) -> u32 {
+    let mask = ((1 << width) - 1) << shift;
+
+    (instruction & !mask) | ((value << shift) & mask)
+}
+
+/// Checks that a signed immediate fits in `width` bits of an instruction.
+fn aarch64_check_range(value: i64, width: u32, r_type: u32) -> u32 {
+    let limit = 1 << (width - 1);
+
+    if !(-limit..limit).contains(&value) {
+        internal_error!(
+            "The target of an aarch64 relocation of type {r_type} is out of range: {value:+x}"
+        );
+    }
+
+    value as u32
+}
+
+/// A `b` or `bl` with its target `offset` bytes away from itself.
+fn aarch64_branch(instruction: u32, offset: i64) -> u32 {
+    let imm26 = aarch64_check_range(offset >> 2, 26, elf::R_AARCH64_JUMP26);
+
+    aarch64_set_imm(instruction, imm26, 0, 26)
+}
+
+/// `adr` and `adrp` split their 21-bit immediate in two.
+fn aarch64_set_adr_imm(instruction: u32, value: i64, r_type: u32) -> u32 {
+    let imm21 = aarch64_check_range(value, 21, r_type);
+    let instruction = aarch64_set_imm(instruction, imm21 & 0b11, 29, 2);
+
+    aarch64_set_imm(instruction, imm21 >> 2, 5, 19)
+}
+
+/// Fills in the immediate of the aarch64 instruction at `place`, for a relocation to `target`
+/// (the address of the symbol plus the addend). Returns `None` for relocations that aren't
+/// in an instruction, which the object crate gives a `RelocationKind` that we can handle instead.
+fn relocate_aarch64(r_type: u32, instruction: u32, target: i64, place: i64) -> Option<u32> {
+    let page = |address: i64| address & !0xFFF;
+    let lo12 = (target & 0xFFF) as u32;
+
+    let relocated = match r_type {
+        elf::R_AARCH64_CALL26 | elf::R_AARCH64_JUMP26 => {
+            aarch64_branch(instruction, target - place)
+        }
+        elf::R_AARCH64_CONDBR19 => {
+            let imm19 = aarch64_check_range((target - place) >> 2, 19, r_type);
+            aarch64_set_imm(instruction, imm19, 5, 19)
+        }
+        elf::R_AARCH64_TSTBR14 => {
+            let imm14 = aarch64_check_range((target - place) >> 2, 14, r_type);
+            aarch64_set_imm(instruction, imm14, 5, 14)
+        }
+        elf::R_AARCH64_ADR_PREL_LO21 => aarch64_set_adr_imm(instruction, target - place, r_type),
+        elf::R_AARCH64_ADR_PREL_PG_HI21
+        | elf::R_AARCH64_ADR_PREL_PG_HI21_NC
+        | elf::R_AARCH64_ADR_GOT_PAGE => {
+            aarch64_set_adr_imm(instruction, (page(target) - page(place)) >> 12, r_type)
+        }
+        elf::R_AARCH64_ADD_ABS_LO12_NC | elf::R_AARCH64_LDST8_ABS_LO12_NC => {
+            aarch64_set_imm(instruction, lo12, 10, 12)
+        }
+        elf::R_AARCH64_LDST16_ABS_LO12_NC => aarch64_set_imm(instruction, lo12 >> 1, 10, 12),
+        elf::R_AARCH64_LDST32_ABS_LO12_NC => aarch64_set_imm(instruction, lo12 >> 2, 10, 12),
+        elf::R_AARCH64_LDST64_ABS_LO12_NC => aarch64_set_imm(instruction, lo12 >> 3, 10, 12),
+        elf::R_AARCH64_LDST128_ABS_LO12_NC => aarch64_set_imm(instruction, lo12 >> 4, 10, 12),
+        elf::R_AARCH64_LD64_GOT_LO12_NC => {
+            // There is no GOT for the app, so rather than loading the address from it, we
+            // compute it: `ldr xT, [xN, :got_lo12:sym]` becomes `add xT, xN, :lo12:sym`.
+            if instruction & 0xFFC0_0000 != 0xF940_0000 {
+                internal_error!("Expected an ldr for a GOT relocation, found {instruction:#x}");
+            }
+            let registers = instruction & 0x3FF;
+
+            0x9100_0000 | (lo12 << 10) | registers
+        }
+        _ => return None,
+    };
+
+    Some(relocated)
 }
 
 /// Constructs a `Metadata` from a host executable binary, and writes it to disk
 pub(crate) fn preprocess_elf_le(
+    architecture: Architecture,
     host_exe_path: &Path,
     metadata_path: &Path,
     preprocessed_path: &Path,
@@ -331,6 +561,14 @@ pub(crate) fn preprocess_elf_le(
         }
     };
 
+    let arch = ElfArch::new(architecture);
+    arch.check_machine(
+        load_struct_inplace::<elf::FileHeader64<LE>>(exec_data, 0)
+            .e_machine
+            .get(LE),
+        "host executable",
+    );
+
     let mut md = Metadata {
         roc_symbol_vaddresses: collect_roc_definitions(&exec_obj),
         ..Default::default()
@@ -408,7 +646,7 @@ pub(crate) fn preprocess_elf_le(
             })
             .filter_map(|(_, reloc)| {
                 if let RelocationFlags::Elf { r_type}  = reloc.flags() {
-                    if r_type == elf::R_X86_64_JUMP_SLOT {
+                    if r_type == arch.r_jump_slot {
                         Some(reloc)
                     } else {
                         None
@@ -420,8 +658,9 @@ pub(crate) fn preprocess_elf_le(
     for (i, reloc) in plt_relocs.enumerate() {
         for symbol in app_syms.iter() {
             if reloc.target() == RelocationTarget::Symbol(symbol.index()) {
-                let func_address = (i as u64 + 1) * PLT_ADDRESS_OFFSET + plt_address;
-                let func_offset = (i as u64 + 1) * PLT_ADDRESS_OFFSET + plt_offset;
+                let func_address =
+                    arch.plt_header_size + i as u64 * PLT_ADDRESS_OFFSET + plt_address;
+                let func_offset = arch.plt_header_size + i as u64 * PLT_ADDRESS_OFFSET + plt_offset;
                 app_func_addresses.insert(func_address, symbol.name().unwrap());
                 md.plt_addresses.insert(
                     symbol.name().unwrap().to_string(),
@@ -457,7 +696,7 @@ pub(crate) fn preprocess_elf_le(
     // look at the text (i.e. code) sections and see collect work needs to be done
     let text_disassembly_start = Instant::now();
 
-    let mut surgeries = Surgeries::new(&app_syms, app_func_addresses);
+    let mut surgeries = Surgeries::new(arch, &app_syms, app_func_addresses);
     surgeries.append_text_sections(exec_data, &exec_obj, verbose);
     md.surgeries = surgeries.surgeries;
 
@@ -472,7 +711,7 @@ pub(crate) fn preprocess_elf_le(
         dynamic_lib_count,
         shared_lib_index,
     } = scan_elf_dynamic_deps(
-        &exec_obj, &mut md, &app_syms, shared_lib, exec_data, verbose,
+        arch, &exec_obj, &mut md, &app_syms, shared_lib, exec_data, verbose,
     );
 
     let scanning_dynamic_deps_duration = scanning_dynamic_deps_start.elapsed();
@@ -480,6 +719,7 @@ pub(crate) fn preprocess_elf_le(
     let platform_gen_start = Instant::now();
 
     let out_mmap = gen_elf_le(
+        arch,
         exec_data,
         &mut md,
         preprocessed_path,
@@ -542,6 +782,7 @@ pub(crate) fn preprocess_elf_le(
 
 #[allow(clippy::too_many_arguments)]
 fn gen_elf_le(
+    arch: ElfArch,
     exec_data: &[u8],
     md: &mut Metadata,
     preprocessed_path: &Path,
@@ -573,8 +814,8 @@ fn gen_elf_le(
     // Copy header and shift everything to enable more program sections.
     let added_header_count = 3;
     md.added_byte_count = ph_ent_size as u64 * added_header_count;
-    md.added_byte_count = md.added_byte_count
-        + (MIN_SECTION_ALIGNMENT as u64 - md.added_byte_count % MIN_SECTION_ALIGNMENT as u64);
+    md.added_byte_count =
+        md.added_byte_count + (arch.shift_alignment - md.added_byte_count % arch.shift_alignment);
     let ph_end = ph_offset as usize + ph_num as usize * ph_ent_size as usize;
     let physical_shift_start = ph_end as u64;
 
@@ -712,18 +953,18 @@ fn gen_elf_le(
                 rel.r_offset.set(LE, r_offset + md.added_byte_count);
                 // Deal with potential adjusts to absolute jumps.
                 // TODO: Verify other relocation types.
-                if rel.r_type(LE, false) == elf::R_X86_64_RELATIVE {
+                if rel.r_type(LE, false) == arch.r_relative {
                     let r_addend = rel.r_addend.get(LE);
                     rel.r_addend.set(LE, r_addend + md.added_byte_count as i64);
                 }
             }
             // If the relocation goes to a roc function, we need to surgically link it and change it to relative.
             let r_type = rel.r_type(LE, false);
-            if r_type == elf::R_X86_64_GLOB_DAT {
+            if r_type == arch.r_glob_dat {
                 let r_sym = rel.r_sym(LE, false);
                 for (name, index) in got_app_syms.iter() {
                     if *index as u32 == r_sym {
-                        rel.set_r_info(LE, false, 0, elf::R_X86_64_RELATIVE);
+                        rel.set_r_info(LE, false, 0, arch.r_relative);
                         let addend_addr = sec_offset as usize
                             + i * mem::size_of::<elf::Rela64<LE>>()
                             // This 16 skips the first 2 fields and gets to the addend field.
@@ -748,8 +989,7 @@ fn gen_elf_le(
             .filter_map(|(i, rel)| {
                 let r_type = rel.r_type(LE, false);
                 let r_sym = rel.r_sym(LE, false);
-                if r_type == elf::R_X86_64_JUMP_SLOT && app_sym_indices.contains(&(r_sym as usize))
-                {
+                if r_type == arch.r_jump_slot && app_sym_indices.contains(&(r_sym as usize)) {
                     Some(i)
                 } else {
                     None
@@ -761,12 +1001,11 @@ fn gen_elf_le(
         to_remove.sort();
         to_remove.reverse();
 
-        let mut j = relocations.len() - 1;
-        for i in to_remove.iter() {
+        for (removed, i) in to_remove.iter().enumerate() {
+            let j = relocations.len() - 1 - removed;
             relocations.swap(*i, j);
             let r_sym = relocations[j].r_sym(LE, false);
-            relocations[j].set_r_info(LE, false, r_sym, elf::R_X86_64_NONE);
-            j -= 1;
+            relocations[j].set_r_info(LE, false, r_sym, arch.r_none);
         }
 
         let section_headers = load_structs_inplace_mut::<elf::SectionHeader64<LE>>(
@@ -929,6 +1168,7 @@ fn gen_elf_le(
 }
 
 fn scan_elf_dynamic_deps(
+    arch: ElfArch,
     exec_obj: &object::File,
     md: &mut Metadata,
     app_syms: &[Symbol],
@@ -1074,7 +1314,7 @@ fn scan_elf_dynamic_deps(
     })
     .filter_map(|(_, reloc)| {
         if let RelocationFlags::Elf { r_type } = reloc.flags() {
-            if r_type == elf::R_X86_64_GLOB_DAT {
+            if r_type == arch.r_glob_dat {
                 for symbol in app_syms.iter() {
                     if reloc.target() == RelocationTarget::Symbol(symbol.index()) {
                         return Some((symbol.name().unwrap().to_string(), symbol.index().0));
@@ -1095,7 +1335,7 @@ fn scan_elf_dynamic_deps(
     })
     .filter_map(|(_, reloc)| {
         if let RelocationFlags::Elf { r_type } = reloc.flags() {
-            if r_type == elf::R_X86_64_JUMP_SLOT {
+            if r_type == arch.r_jump_slot {
                 for symbol in app_syms.iter() {
                     if reloc.target() == RelocationTarget::Symbol(symbol.index()) {
                         return Some(symbol.index().0);
@@ -1117,6 +1357,7 @@ fn scan_elf_dynamic_deps(
 }
 
 pub(crate) fn surgery_elf(
+    architecture: Architecture,
     roc_app_bytes: &[u8],
     metadata_path: &Path,
     executable_path: &Path,
@@ -1130,6 +1371,15 @@ pub(crate) fn surgery_elf(
         }
     };
 
+    let arch = ElfArch::new(architecture);
+    if app_obj.architecture() != arch.object_architecture {
+        internal_error!(
+            "The application was compiled for {:?} instead of {}",
+            app_obj.architecture(),
+            arch.architecture
+        );
+    }
+
     if app_obj
         .sections()
         .filter(|sec| {
@@ -1158,7 +1408,7 @@ pub(crate) fn surgery_elf(
     let out_gen_start = Instant::now();
     let mut offset = 0;
 
-    surgery_elf_help(verbose, &md, &mut exec_mmap, &mut offset, app_obj);
+    surgery_elf_help(arch, verbose, &md, &mut exec_mmap, &mut offset, app_obj);
 
     let out_gen_duration = out_gen_start.elapsed();
     let flushing_data_start = Instant::now();
@@ -1205,6 +1455,7 @@ pub(crate) fn surgery_elf(
 }
 
 fn surgery_elf_help(
+    arch: ElfArch,
     verbose: bool,
     md: &Metadata,
     exec_mmap: &mut MmapMut,
@@ -1217,6 +1468,7 @@ fn surgery_elf_help(
         internal_error!("Only 64bit little endian elf currently supported for surgery");
     }
     let exec_header = load_struct_inplace::<elf::FileHeader64<LE>>(exec_mmap, 0);
+    arch.check_machine(exec_header.e_machine.get(LE), "preprocessed host");
 
     let ph_offset = exec_header.e_phoff.get(LE);
     let ph_ent_size = exec_header.e_phentsize.get(LE);
@@ -1429,6 +1681,30 @@ fn surgery_elf_help(
                     if let Some(target_offset) = target_offset {
                         let virt_base = section_virtual_offset + rel.0 as usize;
                         let base = section_offset + rel.0 as usize;
+
+                        if let (Architecture::Aarch64, RelocationFlags::Elf { r_type }) =
+                            (arch.architecture, rel.1.flags())
+                        {
+                            let bytes = &mut exec_mmap[base..][..4];
+                            let instruction = u32::from_le_bytes((&*bytes).try_into().unwrap());
+                            let relocated = relocate_aarch64(
+                                r_type,
+                                instruction,
+                                target_offset + rel.1.addend(),
+                                virt_base as i64,
+                            );
+
+                            if let Some(relocated) = relocated {
+                                if verbose {
+                                    println!(
+                                        "\t\tRelocated instruction at {base:+x} (virt: {virt_base:+x}): {instruction:#010x} -> {relocated:#010x}",
+                                    );
+                                }
+                                bytes.copy_from_slice(&relocated.to_le_bytes());
+                                continue;
+                            }
+                        }
+
                         let target: i64 = match rel.1.kind() {
                             RelocationKind::Relative | RelocationKind::PltRelative => {
                                 target_offset - virt_base as i64 + rel.1.addend()
@@ -1629,6 +1905,16 @@ fn surgery_elf_help(
                 VirtualOffset::Absolute => 0,
             };
             match s.size {
+                4 if arch.architecture == Architecture::Aarch64 => {
+                    let offset = func_virt_offset as i64 - surgery_virt_offset;
+                    if verbose {
+                        println!("\tTarget Jump: {offset:+x}");
+                    }
+                    let bytes =
+                        &mut exec_mmap[(s.file_offset + md.added_byte_count) as usize..][..4];
+                    let branch = u32::from_le_bytes((&*bytes).try_into().unwrap());
+                    bytes.copy_from_slice(&aarch64_branch(branch, offset).to_le_bytes());
+                }
                 4 => {
                     let target = (func_virt_offset as i64 - surgery_virt_offset) as i32;
                     if verbose {
@@ -1658,18 +1944,33 @@ fn surgery_elf_help(
         if let Some((plt_off, plt_vaddr)) = md.plt_addresses.get(func_name) {
             let plt_off = (*plt_off + md.added_byte_count) as usize;
             let plt_vaddr = *plt_vaddr + md.added_byte_count;
-            let jmp_inst_len = 5;
-            let target =
-                (func_virt_offset as i64 - (plt_vaddr as i64 + jmp_inst_len as i64)) as i32;
-            if verbose {
-                println!("\tPLT: {plt_off:+x}, {plt_vaddr:+x}");
-                println!("\tTarget Jump: {target:+x}");
-            }
-            let data = target.to_le_bytes();
-            exec_mmap[plt_off] = 0xE9;
-            exec_mmap[plt_off + 1..plt_off + jmp_inst_len].copy_from_slice(&data);
-            for i in jmp_inst_len..PLT_ADDRESS_OFFSET as usize {
-                exec_mmap[plt_off + i] = 0x90;
+
+            if arch.architecture == Architecture::Aarch64 {
+                let offset = func_virt_offset as i64 - plt_vaddr as i64;
+                if verbose {
+                    println!("\tPLT: {plt_off:+x}, {plt_vaddr:+x}");
+                    println!("\tTarget Jump: {offset:+x}");
+                }
+                let entry = &mut exec_mmap[plt_off..][..PLT_ADDRESS_OFFSET as usize];
+                let (branch, nops) = entry.split_at_mut(4);
+                branch.copy_from_slice(&aarch64_branch(AARCH64_B, offset).to_le_bytes());
+                for nop in nops.chunks_exact_mut(4) {
+                    nop.copy_from_slice(&AARCH64_NOP.to_le_bytes());
+                }
+            } else {
+                let jmp_inst_len = 5;
+                let target =
+                    (func_virt_offset as i64 - (plt_vaddr as i64 + jmp_inst_len as i64)) as i32;
+                if verbose {
+                    println!("\tPLT: {plt_off:+x}, {plt_vaddr:+x}");
+                    println!("\tTarget Jump: {target:+x}");
+                }
+                let data = target.to_le_bytes();
+                exec_mmap[plt_off] = 0xE9;
+                exec_mmap[plt_off + 1..plt_off + jmp_inst_len].copy_from_slice(&data);
+                for i in jmp_inst_len..PLT_ADDRESS_OFFSET as usize {
+                    exec_mmap[plt_off + i] = 0x90;
+                }
             }
         }
 
@@ -1794,6 +2095,11 @@ mod tests {
         );
 
         let zig = std::env::var("ROC_ZIG").unwrap_or_else(|_| "zig".into());
+        let zig_target = match target {
+            Target::LinuxX64 => "x86_64-linux-gnu",
+            Target::LinuxArm64 => "aarch64-linux-gnu",
+            _ => unreachable!("no zig test host for {target}"),
+        };
 
         std::fs::write(dir.join("host.zig"), host_zig.as_bytes()).unwrap();
         std::fs::write(dir.join("app.zig"), app_zig.as_bytes()).unwrap();
@@ -1801,7 +2107,14 @@ mod tests {
         // we need to compile the app first
         let output = std::process::Command::new(&zig)
             .current_dir(dir)
-            .args(["build-obj", "app.zig", "-fPIC", "-OReleaseFast"])
+            .args([
+                "build-obj",
+                "app.zig",
+                "-fPIC",
+                "-OReleaseFast",
+                "-target",
+                zig_target,
+            ])
             .output()
             .unwrap();
 
@@ -1828,7 +2141,7 @@ mod tests {
                 .collect()
         };
 
-        let dylib_bytes = crate::generate_dylib::create_dylib_elf64(&names).unwrap();
+        let dylib_bytes = crate::generate_dylib::create_dylib_elf64(&names, target).unwrap();
         std::fs::write(dir.join("libapp.so"), dylib_bytes).unwrap();
 
         // now we can compile the host (it uses libapp.so, hence the order here)
@@ -1841,6 +2154,8 @@ mod tests {
                 "-fPIE",
                 "-lc",
                 "-OReleaseFast",
+                "-target",
+                zig_target,
             ])
             .output()
             .unwrap();
@@ -1857,6 +2172,7 @@ mod tests {
         let preprocessed_host_filename = dir.join(target.prebuilt_surgical_host());
 
         preprocess_elf_le(
+            target.architecture(),
             &dir.join("host"),
             &dir.join("metadata"),
             &preprocessed_host_filename,
@@ -1868,6 +2184,7 @@ mod tests {
         std::fs::copy(&preprocessed_host_filename, dir.join("final")).unwrap();
 
         surgery_elf(
+            target.architecture(),
             &roc_app,
             &dir.join("metadata"),
             &dir.join("final"),
@@ -1902,4 +2219,92 @@ mod tests {
 
         assert_eq!("Hello foo\n", output);
     }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn zig_host_app_aarch64() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+
+        // we can't run the result on an x86_64 machine, but we can check that the
+        // call from the host into the app no longer goes through the dynamic linker
+        zig_host_app_help(dir, Target::LinuxArm64);
+
+        let bytes = std::fs::read(dir.join("final")).unwrap();
+        let object = object::File::parse(&*bytes).unwrap();
+
+        assert_eq!(object.architecture(), object::Architecture::Aarch64);
+
+        let roc_magic1 = object
+            .dynamic_symbols()
+            .find(|s| s.name() == Ok("roc_magic1"))
+            .unwrap();
+
+        assert!(roc_magic1.is_definition());
+        assert_ne!(roc_magic1.address(), 0);
+        assert!(
+            !object.dynamic_relocations().unwrap().any(|(_, r)| r.flags()
+                == object::RelocationFlags::Elf {
+                    r_type: elf::R_AARCH64_JUMP_SLOT
+                })
+        );
+    }
+
+    #[test]
+    fn aarch64_branches() {
+        // bl 0x4179c -> 0x11348
+        let bl = aarch64_branch(0x9400_0000, 0x11348 - 0x4179c);
+        assert_eq!(bl, 0x97ff_3eeb);
+        assert_eq!(aarch64_branch_target(bl, 0x4179c), Some(0x11348));
+
+        // b 0x417a4 -> 0x417c0
+        let b = aarch64_branch(AARCH64_B, 0x417c0 - 0x417a4);
+        assert_eq!(b, 0x1400_0007);
+        assert_eq!(aarch64_branch_target(b, 0x417a4), Some(0x417c0));
+
+        assert_eq!(aarch64_branch_target(AARCH64_NOP, 0x417a4), None);
+    }
+
+    #[test]
+    fn aarch64_relocations() {
+        let relocate = |r_type, instruction, target, place| {
+            relocate_aarch64(r_type, instruction, target, place).unwrap()
+        };
+
+        // adrp x2, <page of 0x11348>
+        assert_eq!(
+            relocate(
+                elf::R_AARCH64_ADR_PREL_PG_HI21,
+                0x9000_0002,
+                0x11348,
+                0x4178c
+            ),
+            0x90ff_fe82
+        );
+        // add x0, x0, :lo12:0x41740
+        assert_eq!(
+            relocate(elf::R_AARCH64_ADD_ABS_LO12_NC, 0x9100_0000, 0x41740, 0),
+            0x911d_0000
+        );
+        // ldr x3, [x3, :lo12:0x41748]
+        assert_eq!(
+            relocate(elf::R_AARCH64_LDST64_ABS_LO12_NC, 0xf940_0063, 0x41748, 0),
+            0xf943_a463
+        );
+        // ldr x2, [x2, :got_lo12:sym] is relaxed to add x2, x2, :lo12:sym
+        assert_eq!(
+            relocate(elf::R_AARCH64_LD64_GOT_LO12_NC, 0xf940_0042, 0x11348, 0),
+            0x910d_2042
+        );
+        assert_eq!(
+            relocate(elf::R_AARCH64_CALL26, 0x9400_0000, 0x11348, 0x4179c),
+            0x97ff_3eeb
+        );
+        assert_eq!(
+            relocate(elf::R_AARCH64_JUMP26, 0x1400_0000, 0x417c0, 0x417a4),
+            0x1400_0007
+        );
+
+        assert_eq!(relocate_aarch64(elf::R_AARCH64_ABS64, 0, 0x11348, 0), None);
+    }
 }
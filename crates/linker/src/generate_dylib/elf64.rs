@@ -1,10 +1,22 @@
 use object::{elf, Endianness};
+use roc_error_macros::internal_error;
+use roc_target::{Architecture, Target};
 
 use crate::pe::next_multiple_of;
 
-pub fn create_dylib_elf64(custom_names: &[String]) -> object::read::Result<Vec<u8>> {
+pub fn create_dylib_elf64(
+    custom_names: &[String],
+    target: Target,
+) -> object::read::Result<Vec<u8>> {
     let endian = Endianness::Little;
 
+    // the host is linked against this library, so it must be for the host's architecture
+    let e_machine = match target.architecture() {
+        Architecture::X86_64 => elf::EM_X86_64,
+        Architecture::Aarch64 => elf::EM_AARCH64,
+        other => internal_error!("ELF dylib creation for {:?}", other),
+    };
+
     let mut out_data = Vec::new();
     let mut writer = object::write::elf::Writer::new(endian, true, &mut out_data);
 
@@ -145,7 +157,7 @@ pub fn create_dylib_elf64(custom_names: &[String]) -> object::read::Result<Vec<u
             os_abi: 0,
             abi_version: 0,
             e_type: 3,
-            e_machine: 62,
+            e_machine,
             e_entry: 0x1000,
             e_flags: 0,
         })
@@ -14,7 +14,7 @@ pub(crate) use pe::APP_DLL;
 
 pub fn generate(target: Target, custom_names: &[String]) -> object::read::Result<Vec<u8>> {
     match target.operating_system() {
-        OperatingSystem::Linux => elf64::create_dylib_elf64(custom_names),
+        OperatingSystem::Linux => elf64::create_dylib_elf64(custom_names, target),
         OperatingSystem::Mac => macho::create_dylib_macho(custom_names, target),
         OperatingSystem::Windows => Ok(pe::synthetic_dll(custom_names)),
         other => unimplemented!("dylib creation for {:?}", other),
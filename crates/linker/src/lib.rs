@@ -39,6 +39,7 @@ pub fn support_level(link_type: LinkType, target: Target) -> SupportLevel {
     if let LinkType::Executable = link_type {
         match target {
             Target::LinuxX64 => SupportLevel::Full,
+            Target::LinuxArm64 => SupportLevel::Wip,
             Target::WinX64 => SupportLevel::Full,
             // macho support is incomplete
             Target::MacX64 => SupportLevel::None,
@@ -73,7 +74,16 @@ pub fn generate_stub_lib_from_loaded(
     stub_dll_symbols: &[String],
 ) -> PathBuf {
     let stub_lib = platform_main_roc.with_file_name(target.stub_app_lib_file_name());
-    generate_dynamic_lib(target, stub_dll_symbols, stub_lib.as_path());
+
+    // every app also exports the seed of Dict's hasher, for hosts that build or look up in dicts.
+    // The symbols are sorted, so it goes where a binary search would look for it.
+    let mut stub_dll_symbols = stub_dll_symbols.to_vec();
+    let seed = String::from("roc_dict_pseudo_seed");
+    if let Err(index) = stub_dll_symbols.binary_search(&seed) {
+        stub_dll_symbols.insert(index, seed);
+    }
+
+    generate_dynamic_lib(target, &stub_dll_symbols, stub_lib.as_path());
 
     stub_lib
 }
@@ -314,6 +324,7 @@ pub fn preprocess_host(
     match target.arch_os() {
         (_, OperatingSystem::Linux) => {
             crate::elf::preprocess_elf_le(
+                target.architecture(),
                 host_exe_path,
                 metadata_path,
                 preprocessed_path,
@@ -372,7 +383,14 @@ fn surgery(
 ) {
     match target.arch_os() {
         (_, OperatingSystem::Linux) => {
-            crate::elf::surgery_elf(roc_app_bytes, metadata_path, executable_path, verbose, time);
+            crate::elf::surgery_elf(
+                target.architecture(),
+                roc_app_bytes,
+                metadata_path,
+                executable_path,
+                verbose,
+                time,
+            );
         }
 
         (_, OperatingSystem::Mac) => {
@@ -8,19 +8,25 @@ use roc_mono::ir::OptLevel;
 use roc_repl_eval::gen::Problems;
 use roc_repl_ui::colors::{CYAN, END_COL};
 use roc_repl_ui::repl_state::{ReplAction, ReplState};
-use roc_repl_ui::{format_output, is_incomplete, CONT_PROMPT, PROMPT, SHORT_INSTRUCTIONS, TIPS};
+use roc_repl_ui::{
+    format_defs, format_output, highlight, is_incomplete, CONT_PROMPT, PROMPT, SHORT_INSTRUCTIONS,
+    TIPS,
+};
 use roc_reporting::report::{
     strip_colors, to_file_problem_report_string, ANSI_STYLE_CODES, DEFAULT_PALETTE,
 };
 use roc_target::Target;
+use rustyline::completion::Completer;
 use rustyline::highlight::{Highlighter, PromptInfo};
+use rustyline::hint::{Hint, Hinter};
 use rustyline::validate::{self, ValidationContext, ValidationResult, Validator};
-use rustyline::Config;
-use rustyline_derive::{Completer, Helper, Hinter};
+use rustyline::{CompletionType, Config, Context};
+use rustyline_derive::Helper;
 use std::any::Any;
 use std::backtrace::Backtrace;
 use std::borrow::Cow;
 use std::panic::{AssertUnwindSafe, PanicInfo};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex, OnceLock};
 use target_lexicon::Triple;
 
@@ -35,7 +41,10 @@ pub const WELCOME_MESSAGE: &str = concatcp!(
     "\n\n"
 );
 
-#[derive(Completer, Helper, Hinter, Default)]
+/// How many inputs to remember between sessions
+const MAX_HISTORY_SIZE: usize = 1000;
+
+#[derive(Helper, Default)]
 pub struct ReplHelper {
     validator: InputValidator,
     state: ReplState,
@@ -86,14 +95,28 @@ pub fn main(has_color: bool, has_header: bool) -> i32 {
     } else {
         rustyline::ColorMode::Disabled
     };
-    let mut editor =
-        Editor::<ReplHelper>::with_config(Config::builder().color_mode(editor_color_mode).build());
+    let mut editor = Editor::<ReplHelper>::with_config(
+        Config::builder()
+            .color_mode(editor_color_mode)
+            .completion_type(CompletionType::List)
+            .max_history_size(MAX_HISTORY_SIZE)
+            .history_ignore_dups(true)
+            .build(),
+    );
     let repl_helper = ReplHelper::default();
     editor.set_helper(Some(repl_helper));
+
+    let history_file = history_file();
+
+    if let Some(history_file) = &history_file {
+        // There's no history the first time the REPL runs, and that's fine.
+        let _ = editor.load_history(history_file);
+    }
+
     let target = Triple::host().into();
     let mut arena = Bump::new();
 
-    loop {
+    let exit_code = loop {
         match editor.readline(&strip_colors_if_necessary(PROMPT)) {
             Ok(line) => {
                 let line = line.trim();
@@ -121,8 +144,21 @@ pub fn main(has_color: bool, has_header: bool) -> i32 {
                             println!("{}", strip_colors_if_necessary(&output));
                         }
                     }
+                    ReplAction::Type {
+                        opt_output,
+                        problems,
+                    } => {
+                        let output = format_output(ANSI_STYLE_CODES, opt_output, problems);
+
+                        if !output.is_empty() {
+                            println!("{}", strip_colors_if_necessary(&output));
+                        }
+                    }
+                    ReplAction::Defs(defs) => {
+                        println!("{}", format_defs(&defs));
+                    }
                     ReplAction::Exit => {
-                        return 0;
+                        break 0;
                     }
                     ReplAction::FileProblem { filename, error } => {
                         println!(
@@ -142,18 +178,76 @@ pub fn main(has_color: bool, has_header: bool) -> i32 {
             }
             Err(ReadlineError::Eof) => {
                 // End of input; we're done!
-                return 0;
+                break 0;
             }
             Err(ReadlineError::Interrupted) => {
                 eprintln!("CTRL-C");
-                return 1;
+                break 1;
             }
             Err(err) => {
                 eprintln!("REPL error: {err:?}");
-                return 1;
+                break 1;
+            }
+        }
+    };
+
+    if let Some(history_file) = &history_file {
+        if let Err(err) = save_history(&mut editor, history_file) {
+            eprintln!(
+                "Could not save REPL history to {}: {err}",
+                history_file.display()
+            );
+        }
+    }
+
+    exit_code
+}
+
+/// Where REPL inputs are remembered between sessions, e.g. ~/.local/share/roc/repl_history
+/// on UNIX and %APPDATA%\\Roc\\repl_history on Windows.
+///
+/// Returns None if there's no data directory to put it in (e.g. if $HOME is not set).
+fn history_file() -> Option<PathBuf> {
+    use std::env;
+
+    const HISTORY_FILE_NAME: &str = "repl_history";
+
+    // Respect XDG, if the system appears to be using it.
+    // https://specifications.freedesktop.org/basedir-spec/basedir-spec-latest.html
+    let data_dir = match env::var_os("XDG_DATA_HOME") {
+        Some(xdg_data_home) => Path::new(&xdg_data_home).join("roc"),
+        None => {
+            #[cfg(windows)]
+            {
+                // CSIDL_APPDATA is the same as APPDATA, as with the cache directory.
+                let appdata = env::var_os("APPDATA").or_else(|| env::var_os("CSIDL_APPDATA"))?;
+
+                Path::new(&appdata).join("Roc")
+            }
+
+            #[cfg(not(windows))]
+            {
+                let home = env::var_os("HOME")?;
+
+                Path::new(&home).join(".local").join("share").join("roc")
             }
         }
+    };
+
+    Some(data_dir.join(HISTORY_FILE_NAME))
+}
+
+fn save_history(
+    editor: &mut rustyline::Editor<ReplHelper>,
+    history_file: &Path,
+) -> Result<(), String> {
+    if let Some(parent) = history_file.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
     }
+
+    editor
+        .save_history(history_file)
+        .map_err(|err| err.to_string())
 }
 
 fn notify_repl_panic(target: Target, e: Box<dyn Any + Send>) -> ReplAction<'static> {
@@ -209,7 +303,65 @@ impl Validator for InputValidator {
     }
 }
 
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let target = Triple::host().into();
+
+        // Completing record fields compiles the input, which must never take the REPL down.
+        let completions = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            self.state.completions(line, pos, target)
+        }))
+        .unwrap_or((pos, Vec::new()));
+
+        Ok(completions)
+    }
+}
+
+/// The type of a def, shown after its name while typing. It only shows information,
+/// so unlike a `String` hint it can't be accepted into the line.
+pub struct TypeHint(String);
+
+impl Hint for TypeHint {
+    fn display(&self) -> &str {
+        &self.0
+    }
+
+    fn completion(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = TypeHint;
+
+    fn hint(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> Option<TypeHint> {
+        self.state
+            .type_hint(line, pos)
+            .map(|def_type| TypeHint(format!(" : {def_type}")))
+    }
+}
+
 impl Highlighter for ReplHelper {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        highlight(ANSI_STYLE_CODES, line).into()
+    }
+
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        format!("{}{hint}{}", ANSI_STYLE_CODES.white, ANSI_STYLE_CODES.reset).into()
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize) -> bool {
+        // Any edit can change how the rest of the line tokenizes, e.g. opening a string.
+        true
+    }
+
     fn has_continuation_prompt(&self) -> bool {
         true
     }
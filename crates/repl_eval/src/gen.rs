@@ -13,6 +13,8 @@ use roc_region::all::LineInfo;
 use roc_reporting::report::{can_problem, type_problem, RocDocAllocator};
 use roc_solve::FunctionKind;
 use roc_target::Target;
+use roc_types::pretty_print::{name_and_print_var, DebugPrint};
+use roc_types::subs::{Content, FlatType};
 
 #[derive(Debug)]
 pub struct ReplOutput {
@@ -39,6 +41,41 @@ pub fn format_answer<'a>(arena: &'a Bump, answer: Expr<'_>) -> &'a str {
     }
 }
 
+/// The type of the expression the REPL would evaluate, without evaluating it.
+pub fn expr_type(mono: &mut MonomorphizedModule<'_>) -> Option<String> {
+    let (_, main_fn_var) = mono.exposed_to_host.top_level_values.iter().next()?;
+
+    Some(name_and_print_var(
+        *main_fn_var,
+        &mut mono.subs,
+        mono.module_id,
+        &mono.interns,
+        DebugPrint::NOTHING,
+    ))
+}
+
+/// The field names of the record the REPL would evaluate, e.g. for completing `rec.`
+pub fn record_fields(mono: &MonomorphizedModule<'_>) -> Vec<String> {
+    let subs = &mono.subs;
+    let mut var = match mono.exposed_to_host.top_level_values.iter().next() {
+        Some((_, main_fn_var)) => *main_fn_var,
+        None => return Vec::new(),
+    };
+
+    loop {
+        match subs.get_content_without_compacting(var) {
+            Content::Alias(_, _, real_var, _) => var = *real_var,
+            Content::Structure(FlatType::Record(fields, ext)) => {
+                return match fields.unsorted_iterator(subs, *ext) {
+                    Ok(fields) => fields.map(|(label, _)| label.to_string()).collect(),
+                    Err(_) => Vec::new(),
+                };
+            }
+            _ => return Vec::new(),
+        }
+    }
+}
+
 #[derive(Default, Debug)]
 pub struct Problems {
     pub errors: Vec<String>,
@@ -51,16 +88,25 @@ impl Problems {
     }
 }
 
-pub fn compile_to_mono<'a, 'i, I: Iterator<Item = &'i str>>(
+/// Compile the given defs and expression as the body of an app module.
+///
+/// `packages` are (shorthand, location) pairs for the app header's `packages`,
+/// where location is either an `https://` URL or the path to a package's root module.
+pub fn compile_to_mono<'a, 'i, P, I>(
     arena: &'a Bump,
+    packages: P,
     defs: I,
     expr: &str,
     target: Target,
     palette: Palette,
-) -> (Option<MonomorphizedModule<'a>>, Problems) {
+) -> (Option<MonomorphizedModule<'a>>, Problems)
+where
+    P: Iterator<Item = (&'i str, &'i str)>,
+    I: Iterator<Item = &'i str>,
+{
     let filename = PathBuf::from("replfile.roc");
     let src_dir = PathBuf::from(".");
-    let (bytes_before_expr, module_src) = promote_expr_to_module(arena, defs, expr);
+    let (bytes_before_expr, module_src) = promote_expr_to_module(arena, packages, defs, expr);
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
         filename,
@@ -75,6 +121,7 @@ pub fn compile_to_mono<'a, 'i, I: Iterator<Item = &'i str>>(
             palette,
             threading: Threading::Single,
             exec_mode: ExecutionMode::Executable,
+            solved_modules: None,
         },
     );
 
@@ -86,11 +133,11 @@ pub fn compile_to_mono<'a, 'i, I: Iterator<Item = &'i str>>(
                 (m.can_problems, m.type_problems)
             );
         }
-        Err(LoadMonomorphizedError::LoadingProblem(LoadingProblem::FormattedReport(report, _))) => {
+        Err(LoadMonomorphizedError::LoadingProblem(LoadingProblem::FormattedReport(report))) => {
             return (
                 None,
                 Problems {
-                    errors: vec![report],
+                    errors: vec![report.text],
                     warnings: Vec::new(),
                 },
             );
@@ -174,16 +221,53 @@ pub fn compile_to_mono<'a, 'i, I: Iterator<Item = &'i str>>(
     (Some(loaded), problems)
 }
 
-fn promote_expr_to_module<'a, 'i, I: Iterator<Item = &'i str>>(
+fn promote_expr_to_module<'a, 'i, P, I>(
     arena: &'a Bump,
+    packages: P,
     defs: I,
     expr: &str,
-) -> (usize, &'a str) {
-    const REPL_MODULE_HEADER: &str = "app \"app\" provides [repl_output] to \"./platform\"\n\n";
+) -> (usize, &'a str)
+where
+    P: Iterator<Item = (&'i str, &'i str)>,
+    I: Iterator<Item = &'i str>,
+{
+    const REPL_MODULE_HEADER_START: &str = "app \"app\" ";
+    const REPL_MODULE_HEADER_END: &str = "provides [repl_output] to \"./platform\"\n\n";
     const REPL_MODULE_MAIN_DEF: &str = "repl_output =\n";
     const INDENT: &str = "    ";
 
-    let mut buffer = bumpalo::collections::string::String::from_str_in(REPL_MODULE_HEADER, arena);
+    let mut buffer =
+        bumpalo::collections::string::String::from_str_in(REPL_MODULE_HEADER_START, arena);
+    let mut packages = packages.peekable();
+
+    if packages.peek().is_some() {
+        buffer.push_str("packages {");
+
+        for (index, (shorthand, location)) in packages.enumerate() {
+            if index > 0 {
+                buffer.push(',');
+            }
+
+            buffer.push(' ');
+            buffer.push_str(shorthand);
+            buffer.push_str(": \"");
+
+            for ch in location.chars() {
+                // e.g. backslashes in Windows paths
+                if matches!(ch, '\\' | '"') {
+                    buffer.push('\\');
+                }
+
+                buffer.push(ch);
+            }
+
+            buffer.push('"');
+        }
+
+        buffer.push_str(" } ");
+    }
+
+    buffer.push_str(REPL_MODULE_HEADER_END);
 
     for line in defs {
         // don't indent the defs
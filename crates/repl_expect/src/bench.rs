@@ -0,0 +1,173 @@
+//! Timing the benchmarks that `roc bench` runs.
+//!
+//! A benchmark first runs for a while without being measured, which also tells us roughly how
+//! long one run takes. From that, we pick how many runs go in a sample, so that every sample
+//! takes about as long, whether one run takes a nanosecond or a second.
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+pub struct BenchConfig {
+    /// How long to run a benchmark before measuring it
+    pub warmup: Duration,
+    /// How long to spend measuring a benchmark, at least one run per sample
+    pub measurement: Duration,
+    pub samples: usize,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            warmup: Duration::from_millis(500),
+            measurement: Duration::from_secs(2),
+            samples: 50,
+        }
+    }
+}
+
+/// The time one run of a benchmark takes, in nanoseconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Measurement {
+    /// How many times the benchmark ran while being measured
+    pub iterations: u64,
+    pub mean: f64,
+    pub median: f64,
+    pub stddev: f64,
+}
+
+impl Measurement {
+    /// The statistics of samples that each are the average time of one run, in nanoseconds.
+    pub fn from_samples(samples: &[f64], iterations: u64) -> Self {
+        debug_assert!(!samples.is_empty(), "a benchmark takes at least one sample");
+
+        let count = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / count;
+
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let middle = sorted.len() / 2;
+        let median = match sorted.len() % 2 {
+            0 => (sorted[middle - 1] + sorted[middle]) / 2.0,
+            _ => sorted[middle],
+        };
+
+        // The sample standard deviation, because the samples are all we know of the benchmark.
+        let stddev = match samples.len() {
+            1 => 0.0,
+            _ => {
+                let squares: f64 = samples.iter().map(|sample| (sample - mean).powi(2)).sum();
+
+                (squares / (count - 1.0)).sqrt()
+            }
+        };
+
+        Self {
+            iterations,
+            mean,
+            median,
+            stddev,
+        }
+    }
+}
+
+/// Time `run_once`, stopping at the first error it returns.
+pub fn measure<E>(
+    config: &BenchConfig,
+    mut run_once: impl FnMut() -> Result<(), E>,
+) -> Result<Measurement, E> {
+    // Warm up in batches that double in size, so that checking the time doesn't slow down
+    // benchmarks that are over in nanoseconds.
+    let warmup_start = Instant::now();
+    let mut warmup_iterations = 0u64;
+    let mut batch = 1u64;
+
+    loop {
+        for _ in 0..batch {
+            run_once()?;
+        }
+
+        warmup_iterations += batch;
+        batch *= 2;
+
+        if warmup_start.elapsed() >= config.warmup {
+            break;
+        }
+    }
+
+    let nanos_per_run = warmup_start.elapsed().as_nanos() as f64 / warmup_iterations as f64;
+    let samples = config.samples.max(1);
+    let nanos_per_sample = config.measurement.as_nanos() as f64 / samples as f64;
+    let runs_per_sample = ((nanos_per_sample / nanos_per_run) as u64).max(1);
+
+    let mut sample_nanos = Vec::with_capacity(samples);
+
+    for _ in 0..samples {
+        let sample_start = Instant::now();
+
+        for _ in 0..runs_per_sample {
+            run_once()?;
+        }
+
+        sample_nanos.push(sample_start.elapsed().as_nanos() as f64 / runs_per_sample as f64);
+    }
+
+    Ok(Measurement::from_samples(
+        &sample_nanos,
+        runs_per_sample * samples as u64,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{measure, BenchConfig, Measurement};
+    use std::time::Duration;
+
+    #[test]
+    fn statistics() {
+        let measurement = Measurement::from_samples(&[4.0, 1.0, 3.0, 2.0], 8);
+
+        assert_eq!(measurement.iterations, 8);
+        assert_eq!(measurement.mean, 2.5);
+        assert_eq!(measurement.median, 2.5);
+        assert!((measurement.stddev - 1.290_994).abs() < 1e-6);
+
+        let measurement = Measurement::from_samples(&[7.0], 1);
+
+        assert_eq!(measurement.median, 7.0);
+        assert_eq!(measurement.stddev, 0.0);
+    }
+
+    #[test]
+    fn stops_at_first_error() {
+        let config = BenchConfig {
+            warmup: Duration::ZERO,
+            measurement: Duration::from_millis(1),
+            samples: 3,
+        };
+        let mut runs = 0;
+
+        let result = measure(&config, || {
+            runs += 1;
+
+            match runs {
+                5 => Err("crashed"),
+                _ => Ok(()),
+            }
+        });
+
+        assert_eq!(result, Err("crashed"));
+        assert_eq!(runs, 5);
+    }
+
+    #[test]
+    fn every_sample_runs_at_least_once() {
+        let config = BenchConfig {
+            warmup: Duration::ZERO,
+            measurement: Duration::ZERO,
+            samples: 3,
+        };
+
+        let measurement = measure(&config, || Ok::<(), ()>(())).unwrap();
+
+        assert_eq!(measurement.iterations, 3);
+    }
+}
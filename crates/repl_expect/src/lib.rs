@@ -15,7 +15,11 @@ use {
 #[cfg(not(windows))]
 mod app;
 #[cfg(not(windows))]
+pub mod bench;
+#[cfg(not(windows))]
 pub mod run;
+#[cfg(not(windows))]
+pub mod snapshot;
 
 #[cfg(not(windows))]
 use app::{ExpectMemory, ExpectReplApp};
@@ -124,6 +128,7 @@ mod test {
             palette: DEFAULT_PALETTE,
             threading: Threading::Single,
             exec_mode: ExecutionMode::Test,
+            solved_modules: None,
         };
         let loaded = match roc_load::load_and_monomorphize_from_str(
             arena,
@@ -178,6 +183,8 @@ mod test {
                 &mut expectations,
                 expect_funcs,
                 &mut memory,
+                0,
+                &mut crate::snapshot::SnapshotFiles::new(false),
             )
             .unwrap();
         }
@@ -320,6 +327,38 @@ mod test {
         );
     }
 
+    #[test]
+    fn lookup_too_big_to_report() {
+        run_expect_test(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main = 0
+
+                expect
+                    a = List.repeat "a string so long that it cannot be short" 40
+
+                    List.is_empty a
+                "#
+            ),
+            indoc!(
+                r#"
+                This expectation crashed while running:
+
+                5│>  expect
+                6│>      a = List.repeat "a string so long that it cannot be short" 40
+                7│>
+                8│>      List.is_empty a
+
+                The crash reported this message:
+
+                The values of this failed expect are too big to report.
+                "#
+            ),
+        );
+    }
+
     #[test]
     fn lookup_list_of_list_of_strings() {
         run_expect_test(
@@ -1249,4 +1288,31 @@ mod test {
             ),
         );
     }
+
+    #[test]
+    fn snapshot_not_stored_yet() {
+        run_expect_test(
+            indoc!(
+                r#"
+                interface Test exposes [] imports []
+
+                expect Inspect.snapshot ["a", "b"]
+                "#
+            ),
+            indoc!(
+                r#"
+                This snapshot isn't stored yet:
+
+                3│  expect Inspect.snapshot ["a", "b"]
+                    ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+
+                Its value looks like this:
+
+                    ["a", "b"]
+
+                Tip: To store it, run roc test --update-snapshots.
+                "#
+            ),
+        );
+    }
 }
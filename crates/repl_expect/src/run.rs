@@ -18,14 +18,19 @@ use roc_gen_llvm::{
 use roc_load::{Expectations, MonomorphizedModule};
 use roc_module::symbol::{Interns, ModuleId, Symbol};
 use roc_mono::{
-    ir::OptLevel,
+    coverage::{counter_name, CoverageRegions},
+    ir::{CrashTag, OptLevel},
     layout::{GlobalLayoutInterner, STLayoutInterner},
 };
 use roc_region::all::Region;
 use roc_reporting::{error::expect::Renderer, report::RenderTarget};
+use roc_std::RocStr;
 use roc_target::Target;
 use roc_types::subs::Subs;
 
+use crate::bench::{BenchConfig, Measurement};
+use crate::snapshot::{SnapshotFiles, Snapshots};
+
 pub struct ExpectMemory<'a> {
     ptr: *mut u8,
     length: usize,
@@ -33,7 +38,10 @@ pub struct ExpectMemory<'a> {
 }
 
 impl<'a> ExpectMemory<'a> {
-    const SHM_SIZE: usize = 1024;
+    // The generated arguments of a property at `PROPERTY_MAX_SIZE` take up to about 90KB when
+    // they're nested, like a `List (List Str)`. An expect whose values don't fit crashes, rather
+    // than writing past the end.
+    const SHM_SIZE: usize = 1024 * 1024;
 
     #[cfg(test)]
     pub(crate) fn from_slice(slice: &mut [u8]) -> Self {
@@ -123,6 +131,8 @@ pub fn run_toplevel_expects<'a, W: std::io::Write>(
     lib: &libloading::Library,
     expectations: &mut VecMap<ModuleId, Expectations>,
     expects: ExpectFunctions<'_>,
+    seed: u64,
+    snapshot_files: &mut SnapshotFiles,
 ) -> std::io::Result<(usize, usize)> {
     let shm_name = format!("/roc_expect_buffer_{}", std::process::id());
     let mut memory = ExpectMemory::create_or_reuse_mmap(&shm_name);
@@ -137,6 +147,8 @@ pub fn run_toplevel_expects<'a, W: std::io::Write>(
         expectations,
         expects,
         &mut memory,
+        seed,
+        snapshot_files,
     )
 }
 
@@ -151,6 +163,8 @@ pub(crate) fn run_expects_with_memory<'a, W: std::io::Write>(
     expectations: &mut VecMap<ModuleId, Expectations>,
     expects: ExpectFunctions<'_>,
     memory: &mut ExpectMemory,
+    seed: u64,
+    snapshot_files: &mut SnapshotFiles,
 ) -> std::io::Result<(usize, usize)> {
     let mut failed = 0;
     let mut passed = 0;
@@ -158,17 +172,50 @@ pub(crate) fn run_expects_with_memory<'a, W: std::io::Write>(
     memory.set_shared_buffer(lib);
 
     for expect in expects.pure {
-        let result = run_expect_pure(
-            writer,
-            render_target,
-            arena,
-            interns,
-            layout_interner,
-            lib,
-            expectations,
-            memory,
-            expect,
-        )?;
+        if let Some(data) = expectations.get(&expect.symbol.module_id()) {
+            snapshot_files.visit_module(&data.path)?;
+        }
+
+        let result = if expect.property {
+            run_property(
+                writer,
+                render_target,
+                arena,
+                interns,
+                layout_interner,
+                lib,
+                expectations,
+                memory,
+                expect,
+                seed,
+            )?
+        } else if let Some(value_region) = expect.snapshot {
+            run_snapshot(
+                writer,
+                render_target,
+                arena,
+                interns,
+                layout_interner,
+                lib,
+                expectations,
+                memory,
+                expect,
+                value_region,
+                snapshot_files,
+            )?
+        } else {
+            run_expect_pure(
+                writer,
+                render_target,
+                arena,
+                interns,
+                layout_interner,
+                lib,
+                expectations,
+                memory,
+                expect,
+            )?
+        };
 
         match result {
             true => passed += 1,
@@ -197,38 +244,110 @@ fn run_expect_pure<'a, W: std::io::Write>(
 
     let result: Result<(), (String, _)> = try_run_jit_function!(lib, expect.name, (), |v: ()| v);
 
-    let shared_memory_ptr: *const u8 = shared_memory.ptr.cast();
-
     if result.is_err() || sequence.count_failures() > 0 {
-        let module_id = expect.symbol.module_id();
-        let data = expectations.get_mut(&module_id).unwrap();
+        render_failed_expect(
+            writer,
+            render_target,
+            arena,
+            interns,
+            layout_interner,
+            expectations,
+            shared_memory,
+            expect,
+            result,
+            &sequence,
+        )?;
 
-        let path = &data.path;
-        let filename = data.path.to_owned();
-        let source = std::fs::read_to_string(path).unwrap();
+        writeln!(writer)?;
 
-        let renderer = Renderer::new(arena, interns, render_target, module_id, filename, &source);
+        Ok(false)
+    } else {
+        Ok(true)
+    }
+}
 
-        if let Err((roc_panic_message, _roc_panic_tag)) = result {
-            renderer.render_panic(writer, &roc_panic_message, expect.region)?;
-        } else {
-            let mut offset = ExpectSequence::START_OFFSET;
+/// A snapshot's thunk returns the `Inspect` rendering of its value, which is compared with the
+/// stored one, or stored if the snapshots are being updated.
+#[allow(clippy::too_many_arguments)]
+fn run_snapshot<'a, W: std::io::Write>(
+    writer: &mut W,
+    render_target: RenderTarget,
+    arena: &'a Bump,
+    interns: &'a Interns,
+    layout_interner: &GlobalLayoutInterner<'a>,
+    lib: &libloading::Library,
+    expectations: &mut VecMap<ModuleId, Expectations>,
+    shared_memory: &mut ExpectMemory,
+    expect: ToplevelExpect<'_>,
+    value_region: Region,
+    snapshot_files: &mut SnapshotFiles,
+) -> std::io::Result<bool> {
+    use roc_gen_llvm::try_run_jit_function;
+    use std::mem::ManuallyDrop;
+
+    let sequence = ExpectSequence::new(shared_memory.ptr.cast());
+
+    // The rendering was allocated by the Roc code, so it's copied rather than dropped here.
+    let result: Result<ManuallyDrop<RocStr>, (String, _)> =
+        try_run_jit_function!(lib, expect.name, ManuallyDrop<RocStr>, |v| v);
+    let current = result
+        .as_ref()
+        .ok()
+        .map(|rendering| rendering.as_str().to_string());
+
+    let module_id = expect.symbol.module_id();
+    let path = expectations.get(&module_id).unwrap().path.to_owned();
+    let source = std::fs::read_to_string(&path).unwrap();
+    let key = Snapshots::key(
+        &source[value_region.start().offset as usize..value_region.end().offset as usize],
+    );
+
+    // Expectations in the functions that the snapshot calls can fail too.
+    let other_failures = result.is_err() || sequence.count_failures() > 0;
 
-            for _ in 0..sequence.count_failures() {
-                offset = render_expect_failure(
+    if other_failures {
+        render_failed_expect(
+            writer,
+            render_target,
+            arena,
+            interns,
+            layout_interner,
+            expectations,
+            shared_memory,
+            expect,
+            result.map(|_| ()),
+            &sequence,
+        )?;
+    }
+
+    let passed = match current {
+        Some(current) => {
+            let updating = snapshot_files.is_updating();
+            let comparison = snapshot_files.compare(expect.symbol, &path, key, current)?;
+            let passed = comparison.matches() || updating;
+
+            if !passed {
+                let renderer =
+                    Renderer::new(arena, interns, render_target, module_id, path, &source);
+
+                renderer.render_snapshot(
                     writer,
-                    &renderer,
-                    arena,
-                    Some(expect),
-                    expectations,
-                    interns,
-                    layout_interner,
-                    shared_memory_ptr,
-                    offset,
+                    comparison.stored.as_deref(),
+                    &comparison.current,
+                    expect.region,
                 )?;
             }
+
+            passed
         }
+        None => {
+            snapshot_files.keep(&path, key)?;
 
+            false
+        }
+    };
+
+    if other_failures || !passed {
         writeln!(writer)?;
 
         Ok(false)
@@ -237,6 +356,223 @@ fn run_expect_pure<'a, W: std::io::Write>(
     }
 }
 
+/// How many times a property runs with generated arguments before it passes.
+const PROPERTY_RUNS: u64 = 100;
+
+/// The largest size that a property runs with. Generated numbers are at most this far from zero,
+/// and generated lists and strings are at most this long.
+const PROPERTY_MAX_SIZE: u64 = 50;
+
+/// How many seeds to try at each smaller size while reducing the size of a failing case.
+const SIZE_REDUCTION_ATTEMPTS: u64 = 20;
+
+type PropertyRunner = unsafe extern "C" fn(u64, u64, *mut RocCallResult<()>);
+
+/// A seed to generate the arguments of properties from, when `roc test` wasn't given one.
+pub fn random_seed() -> u64 {
+    use std::hash::{BuildHasher, Hasher};
+
+    std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish()
+}
+
+/// The seed of the `n`th run of a property, so that every run follows from the seed that
+/// `roc test` started with. This is the output function of SplitMix64.
+fn nth_seed(seed: u64, n: u64) -> u64 {
+    let mut z = seed.wrapping_add((n + 1).wrapping_mul(0x9e3779b97f4a7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+
+    z ^ (z >> 31)
+}
+
+fn run_property_once(
+    runner: &libloading::Symbol<PropertyRunner>,
+    shared_memory: &ExpectMemory,
+    seed: u64,
+    size: u64,
+) -> (Result<(), (String, CrashTag)>, ExpectSequence) {
+    let sequence = ExpectSequence::new(shared_memory.ptr.cast());
+
+    let mut result = std::mem::MaybeUninit::uninit();
+    let result = unsafe {
+        runner(seed, size, result.as_mut_ptr());
+
+        result.assume_init().into()
+    };
+
+    (result, sequence)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_property<'a, W: std::io::Write>(
+    writer: &mut W,
+    render_target: RenderTarget,
+    arena: &'a Bump,
+    interns: &'a Interns,
+    layout_interner: &GlobalLayoutInterner<'a>,
+    lib: &libloading::Library,
+    expectations: &mut VecMap<ModuleId, Expectations>,
+    shared_memory: &mut ExpectMemory,
+    expect: ToplevelExpect<'_>,
+    seed: u64,
+) -> std::io::Result<bool> {
+    let runner: libloading::Symbol<PropertyRunner> = unsafe { lib.get(expect.name.as_bytes()) }
+        .unwrap_or_else(|_| internal_error!("Unable to JIT compile `{}`", expect.name));
+
+    let fails = |seed, size| {
+        let (result, sequence) = run_property_once(&runner, shared_memory, seed, size);
+
+        result.is_err() || sequence.count_failures() > 0
+    };
+
+    let first_failure = (0..PROPERTY_RUNS)
+        .map(|n| (nth_seed(seed, n), n.min(PROPERTY_MAX_SIZE)))
+        .find(|(seed, size)| fails(*seed, *size));
+
+    let Some((mut failing_seed, mut failing_size)) = first_failure else {
+        return Ok(true);
+    };
+
+    // Reduce the size of the failing case: find the smallest size that some seed still fails
+    // with. This isn't shrinking, as the failing values aren't made smaller one part at a time;
+    // the arguments are generated again, so the case that gets reported can look nothing like
+    // the one that first failed, and it isn't necessarily the smallest one that fails.
+    'reduce_size: for size in 0..failing_size {
+        for n in 0..SIZE_REDUCTION_ATTEMPTS {
+            let candidate = nth_seed(failing_seed, n);
+
+            if fails(candidate, size) {
+                failing_seed = candidate;
+                failing_size = size;
+
+                break 'reduce_size;
+            }
+        }
+    }
+
+    // Run the failing case with the smallest size once more, so its values are the ones in shared memory.
+    let (result, sequence) = run_property_once(&runner, shared_memory, failing_seed, failing_size);
+
+    render_failed_expect(
+        writer,
+        render_target,
+        arena,
+        interns,
+        layout_interner,
+        expectations,
+        shared_memory,
+        expect,
+        result,
+        &sequence,
+    )?;
+
+    writeln!(
+        writer,
+        "To run this property with the same arguments again, use `roc test --seed {seed}`."
+    )?;
+    writeln!(writer)?;
+
+    Ok(false)
+}
+
+type BenchRunner = unsafe extern "C" fn(u64, *mut RocCallResult<()>);
+
+/// Time the benchmark of `expect`, or get the message of the crash that stopped it.
+pub fn run_bench(
+    lib: &libloading::Library,
+    shared_memory: &mut ExpectMemory,
+    expect: ToplevelExpect<'_>,
+    config: &BenchConfig,
+) -> Result<Measurement, String> {
+    debug_assert!(expect.bench, "only the runners of benchmarks can be timed");
+
+    let runner: libloading::Symbol<BenchRunner> = unsafe { lib.get(expect.name.as_bytes()) }
+        .unwrap_or_else(|_| internal_error!("Unable to JIT compile `{}`", expect.name));
+
+    shared_memory.set_shared_buffer(lib);
+
+    crate::bench::measure(config, || {
+        let mut result = std::mem::MaybeUninit::uninit();
+        // The runner's expect only fails when it's given something other than 0.
+        let result: Result<(), (String, CrashTag)> = unsafe {
+            runner(0, result.as_mut_ptr());
+
+            result.assume_init().into()
+        };
+
+        result.map_err(|(message, _)| message)
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn render_failed_expect<'a, W: std::io::Write>(
+    writer: &mut W,
+    render_target: RenderTarget,
+    arena: &'a Bump,
+    interns: &'a Interns,
+    layout_interner: &GlobalLayoutInterner<'a>,
+    expectations: &mut VecMap<ModuleId, Expectations>,
+    shared_memory: &ExpectMemory,
+    expect: ToplevelExpect<'_>,
+    result: Result<(), (String, CrashTag)>,
+    sequence: &ExpectSequence,
+) -> std::io::Result<()> {
+    let shared_memory_ptr: *const u8 = shared_memory.ptr.cast();
+
+    let module_id = expect.symbol.module_id();
+    let data = expectations.get_mut(&module_id).unwrap();
+
+    let path = &data.path;
+    let filename = data.path.to_owned();
+    let source = std::fs::read_to_string(path).unwrap();
+
+    let renderer = Renderer::new(arena, interns, render_target, module_id, filename, &source);
+
+    if let Err((roc_panic_message, _roc_panic_tag)) = result {
+        renderer.render_panic(writer, &roc_panic_message, expect.region)?;
+    } else {
+        let mut offset = ExpectSequence::START_OFFSET;
+
+        for _ in 0..sequence.count_failures() {
+            offset = render_expect_failure(
+                writer,
+                &renderer,
+                arena,
+                Some(expect),
+                expectations,
+                interns,
+                layout_interner,
+                shared_memory_ptr,
+                offset,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// How many times each coverage counter of a module went up while the tests in `lib` ran. The
+/// counters of code that was never compiled, because nothing used it, are 0.
+pub fn read_coverage_counts(
+    lib: &libloading::Library,
+    module_id: ModuleId,
+    coverage: &CoverageRegions,
+) -> Vec<u64> {
+    (0..coverage.counters().len() as u32)
+        .map(|counter| {
+            let name = counter_name(module_id, counter);
+
+            // The backend makes every coverage counter a global u64.
+            match unsafe { lib.get::<*const u64>(name.as_bytes()) } {
+                Ok(count) => unsafe { **count },
+                Err(_) => 0,
+            }
+        })
+        .collect()
+}
+
 pub fn render_expects_in_memory<'a>(
     writer: &mut impl std::io::Write,
     arena: &'a Bump,
@@ -444,6 +780,12 @@ pub struct ToplevelExpect<'a> {
     pub name: &'a str,
     pub symbol: Symbol,
     pub region: Region,
+    /// Whether this is a property, which runs many times with generated arguments
+    pub property: bool,
+    /// The region of the value of a snapshot, which is compared with the stored one
+    pub snapshot: Option<Region>,
+    /// Whether this is a benchmark, which `roc bench` times rather than `roc test` runs
+    pub bench: bool,
 }
 
 #[derive(Debug)]
@@ -512,11 +854,29 @@ pub fn expect_mono_module_to_dylib<'a>(
         })
         .collect();
 
+    let properties: Vec<Symbol> = toplevel_expects
+        .values()
+        .flat_map(|expects| expects.properties.iter().copied())
+        .collect();
+
+    let benches: Vec<Symbol> = toplevel_expects
+        .values()
+        .flat_map(|expects| expects.benches.iter().copied())
+        .collect();
+
+    let snapshots: Vec<Symbol> = toplevel_expects
+        .values()
+        .flat_map(|expects| expects.snapshots.keys().copied())
+        .collect();
+
     let expect_names = roc_gen_llvm::llvm::build::build_procedures_expose_expects(
         &env,
         &layout_interner,
         opt_level,
         expects_symbols,
+        &properties,
+        &benches,
+        &snapshots,
         procedures,
     );
 
@@ -532,6 +892,9 @@ pub fn expect_mono_module_to_dylib<'a>(
                         symbol,
                         region,
                         name,
+                        property: expects.properties.contains(&symbol),
+                        bench: expects.benches.contains(&symbol),
+                        snapshot: expects.snapshots.get(&symbol).copied(),
                     },
                 ),
                 env.arena,
@@ -0,0 +1,322 @@
+//! The stored values of `expect Inspect.snapshot(value)`s, in a `.snap` file next to their module.
+//!
+//! Each snapshot is stored under the source code of its value, so that moving it around in
+//! the module doesn't change which snapshot it's compared with:
+//!
+//! ```text
+//! -- render(model)
+//!     { name: "model", size: 3 }
+//! ```
+use std::path::{Path, PathBuf};
+
+use roc_collections::{MutMap, MutSet};
+use roc_module::symbol::Symbol;
+
+const HEADER: &str = "# Snapshots of the `expect Inspect.snapshot(value)`s in this module, kept up to date by `roc test --update-snapshots`.";
+const KEY_PREFIX: &str = "-- ";
+const INDENT: &str = "    ";
+
+/// The `.snap` files of the modules that `roc test` runs snapshots in. Each one is read when the
+/// first snapshot of its module runs, and with `--update-snapshots`, written once they all have.
+#[derive(Debug, Default)]
+pub struct SnapshotFiles {
+    update: bool,
+    files: MutMap<PathBuf, SnapshotFile>,
+    comparisons: MutMap<Symbol, SnapshotComparison>,
+}
+
+/// The rendering of a snapshot in this run, and the one it was compared with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotComparison {
+    /// The stored rendering, or None if the snapshot wasn't stored yet.
+    pub stored: Option<String>,
+    pub current: String,
+}
+
+impl SnapshotComparison {
+    pub fn matches(&self) -> bool {
+        self.stored.as_deref() == Some(self.current.as_str())
+    }
+}
+
+#[derive(Debug)]
+struct SnapshotFile {
+    snapshots: Snapshots,
+    /// The keys of the snapshots that ran, so that the others can be dropped.
+    visited: MutSet<String>,
+    changed: bool,
+}
+
+impl SnapshotFiles {
+    /// With `update`, the snapshots that don't match are stored rather than reported.
+    pub fn new(update: bool) -> Self {
+        Self {
+            update,
+            files: MutMap::default(),
+            comparisons: MutMap::default(),
+        }
+    }
+
+    pub(crate) fn is_updating(&self) -> bool {
+        self.update
+    }
+
+    /// Compares the `current` rendering of the snapshot `expect` in the module at `module_path`
+    /// with the stored one, and stores `current` in its place if this run is updating.
+    pub(crate) fn compare(
+        &mut self,
+        expect: Symbol,
+        module_path: &Path,
+        key: String,
+        current: String,
+    ) -> std::io::Result<&SnapshotComparison> {
+        let update = self.update;
+        let file = self.file(module_path)?;
+        let stored = file.snapshots.get(&key).map(str::to_string);
+
+        if update && stored.as_deref() != Some(current.as_str()) {
+            file.snapshots.insert(key.clone(), current.clone());
+            file.changed = true;
+        }
+
+        file.visited.insert(key);
+
+        let comparison = SnapshotComparison { stored, current };
+
+        self.comparisons.insert(expect, comparison);
+
+        Ok(&self.comparisons[&expect])
+    }
+
+    /// Keeps the stored value of a snapshot that crashed before it had a rendering to compare.
+    pub(crate) fn keep(&mut self, module_path: &Path, key: String) -> std::io::Result<()> {
+        self.file(module_path)?.visited.insert(key);
+
+        Ok(())
+    }
+
+    /// How the snapshot `expect` compared with the stored one, if it ran and got that far.
+    pub fn comparison(&self, expect: Symbol) -> Option<&SnapshotComparison> {
+        self.comparisons.get(&expect)
+    }
+
+    /// Records that the tests of the module at `module_path` ran, so that its snapshots that
+    /// didn't run can be dropped even if none of them did.
+    pub(crate) fn visit_module(&mut self, module_path: &Path) -> std::io::Result<()> {
+        if self.update {
+            self.file(module_path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the `.snap` files that changed. With `prune`, the snapshots that didn't run are
+    /// dropped, which is only right when every test in their modules ran; a module without
+    /// any snapshots left doesn't keep a `.snap` file.
+    pub fn write(self, prune: bool) -> std::io::Result<()> {
+        if !self.update {
+            return Ok(());
+        }
+
+        for (module_path, mut file) in self.files {
+            if prune {
+                let count = file.snapshots.entries.len();
+                (file.snapshots.entries).retain(|(key, _)| file.visited.contains(key));
+                file.changed |= file.snapshots.entries.len() != count;
+            }
+
+            if !file.changed {
+                continue;
+            }
+
+            let path = Snapshots::path(&module_path);
+
+            if file.snapshots.entries.is_empty() {
+                match std::fs::remove_file(&path) {
+                    Ok(()) => {}
+                    Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(error) => return Err(error),
+                }
+            } else {
+                file.snapshots.write(&path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn file(&mut self, module_path: &Path) -> std::io::Result<&mut SnapshotFile> {
+        if !self.files.contains_key(module_path) {
+            let file = SnapshotFile {
+                snapshots: Snapshots::read(&Snapshots::path(module_path))?,
+                visited: MutSet::default(),
+                changed: false,
+            };
+
+            self.files.insert(module_path.to_path_buf(), file);
+        }
+
+        Ok(self.files.get_mut(module_path).unwrap())
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Snapshots {
+    entries: Vec<(String, String)>,
+}
+
+impl Snapshots {
+    /// `Module.roc` keeps its snapshots in `Module.snap`.
+    pub(crate) fn path(module_path: &Path) -> PathBuf {
+        module_path.with_extension("snap")
+    }
+
+    /// The snapshots of a module, or none if it doesn't have a `.snap` file yet.
+    pub(crate) fn read(path: &Path) -> std::io::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Ok(Self::parse(&contents)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub(crate) fn write(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+
+    /// Snapshots are stored under their value's source code, with whitespace collapsed, so
+    /// that reformatting the module doesn't change where they're stored.
+    pub(crate) fn key(value_source: &str) -> String {
+        value_source
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub(crate) fn insert(&mut self, key: String, value: String) {
+        match self.entries.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, old)) => *old = value,
+            None => self.entries.push((key, value)),
+        }
+    }
+
+    fn parse(contents: &str) -> Self {
+        let mut entries: Vec<(String, Vec<&str>)> = Vec::new();
+
+        for line in contents.lines() {
+            if let Some(key) = line.strip_prefix(KEY_PREFIX) {
+                entries.push((key.to_string(), Vec::new()));
+            } else if let Some((_, value)) = entries.last_mut() {
+                // Editors may strip the indentation of blank lines, so those are kept as well.
+                if line.trim().is_empty() {
+                    value.push("");
+                } else if let Some(line) = line.strip_prefix(INDENT) {
+                    value.push(line);
+                }
+            }
+        }
+
+        let entries = entries
+            .into_iter()
+            .map(|(key, mut lines)| {
+                // The blank line that separates snapshots isn't part of them.
+                while lines.last() == Some(&"") {
+                    lines.pop();
+                }
+
+                (key, lines.join("\n"))
+            })
+            .collect();
+
+        Self { entries }
+    }
+}
+
+impl std::fmt::Display for Snapshots {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{HEADER}")?;
+
+        for (key, value) in self.entries.iter() {
+            writeln!(f)?;
+            writeln!(f, "{KEY_PREFIX}{key}")?;
+
+            for line in value.lines() {
+                match line {
+                    "" => writeln!(f)?,
+                    _ => writeln!(f, "{INDENT}{line}")?,
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SnapshotFiles, Snapshots};
+    use roc_module::symbol::Symbol;
+
+    #[test]
+    fn round_trip() {
+        let mut snapshots = Snapshots::default();
+        snapshots.insert(Snapshots::key("render   model"), "{ a: 1 }".to_string());
+        snapshots.insert(
+            Snapshots::key("List.map(xs, |x|\n    x + 1)"),
+            "\"first\n\nlast\"".to_string(),
+        );
+
+        let parsed = Snapshots::parse(&snapshots.to_string());
+
+        assert_eq!(parsed, snapshots);
+        assert_eq!(parsed.get("render model"), Some("{ a: 1 }"));
+        assert_eq!(
+            parsed.get("List.map(xs, |x| x + 1)"),
+            Some("\"first\n\nlast\"")
+        );
+    }
+
+    #[test]
+    fn insert_replaces() {
+        let mut snapshots = Snapshots::default();
+        snapshots.insert("x".to_string(), "1".to_string());
+        snapshots.insert("y".to_string(), "2".to_string());
+        snapshots.insert("x".to_string(), "3".to_string());
+
+        assert_eq!(snapshots.get("x"), Some("3"));
+        assert_eq!(snapshots.get("y"), Some("2"));
+        assert_eq!(snapshots.get("z"), None);
+    }
+
+    #[test]
+    fn update_drops_snapshots_that_did_not_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let module_path = dir.path().join("Test.roc");
+
+        let mut stored = Snapshots::default();
+        stored.insert("kept".to_string(), "1".to_string());
+        stored.insert("stale".to_string(), "2".to_string());
+        stored.write(&Snapshots::path(&module_path)).unwrap();
+
+        let mut files = SnapshotFiles::new(true);
+        let expect = Symbol::INSPECT_SNAPSHOT;
+        let comparison = files
+            .compare(expect, &module_path, "kept".to_string(), "3".to_string())
+            .unwrap();
+        assert_eq!(comparison.stored.as_deref(), Some("1"));
+        assert!(!comparison.matches());
+        assert_eq!(files.comparison(expect).unwrap().current, "3");
+        files.write(true).unwrap();
+
+        let written = Snapshots::read(&Snapshots::path(&module_path)).unwrap();
+        assert_eq!(written.get("kept"), Some("3"));
+        assert_eq!(written.get("stale"), None);
+    }
+}
@@ -1,9 +1,9 @@
 use bumpalo::Bump;
 use indoc::indoc;
 use roc_repl_cli::{evaluate, ReplHelper};
-use roc_repl_ui::is_incomplete;
 use roc_repl_ui::repl_state::{ReplAction, ReplState};
-use roc_reporting::report::DEFAULT_PALETTE;
+use roc_repl_ui::{format_output, is_incomplete};
+use roc_reporting::report::{strip_colors, ANSI_STYLE_CODES, DEFAULT_PALETTE};
 use rustyline::Editor;
 use target_lexicon::Triple;
 
@@ -145,6 +145,193 @@ fn standalone_annotation() {
     assert!(matches!(action, ReplAction::Nothing));
 }
 
+#[test]
+fn type_without_evaluating() {
+    let mut state = ReplState::new();
+
+    complete("x = 5", &mut state, "5 : Num *");
+
+    let arena = Bump::new();
+    let target = Triple::host().into();
+    let action = state.step(&arena, ":type x + 1", target, DEFAULT_PALETTE);
+
+    match action {
+        ReplAction::Type {
+            opt_output,
+            problems,
+        } => {
+            let output = format_output(ANSI_STYLE_CODES, opt_output, problems);
+
+            assert_eq!(strip_colors(output.trim()), "x + 1 : Num *");
+        }
+        _ => {
+            panic!("Unexpected action: {:?}", action);
+        }
+    }
+}
+
+#[test]
+fn defs_and_reset() {
+    let mut state = ReplState::new();
+
+    complete("x = 5", &mut state, "5 : Num *");
+    complete("y = \"foo\"", &mut state, "\"foo\" : Str");
+
+    let arena = Bump::new();
+    let target = Triple::host().into();
+
+    let action = state.step(&arena, ":defs", target, DEFAULT_PALETTE);
+    assert!(matches!(action, ReplAction::Defs(defs) if defs == ["x = 5", "y = \"foo\""]));
+
+    let action = state.step(&arena, ":reset", target, DEFAULT_PALETTE);
+    assert!(matches!(action, ReplAction::Nothing));
+
+    let action = state.step(&arena, ":defs", target, DEFAULT_PALETTE);
+    assert!(matches!(action, ReplAction::Defs(defs) if defs.is_empty()));
+}
+
+#[test]
+fn load_missing_module() {
+    let arena = Bump::new();
+    let target = Triple::host().into();
+    let action = ReplState::new().step(&arena, ":load NotThere.roc", target, DEFAULT_PALETTE);
+
+    assert!(matches!(
+        action,
+        ReplAction::FileProblem {
+            error: std::io::ErrorKind::NotFound,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn load_and_reload_module() {
+    // Modules are loaded from the current directory, and the name of this one becomes part of
+    // the module's name, so it can only have letters and digits.
+    let dir = tempfile::Builder::new()
+        .prefix("ReplTest")
+        .tempdir_in(".")
+        .unwrap();
+    let dir_name = dir.path().file_name().unwrap().to_str().unwrap();
+    let module_path = dir.path().join("Greeting.roc");
+
+    std::fs::write(
+        &module_path,
+        "module [greet]\n\ngreet = \\name -> \"Hello, $(name)!\"\n",
+    )
+    .unwrap();
+
+    let mut state = ReplState::new();
+    let arena = Bump::new();
+    let target = Triple::host().into();
+
+    let load = format!(":load {dir_name}/Greeting.roc");
+    let action = state.step(&arena, &load, target, DEFAULT_PALETTE);
+    assert!(matches!(action, ReplAction::Nothing));
+
+    complete("greet \"Roc\"", &mut state, "\"Hello, Roc!\" : Str");
+
+    // A def that the module didn't expose when it was loaded is only in scope after reloading.
+    std::fs::write(
+        &module_path,
+        "module [greet, shout]\n\ngreet = \\name -> \"Hi, $(name)!\"\n\nshout = \\name -> Str.concat name \"!\"\n",
+    )
+    .unwrap();
+
+    let action = state.step(&arena, ":reload", target, DEFAULT_PALETTE);
+    assert!(matches!(action, ReplAction::Nothing));
+
+    complete("greet \"Roc\"", &mut state, "\"Hi, Roc!\" : Str");
+    complete("shout \"Roc\"", &mut state, "\"Roc!\" : Str");
+
+    let import = format!("import {dir_name}.Greeting exposing [greet, shout]");
+    let action = state.step(&arena, ":defs", target, DEFAULT_PALETTE);
+    assert!(matches!(action, ReplAction::Defs(defs) if defs == [import]));
+}
+
+#[test]
+fn local_package() {
+    let dir = tempfile::tempdir().unwrap();
+    let package_dir = dir.path().join("greeting");
+
+    std::fs::create_dir(&package_dir).unwrap();
+    std::fs::write(package_dir.join("main.roc"), "package [Greet] {}\n").unwrap();
+    std::fs::write(
+        package_dir.join("Greet.roc"),
+        "module [hello]\n\nhello = \\name -> \"Hello, $(name)!\"\n",
+    )
+    .unwrap();
+
+    let mut state = ReplState::new();
+    let arena = Bump::new();
+    let target = Triple::host().into();
+
+    // The package has to be in scope before its modules can be imported.
+    let action = state.step(&arena, "import g.Greet", target, DEFAULT_PALETTE);
+    assert!(
+        matches!(action, ReplAction::Eval { opt_mono: None, problems } if problems.errors.len() == 1)
+    );
+
+    // Backslashes would be escapes in the Roc string, and Windows accepts forward slashes.
+    let import_package = format!(
+        "import \"{}\" as g",
+        package_dir.display().to_string().replace('\\', "/")
+    );
+    let action = state.step(&arena, &import_package, target, DEFAULT_PALETTE);
+    assert!(matches!(action, ReplAction::Nothing));
+
+    let action = state.step(&arena, "import g.Greet", target, DEFAULT_PALETTE);
+    assert!(matches!(action, ReplAction::Nothing));
+
+    complete("Greet.hello \"Roc\"", &mut state, "\"Hello, Roc!\" : Str");
+}
+
+#[test]
+fn tab_completion() {
+    let mut state = ReplState::new();
+    let target = Triple::host().into();
+
+    complete(
+        "record = { name: \"Roc\" }",
+        &mut state,
+        "{ name: \"Roc\" } : { name : Str }",
+    );
+
+    assert_eq!(
+        state.completions(":re", 3, target),
+        (0, vec![":reload".to_string(), ":reset".to_string()])
+    );
+    assert_eq!(
+        state.completions("1 + rec", 7, target),
+        (4, vec!["record".to_string()])
+    );
+    assert_eq!(
+        state.completions("record.n", 8, target),
+        (7, vec!["name".to_string()])
+    );
+    assert_eq!(
+        state.completions("Str.conc", 8, target),
+        (4, vec!["concat".to_string()])
+    );
+    assert!(state
+        .completions("Li", 2, target)
+        .1
+        .contains(&"List".to_string()));
+    assert!(state.completions("", 0, target).1.is_empty());
+}
+
+#[test]
+fn type_hint() {
+    let mut state = ReplState::new();
+
+    complete("greeting = \"Hi\"", &mut state, "\"Hi\" : Str");
+
+    assert_eq!(state.type_hint("greeting", 8), Some("Str"));
+    assert_eq!(state.type_hint("greeting", 4), None);
+    assert_eq!(state.type_hint("record.greeting", 15), None);
+}
+
 /// validate and step the given input, then check the Result vs the output
 /// with ANSI escape codes stripped.
 fn complete(input: &str, state: &mut ReplState, expected_start: &str) {
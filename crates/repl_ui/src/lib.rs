@@ -8,6 +8,7 @@ use colors::{CYAN, END_COL, GREEN};
 use const_format::concatcp;
 use repl_state::{parse_src, ParseOutcome};
 use roc_parse::ast::{Expr, ExtractSpaces, ValueDef};
+use roc_parse::highlight::Token;
 use roc_repl_eval::gen::{Problems, ReplOutput};
 use roc_reporting::report::StyleCodes;
 
@@ -49,7 +50,37 @@ pub const TIPS: &str = concatcp!(
             END_COL,
             " shows this text again\n",
         )
-    }
+    },
+    // The meta-commands work on the web too, except for loading files.
+    CYAN,
+    "  - ",
+    END_COL,
+    GREEN,
+    ":type <expr>",
+    END_COL,
+    " shows the type of an expression without evaluating it\n",
+    CYAN,
+    "  - ",
+    END_COL,
+    GREEN,
+    ":load <file.roc>",
+    END_COL,
+    " brings everything a module exposes into scope, and ",
+    GREEN,
+    ":reload",
+    END_COL,
+    " picks up changes to its exposes\n",
+    CYAN,
+    "  - ",
+    END_COL,
+    GREEN,
+    ":defs",
+    END_COL,
+    " lists the definitions entered so far, and ",
+    GREEN,
+    ":reset",
+    END_COL,
+    " forgets them\n",
 );
 
 // For when nothing is entered in the repl
@@ -84,9 +115,15 @@ pub fn is_incomplete(input: &str) -> bool {
                 false
             }
         }
-        ParseOutcome::Empty | ParseOutcome::Help | ParseOutcome::Exit | ParseOutcome::SyntaxErr => {
-            false
-        }
+        ParseOutcome::Empty
+        | ParseOutcome::Help
+        | ParseOutcome::Exit
+        | ParseOutcome::SyntaxErr
+        | ParseOutcome::Type(_)
+        | ParseOutcome::Load(_)
+        | ParseOutcome::Reload
+        | ParseOutcome::Defs
+        | ParseOutcome::Reset => false,
     }
 }
 
@@ -130,3 +167,75 @@ pub fn format_output(
 
     buf
 }
+
+/// Render the source of each past def, in the order they were entered.
+pub fn format_defs(defs: &[String]) -> String {
+    if defs.is_empty() {
+        return "\nNo definitions yet.".to_string();
+    }
+
+    let mut buf = String::new();
+
+    for def in defs {
+        buf.push('\n');
+        buf.push_str(def);
+        buf.push('\n');
+    }
+
+    buf
+}
+
+/// Color the input as it is being typed, using the same token groups as `roc_highlight`.
+pub fn highlight(style_codes: StyleCodes, line: &str) -> String {
+    let mut buf = String::with_capacity(line.len());
+    let mut end = 0;
+
+    for loc_token in roc_parse::highlight::highlight(line) {
+        let start = loc_token.region.start().offset as usize;
+        let token_end = loc_token.region.end().offset as usize;
+
+        // The tokenizer stops at whatever it can't make sense of; leave the rest as-is.
+        if start < end || token_end > line.len() {
+            break;
+        }
+
+        let color = match loc_token.value {
+            Token::LineComment | Token::DocComment => style_codes.white,
+            Token::SingleQuote
+            | Token::String
+            | Token::UnicodeEscape
+            | Token::EscapedChar
+            | Token::Interpolated
+            | Token::Number => style_codes.cyan,
+            Token::Keyword
+            | Token::Equals
+            | Token::Backslash
+            | Token::Pizza
+            | Token::Arrow
+            | Token::BackArrow
+            | Token::ColonEquals
+            | Token::Colon
+            | Token::And
+            | Token::QuestionMark => style_codes.green,
+            Token::UpperIdent | Token::AtSign => style_codes.yellow,
+            Token::Error => style_codes.red,
+            _ => "",
+        };
+
+        buf.push_str(&line[end..start]);
+
+        if color.is_empty() {
+            buf.push_str(&line[start..token_end]);
+        } else {
+            buf.push_str(color);
+            buf.push_str(&line[start..token_end]);
+            buf.push_str(style_codes.reset);
+        }
+
+        end = token_end;
+    }
+
+    buf.push_str(&line[end..]);
+
+    buf
+}
@@ -1,28 +1,98 @@
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 use std::{fs, io};
 
 use bumpalo::Bump;
-use roc_collections::MutSet;
+use roc_collections::{MutMap, MutSet};
 use roc_load::MonomorphizedModule;
-use roc_parse::ast::{Defs, Expr, Pattern, StrLiteral, TypeDef, TypeHeader, ValueDef};
+use roc_module::symbol::ModuleId;
+use roc_packaging::cache;
+use roc_parse::ast::{
+    Defs, Expr, Header, Pattern, SpacesBefore, StrLiteral, TypeDef, TypeHeader, ValueDef,
+};
 use roc_parse::expr::parse_repl_defs_and_optional_expr;
+use roc_parse::header::parse_header;
+use roc_parse::keyword::KEYWORDS;
 use roc_parse::parser::EWhen;
 use roc_parse::parser::{EClosure, EExpr, EPattern};
 use roc_parse::state::State;
 use roc_region::all::Loc;
-use roc_repl_eval::gen::{compile_to_mono, Problems};
-use roc_reporting::report::Palette;
+use roc_repl_eval::gen::{compile_to_mono, expr_type, record_fields, Problems, ReplOutput};
+use roc_reporting::report::{
+    pretty_header, to_https_problem_report_string, Palette, DEFAULT_PALETTE,
+};
 use roc_target::Target;
 
 #[derive(Debug, Clone, PartialEq)]
 enum PastDef {
-    Def { ident: String, src: String },
+    Def {
+        ident: String,
+        src: String,
+    },
     Import(String),
+    /// A module brought into scope with `:load`, along with the `import` that does it
+    Load {
+        filename: PathBuf,
+        src: String,
+    },
+    /// A package brought into scope with `import "<url or directory>" as shorthand`
+    Package {
+        shorthand: String,
+        location: String,
+        /// Where the package's modules are on disk (in the cache, for URLs)
+        dir: PathBuf,
+        src: String,
+    },
+}
+
+impl PastDef {
+    fn src(&self) -> &str {
+        match self {
+            PastDef::Def { ident: _, src }
+            | PastDef::Import(src)
+            | PastDef::Load { filename: _, src }
+            | PastDef::Package { src, .. } => src,
+        }
+    }
 }
 
+/// The module a package's location refers to, unless it names one explicitly
+const DEFAULT_PACKAGE_ROOT: &str = "main.roc";
+
+/// The builtin modules every REPL session can use without importing them
+const BUILTIN_MODULES: [(ModuleId, &str); 12] = [
+    (ModuleId::BOOL, "Bool"),
+    (ModuleId::BOX, "Box"),
+    (ModuleId::DECODE, "Decode"),
+    (ModuleId::DICT, "Dict"),
+    (ModuleId::ENCODE, "Encode"),
+    (ModuleId::HASH, "Hash"),
+    (ModuleId::INSPECT, "Inspect"),
+    (ModuleId::LIST, "List"),
+    (ModuleId::NUM, "Num"),
+    (ModuleId::RESULT, "Result"),
+    (ModuleId::SET, "Set"),
+    (ModuleId::STR, "Str"),
+];
+
+const META_COMMANDS: [&str; 9] = [
+    ":defs", ":exit", ":help", ":load", ":q", ":quit", ":reload", ":reset", ":type",
+];
+
 pub struct ReplState {
     past_defs: Vec<PastDef>,
     past_def_idents: MutSet<String>,
+    /// The types of past defs which compiled without errors, to show while typing
+    past_def_types: MutMap<String, String>,
+}
+
+/// A module which can be referred to by name in the REPL
+struct ModuleInScope {
+    /// The module's name, or its alias if it was imported `as` something else
+    name: String,
+    /// Everything the module exposes, from its header
+    exposes: Vec<String>,
+    /// What was imported unqualified with `exposing`
+    exposing: Vec<String>,
 }
 
 impl Default for ReplState {
@@ -38,6 +108,11 @@ pub enum ReplAction<'a> {
         opt_mono: Option<MonomorphizedModule<'a>>,
         problems: Problems,
     },
+    Type {
+        opt_output: Option<ReplOutput>,
+        problems: Problems,
+    },
+    Defs(Vec<String>),
     Exit,
     Help,
     FileProblem {
@@ -52,6 +127,7 @@ impl ReplState {
         Self {
             past_defs: Default::default(),
             past_def_idents: Default::default(),
+            past_def_types: Default::default(),
         }
     }
 
@@ -66,6 +142,79 @@ impl ReplState {
         let src: &str = match parse_src(arena, line) {
             ParseOutcome::Empty | ParseOutcome::Help => return ReplAction::Help,
             ParseOutcome::Exit => return ReplAction::Exit,
+            ParseOutcome::Type(expr) => {
+                let (opt_mono, problems) = self.compile(arena, expr, target, palette);
+
+                // Only type-check the expression; don't evaluate it.
+                let opt_output = opt_mono.and_then(|mut mono| {
+                    Some(ReplOutput {
+                        expr: expr.to_string(),
+                        expr_type: expr_type(&mut mono)?,
+                    })
+                });
+
+                return ReplAction::Type {
+                    opt_output,
+                    problems,
+                };
+            }
+            ParseOutcome::Load(filename) => {
+                let filename = PathBuf::from(filename);
+
+                return match load_import_src(arena, &filename, palette) {
+                    Ok(src) => {
+                        // Loading a module again replaces its old import.
+                        self.past_defs.retain(|past_def| {
+                            !matches!(past_def, PastDef::Load { filename: loaded, .. } if *loaded == filename)
+                        });
+                        self.past_defs.push(PastDef::Load { filename, src });
+
+                        ReplAction::Nothing
+                    }
+                    Err(action) => action,
+                };
+            }
+            ParseOutcome::Reload => {
+                // The modules themselves are read from disk on every evaluation,
+                // but what they expose may have changed since they were loaded.
+                let mut opt_problem = None;
+
+                self.past_defs.retain_mut(|past_def| match past_def {
+                    PastDef::Load { filename, src } => {
+                        match load_import_src(arena, filename, palette) {
+                            Ok(new_src) => {
+                                *src = new_src;
+
+                                true
+                            }
+                            Err(action) => {
+                                // Drop the module, so we don't report this on every evaluation.
+                                opt_problem.get_or_insert(action);
+
+                                false
+                            }
+                        }
+                    }
+                    PastDef::Def { .. } | PastDef::Import(_) | PastDef::Package { .. } => true,
+                });
+
+                return opt_problem.unwrap_or(ReplAction::Nothing);
+            }
+            ParseOutcome::Defs => {
+                return ReplAction::Defs(
+                    self.past_defs
+                        .iter()
+                        .map(|past_def| past_def.src().to_string())
+                        .collect(),
+                );
+            }
+            ParseOutcome::Reset => {
+                self.past_defs.clear();
+                self.past_def_idents.clear();
+                self.past_def_types.clear();
+
+                return ReplAction::Nothing;
+            }
             ParseOutcome::Incomplete | ParseOutcome::SyntaxErr => {
                 pending_past_def = None;
 
@@ -197,8 +346,39 @@ impl ReplState {
                                     todo!("handle receiving an `expect` - what should the repl do for that?")
                                 }
                                 ValueDef::ModuleImport(import) => match import.name.value.package {
-                                    Some(_) => {
-                                        todo!("handle importing a module from a package")
+                                    Some(shorthand) => {
+                                        let Some(package_dir) = self.package_dir(shorthand) else {
+                                            return report_problem(
+                                                palette,
+                                                "UNKNOWN PACKAGE",
+                                                format!(
+                                                    "I don't know about a package called `{shorthand}` yet. Bring it into scope first, with its URL or directory:\n\n    import \"https://example.com/package.tar.br\" as {shorthand}",
+                                                ),
+                                            );
+                                        };
+
+                                        let mut filename = package_dir.to_path_buf();
+
+                                        for part in import.name.value.name.parts() {
+                                            filename.push(part);
+                                        }
+
+                                        filename.set_extension("roc");
+
+                                        // Like local imports, check the module exists first so a bad
+                                        // import doesn't stay in past_defs.
+                                        if let Err(err) = fs::metadata(&filename) {
+                                            return ReplAction::FileProblem {
+                                                filename,
+                                                error: err.kind(),
+                                            };
+                                        }
+
+                                        self.past_defs.push(PastDef::Import(
+                                            line[vd.byte_range()].to_string(),
+                                        ));
+
+                                        return ReplAction::Nothing;
                                     }
                                     None => {
                                         let mut filename = PathBuf::new();
@@ -228,6 +408,21 @@ impl ReplState {
                                 },
                                 ValueDef::IngestedFileImport(file) => {
                                     if let StrLiteral::PlainLine(path) = file.path.value {
+                                        // Neither URLs nor directories can be ingested as files,
+                                        // so importing one of those means importing a package.
+                                        if file.annotation.is_none()
+                                            && (path.starts_with("https://")
+                                                || Path::new(path).is_dir())
+                                        {
+                                            return self.add_package(
+                                                arena,
+                                                file.name.item.value,
+                                                path,
+                                                line[vd.byte_range()].to_string(),
+                                                palette,
+                                            );
+                                        }
+
                                         let filename = PathBuf::from(path);
                                         if let Err(err) = fs::metadata(&filename) {
                                             return ReplAction::FileProblem {
@@ -257,24 +452,331 @@ impl ReplState {
             }
         };
 
-        let (opt_mono, problems) = compile_to_mono(
-            arena,
-            self.past_defs.iter().map(|past_def| match past_def {
-                PastDef::Def { ident: _, src } => src.as_str(),
-                PastDef::Import(src) => src.as_str(),
-            }),
-            src,
-            target,
-            palette,
-        );
+        let (mut opt_mono, problems) = self.compile(arena, src, target, palette);
 
         if let Some((ident, src)) = pending_past_def {
+            match opt_mono.as_mut().and_then(expr_type) {
+                Some(def_type) if problems.errors.is_empty() => {
+                    self.past_def_types.insert(ident.clone(), def_type);
+                }
+                _ => {
+                    self.past_def_types.remove(&ident);
+                }
+            }
+
             self.add_past_def(ident, src);
         }
 
         ReplAction::Eval { opt_mono, problems }
     }
 
+    /// Candidates for completing the meta-command, identifier, module name or record field
+    /// which ends at `pos` in `line`, along with the position where the completed part starts.
+    pub fn completions(&self, line: &str, pos: usize, target: Target) -> (usize, Vec<String>) {
+        let before = &line[..pos];
+        let trimmed = before.trim_start();
+
+        if trimmed.starts_with(':') && !trimmed.contains(char::is_whitespace) {
+            let candidates = META_COMMANDS
+                .iter()
+                .filter(|command| command.starts_with(trimmed))
+                .map(|command| command.to_string())
+                .collect();
+
+            return (pos - trimmed.len(), candidates);
+        }
+
+        let is_word_char = |c: char| c.is_alphanumeric() || matches!(c, '_' | '.' | '!');
+        let start = before
+            .char_indices()
+            .rev()
+            .find(|(_, c)| !is_word_char(*c))
+            .map_or(0, |(index, c)| index + c.len_utf8());
+        let word = &before[start..];
+
+        if word.is_empty() {
+            return (pos, Vec::new());
+        }
+
+        let arena = Bump::new();
+        let modules = self.modules_in_scope(&arena);
+
+        let (prefix, mut candidates) = match word.rsplit_once('.') {
+            Some((qualifier, prefix)) if qualifier.starts_with(char::is_uppercase) => {
+                let exposes = modules
+                    .into_iter()
+                    .find(|module| module.name == qualifier)
+                    .map(|module| module.exposes)
+                    .unwrap_or_default();
+
+                (prefix, exposes)
+            }
+            Some((qualifier, prefix)) if qualifier.starts_with(char::is_lowercase) => {
+                (prefix, self.field_names(qualifier, target))
+            }
+            Some((_, prefix)) => (prefix, Vec::new()),
+            None => {
+                let mut names: Vec<String> = KEYWORDS.iter().map(|kw| kw.to_string()).collect();
+
+                names.extend(self.past_def_idents.iter().cloned());
+
+                for module in modules {
+                    names.extend(module.exposing);
+                    names.push(module.name);
+                }
+
+                names.extend(self.past_defs.iter().filter_map(|past_def| {
+                    ingested_file_name(&arena, past_def.src()).map(String::from)
+                }));
+
+                (word, names)
+            }
+        };
+
+        candidates.retain(|candidate| candidate.starts_with(prefix));
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        (pos - prefix.len(), candidates)
+    }
+
+    /// The type of the past def named at the end of `line`, to show as a hint while typing
+    pub fn type_hint(&self, line: &str, pos: usize) -> Option<&str> {
+        if pos != line.len() {
+            return None;
+        }
+
+        let ident = line
+            .rsplit(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '!')))
+            .next()?;
+
+        // `x.y` is a field access (or a module member) rather than the def `y`
+        if line[..line.len() - ident.len()].ends_with('.') {
+            return None;
+        }
+
+        self.past_def_types.get(ident).map(String::as_str)
+    }
+
+    fn field_names(&self, qualifier: &str, target: Target) -> Vec<String> {
+        // Only compile things that could be records, since this runs while typing.
+        let first = qualifier.split('.').next().unwrap_or_default();
+
+        if !self.past_def_idents.contains(first) {
+            return Vec::new();
+        }
+
+        let arena = Bump::new();
+
+        let fields = match self.compile(&arena, qualifier, target, DEFAULT_PALETTE) {
+            (Some(mono), problems) if problems.errors.is_empty() => record_fields(&mono),
+            _ => Vec::new(),
+        };
+
+        fields
+    }
+
+    /// The builtin modules, followed by the imported ones
+    fn modules_in_scope(&self, arena: &Bump) -> Vec<ModuleInScope> {
+        let mut modules: Vec<ModuleInScope> = BUILTIN_MODULES
+            .iter()
+            .map(|&(module_id, name)| {
+                let src = roc_builtins::roc::module_source(module_id);
+
+                ModuleInScope {
+                    name: name.to_string(),
+                    exposes: header_exposes(arena, src),
+                    exposing: Vec::new(),
+                }
+            })
+            .collect();
+
+        for past_def in self.past_defs.iter() {
+            let src = arena.alloc_str(past_def.src());
+
+            let defs = match parse_src(arena, src) {
+                ParseOutcome::DefsAndExpr(defs, None) => defs,
+                _ => continue,
+            };
+
+            for value_def in defs.value_defs.iter() {
+                let ValueDef::ModuleImport(import) = value_def else {
+                    continue;
+                };
+
+                let module_name = import.name.value.name;
+                let mut filename = match import.name.value.package {
+                    Some(shorthand) => match self.package_dir(shorthand) {
+                        Some(dir) => dir.to_path_buf(),
+                        None => continue,
+                    },
+                    None => PathBuf::new(),
+                };
+
+                for part in module_name.parts() {
+                    filename.push(part);
+                }
+
+                filename.set_extension("roc");
+
+                let exposes = match fs::read_to_string(&filename) {
+                    Ok(module_src) => header_exposes(arena, arena.alloc_str(&module_src)),
+                    Err(_) => Vec::new(),
+                };
+
+                modules.push(ModuleInScope {
+                    name: match &import.alias {
+                        Some(alias) => alias.item.value.as_str().to_string(),
+                        None => module_name.as_str().to_string(),
+                    },
+                    exposes,
+                    exposing: import
+                        .exposed
+                        .iter()
+                        .flat_map(|exposed| exposed.item.iter())
+                        .map(|name| name.value.item().as_str().to_string())
+                        .collect(),
+                });
+            }
+        }
+
+        modules
+    }
+
+    fn compile<'a>(
+        &self,
+        arena: &'a Bump,
+        src: &str,
+        target: Target,
+        palette: Palette,
+    ) -> (Option<MonomorphizedModule<'a>>, Problems) {
+        let packages = self.past_defs.iter().filter_map(|past_def| match past_def {
+            PastDef::Package {
+                shorthand,
+                location,
+                ..
+            } => Some((shorthand.as_str(), location.as_str())),
+            PastDef::Def { .. } | PastDef::Import(_) | PastDef::Load { .. } => None,
+        });
+
+        // Packages go in the app header rather than in its defs.
+        let defs = self
+            .past_defs
+            .iter()
+            .filter(|past_def| !matches!(past_def, PastDef::Package { .. }))
+            .map(PastDef::src);
+
+        compile_to_mono(arena, packages, defs, src, target, palette)
+    }
+
+    fn package_dir(&self, shorthand: &str) -> Option<&Path> {
+        self.past_defs.iter().find_map(|past_def| match past_def {
+            PastDef::Package {
+                shorthand: existing,
+                dir,
+                ..
+            } if existing == shorthand => Some(dir.as_path()),
+            _ => None,
+        })
+    }
+
+    fn add_package<'a>(
+        &mut self,
+        arena: &'a Bump,
+        shorthand: &str,
+        location: &str,
+        src: String,
+        palette: Palette,
+    ) -> ReplAction<'a> {
+        let (root_module, location) = if location.starts_with("https://") {
+            match install_package(location) {
+                // The loader finds the package in the cache by its URL.
+                Ok(root_module) => (root_module, location.to_string()),
+                Err(action) => return action,
+            }
+        } else {
+            let root_module = Path::new(location).join(DEFAULT_PACKAGE_ROOT);
+
+            (
+                root_module.clone(),
+                root_module.to_string_lossy().into_owned(),
+            )
+        };
+
+        let header_src = match fs::read_to_string(&root_module) {
+            Ok(header_src) => arena.alloc_str(&header_src),
+            Err(err) => {
+                return ReplAction::FileProblem {
+                    filename: root_module,
+                    error: err.kind(),
+                }
+            }
+        };
+
+        match parse_header(arena, State::new(header_src.as_bytes())) {
+            Ok((
+                SpacesBefore {
+                    item: Header::Package(_),
+                    ..
+                },
+                _,
+            )) => {}
+            Ok((
+                SpacesBefore {
+                    item: Header::Platform(_),
+                    ..
+                },
+                _,
+            )) => {
+                return report_problem(
+                    palette,
+                    "PLATFORM IMPORT",
+                    format!(
+                        "{} is a platform, and I can only import packages. A platform needs an app to provide what it `requires`, which the REPL doesn't do.",
+                        root_module.display()
+                    ),
+                );
+            }
+            Ok(_) => {
+                return report_problem(
+                    palette,
+                    "NOT A PACKAGE",
+                    format!(
+                        "I can only import packages this way, but {} has a different kind of header. Packages start with a header like `package [Json] {{}}`.",
+                        root_module.display()
+                    ),
+                );
+            }
+            Err(_) => {
+                return report_problem(
+                    palette,
+                    "PACKAGE HEADER PROBLEM",
+                    format!(
+                        "I could not parse the header of {}. Running `roc check {}` will show what went wrong.",
+                        root_module.display(),
+                        root_module.display()
+                    ),
+                );
+            }
+        }
+
+        // Importing a package again with the same shorthand replaces it.
+        self.past_defs.retain(
+            |past_def| !matches!(past_def, PastDef::Package { shorthand: existing, .. } if existing == shorthand),
+        );
+        let mut dir = root_module;
+        dir.pop();
+
+        self.past_defs.push(PastDef::Package {
+            shorthand: shorthand.to_string(),
+            location,
+            dir,
+            src,
+        });
+
+        ReplAction::Nothing
+    }
+
     fn add_past_def(&mut self, ident: String, src: String) {
         let existing_idents = &mut self.past_def_idents;
 
@@ -284,6 +786,205 @@ impl ReplState {
     }
 }
 
+/// Download the package at this URL into the cache (if it isn't there already),
+/// and return the path to its root module.
+#[cfg(not(target_family = "wasm"))]
+#[allow(clippy::result_large_err)]
+fn install_package<'a>(url: &str) -> Result<PathBuf, ReplAction<'a>> {
+    let packages_dir = cache::roc_cache_packages_dir();
+
+    match cache::install_package(cache::RocCacheDir::Persistent(&packages_dir), url) {
+        Ok((package_dir, opt_root_module)) => {
+            Ok(package_dir.join(opt_root_module.unwrap_or(DEFAULT_PACKAGE_ROOT)))
+        }
+        Err(problem) => Err(ReplAction::Eval {
+            opt_mono: None,
+            problems: Problems {
+                errors: vec![to_https_problem_report_string(
+                    url,
+                    problem,
+                    PathBuf::from("replfile.roc"),
+                )],
+                warnings: Vec::new(),
+            },
+        }),
+    }
+}
+
+#[cfg(target_family = "wasm")]
+#[allow(clippy::result_large_err)]
+fn install_package<'a>(url: &str) -> Result<PathBuf, ReplAction<'a>> {
+    Err(ReplAction::FileProblem {
+        filename: PathBuf::from(url),
+        error: io::ErrorKind::Unsupported,
+    })
+}
+
+/// The `import` which brings everything the given module exposes into scope.
+///
+/// Imports are resolved relative to the current directory, so the module's
+/// path there determines its name, e.g. `Parser/Json.roc` is `Parser.Json`.
+#[allow(clippy::result_large_err)]
+fn load_import_src<'a>(
+    arena: &'a Bump,
+    filename: &Path,
+    palette: Palette,
+) -> Result<String, ReplAction<'a>> {
+    if filename.extension().and_then(|ext| ext.to_str()) != Some("roc") {
+        return Err(ReplAction::FileProblem {
+            filename: filename.to_path_buf(),
+            error: io::ErrorKind::Unsupported,
+        });
+    }
+
+    let mut name_parts = Vec::new();
+
+    for component in filename.with_extension("").components() {
+        match component {
+            Component::CurDir => {}
+            Component::Normal(part) => {
+                let part = part.to_string_lossy().into_owned();
+                let is_module_name = part.starts_with(|c: char| c.is_ascii_uppercase())
+                    && part.chars().all(|c| c.is_ascii_alphanumeric());
+
+                if !is_module_name {
+                    return Err(report_problem(
+                        palette,
+                        "INVALID MODULE NAME",
+                        format!(
+                            "I can't load {} because `{}` is not a valid module name. Module names start with an uppercase letter, like `Json`.",
+                            filename.display(),
+                            part
+                        ),
+                    ));
+                }
+
+                name_parts.push(part);
+            }
+            Component::Prefix(_) | Component::RootDir | Component::ParentDir => {
+                return Err(report_problem(
+                    palette,
+                    "MODULE OUTSIDE CURRENT DIRECTORY",
+                    format!(
+                        "I can only load modules from the current directory, but {} is outside of it.",
+                        filename.display()
+                    ),
+                ));
+            }
+        }
+    }
+
+    let src = match fs::read_to_string(filename) {
+        Ok(src) => arena.alloc_str(&src),
+        Err(err) => {
+            return Err(ReplAction::FileProblem {
+                filename: filename.to_path_buf(),
+                error: err.kind(),
+            })
+        }
+    };
+
+    let exposed: Vec<&str> = match parse_header(arena, State::new(src.as_bytes())) {
+        Ok((
+            SpacesBefore {
+                item: Header::Module(header),
+                ..
+            },
+            _,
+        )) => header
+            .exposes
+            .iter()
+            .map(|exposed| exposed.value.item().as_str())
+            .collect(),
+        Ok(_) => {
+            return Err(report_problem(
+                palette,
+                "NOT A MODULE",
+                format!(
+                    "I can only load modules, but {} has a different kind of header. Modules start with a header like `module [parse, Json]`.",
+                    filename.display()
+                ),
+            ));
+        }
+        Err(_) => {
+            return Err(report_problem(
+                palette,
+                "MODULE HEADER PROBLEM",
+                format!(
+                    "I could not parse the header of {}. Running `roc check {}` will show what went wrong.",
+                    filename.display(),
+                    filename.display()
+                ),
+            ));
+        }
+    };
+
+    let module_name = name_parts.join(".");
+
+    if exposed.is_empty() {
+        Ok(format!("import {module_name}"))
+    } else {
+        Ok(format!(
+            "import {module_name} exposing [{}]",
+            exposed.join(", ")
+        ))
+    }
+}
+
+/// Everything a module's header exposes
+fn header_exposes<'a>(arena: &'a Bump, src: &'a str) -> Vec<String> {
+    match parse_header(arena, State::new(src.as_bytes())) {
+        Ok((
+            SpacesBefore {
+                item: Header::Module(header),
+                ..
+            },
+            _,
+        )) => header
+            .exposes
+            .iter()
+            .map(|exposed| exposed.value.item().as_str().to_string())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The name an ingested file is imported `as`, e.g. `content` in `import "file.txt" as content`
+fn ingested_file_name<'a>(arena: &'a Bump, src: &str) -> Option<&'a str> {
+    let src = arena.alloc_str(src);
+
+    match parse_src(arena, src) {
+        ParseOutcome::DefsAndExpr(defs, None) => {
+            defs.value_defs
+                .iter()
+                .find_map(|value_def| match value_def {
+                    ValueDef::IngestedFileImport(file) => Some(file.name.item.value),
+                    _ => None,
+                })
+        }
+        _ => None,
+    }
+}
+
+/// Report a problem with a meta-command or import the same way as a compile error.
+fn report_problem<'a>(palette: Palette, title: &str, message: String) -> ReplAction<'a> {
+    let report = format!(
+        "{}{}{}\n\n{}",
+        palette.header,
+        pretty_header(title),
+        palette.reset,
+        message
+    );
+
+    ReplAction::Eval {
+        opt_mono: None,
+        problems: Problems {
+            errors: vec![report],
+            warnings: Vec::new(),
+        },
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum ParseOutcome<'a> {
     DefsAndExpr(Defs<'a>, Option<Loc<Expr<'a>>>),
@@ -292,6 +993,11 @@ pub enum ParseOutcome<'a> {
     Empty,
     Help,
     Exit,
+    Type(&'a str),
+    Load(&'a str),
+    Reload,
+    Defs,
+    Reset,
 }
 
 /// Special case some syntax errors to allow for multi-line inputs
@@ -306,7 +1012,33 @@ fn parse_outcome_for_error(e: EExpr<'_>) -> ParseOutcome<'_> {
     }
 }
 
+/// Meta-commands which take an argument, whose case must be preserved
+fn parse_meta_command(line: &str) -> Option<ParseOutcome<'_>> {
+    let line = line.trim();
+    let (command, arg) = match line.split_once(char::is_whitespace) {
+        Some((command, arg)) => (command, arg.trim()),
+        None => (line, ""),
+    };
+
+    let outcome = match (command.to_lowercase().as_str(), arg) {
+        (":type" | ":load", "") => ParseOutcome::Help,
+        (":type", expr) => ParseOutcome::Type(expr),
+        (":load", filename) => ParseOutcome::Load(filename),
+        (":reload", "") => ParseOutcome::Reload,
+        (":defs", "") => ParseOutcome::Defs,
+        (":reset", "") => ParseOutcome::Reset,
+        (":reload" | ":defs" | ":reset", _) => ParseOutcome::Help,
+        _ => return None,
+    };
+
+    Some(outcome)
+}
+
 pub fn parse_src<'a>(arena: &'a Bump, line: &'a str) -> ParseOutcome<'a> {
+    if let Some(outcome) = parse_meta_command(line) {
+        return outcome;
+    }
+
     match line.trim().to_lowercase().as_str() {
         "" => ParseOutcome::Empty,
         ":help" => ParseOutcome::Help,
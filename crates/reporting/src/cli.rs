@@ -1,12 +1,237 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use roc_collections::MutMap;
+use roc_error_macros::internal_error;
 use roc_module::symbol::{Interns, ModuleId};
 use roc_problem::can::Problem;
-use roc_region::all::LineInfo;
+use roc_problem::Severity;
+use roc_region::all::{LineColumnRegion, LineInfo, Region};
 use roc_solve_problem::TypeError;
+use serde_json::{json, Value};
 
-use crate::report::ANSI_STYLE_CODES;
+use crate::report::{
+    strip_colors, Palette, RenderTarget, RenderedReport, Report, RocDocAllocator, ANSI_STYLE_CODES,
+};
+
+/// How problems found during compilation are presented to the user.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Colored reports meant to be read by a human in a terminal.
+    #[default]
+    Human,
+    /// A JSON array with one record per problem.
+    Json,
+    /// A SARIF 2.1.0 log, as consumed by code scanning tools.
+    Sarif,
+}
+
+impl ReportFormat {
+    pub const FLAG_VALUES: [&'static str; 3] = ["human", "json", "sarif"];
+
+    /// In machine-readable formats, stdout is reserved for the problems document,
+    /// so callers should not print anything else there.
+    pub fn is_machine_readable(&self) -> bool {
+        !matches!(self, ReportFormat::Human)
+    }
+
+    /// The target for reports that are already rendered while loading
+    /// (e.g. parse errors), so they don't contain ANSI escape codes
+    /// when they end up inside a JSON string.
+    pub fn render_target(&self) -> RenderTarget {
+        match self {
+            ReportFormat::Human => RenderTarget::ColorTerminal,
+            ReportFormat::Json | ReportFormat::Sarif => RenderTarget::Generic,
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for ReportFormat {
+    type Error = ();
+
+    fn try_from(flag_value: &'a str) -> Result<Self, Self::Error> {
+        match flag_value {
+            "human" => Ok(ReportFormat::Human),
+            "json" => Ok(ReportFormat::Json),
+            "sarif" => Ok(ReportFormat::Sarif),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A single problem, in a form that can be serialized for tools like editors and CI.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub module_path: Option<PathBuf>,
+    pub title: String,
+    /// The full report, rendered without colors.
+    pub message: String,
+    pub region: Option<LineColumnRegion>,
+    /// Other places in the source that are relevant to this problem,
+    /// e.g. the original definition of a shadowed name.
+    pub related_regions: Vec<LineColumnRegion>,
+}
+
+impl Diagnostic {
+    /// A problem that was already rendered into a report while loading,
+    /// such as a parse error.
+    pub fn from_rendered_report(report: &RenderedReport) -> Self {
+        Diagnostic {
+            severity: Severity::Fatal,
+            module_path: report.filename.clone(),
+            title: report.title.clone(),
+            message: strip_colors(&report.text),
+            region: report.region,
+            related_regions: Vec::new(),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "severity": severity_str(self.severity),
+            "module_path": self.module_path.as_deref().map(path_to_string),
+            "title": self.title,
+            "message": self.message,
+            "region": self.region.map(region_to_json),
+            "related_regions": self.related_regions.iter().copied().map(region_to_json).collect::<Vec<_>>(),
+        })
+    }
+
+    fn to_sarif_result(&self) -> Value {
+        let location = |region: Option<LineColumnRegion>| {
+            let mut physical_location = json!({});
+
+            if let Some(path) = &self.module_path {
+                physical_location["artifactLocation"] = json!({ "uri": path_to_string(path) });
+            }
+
+            if let Some(region) = region {
+                physical_location["region"] = json!({
+                    "startLine": region.start.line + 1,
+                    "startColumn": region.start.column + 1,
+                    "endLine": region.end.line + 1,
+                    "endColumn": region.end.column + 1,
+                });
+            }
+
+            json!({ "physicalLocation": physical_location })
+        };
+
+        let level = match self.severity {
+            Severity::Warning => "warning",
+            Severity::RuntimeError | Severity::Fatal => "error",
+        };
+
+        json!({
+            "ruleId": self.title,
+            "level": level,
+            "message": { "text": self.message },
+            "locations": [location(self.region)],
+            "relatedLocations": self
+                .related_regions
+                .iter()
+                .map(|region| location(Some(*region)))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+fn severity_str(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Fatal => "fatal",
+        Severity::RuntimeError => "error",
+        Severity::Warning => "warning",
+    }
+}
+
+fn path_to_string(path: &Path) -> String {
+    // ensure paths contain only unix slashes, like in the report headers
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// Lines and columns are 1-based in the serialized output, like in editors.
+fn region_to_json(region: LineColumnRegion) -> Value {
+    json!({
+        "start": { "line": region.start.line + 1, "column": region.start.column + 1 },
+        "end": { "line": region.end.line + 1, "column": region.end.column + 1 },
+    })
+}
+
+/// Render diagnostics as a single document in the given machine-readable format.
+pub fn diagnostics_to_string(format: ReportFormat, diagnostics: &[Diagnostic]) -> String {
+    let document = match format {
+        ReportFormat::Human => {
+            internal_error!("Human-readable reports are rendered by `report_problems`.")
+        }
+        ReportFormat::Json => Value::Array(diagnostics.iter().map(Diagnostic::to_json).collect()),
+        ReportFormat::Sarif => json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "roc",
+                        "informationUri": "https://www.roc-lang.org",
+                    }
+                },
+                "results": diagnostics.iter().map(Diagnostic::to_sarif_result).collect::<Vec<_>>(),
+            }],
+        }),
+    };
+
+    serde_json::to_string_pretty(&document).expect("serializing a JSON value cannot fail")
+}
+
+/// The problems that a command reports, e.g. `roc test` once per file.
+///
+/// Human-readable reports are printed right away, but tools need a single document on stdout,
+/// so in machine-readable formats the diagnostics are collected here until the command is done.
+#[derive(Debug)]
+pub struct Diagnostics {
+    format: ReportFormat,
+    collected: Option<Vec<Diagnostic>>,
+}
+
+impl Diagnostics {
+    pub fn new(format: ReportFormat) -> Self {
+        Self {
+            format,
+            collected: None,
+        }
+    }
+
+    pub fn format(&self) -> ReportFormat {
+        self.format
+    }
+
+    /// Print a report that was formatted while loading (e.g. a parse error).
+    ///
+    /// In machine-readable formats, it's added to the document printed by [Diagnostics::print].
+    pub fn print_formatted_report(&mut self, report: &RenderedReport) {
+        match self.format {
+            ReportFormat::Human => print!("{}", report.text),
+            ReportFormat::Json | ReportFormat::Sarif => {
+                self.extend([Diagnostic::from_rendered_report(report)]);
+            }
+        }
+    }
+
+    fn extend(&mut self, diagnostics: impl IntoIterator<Item = Diagnostic>) {
+        self.collected
+            .get_or_insert_with(Vec::new)
+            .extend(diagnostics);
+    }
+
+    /// Print every collected diagnostic as one document.
+    ///
+    /// Prints nothing in the human-readable format, or if no problems were reported at all
+    /// (not even an empty document).
+    pub fn print(self) {
+        if let Some(diagnostics) = self.collected {
+            println!("{}", diagnostics_to_string(self.format, &diagnostics));
+        }
+    }
+}
 
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub struct Problems {
@@ -60,15 +285,21 @@ impl Problems {
     }
 }
 
+/// Print the problems found in the loaded modules.
+///
+/// In machine-readable formats, every problem is added to the document printed by
+/// [Diagnostics::print] instead.
 pub fn report_problems(
     sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
     interns: &Interns,
     can_problems: &mut MutMap<ModuleId, Vec<roc_problem::can::Problem>>,
     type_problems: &mut MutMap<ModuleId, Vec<TypeError>>,
+    diagnostics: &mut Diagnostics,
 ) -> Problems {
-    use crate::report::{can_problem, type_problem, Report, RocDocAllocator, DEFAULT_PALETTE};
+    use crate::report::{can_problem, type_problem, DEFAULT_PALETTE};
     use roc_problem::Severity::*;
 
+    let format = diagnostics.format();
     let palette = DEFAULT_PALETTE;
     let mut total_problems = 0;
 
@@ -99,22 +330,31 @@ pub fn report_problems(
         let problems = type_problems.remove(home).unwrap_or_default();
 
         for problem in problems {
+            let region = problem.region();
+            let related_regions = type_problem_related_regions(&problem);
+
             if let Some(report) = type_problem(&alloc, &lines, module_path.clone(), problem) {
                 let severity = report.severity;
-                let mut buf = String::new();
-
-                report.render_color_terminal(&mut buf, &alloc, &palette);
+                let rendered = RenderedProblem::new(
+                    report,
+                    &alloc,
+                    &palette,
+                    &lines,
+                    region,
+                    related_regions,
+                    format,
+                );
 
                 match severity {
                     Warning => {
-                        warnings.push(buf);
+                        warnings.push(rendered);
                     }
                     RuntimeError => {
-                        errors.push(buf);
+                        errors.push(rendered);
                     }
                     Fatal => {
                         fatally_errored = true;
-                        errors.push(buf);
+                        errors.push(rendered);
                     }
                 }
             }
@@ -137,22 +377,29 @@ pub fn report_problems(
         ordered.extend(shadowing_errs);
 
         for problem in ordered.into_iter() {
+            let (region, related_regions) = can_problem_regions(&problem);
             let report = can_problem(&alloc, &lines, module_path.clone(), problem);
             let severity = report.severity;
-            let mut buf = String::new();
-
-            report.render_color_terminal(&mut buf, &alloc, &palette);
+            let rendered = RenderedProblem::new(
+                report,
+                &alloc,
+                &palette,
+                &lines,
+                region,
+                related_regions,
+                format,
+            );
 
             match severity {
                 Warning => {
-                    warnings.push(buf);
+                    warnings.push(rendered);
                 }
                 RuntimeError => {
-                    errors.push(buf);
+                    errors.push(rendered);
                 }
                 Fatal => {
                     fatally_errored = true;
-                    errors.push(buf);
+                    errors.push(rendered);
                 }
             }
         }
@@ -161,6 +408,24 @@ pub fn report_problems(
     debug_assert!(can_problems.is_empty() && type_problems.is_empty(), "After reporting problems, there were {:?} can_problems and {:?} type_problems that could not be reported because they did not have corresponding entries in `sources`.", can_problems.len(), type_problems.len());
     debug_assert_eq!(errors.len() + warnings.len(), total_problems);
 
+    let problems = Problems {
+        fatally_errored,
+        errors: errors.len(),
+        warnings: warnings.len(),
+    };
+
+    if format.is_machine_readable() {
+        // Tools get every problem, not just the errors, and decide for themselves what to show.
+        diagnostics.extend(errors.into_iter().chain(warnings).filter_map(
+            |rendered| match rendered {
+                RenderedProblem::Diagnostic(diagnostic) => Some(diagnostic),
+                RenderedProblem::Text(_) => None,
+            },
+        ));
+
+        return problems;
+    }
+
     let problems_reported;
 
     // Only print warnings if there are no errors
@@ -188,9 +453,87 @@ pub fn report_problems(
         println!("{}\u{001B}[0m\n", Report::horizontal_rule(&palette));
     }
 
-    Problems {
-        fatally_errored,
-        errors: errors.len(),
-        warnings: warnings.len(),
+    problems
+}
+
+enum RenderedProblem {
+    Text(String),
+    Diagnostic(Diagnostic),
+}
+
+impl RenderedProblem {
+    fn new<'b>(
+        report: Report<'b>,
+        alloc: &'b RocDocAllocator<'b>,
+        palette: &'b Palette,
+        lines: &LineInfo,
+        region: Option<Region>,
+        related_regions: Vec<Region>,
+        format: ReportFormat,
+    ) -> Self {
+        let mut buf = String::new();
+
+        match format {
+            ReportFormat::Human => {
+                report.render_color_terminal(&mut buf, alloc, palette);
+
+                RenderedProblem::Text(buf)
+            }
+            ReportFormat::Json | ReportFormat::Sarif => {
+                let severity = report.severity;
+                let module_path = Some(report.filename.clone());
+                let title = report.title.clone();
+
+                report.render_ci(&mut buf, alloc);
+
+                RenderedProblem::Diagnostic(Diagnostic {
+                    severity,
+                    module_path,
+                    title,
+                    message: buf,
+                    region: region.map(|region| lines.convert_region(region)),
+                    related_regions: related_regions
+                        .into_iter()
+                        .map(|region| lines.convert_region(region))
+                        .collect(),
+                })
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for RenderedProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RenderedProblem::Text(text) => write!(f, "{text}"),
+            RenderedProblem::Diagnostic(diagnostic) => write!(f, "{}", diagnostic.message),
+        }
+    }
+}
+
+/// The region a tool should point at, and other regions worth pointing it at.
+fn can_problem_regions(problem: &Problem) -> (Option<Region>, Vec<Region>) {
+    match problem {
+        // `Problem::region` is where the name was first defined, but the problem
+        // is the new definition that shadows it.
+        Problem::Shadowing {
+            original_region,
+            shadow,
+            ..
+        } => (Some(shadow.region), vec![*original_region]),
+        _ => (problem.region(), Vec::new()),
+    }
+}
+
+/// Regions other than `TypeError::region` that are worth pointing a tool at.
+fn type_problem_related_regions(problem: &TypeError) -> Vec<Region> {
+    match problem {
+        TypeError::CircularDef(entries) => entries
+            .iter()
+            .skip(1)
+            .map(|entry| entry.symbol_region)
+            .collect(),
+        TypeError::FxInPureFunction(_, _, Some(annotation_region)) => vec![*annotation_region],
+        _ => Vec::new(),
     }
 }
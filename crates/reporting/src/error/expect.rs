@@ -263,4 +263,153 @@ impl<'a> Renderer<'a> {
 
         write!(writer, "{buf}")
     }
+
+    /// Render a snapshot that isn't stored yet (`stored` is `None`), or doesn't match the one
+    /// that is, with a diff of the two.
+    pub fn render_snapshot<W>(
+        &self,
+        writer: &mut W,
+        stored: Option<&str>,
+        current: &str,
+        expect_region: Region,
+    ) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        use crate::report::{Annotation, Report};
+        use ven_pretty::DocAllocator;
+
+        let line_col_region = self.line_info.convert_region(expect_region);
+        let severity = Severity::RuntimeError;
+
+        let (title, doc) = match stored {
+            None => (
+                "NEW SNAPSHOT",
+                self.alloc.stack([
+                    self.alloc.text("This snapshot isn't stored yet:"),
+                    self.alloc.region(line_col_region, severity),
+                    self.alloc.text("Its value looks like this:"),
+                    self.alloc
+                        .vcat(
+                            current
+                                .lines()
+                                .map(|line| self.alloc.text(line.to_string())),
+                        )
+                        .indent(4),
+                    self.alloc.concat([
+                        self.alloc.tip(),
+                        self.alloc.reflow("To store it, run "),
+                        self.alloc.keyword("roc test --update-snapshots"),
+                        self.alloc.reflow("."),
+                    ]),
+                ]),
+            ),
+            Some(stored) => {
+                let lines = diff_lines(stored, current)
+                    .into_iter()
+                    .map(|line| match line {
+                        DiffLine::Same(line) => self.alloc.text(format!("  {line}")),
+                        DiffLine::Removed(line) => self
+                            .alloc
+                            .text(format!("- {line}"))
+                            .annotate(Annotation::Error),
+                        DiffLine::Added(line) => self
+                            .alloc
+                            .text(format!("+ {line}"))
+                            .annotate(Annotation::Structure),
+                    });
+
+                (
+                    "SNAPSHOT CHANGED",
+                    self.alloc.stack([
+                        self.alloc
+                            .text("This snapshot doesn't match the stored one:"),
+                        self.alloc.region(line_col_region, severity),
+                        self.alloc.concat([
+                            self.alloc
+                                .reflow("Here is how its value changed, from the stored one ("),
+                            self.alloc.text("-").annotate(Annotation::Error),
+                            self.alloc.reflow(") to the current one ("),
+                            self.alloc.text("+").annotate(Annotation::Structure),
+                            self.alloc.reflow("):"),
+                        ]),
+                        self.alloc.vcat(lines).indent(4),
+                        self.alloc.concat([
+                            self.alloc.tip(),
+                            self.alloc.reflow(
+                                "If this change is expected, store the current value by running ",
+                            ),
+                            self.alloc.keyword("roc test --update-snapshots"),
+                            self.alloc.reflow("."),
+                        ]),
+                    ]),
+                )
+            }
+        };
+
+        let report = Report {
+            title: title.into(),
+            doc,
+            filename: self.filename.clone(),
+            severity,
+        };
+
+        let mut buf = String::new();
+
+        report.render(
+            self.render_target,
+            &mut buf,
+            &self.alloc,
+            &crate::report::DEFAULT_PALETTE,
+        );
+
+        write!(writer, "{buf}")
+    }
+}
+
+enum DiffLine<'s> {
+    Same(&'s str),
+    Removed(&'s str),
+    Added(&'s str),
+}
+
+/// The lines of `before` and `after`, marked as kept, removed or added, by way of the longest
+/// common subsequence of their lines. Snapshots are small, so quadratic time is fine.
+fn diff_lines<'s>(before: &'s str, after: &'s str) -> Vec<DiffLine<'s>> {
+    let before: Vec<&str> = before.lines().collect();
+    let after: Vec<&str> = after.lines().collect();
+
+    // common[i][j] is the length of the longest common subsequence of before[i..] and after[j..]
+    let mut common = vec![vec![0usize; after.len() + 1]; before.len() + 1];
+    for i in (0..before.len()).rev() {
+        for j in (0..after.len()).rev() {
+            common[i][j] = if before[i] == after[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::with_capacity(before.len().max(after.len()));
+    let (mut i, mut j) = (0, 0);
+
+    while i < before.len() && j < after.len() {
+        if before[i] == after[j] {
+            lines.push(DiffLine::Same(before[i]));
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            lines.push(DiffLine::Removed(before[i]));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(after[j]));
+            j += 1;
+        }
+    }
+
+    lines.extend(before[i..].iter().map(|line| DiffLine::Removed(line)));
+    lines.extend(after[j..].iter().map(|line| DiffLine::Added(line)));
+
+    lines
 }
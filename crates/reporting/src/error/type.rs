@@ -30,6 +30,9 @@ use ven_pretty::{text, DocAllocator};
 
 const ADD_ANNOTATIONS: &str = r#"Can more type annotations be added? Type annotations always help me give more specific messages, and I think they could help a lot in this case"#;
 
+/// How many of the names that fit a typed hole to suggest.
+const MAX_TYPED_HOLE_FITS: usize = 5;
+
 const OPAQUE_NUM_SYMBOLS: &[Symbol] = &[
     Symbol::NUM_NUM,
     Symbol::NUM_INTEGER,
@@ -513,6 +516,49 @@ pub fn type_problem<'b>(
                 severity,
             })
         }
+        TypedHole {
+            region,
+            name,
+            typ,
+            fits,
+        } => {
+            let hole = match name {
+                Some(name) => alloc.concat([
+                    alloc.reflow("The "),
+                    alloc.string(format!("?{name}")),
+                    alloc.reflow(" hole still needs to be filled in:"),
+                ]),
+                None => alloc.reflow("This hole still needs to be filled in:"),
+            };
+
+            // The innermost names first, leaving out the ones the compiler made up
+            let fits = (fits.into_iter().rev())
+                .filter(|symbol| {
+                    (symbol.as_str(alloc.interns)).starts_with(|c: char| c.is_alphabetic())
+                })
+                .take(MAX_TYPED_HOLE_FITS)
+                .map(|symbol| alloc.symbol_unqualified(symbol))
+                .collect::<Vec<_>>();
+
+            let fits = if fits.is_empty() {
+                alloc.reflow("Nothing in scope has that type.")
+            } else {
+                alloc.stack([
+                    alloc.reflow("These names in scope have that type:"),
+                    alloc.vcat(fits).indent(4),
+                ])
+            };
+
+            let stack = [
+                hole,
+                alloc.region(lines.convert_region(region), severity),
+                alloc.reflow("It needs a value of this type:"),
+                alloc.type_block(error_type_to_doc(alloc, typ)),
+                fits,
+            ];
+
+            report("TYPED HOLE".to_string(), alloc.stack(stack), filename)
+        }
     }
 }
 
@@ -2272,6 +2318,10 @@ fn format_category<'b>(
         Crash => {
             internal_error!("calls to crash should be unconditionally admitted in any context, unexpected reachability!");
         }
+        TypedHole => (
+            alloc.concat([this_is, alloc.reflow(" a typed hole")]),
+            alloc.text(" of type:"),
+        ),
 
         Storage(..) | Unknown => (
             alloc.concat([this_is, alloc.text(" a value")]),
@@ -5460,6 +5510,31 @@ pub fn unhandled_patterns_to_doc_block<'b>(
         .annotate(Annotation::TypeBlock)
 }
 
+/// Renders a pattern that isn't handled as source code, e.g. to add it as a new `when` branch.
+pub fn unhandled_pattern_to_source<'b>(
+    alloc: &'b RocDocAllocator<'b>,
+    pattern: roc_exhaustive::Pattern,
+) -> String {
+    use roc_exhaustive::{Pattern::Ctor, RenderAs};
+
+    // A pattern that's only handled behind an `if` guard is rendered with a note about the
+    // guard; the pattern itself is the second argument.
+    let pattern = match pattern {
+        Ctor(union, _, mut args) if matches!(union.render_as, RenderAs::Guard) => args.remove(1),
+        pattern => pattern,
+    };
+
+    let mut buf = String::new();
+
+    exhaustive_pattern_to_doc(alloc, pattern)
+        .annotate(Annotation::CodeBlock)
+        .1
+        .render_raw(1000, &mut crate::report::CiWrite::new(&mut buf))
+        .expect("<buffer is not a utf-8 encoded string>");
+
+    buf
+}
+
 fn exhaustive_pattern_to_doc<'b>(
     alloc: &'b RocDocAllocator<'b>,
     pattern: roc_exhaustive::Pattern,
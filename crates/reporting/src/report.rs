@@ -126,7 +126,56 @@ pub struct Report<'b> {
     pub severity: Severity,
 }
 
+/// A report that was rendered as soon as its problem was found, e.g. a parse error, along with
+/// the parts of it that tools need, so they don't have to read them back out of the text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenderedReport {
+    pub text: String,
+    pub title: String,
+    pub filename: Option<PathBuf>,
+    /// Where in the module the problem is, if it's about one place in it.
+    pub region: Option<LineColumnRegion>,
+}
+
+impl RenderedReport {
+    /// A report that is only text, like the one for a compiler crash.
+    pub fn from_text(text: String) -> Self {
+        Self {
+            text,
+            title: String::new(),
+            filename: None,
+            region: None,
+        }
+    }
+}
+
 impl<'b> Report<'b> {
+    /// Renders the report like [Report::render], keeping its title and module alongside the text.
+    pub fn render_with_details(
+        self,
+        target: RenderTarget,
+        alloc: &'b RocDocAllocator<'b>,
+        palette: &'b Palette,
+        region: Option<LineColumnRegion>,
+    ) -> RenderedReport {
+        let title = self.title.clone();
+        let filename = (!self.filename.as_os_str().is_empty()).then(|| self.filename.clone());
+
+        let mut text = String::new();
+        match target {
+            RenderTarget::Generic => self.render_ci(&mut text, alloc),
+            RenderTarget::ColorTerminal => self.render_color_terminal(&mut text, alloc, palette),
+            RenderTarget::LanguageServer => self.render_language_server(&mut text, alloc),
+        }
+
+        RenderedReport {
+            text,
+            title,
+            filename,
+            region,
+        }
+    }
+
     pub fn render(
         self,
         target: RenderTarget,
@@ -1210,6 +1259,15 @@ pub fn to_https_problem_report_string(
     https_problem: Problem,
     filename: PathBuf,
 ) -> String {
+    to_https_problem_rendered_report(url, https_problem, filename).text
+}
+
+#[cfg(not(target_family = "wasm"))]
+pub fn to_https_problem_rendered_report(
+    url: &str,
+    https_problem: Problem,
+    filename: PathBuf,
+) -> RenderedReport {
     let src_lines: Vec<&str> = Vec::new();
 
     let mut module_ids = ModuleIds::default();
@@ -1221,12 +1279,10 @@ pub fn to_https_problem_report_string(
     // Report parsing and canonicalization problems
     let alloc = RocDocAllocator::new(&src_lines, module_id, &interns);
 
-    let mut buf = String::new();
     let palette = DEFAULT_PALETTE;
     let report = to_https_problem_report(&alloc, url, https_problem, filename);
-    report.render_color_terminal(&mut buf, &alloc, &palette);
 
-    buf
+    report.render_with_details(RenderTarget::ColorTerminal, &alloc, &palette, None)
 }
 
 #[cfg(not(target_family = "wasm"))]
@@ -1696,6 +1752,14 @@ pub fn to_file_problem_report_string(
     error: io::ErrorKind,
     has_color: bool,
 ) -> String {
+    to_file_problem_rendered_report(filename, error, has_color).text
+}
+
+pub fn to_file_problem_rendered_report(
+    filename: PathBuf,
+    error: io::ErrorKind,
+    has_color: bool,
+) -> RenderedReport {
     let src_lines: Vec<&str> = Vec::new();
     let mut module_ids = ModuleIds::default();
     let module_id = module_ids.get_or_insert(&"find module name somehow?".into());
@@ -1704,16 +1768,14 @@ pub fn to_file_problem_report_string(
     // Report parsing and canonicalization problems
     let alloc = RocDocAllocator::new(&src_lines, module_id, &interns);
 
-    let mut buf = String::new();
     let palette = if has_color {
         DEFAULT_PALETTE
     } else {
         NO_COLOR_PALETTE
     };
     let report = to_file_problem_report(&alloc, filename, error);
-    report.render_color_terminal(&mut buf, &alloc, &palette);
 
-    buf
+    report.render_with_details(RenderTarget::ColorTerminal, &alloc, &palette, None)
 }
 
 pub fn to_file_problem_report<'b>(
@@ -13,12 +13,18 @@ use core::str;
 use std::convert::Infallible;
 
 mod roc_box;
+mod roc_dict;
+mod roc_hash;
 mod roc_list;
+mod roc_set;
 mod roc_str;
 mod storage;
 
 pub use roc_box::RocBox;
+pub use roc_dict::RocDict;
+pub use roc_hash::{RocHash, RocHasher};
 pub use roc_list::{ReadOnlyRocList, RocList, SendSafeRocList};
+pub use roc_set::RocSet;
 pub use roc_str::{InteriorNulError, ReadOnlyRocStr, RocStr, SendSafeRocStr};
 pub use storage::Storage;
 
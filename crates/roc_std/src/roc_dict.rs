@@ -0,0 +1,732 @@
+#![deny(unsafe_op_in_unsafe_fn)]
+
+use core::{
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    mem::{self, ManuallyDrop},
+};
+use std::collections::hash_map::DefaultHasher;
+
+use crate::{RocList, RocRefcounted};
+
+#[cfg(feature = "dict-seed")]
+use crate::{RocHash, RocHasher};
+#[cfg(feature = "dict-seed")]
+use core::iter::FromIterator;
+
+#[cfg(all(feature = "serde", feature = "dict-seed"))]
+use core::marker::PhantomData;
+#[cfg(all(feature = "serde", feature = "dict-seed"))]
+use serde::{
+    de::{Deserializer, MapAccess, Visitor},
+    Deserialize,
+};
+#[cfg(feature = "serde")]
+use serde::{
+    ser::{SerializeMap, Serializer},
+    Serialize,
+};
+
+/// `initial_shifts` in Dict.roc, which gives 8 buckets.
+const INITIAL_SHIFTS: u8 = 64 - 3;
+/// `default_max_load_factor` in Dict.roc.
+const MAX_LOAD_FACTOR: f32 = 0.8;
+/// `max_bucket_count` in Dict.roc.
+#[cfg(feature = "dict-seed")]
+const MAX_BUCKET_COUNT: u64 = 1 << 32;
+/// `dist_inc` in Dict.roc: the distance is above the 1-byte fingerprint.
+#[cfg(feature = "dict-seed")]
+const DIST_INC: u32 = 1 << 8;
+#[cfg(feature = "dict-seed")]
+const FINGERPRINT_MASK: u32 = DIST_INC - 1;
+#[cfg(feature = "dict-seed")]
+const EMPTY_BUCKET: Bucket = Bucket {
+    data_index: 0,
+    dist_and_fingerprint: 0,
+};
+
+/// A Roc `Dict`, with the same memory layout as the one in Dict.roc: the key-value pairs in
+/// the order they were inserted, and buckets of indices into them.
+///
+/// The keys are hashed with [RocHash](crate::RocHash), which gives the same hashes as Roc, so
+/// the buckets are kept up to date the same way Dict.roc keeps them. This needs the seed that
+/// the Roc application exports as `roc_dict_pseudo_seed`, so building a dictionary, looking up
+/// in one, and comparing two need the `dict-seed` feature, and a host that's linked with a Roc
+/// application.
+#[repr(C)]
+pub struct RocDict<K, V>
+where
+    K: RocRefcounted,
+    V: RocRefcounted,
+{
+    // Roc sorts the fields by alignment, and then by name.
+    #[cfg(target_pointer_width = "32")]
+    max_bucket_capacity: u64,
+    buckets: RocList<Bucket>,
+    data: RocList<RocDictItem<K, V>>,
+    #[cfg(target_pointer_width = "64")]
+    max_bucket_capacity: u64,
+    max_load_factor: f32,
+    shifts: u8,
+}
+
+impl<K, V> RocDict<K, V>
+where
+    K: RocRefcounted,
+    V: RocRefcounted,
+{
+    pub fn empty() -> Self {
+        Self {
+            buckets: RocList::empty(),
+            data: RocList::empty(),
+            max_bucket_capacity: 0,
+            max_load_factor: MAX_LOAD_FACTOR,
+            shifts: INITIAL_SHIFTS,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// The entries in the order they were inserted, like `Dict.walk` visits them.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            items: self.data.as_slice().iter(),
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.data.iter().map(RocDictItem::key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.data.iter().map(RocDictItem::value)
+    }
+}
+
+#[cfg(feature = "dict-seed")]
+impl<K, V> RocDict<K, V>
+where
+    K: RocHash + RocRefcounted,
+    V: RocRefcounted,
+{
+    /// Create an empty RocDict with enough space preallocated to store
+    /// the requested number of entries, like `Dict.with_capacity`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut dict = Self {
+            data: RocList::with_capacity(capacity),
+            ..Self::empty()
+        };
+        let size = (capacity as u64).min(MAX_BUCKET_COUNT);
+        dict.rebuild_buckets(shifts_for_size(size, dict.max_load_factor));
+
+        dict
+    }
+
+    /// `increase_size` in Dict.roc.
+    fn increase_size(&mut self) {
+        if self.max_bucket_capacity == MAX_BUCKET_COUNT {
+            panic!("Dict hit limit of {MAX_BUCKET_COUNT} elements. Unable to grow more.");
+        }
+
+        self.rebuild_buckets(self.shifts - 1);
+    }
+
+    /// `alloc_buckets_from_shift` and `fill_buckets_from_data` in Dict.roc.
+    fn rebuild_buckets(&mut self, shifts: u8) {
+        let bucket_count = bucket_count(shifts);
+        let mut buckets = RocList::from_iter((0..bucket_count).map(|_| EMPTY_BUCKET));
+
+        for (data_index, item) in self.data.iter().enumerate() {
+            let (bucket_index, dist_and_fingerprint) =
+                next_while_less(&buckets, item.key(), shifts);
+            let bucket = Bucket {
+                data_index: data_index as u32,
+                dist_and_fingerprint,
+            };
+
+            place_and_shift_up(&mut buckets, bucket, bucket_index);
+        }
+
+        self.buckets = buckets;
+        self.max_bucket_capacity = max_bucket_capacity(shifts, self.max_load_factor);
+        self.shifts = shifts;
+    }
+}
+
+#[cfg(feature = "dict-seed")]
+impl<K, V> RocDict<K, V>
+where
+    K: PartialEq + RocHash + RocRefcounted,
+    V: RocRefcounted,
+{
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.find(key)
+            .map(|data_index| self.data[data_index].value())
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// `find` in Dict.roc: the index of the key's entry.
+    fn find(&self, key: &K) -> Option<usize> {
+        if self.data.is_empty() {
+            return None;
+        }
+
+        let hash = hash_key(key);
+        let mut dist_and_fingerprint = dist_and_fingerprint_from_hash(hash);
+        let mut bucket_index = bucket_index_from_hash(hash, self.shifts);
+
+        loop {
+            let bucket = self.buckets[bucket_index];
+
+            if dist_and_fingerprint == bucket.dist_and_fingerprint {
+                let data_index = bucket.data_index as usize;
+
+                if self.data[data_index].key() == key {
+                    return Some(data_index);
+                }
+            } else if dist_and_fingerprint > bucket.dist_and_fingerprint {
+                return None;
+            }
+
+            bucket_index = next_bucket_index(bucket_index, self.buckets.len());
+            dist_and_fingerprint += DIST_INC;
+        }
+    }
+}
+
+#[cfg(feature = "dict-seed")]
+impl<K, V> RocDict<K, V>
+where
+    K: Clone + PartialEq + RocHash + RocRefcounted,
+    V: Clone + RocRefcounted,
+{
+    /// Insert a value for a key, returning the value it replaced, if any. Like `Dict.insert`,
+    /// a new key goes after all the existing ones.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if self.len() as u64 >= self.max_bucket_capacity {
+            self.increase_size();
+        }
+
+        let hash = hash_key(&key);
+        let mut dist_and_fingerprint = dist_and_fingerprint_from_hash(hash);
+        let mut bucket_index = bucket_index_from_hash(hash, self.shifts);
+
+        loop {
+            let bucket = self.buckets[bucket_index];
+
+            if dist_and_fingerprint == bucket.dist_and_fingerprint {
+                let data_index = bucket.data_index as usize;
+
+                if *self.data[data_index].key() == key {
+                    let item = &mut make_unique(&mut self.data)[data_index];
+
+                    return Some(mem::replace(item.value_mut(), value));
+                }
+            } else if dist_and_fingerprint > bucket.dist_and_fingerprint {
+                let data_index = self.len() as u32;
+                make_unique(&mut self.data).push(RocDictItem::new(key, value));
+
+                let bucket = Bucket {
+                    data_index,
+                    dist_and_fingerprint,
+                };
+                place_and_shift_up(&mut self.buckets, bucket, bucket_index);
+
+                return None;
+            }
+
+            bucket_index = next_bucket_index(bucket_index, self.buckets.len());
+            dist_and_fingerprint += DIST_INC;
+        }
+    }
+}
+
+/// Copies the list if someone else can see it, so that changing it doesn't change theirs.
+#[cfg(feature = "dict-seed")]
+fn make_unique<T>(list: &mut RocList<T>) -> &mut RocList<T>
+where
+    T: Clone + RocRefcounted,
+{
+    if !list.is_unique() {
+        *list = RocList::from_slice(list);
+    }
+
+    list
+}
+
+#[cfg(feature = "dict-seed")]
+fn hash_key<K: RocHash>(key: &K) -> u64 {
+    let mut hasher = RocHasher::for_dict();
+    key.roc_hash(&mut hasher);
+
+    hasher.complete()
+}
+
+#[cfg(feature = "dict-seed")]
+fn dist_and_fingerprint_from_hash(hash: u64) -> u32 {
+    (hash as u32 & FINGERPRINT_MASK) | DIST_INC
+}
+
+#[cfg(feature = "dict-seed")]
+fn bucket_index_from_hash(hash: u64, shifts: u8) -> usize {
+    (hash >> shifts) as usize
+}
+
+#[cfg(feature = "dict-seed")]
+fn next_bucket_index(bucket_index: usize, bucket_count: usize) -> usize {
+    if bucket_index + 1 != bucket_count {
+        bucket_index + 1
+    } else {
+        0
+    }
+}
+
+/// `next_while_less` in Dict.roc: the first bucket the key can go in, and its distance there.
+#[cfg(feature = "dict-seed")]
+fn next_while_less<K: RocHash>(buckets: &[Bucket], key: &K, shifts: u8) -> (usize, u32) {
+    let hash = hash_key(key);
+    let mut dist_and_fingerprint = dist_and_fingerprint_from_hash(hash);
+    let mut bucket_index = bucket_index_from_hash(hash, shifts);
+
+    while dist_and_fingerprint < buckets[bucket_index].dist_and_fingerprint {
+        bucket_index = next_bucket_index(bucket_index, buckets.len());
+        dist_and_fingerprint += DIST_INC;
+    }
+
+    (bucket_index, dist_and_fingerprint)
+}
+
+/// `place_and_shift_up` in Dict.roc: the buckets from here up to the next empty one move
+/// up by one to make room.
+#[cfg(feature = "dict-seed")]
+fn place_and_shift_up(buckets: &mut RocList<Bucket>, mut bucket: Bucket, mut bucket_index: usize) {
+    let buckets = make_unique(buckets);
+
+    loop {
+        let loaded = mem::replace(&mut buckets[bucket_index], bucket);
+
+        if loaded.dist_and_fingerprint == 0 {
+            return;
+        }
+
+        bucket = Bucket {
+            dist_and_fingerprint: loaded.dist_and_fingerprint + DIST_INC,
+            ..loaded
+        };
+        bucket_index = next_bucket_index(bucket_index, buckets.len());
+    }
+}
+
+/// `calc_shifts_for_size` in Dict.roc.
+#[cfg(feature = "dict-seed")]
+fn shifts_for_size(size: u64, max_load_factor: f32) -> u8 {
+    let mut shifts = INITIAL_SHIFTS;
+
+    while shifts > 0 && max_bucket_capacity(shifts, max_load_factor) < size {
+        shifts -= 1;
+    }
+
+    shifts
+}
+
+/// `calc_num_buckets` in Dict.roc.
+#[cfg(feature = "dict-seed")]
+fn bucket_count(shifts: u8) -> u64 {
+    1u64.checked_shl(64 - shifts as u32)
+        .unwrap_or(MAX_BUCKET_COUNT)
+        .min(MAX_BUCKET_COUNT)
+}
+
+#[cfg(feature = "dict-seed")]
+fn max_bucket_capacity(shifts: u8, max_load_factor: f32) -> u64 {
+    let bucket_count = bucket_count(shifts);
+
+    if bucket_count == MAX_BUCKET_COUNT {
+        // Every bucket can be used once there can't be more of them.
+        MAX_BUCKET_COUNT
+    } else {
+        (bucket_count as f32 * max_load_factor).floor() as u64
+    }
+}
+
+impl<K, V> Default for RocDict<K, V>
+where
+    K: RocRefcounted,
+    V: RocRefcounted,
+{
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<K, V> Clone for RocDict<K, V>
+where
+    K: RocRefcounted,
+    V: RocRefcounted,
+{
+    fn clone(&self) -> Self {
+        Self {
+            buckets: self.buckets.clone(),
+            data: self.data.clone(),
+            max_bucket_capacity: self.max_bucket_capacity,
+            max_load_factor: self.max_load_factor,
+            shifts: self.shifts,
+        }
+    }
+}
+
+impl<K, V> RocRefcounted for RocDict<K, V>
+where
+    K: RocRefcounted,
+    V: RocRefcounted,
+{
+    fn inc(&mut self) {
+        self.buckets.inc();
+        self.data.inc();
+    }
+
+    fn dec(&mut self) {
+        self.buckets.dec();
+        self.data.dec();
+    }
+
+    fn is_refcounted() -> bool {
+        true
+    }
+}
+
+/// Like `Dict.is_eq`, the order of the entries doesn't matter.
+#[cfg(feature = "dict-seed")]
+impl<K, V> PartialEq for RocDict<K, V>
+where
+    K: PartialEq + RocHash + RocRefcounted,
+    V: PartialEq + RocRefcounted,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key).is_some_and(|other| value == other))
+    }
+}
+
+#[cfg(feature = "dict-seed")]
+impl<K, V> Eq for RocDict<K, V>
+where
+    K: Eq + RocHash + RocRefcounted,
+    V: Eq + RocRefcounted,
+{
+}
+
+impl<K, V> Hash for RocDict<K, V>
+where
+    K: Hash + RocRefcounted,
+    V: Hash + RocRefcounted,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Equal dictionaries can have their entries in different orders, so they're hashed
+        // separately and then combined in a way that ignores the order, like
+        // `Hash.hash_unordered` does.
+        let entries = self.iter().fold(0u64, |combined, entry| {
+            let mut hasher = DefaultHasher::new();
+            entry.hash(&mut hasher);
+
+            combined.wrapping_add(hasher.finish())
+        });
+
+        self.len().hash(state);
+        entries.hash(state);
+    }
+}
+
+impl<K, V> Debug for RocDict<K, V>
+where
+    K: Debug + RocRefcounted,
+    V: Debug + RocRefcounted,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Later entries replace earlier ones with the same key.
+#[cfg(feature = "dict-seed")]
+impl<K, V> FromIterator<(K, V)> for RocDict<K, V>
+where
+    K: Clone + PartialEq + RocHash + RocRefcounted,
+    V: Clone + RocRefcounted,
+{
+    fn from_iter<I>(into: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let iter = into.into_iter();
+        let mut dict = Self::with_capacity(iter.size_hint().0);
+
+        for (key, value) in iter {
+            dict.insert(key, value);
+        }
+
+        dict
+    }
+}
+
+#[cfg(feature = "dict-seed")]
+impl<K, V, const SIZE: usize> From<[(K, V); SIZE]> for RocDict<K, V>
+where
+    K: Clone + PartialEq + RocHash + RocRefcounted,
+    V: Clone + RocRefcounted,
+{
+    fn from(entries: [(K, V); SIZE]) -> Self {
+        Self::from_iter(entries)
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a RocDict<K, V>
+where
+    K: RocRefcounted,
+    V: RocRefcounted,
+{
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// The entries of a [RocDict], in the order they were inserted.
+pub struct Iter<'a, K, V> {
+    items: core::slice::Iter<'a, RocDictItem<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.items.next().map(|item| (item.key(), item.value()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.items.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+/// `Bucket` in Dict.roc.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct Bucket {
+    data_index: u32,
+    /// The upper 3 bytes are the distance to the bucket the hash picked, and the lowest byte
+    /// is a fingerprint of the hash.
+    dist_and_fingerprint: u32,
+}
+
+crate::roc_refcounted_noop_impl!(Bucket);
+
+/// A `(k, v)` tuple, which Roc lays out with the more aligned element first.
+#[repr(C)]
+union RocDictItem<K, V> {
+    key_first: ManuallyDrop<KeyFirst<K, V>>,
+    value_first: ManuallyDrop<ValueFirst<K, V>>,
+}
+
+#[repr(C)]
+struct KeyFirst<K, V> {
+    key: K,
+    value: V,
+}
+
+#[repr(C)]
+struct ValueFirst<K, V> {
+    value: V,
+    key: K,
+}
+
+impl<K, V> RocDictItem<K, V> {
+    fn key_first() -> bool {
+        mem::align_of::<K>() >= mem::align_of::<V>()
+    }
+
+    fn new(key: K, value: V) -> Self {
+        if Self::key_first() {
+            Self {
+                key_first: ManuallyDrop::new(KeyFirst { key, value }),
+            }
+        } else {
+            Self {
+                value_first: ManuallyDrop::new(ValueFirst { value, key }),
+            }
+        }
+    }
+
+    fn key(&self) -> &K {
+        if Self::key_first() {
+            unsafe { &self.key_first.key }
+        } else {
+            unsafe { &self.value_first.key }
+        }
+    }
+
+    fn value(&self) -> &V {
+        if Self::key_first() {
+            unsafe { &self.key_first.value }
+        } else {
+            unsafe { &self.value_first.value }
+        }
+    }
+
+    fn key_mut(&mut self) -> &mut K {
+        if Self::key_first() {
+            unsafe { &mut self.key_first.key }
+        } else {
+            unsafe { &mut self.value_first.key }
+        }
+    }
+
+    fn value_mut(&mut self) -> &mut V {
+        if Self::key_first() {
+            unsafe { &mut self.key_first.value }
+        } else {
+            unsafe { &mut self.value_first.value }
+        }
+    }
+}
+
+impl<K, V> Clone for RocDictItem<K, V>
+where
+    K: Clone,
+    V: Clone,
+{
+    fn clone(&self) -> Self {
+        Self::new(self.key().clone(), self.value().clone())
+    }
+}
+
+impl<K, V> RocRefcounted for RocDictItem<K, V>
+where
+    K: RocRefcounted,
+    V: RocRefcounted,
+{
+    fn inc(&mut self) {
+        self.key_mut().inc();
+        self.value_mut().inc();
+    }
+
+    fn dec(&mut self) {
+        self.key_mut().dec();
+        self.value_mut().dec();
+    }
+
+    fn is_refcounted() -> bool {
+        K::is_refcounted() || V::is_refcounted()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K, V> Serialize for RocDict<K, V>
+where
+    K: Serialize + RocRefcounted,
+    V: Serialize + RocRefcounted,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for (key, value) in self.iter() {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+#[cfg(all(feature = "serde", feature = "dict-seed"))]
+impl<'de, K, V> Deserialize<'de> for RocDict<K, V>
+where
+    K: Deserialize<'de> + Clone + PartialEq + RocHash + RocRefcounted,
+    V: Deserialize<'de> + Clone + RocRefcounted,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(RocDictVisitor {
+            marker: PhantomData,
+        })
+    }
+}
+
+#[cfg(all(feature = "serde", feature = "dict-seed"))]
+struct RocDictVisitor<K, V> {
+    marker: PhantomData<(K, V)>,
+}
+
+#[cfg(all(feature = "serde", feature = "dict-seed"))]
+impl<'de, K, V> Visitor<'de> for RocDictVisitor<K, V>
+where
+    K: Deserialize<'de> + Clone + PartialEq + RocHash + RocRefcounted,
+    V: Deserialize<'de> + Clone + RocRefcounted,
+{
+    type Value = RocDict<K, V>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a map")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut out = RocDict::with_capacity(map.size_hint().unwrap_or(0));
+
+        while let Some((key, value)) = map.next_entry()? {
+            out.insert(key, value);
+        }
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_matches_dict_roc() {
+        assert_eq!(mem::size_of::<Bucket>(), 8);
+        assert_eq!(
+            mem::size_of::<RocDict<u8, u8>>(),
+            2 * mem::size_of::<RocList<u8>>() + 16
+        );
+    }
+
+    #[test]
+    fn more_aligned_element_goes_first() {
+        let item = RocDictItem::new(1u8, 2u64);
+        let first = unsafe { *(&item as *const RocDictItem<u8, u64>).cast::<u64>() };
+
+        assert_eq!(first, 2);
+        assert_eq!((*item.key(), *item.value()), (1, 2));
+    }
+
+    #[test]
+    #[cfg(feature = "dict-seed")]
+    fn shifts_leave_room_for_the_entries() {
+        assert_eq!(shifts_for_size(0, MAX_LOAD_FACTOR), INITIAL_SHIFTS);
+        assert_eq!(shifts_for_size(6, MAX_LOAD_FACTOR), INITIAL_SHIFTS);
+        assert_eq!(shifts_for_size(7, MAX_LOAD_FACTOR), INITIAL_SHIFTS - 1);
+        assert_eq!(shifts_for_size(100, MAX_LOAD_FACTOR), 64 - 7);
+    }
+}
@@ -0,0 +1,344 @@
+//! The hashing that Roc's `Dict` and `Set` use to find the bucket for a key, ported from
+//! `LowLevelHasher` in Dict.roc.
+
+use crate::{RocDec, RocList, RocRefcounted, RocStr};
+
+#[cfg(feature = "dict-seed")]
+extern "C" {
+    /// `pseudo_seed` in Dict.roc. Every Roc application exports this, and it can give a
+    /// different seed on every run, so it's looked up each time a key is hashed.
+    fn roc_dict_pseudo_seed() -> u64;
+}
+
+const WYP0: u64 = 0xa0761d6478bd642f;
+const WYP1: u64 = 0xe7037ed1a0b428db;
+const WYP2: u64 = 0x8ebc6af09c88c6e3;
+const WYP3: u64 = 0x589965cc75374cc3;
+
+/// `LowLevelHasher` in Dict.roc, which gives the same hashes as Roc for the same seed.
+#[derive(Clone, Copy, Debug)]
+pub struct RocHasher {
+    initialized_seed: u64,
+    state: u64,
+}
+
+impl RocHasher {
+    /// The hasher that Roc's `Dict` uses in the application this is linked with, which has to
+    /// export `roc_dict_pseudo_seed`.
+    #[cfg(feature = "dict-seed")]
+    pub fn for_dict() -> Self {
+        Self::with_seed(unsafe { roc_dict_pseudo_seed() })
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            initialized_seed: wymix(seed ^ WYP0, WYP1) ^ seed,
+            state: seed,
+        }
+    }
+
+    pub fn complete(self) -> u64 {
+        self.state
+    }
+
+    pub fn add_u8(&mut self, u8: u8) {
+        let p0 = u8 as u64;
+        let a = (p0 << 16) | (p0 << 8) | p0;
+
+        self.combine_state(a, 0, self.initialized_seed, 1);
+    }
+
+    pub fn add_u16(&mut self, u16: u16) {
+        let p0 = (u16 & 0xFF) as u64;
+        let p1 = (u16 >> 8) as u64;
+        let a = (p0 << 16) | (p1 << 8) | p1;
+
+        self.combine_state(a, 0, self.initialized_seed, 2);
+    }
+
+    pub fn add_u32(&mut self, u32: u32) {
+        let p0 = u32 as u64;
+        let a = (p0 << 32) | p0;
+
+        self.combine_state(a, a, self.initialized_seed, 4);
+    }
+
+    pub fn add_u64(&mut self, u64: u64) {
+        let p0 = u64 & 0xFFFF_FFFF;
+        let p1 = u64 >> 32;
+        let a = (p0 << 32) | p1;
+        let b = (p1 << 32) | p0;
+
+        self.combine_state(a, b, self.initialized_seed, 8);
+    }
+
+    pub fn add_u128(&mut self, u128: u128) {
+        let lower = u128 as u64;
+        let upper = (u128 >> 64) as u64;
+        let p0 = lower & 0xFFFF_FFFF;
+        let p1 = lower >> 32;
+        let p2 = upper & 0xFFFF_FFFF;
+        let p3 = upper >> 32;
+        let a = (p0 << 32) | p2;
+        let b = (p3 << 32) | p1;
+
+        self.combine_state(a, b, self.initialized_seed, 16);
+    }
+
+    pub fn add_bytes(&mut self, bytes: &[u8]) {
+        let length = bytes.len();
+        let (a, b, seed) = if length <= 16 {
+            if length >= 4 {
+                let x = (length >> 3) << 2;
+                let a = (wyr4(bytes, 0) << 32) | wyr4(bytes, x);
+                let b = (wyr4(bytes, length - 4) << 32) | wyr4(bytes, length - 4 - x);
+
+                (a, b, self.initialized_seed)
+            } else if length > 0 {
+                (wyr3(bytes, 0, length), 0, self.initialized_seed)
+            } else {
+                (0, 0, self.initialized_seed)
+            }
+        } else if length <= 48 {
+            hash_bytes_helper16(self.initialized_seed, bytes, 0, length)
+        } else {
+            hash_bytes_helper48(self.initialized_seed, bytes)
+        };
+
+        self.combine_state(a, b, seed, length as u64);
+    }
+
+    fn combine_state(&mut self, a: u64, b: u64, seed: u64, length: u64) {
+        let (lower, upper) = wymum(a ^ WYP1, b ^ seed);
+        let hash = wymix(lower ^ WYP0 ^ length, upper ^ WYP1);
+
+        self.state = wymix(self.state, hash);
+    }
+}
+
+fn hash_bytes_helper48(initialized_seed: u64, bytes: &[u8]) -> (u64, u64, u64) {
+    let mut seed = initialized_seed;
+    let mut see1 = initialized_seed;
+    let mut see2 = initialized_seed;
+    let mut index = 0;
+    let mut remaining = bytes.len();
+
+    loop {
+        seed = wymix(wyr8(bytes, index) ^ WYP1, wyr8(bytes, index + 8) ^ seed);
+        see1 = wymix(
+            wyr8(bytes, index + 16) ^ WYP2,
+            wyr8(bytes, index + 24) ^ see1,
+        );
+        see2 = wymix(
+            wyr8(bytes, index + 32) ^ WYP3,
+            wyr8(bytes, index + 40) ^ see2,
+        );
+        remaining -= 48;
+        index += 48;
+
+        if remaining <= 48 {
+            break;
+        }
+    }
+
+    let seed = see2 ^ see1 ^ seed;
+
+    if remaining > 16 {
+        hash_bytes_helper16(seed, bytes, index, remaining)
+    } else {
+        let end = index + remaining;
+
+        (wyr8(bytes, end - 16), wyr8(bytes, end - 8), seed)
+    }
+}
+
+fn hash_bytes_helper16(
+    mut seed: u64,
+    bytes: &[u8],
+    mut index: usize,
+    mut remaining: usize,
+) -> (u64, u64, u64) {
+    loop {
+        seed = wymix(wyr8(bytes, index) ^ WYP1, wyr8(bytes, index + 8) ^ seed);
+        remaining -= 16;
+        index += 16;
+
+        if remaining <= 16 {
+            let end = index + remaining;
+
+            return (wyr8(bytes, end - 16), wyr8(bytes, end - 8), seed);
+        }
+    }
+}
+
+fn wymix(a: u64, b: u64) -> u64 {
+    let (lower, upper) = wymum(a, b);
+
+    lower ^ upper
+}
+
+fn wymum(a: u64, b: u64) -> (u64, u64) {
+    let r = (a as u128).wrapping_mul(b as u128);
+
+    (r as u64, (r >> 64) as u64)
+}
+
+fn wyr8(bytes: &[u8], index: usize) -> u64 {
+    let mut le = [0; 8];
+    le.copy_from_slice(&bytes[index..index + 8]);
+
+    u64::from_le_bytes(le)
+}
+
+fn wyr4(bytes: &[u8], index: usize) -> u64 {
+    let mut le = [0; 4];
+    le.copy_from_slice(&bytes[index..index + 4]);
+
+    u32::from_le_bytes(le) as u64
+}
+
+/// The first, middle and last of up to 3 bytes.
+fn wyr3(bytes: &[u8], index: usize, k: usize) -> u64 {
+    let p1 = bytes[index] as u64;
+    let p2 = bytes[index + (k >> 1)] as u64;
+    let p3 = bytes[index + k - 1] as u64;
+
+    (p1 << 16) | (p2 << 8) | p3
+}
+
+/// A value that Roc's `Hash` ability can hash, hashed the way Roc hashes it, so that it can
+/// be a key in a [RocDict](crate::RocDict).
+///
+/// A record hashes its fields in alphabetical order, and a tuple its elements in order, the
+/// way Roc derives `Hash` for them.
+pub trait RocHash {
+    fn roc_hash(&self, hasher: &mut RocHasher);
+}
+
+macro_rules! roc_hash_as {
+    ($($ty:ty => $add:ident as $as:ty),* $(,)?) => {
+        $(
+            impl RocHash for $ty {
+                fn roc_hash(&self, hasher: &mut RocHasher) {
+                    hasher.$add(*self as $as)
+                }
+            }
+        )*
+    };
+}
+
+roc_hash_as! {
+    bool => add_u8 as u8,
+    u8 => add_u8 as u8,
+    i8 => add_u8 as u8,
+    u16 => add_u16 as u16,
+    i16 => add_u16 as u16,
+    u32 => add_u32 as u32,
+    i32 => add_u32 as u32,
+    u64 => add_u64 as u64,
+    i64 => add_u64 as u64,
+    u128 => add_u128 as u128,
+    i128 => add_u128 as u128,
+}
+
+/// Like a record with no fields, this adds nothing.
+impl RocHash for () {
+    fn roc_hash(&self, _hasher: &mut RocHasher) {}
+}
+
+impl RocHash for RocDec {
+    fn roc_hash(&self, hasher: &mut RocHasher) {
+        hasher.add_u128(self.as_i128() as u128)
+    }
+}
+
+impl RocHash for RocStr {
+    fn roc_hash(&self, hasher: &mut RocHasher) {
+        hasher.add_bytes(self.as_str().as_bytes())
+    }
+}
+
+impl<T> RocHash for RocList<T>
+where
+    T: RocHash + RocRefcounted,
+{
+    fn roc_hash(&self, hasher: &mut RocHasher) {
+        for element in self.iter() {
+            element.roc_hash(hasher);
+        }
+    }
+}
+
+macro_rules! roc_hash_tuple {
+    ($($name:ident)+) => {
+        impl<$($name: RocHash),+> RocHash for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn roc_hash(&self, hasher: &mut RocHasher) {
+                let ($($name,)+) = self;
+                $($name.roc_hash(hasher);)+
+            }
+        }
+    };
+}
+
+roc_hash_tuple! { A B }
+roc_hash_tuple! { A B C }
+roc_hash_tuple! { A B C D }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `test_seed` in Dict.roc, which the hashes below come from.
+    const TEST_SEED: u64 = 0x526F_6352_616E_643F;
+
+    fn hash(add: impl FnOnce(&mut RocHasher)) -> u64 {
+        let mut hasher = RocHasher::with_seed(TEST_SEED);
+        add(&mut hasher);
+
+        hasher.complete()
+    }
+
+    #[test]
+    fn matches_dict_roc() {
+        assert_eq!(hash(|h| h.add_bytes(&[])), 0xD59C59757DBBE6B3);
+        assert_eq!(hash(|h| h.add_bytes(&[0x42])), 0x38CE03D0E61AF963);
+        assert_eq!(hash(|h| h.add_u8(0x42)), 0x38CE03D0E61AF963);
+        assert_eq!(hash(|h| h.add_bytes(&[0xFF, 0xFF])), 0xE1CB2FA0D6A64113);
+        assert_eq!(hash(|h| h.add_u16(0xFFFF)), 0xE1CB2FA0D6A64113);
+        assert_eq!(hash(|h| h.add_bytes(&[0x36, 0xA7])), 0x26B8319EDAF81B15);
+        assert_eq!(hash(|h| h.add_u16(0xA736)), 0x26B8319EDAF81B15);
+        assert_eq!(hash(|h| h.add_bytes(&[0, 0, 0, 0])), 0xA187D7CA074F9EE7);
+        assert_eq!(hash(|h| h.add_u32(0)), 0xA187D7CA074F9EE7);
+        assert_eq!(
+            hash(|h| h.add_bytes(&[0xA9, 0x2F, 0xEE, 0x21])),
+            0xA499EFE4C1454D09
+        );
+        assert_eq!(hash(|h| h.add_u32(0x21EE_2FA9)), 0xA499EFE4C1454D09);
+        assert_eq!(
+            hash(|h| h.add_u128(0xA742_9ABB_3423_DDE1_03C7_4468_8FB1_665D)),
+            0xDD39A206AED64C73
+        );
+        assert_eq!(
+            hash(|h| h.add_bytes(&0xA742_9ABB_3423_DDE1_03C7_4468_8FB1_665Du128.to_le_bytes())),
+            0xDD39A206AED64C73
+        );
+        assert_eq!(
+            hash(|h| h.add_bytes(b"abcdefghijklmnopqrstuvwxyz")),
+            0x51C59DF5B1D15F40
+        );
+        assert_eq!(
+            hash(|h| {
+                h.add_bytes(b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789")
+            }),
+            0xD8D0A129D97A4E95
+        );
+        assert_eq!(
+            hash(|h| h.add_bytes(
+                b"1234567890123456789012345678901234567890123456789012345678901234567890"
+            )),
+            0x8188065B44FB4AAA
+        );
+        assert_eq!(hash(|h| h.add_bytes(&[0x77; 100])), 0x47A2A606EADF3378);
+    }
+}
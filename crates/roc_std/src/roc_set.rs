@@ -0,0 +1,286 @@
+use core::{
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+};
+
+use crate::{roc_dict, RocDict, RocRefcounted};
+
+#[cfg(feature = "dict-seed")]
+use crate::RocHash;
+#[cfg(feature = "dict-seed")]
+use core::iter::FromIterator;
+
+#[cfg(all(feature = "serde", feature = "dict-seed"))]
+use core::marker::PhantomData;
+#[cfg(all(feature = "serde", feature = "dict-seed"))]
+use serde::{
+    de::{Deserializer, SeqAccess, Visitor},
+    Deserialize,
+};
+#[cfg(feature = "serde")]
+use serde::{
+    ser::{SerializeSeq, Serializer},
+    Serialize,
+};
+
+/// A Roc `Set`, which is a [RocDict] with empty values, like it is in Set.roc.
+///
+/// Like a [RocDict], building a set, looking up in one, and comparing two need the `dict-seed`
+/// feature, and a host that's linked with a Roc application.
+#[repr(transparent)]
+pub struct RocSet<T>(RocDict<T, ()>)
+where
+    T: RocRefcounted;
+
+impl<T> RocSet<T>
+where
+    T: RocRefcounted,
+{
+    pub fn empty() -> Self {
+        Self(RocDict::empty())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The elements in the order they were inserted.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter(self.0.iter())
+    }
+}
+
+#[cfg(feature = "dict-seed")]
+impl<T> RocSet<T>
+where
+    T: RocHash + RocRefcounted,
+{
+    /// Create an empty RocSet with enough space preallocated to store
+    /// the requested number of elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(RocDict::with_capacity(capacity))
+    }
+}
+
+#[cfg(feature = "dict-seed")]
+impl<T> RocSet<T>
+where
+    T: PartialEq + RocHash + RocRefcounted,
+{
+    pub fn contains(&self, element: &T) -> bool {
+        self.0.contains_key(element)
+    }
+}
+
+#[cfg(feature = "dict-seed")]
+impl<T> RocSet<T>
+where
+    T: Clone + PartialEq + RocHash + RocRefcounted,
+{
+    /// Insert an element, returning whether it was new.
+    pub fn insert(&mut self, element: T) -> bool {
+        self.0.insert(element, ()).is_none()
+    }
+}
+
+impl<T> Default for RocSet<T>
+where
+    T: RocRefcounted,
+{
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<T> Clone for RocSet<T>
+where
+    T: RocRefcounted,
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> RocRefcounted for RocSet<T>
+where
+    T: RocRefcounted,
+{
+    fn inc(&mut self) {
+        self.0.inc()
+    }
+
+    fn dec(&mut self) {
+        self.0.dec()
+    }
+
+    fn is_refcounted() -> bool {
+        true
+    }
+}
+
+#[cfg(feature = "dict-seed")]
+impl<T> PartialEq for RocSet<T>
+where
+    T: PartialEq + RocHash + RocRefcounted,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+#[cfg(feature = "dict-seed")]
+impl<T> Eq for RocSet<T> where T: Eq + RocHash + RocRefcounted {}
+
+impl<T> Hash for RocSet<T>
+where
+    T: Hash + RocRefcounted,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl<T> Debug for RocSet<T>
+where
+    T: Debug + RocRefcounted,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(feature = "dict-seed")]
+impl<T> FromIterator<T> for RocSet<T>
+where
+    T: Clone + PartialEq + RocHash + RocRefcounted,
+{
+    fn from_iter<I>(into: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        Self(into.into_iter().map(|element| (element, ())).collect())
+    }
+}
+
+#[cfg(feature = "dict-seed")]
+impl<T, const SIZE: usize> From<[T; SIZE]> for RocSet<T>
+where
+    T: Clone + PartialEq + RocHash + RocRefcounted,
+{
+    fn from(elements: [T; SIZE]) -> Self {
+        Self::from_iter(elements)
+    }
+}
+
+impl<T> From<RocDict<T, ()>> for RocSet<T>
+where
+    T: RocRefcounted,
+{
+    fn from(dict: RocDict<T, ()>) -> Self {
+        Self(dict)
+    }
+}
+
+impl<T> From<RocSet<T>> for RocDict<T, ()>
+where
+    T: RocRefcounted,
+{
+    fn from(set: RocSet<T>) -> Self {
+        set.0
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RocSet<T>
+where
+    T: RocRefcounted,
+{
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// The elements of a [RocSet], in the order they were inserted.
+pub struct Iter<'a, T>(roc_dict::Iter<'a, T, ()>);
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(element, _)| element)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+#[cfg(feature = "serde")]
+impl<T> Serialize for RocSet<T>
+where
+    T: Serialize + RocRefcounted,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for element in self {
+            seq.serialize_element(element)?;
+        }
+        seq.end()
+    }
+}
+
+#[cfg(all(feature = "serde", feature = "dict-seed"))]
+impl<'de, T> Deserialize<'de> for RocSet<T>
+where
+    T: Deserialize<'de> + Clone + PartialEq + RocHash + RocRefcounted,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(RocSetVisitor {
+            marker: PhantomData,
+        })
+    }
+}
+
+#[cfg(all(feature = "serde", feature = "dict-seed"))]
+struct RocSetVisitor<T> {
+    marker: PhantomData<T>,
+}
+
+#[cfg(all(feature = "serde", feature = "dict-seed"))]
+impl<'de, T> Visitor<'de> for RocSetVisitor<T>
+where
+    T: Deserialize<'de> + Clone + PartialEq + RocHash + RocRefcounted,
+{
+    type Value = RocSet<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a sequence")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut out = RocSet::with_capacity(seq.size_hint().unwrap_or(0));
+
+        while let Some(next) = seq.next_element()? {
+            out.insert(next);
+        }
+
+        Ok(out)
+    }
+}
@@ -52,9 +52,16 @@ pub unsafe extern "C" fn roc_memset(dst: *mut c_void, c: i32, n: usize) -> *mut
     libc::memset(dst, c, n)
 }
 
+/// The seed a Roc application would give its dicts; any seed works as long as it doesn't change.
+#[cfg(test)]
+#[no_mangle]
+pub extern "C" fn roc_dict_pseudo_seed() -> u64 {
+    0x526F_6352_616E_643F
+}
+
 #[cfg(test)]
 mod test_roc_std {
-    use roc_std::{RocBox, RocDec, RocList, RocResult, RocStr, SendSafeRocStr};
+    use roc_std::{RocBox, RocDec, RocDict, RocList, RocResult, RocSet, RocStr, SendSafeRocStr};
 
     fn roc_str_byte_representation(string: &RocStr) -> [u8; RocStr::SIZE] {
         unsafe { core::mem::transmute_copy(string) }
@@ -465,6 +472,81 @@ mod test_roc_std {
             ]
         );
     }
+
+    #[test]
+    fn roc_dict_insert_and_get() {
+        let mut dict = RocDict::empty();
+
+        assert_eq!(dict.insert(RocStr::from("one"), 1u64), None);
+        assert_eq!(dict.insert(RocStr::from("two"), 2), None);
+        assert_eq!(dict.insert(RocStr::from("one"), 3), Some(1));
+
+        assert_eq!(dict.len(), 2);
+        assert_eq!(dict.get(&RocStr::from("one")), Some(&3));
+        assert_eq!(dict.get(&RocStr::from("three")), None);
+
+        // insertion order, like Dict.walk
+        let entries: Vec<_> = dict.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+        assert_eq!(entries, vec![("one", 3), ("two", 2)]);
+    }
+
+    #[test]
+    fn roc_dict_grows() {
+        let dict: RocDict<u64, u64> = (0..1000).map(|n| (n, n * n)).collect();
+
+        assert_eq!(dict.len(), 1000);
+        assert!((0..1000).all(|n| dict.get(&n) == Some(&(n * n))));
+        assert_eq!(dict.get(&1000), None);
+    }
+
+    #[test]
+    fn roc_dict_insert_does_not_change_clones() {
+        let mut dict = RocDict::from([(1u8, RocStr::from("a"))]);
+        let clone = dict.clone();
+
+        dict.insert(1, RocStr::from("b"));
+        dict.insert(2, RocStr::from("c"));
+
+        assert_eq!(clone, RocDict::from([(1, RocStr::from("a"))]));
+        assert_eq!(dict.get(&1), Some(&RocStr::from("b")));
+    }
+
+    #[test]
+    fn roc_dict_eq_ignores_order() {
+        let a = RocDict::from([(1u32, 10u8), (2, 20)]);
+        let b = RocDict::from([(2u32, 20u8), (1, 10)]);
+
+        assert_eq!(a, b);
+        assert_ne!(a, RocDict::from([(1u32, 10u8)]));
+    }
+
+    #[test]
+    fn roc_set_from_iter() {
+        let set: RocSet<i64> = [3, 1, 3, 2].into_iter().collect();
+
+        assert_eq!(set.len(), 3);
+        assert!(set.contains(&1));
+        assert!(!set.contains(&4));
+        assert_eq!(set.iter().copied().collect::<Vec<_>>(), vec![3, 1, 2]);
+        assert_eq!(set, RocSet::from([1, 2, 3]));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn dict_and_set_serde_roundtrip() {
+        let dict = RocDict::from([(RocStr::from("a"), 1u8), (RocStr::from("b"), 2)]);
+        let serialized = serde_json::to_string(&dict).expect("failed to serialize dict");
+        assert_eq!(serialized, r#"{"a":1,"b":2}"#);
+        let deserialized = serde_json::from_str::<RocDict<RocStr, u8>>(&serialized)
+            .expect("failed to deserialize dict");
+        assert_eq!(dict, deserialized);
+
+        let set = RocSet::from([1u8, 2, 3]);
+        let serialized = serde_json::to_string(&set).expect("failed to serialize set");
+        let deserialized =
+            serde_json::from_str::<RocSet<u8>>(&serialized).expect("failed to deserialize set");
+        assert_eq!(set, deserialized);
+    }
 }
 
 #[cfg(test)]
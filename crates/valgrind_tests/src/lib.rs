@@ -158,6 +158,7 @@ fn valgrind_test_linux(source: &str) {
             problems,
             total_time: _,
             expect_metadata: _,
+            source_paths: _,
         }) => {
             if problems.exit_code() != 0 {
                 panic!("there are problems")
@@ -166,9 +167,9 @@ fn valgrind_test_linux(source: &str) {
             run_with_valgrind(&binary_path);
         }
         Err(roc_build::program::BuildFileError::LoadingProblem(
-            roc_load::LoadingProblem::FormattedReport(report, _),
+            roc_load::LoadingProblem::FormattedReport(report),
         )) => {
-            eprintln!("{report}");
+            eprintln!("{}", report.text);
             panic!("");
         }
         Err(e) => panic!("{e:?}"),